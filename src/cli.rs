@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use crate::utils::time_expr::TimeRange;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -15,6 +16,9 @@ Examples:
   rmz restore --interactive     # Interactively restore files  
   rmz list --since=yesterday    # List recently deleted files
   rmz purge --days=30           # Permanently delete old files
+  rmz --profile scratch list    # Use the trash of a named profile
+
+Set RMZ_HOME to keep the configuration file and trash in one directory.
 "#)]
 pub struct Cli {
     #[command(subcommand)]
@@ -27,6 +31,22 @@ pub struct Cli {
     /// Disable colored output
     #[arg(long, global = true)]
     pub no_color: bool,
+
+    /// Read user settings from FILE instead of the default configuration file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Use DIR as the trash, overriding every configuration layer
+    #[arg(long, global = true, value_name = "DIR")]
+    pub trash_dir: Option<PathBuf>,
+
+    /// Apply a [profiles.NAME] table from the configuration files
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Skip the pre_delete, post_delete, pre_purge and post_restore hooks
+    #[arg(long, global = true)]
+    pub no_hooks: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -45,9 +65,13 @@ pub enum Commands {
         #[arg(long)]
         dry_run: bool,
 
-        /// Add a tag/reason for deletion
-        #[arg(short, long)]
-        tag: Option<String>,
+        /// Tag the deletion with a reason; repeat or separate with commas for several
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Record why the files were deleted, e.g. "superseded by v2 schema"
+        #[arg(long, value_name = "TEXT")]
+        note: Option<String>,
 
         /// Interactive mode with confirmation prompts
         #[arg(short, long)]
@@ -79,6 +103,43 @@ pub enum Commands {
         /// Restore to specific path instead of original location
         #[arg(long)]
         to: Option<PathBuf>,
+
+        #[command(flatten)]
+        range: TimeRangeArgs,
+
+        /// Only restore items carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Restore the Nth trashed version of the path (1 = oldest, see `rmz history`)
+        #[arg(long, requires = "file", conflicts_with_all = ["at", "all"])]
+        version: Option<usize>,
+
+        /// Restore the version that was current at this time (e.g. '2024-01-01 12:00', 'yesterday')
+        #[arg(long, requires = "file", conflicts_with = "all")]
+        at: Option<String>,
+
+        /// Rebuild the tree beneath this directory as it was at --as-of
+        #[arg(long, requires = "as_of", conflicts_with_all = ["file", "id", "interactive", "all", "to", "version", "at", "since", "before", "between", "tags"])]
+        under: Option<PathBuf>,
+
+        /// Point in time for --under (e.g. '2026-10-14 13:00')
+        #[arg(long, requires = "under")]
+        as_of: Option<String>,
+
+        /// Show the --under restore plan without restoring anything
+        #[arg(long, requires = "under")]
+        dry_run: bool,
+
+        /// What to do with paths that already exist when restoring --under
+        #[arg(long, value_enum, requires = "under")]
+        on_conflict: Option<ConflictPolicy>,
+    },
+
+    /// Show every trashed version of a path, or of everything beneath a directory
+    History {
+        /// Original path of the file or directory
+        path: PathBuf,
     },
 
     /// List deleted files in trash zone
@@ -91,21 +152,47 @@ pub enum Commands {
         #[arg(long)]
         filter: Option<String>,
 
-        /// Show files deleted since specific date (e.g., 'yesterday', '2024-01-01')
-        #[arg(long)]
-        since: Option<String>,
+        /// Only list items carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        #[command(flatten)]
+        range: TimeRangeArgs,
 
         /// Group results by date or tag
         #[arg(long, value_enum)]
         group_by: Option<GroupBy>,
 
-        /// Maximum number of files to show
+        /// Maximum number of files to show, counted after sorting
         #[arg(short, long)]
         limit: Option<usize>,
+
+        /// Sort by this field (deleted: newest first, size: largest first, others: A to Z)
+        #[arg(long, value_enum, default_value = "deleted")]
+        sort: SortBy,
+
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
+
+        /// Comma-separated fields to show as a table (e.g. 'name,size,deleted')
+        #[arg(long, value_enum, value_delimiter = ',')]
+        columns: Option<Vec<ListColumn>>,
+
+        /// Show items arranged by their original directories with aggregated sizes
+        #[arg(long, conflicts_with_all = ["json", "group_by", "columns"])]
+        tree: bool,
+
+        /// Only show items deleted from the current directory or beneath it
+        #[arg(long)]
+        here: bool,
     },
 
     /// Show deletion history and audit logs
     Log {
+        #[command(subcommand)]
+        action: Option<LogAction>,
+
         /// Show detailed operation logs
         #[arg(short, long)]
         detailed: bool,
@@ -114,15 +201,46 @@ pub enum Commands {
         #[arg(long, value_enum)]
         operation: Option<OperationType>,
 
-        /// Show logs since specific date
+        /// Only operations on this path or beneath it, or on paths matching a glob (e.g. '*.log', '~/src/**')
+        #[arg(long, value_name = "PATH|GLOB")]
+        path: Option<String>,
+
+        /// Only operations with this entry or file ID (or unique prefix)
         #[arg(long)]
-        since: Option<String>,
+        id: Option<String>,
+
+        /// Only operations performed by this user
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Only operations with this result
+        #[arg(long, value_enum)]
+        result: Option<LogResult>,
+
+        /// Only operations on items carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Maximum number of operations to show, newest first (oldest first with --reverse)
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Show the oldest operations first
+        #[arg(short, long, conflicts_with = "follow")]
+        reverse: bool,
+
+        /// Print matching operations as NDJSON as they are logged, after the last --limit ones
+        #[arg(short, long, conflicts_with = "detailed")]
+        follow: bool,
+
+        #[command(flatten)]
+        range: TimeRangeArgs,
     },
 
     /// Permanently delete files from trash zone
     Purge {
         /// Purge all files
-        #[arg(long, conflicts_with_all = ["days", "size", "id"])]
+        #[arg(long, conflicts_with_all = ["days", "size", "id", "since", "before", "between", "tags"])]
         all: bool,
 
         /// Purge files older than N days
@@ -140,6 +258,36 @@ pub enum Commands {
         /// Confirm before purging
         #[arg(short, long)]
         interactive: bool,
+
+        #[command(flatten)]
+        range: TimeRangeArgs,
+
+        /// Only purge items carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+
+    /// Add, remove or show tags of trashed items
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+
+    /// Show every tag with the number and size of items carrying it
+    Tags,
+
+    /// Edit the deletion note of an item in $EDITOR
+    Annotate {
+        /// Item ID or unique ID prefix
+        id: String,
+
+        /// Set the note to TEXT instead of opening an editor
+        #[arg(long, value_name = "TEXT", conflicts_with = "clear")]
+        note: Option<String>,
+
+        /// Remove the note
+        #[arg(long)]
+        clear: bool,
     },
 
     /// Manage protected paths
@@ -155,6 +303,12 @@ pub enum Commands {
         detailed: bool,
     },
 
+    /// Manage the encryption key for trashed items
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+
     /// Configure rmz settings
     Config {
         #[command(subcommand)]
@@ -171,6 +325,14 @@ pub enum Commands {
         #[arg(long)]
         fix: bool,
 
+        /// Show the repairs --fix would make without changing anything
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+
+        /// Move broken metadata and leftover payloads to the quarantine directory instead of deleting them
+        #[arg(long)]
+        quarantine: bool,
+
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
@@ -180,6 +342,48 @@ pub enum Commands {
         force: bool,
     },
 
+    /// Show an item's full metadata and a preview or tree of its contents
+    Show {
+        /// Item ID, ID prefix, or name pattern
+        query: String,
+    },
+
+    /// Print a trashed file to stdout without restoring it
+    Cat {
+        /// Item ID or unique ID prefix
+        id: String,
+
+        /// File inside a trashed directory
+        path: Option<PathBuf>,
+
+        /// Print binary content as-is instead of a hexdump
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Compare a trashed item with the file at its original location
+    Diff {
+        /// Item ID or unique ID prefix
+        id: String,
+
+        /// Compare with this path instead of the original location
+        path: Option<PathBuf>,
+    },
+
+    /// Browse the trash in a full-screen terminal UI
+    Tui,
+
+    /// Run non-interactive maintenance: retention, orphan cleanup, log compaction
+    Gc {
+        /// Install a systemd user timer that runs `rmz gc` daily
+        #[arg(long)]
+        install_timer: bool,
+
+        /// Print a crontab line instead of installing a systemd timer
+        #[arg(long)]
+        cron: bool,
+    },
+
     /// Generate shell completion scripts
     Completions {
         /// Shell type
@@ -212,6 +416,39 @@ pub enum Commands {
         /// Show tree structure of directory contents
         #[arg(long)]
         tree: bool,
+
+        /// Only extract items carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagAction {
+    /// Add tags to items
+    Add {
+        /// Item IDs or unique ID prefixes
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Tag to add; repeat or separate with commas for several
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',', required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from items
+    Remove {
+        /// Item IDs or unique ID prefixes
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Tag to remove; repeat or separate with commas for several
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',', required = true)]
+        tags: Vec<String>,
+    },
+    /// Show the tags of items
+    List {
+        /// Item IDs or unique ID prefixes; all items when omitted
+        ids: Vec<String>,
     },
 }
 
@@ -233,23 +470,131 @@ pub enum ProtectAction {
     List,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum LogAction {
+    /// Check the log's hash chain for deleted, reordered or modified entries
+    Verify,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeyAction {
+    /// Set up the encryption key from a passphrase or the configured key file
+    Init {
+        /// Replace an existing key (items encrypted with it become undecryptable)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Re-encrypt all items with a new passphrase or key file
+    Rotate {
+        /// Use this key file for the new key instead of a passphrase
+        #[arg(long)]
+        new_key_file: Option<PathBuf>,
+    },
+
+    /// Show key and encrypted item status
+    Status,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum ConfigAction {
     /// Show current configuration
-    Show,
+    Show {
+        /// Show which layer (default, system, user, project or environment) set each value
+        #[arg(long)]
+        origin: bool,
+    },
+
+    /// Print the effective value of one setting
+    Get {
+        /// Configuration key, e.g. compression.level
+        key: String,
+
+        /// Also print which layer set the value
+        #[arg(long)]
+        origin: bool,
+    },
 
     /// Set configuration value
     Set {
-        /// Configuration key
+        /// Configuration key, e.g. compression.level (see 'rmz config list-keys')
         key: String,
-        /// Configuration value
+        /// Configuration value; 'none' disables optional settings, ':' separates paths in lists
         value: String,
     },
 
+    /// Remove a setting from the configuration file so lower layers apply again
+    Unset {
+        /// Configuration key
+        key: String,
+    },
+
+    /// List every setting with its type, default and description
+    ListKeys,
+
+    /// Open the configuration file in $VISUAL or $EDITOR and validate it before saving
+    Edit,
+
     /// Reset configuration to defaults
     Reset,
 }
 
+/// Time window accepted by commands that select by deletion or log time
+///
+/// See `utils::time_expr` for the accepted expressions.
+#[derive(Args, Clone, Debug, Default)]
+pub struct TimeRangeArgs {
+    /// Only include entries from this time on (e.g. '2h', '3 days ago', 'yesterday', '2024-01-01 13:00')
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only include entries from before this time
+    #[arg(long)]
+    pub before: Option<String>,
+
+    /// Only include entries within START..END (e.g. 'yesterday..today')
+    #[arg(long, conflicts_with_all = ["since", "before"])]
+    pub between: Option<String>,
+}
+
+impl TimeRangeArgs {
+    /// Parse the expressions into a time range
+    pub fn parse(&self) -> anyhow::Result<TimeRange> {
+        TimeRange::parse(self.since.as_deref(), self.before.as_deref(), self.between.as_deref())
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Leave the existing path alone
+    Skip,
+    /// Move the existing path to the trash and restore in its place
+    Overwrite,
+    /// Restore next to the existing path with a .restored suffix
+    Rename,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    Deleted,
+    Size,
+    Path,
+    Name,
+    Tag,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListColumn {
+    Id,
+    Name,
+    Path,
+    Size,
+    Deleted,
+    Tags,
+    User,
+    Note,
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum GroupBy {
     Date,
@@ -264,6 +609,15 @@ pub enum OperationType {
     Status,
     Protect,
     Doctor,
+    Tag,
+    Annotate,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LogResult {
+    Success,
+    Failed,
+    Cancelled,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -273,5 +627,6 @@ pub enum DiagnosticCheck {
     Permissions,
     Config,
     Dependencies,
+    Encryption,
     All,
 }
@@ -0,0 +1,82 @@
+use super::Context;
+use crate::domain::TrashItem;
+use anyhow::Result;
+use dialoguer::Editor;
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Execute the annotate command
+pub fn execute(ctx: &Context, id: String, note: Option<String>, clear: bool) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let item = trash.find_one(&id)?;
+
+    let new_note = if clear {
+        None
+    } else if let Some(note) = note {
+        parse_note(&note)
+    } else {
+        match Editor::new().extension(".txt").edit(&editor_template(&item))? {
+            Some(text) => parse_note(&text),
+            None => {
+                println!("Note unchanged (editor closed without saving)");
+                return Ok(());
+            }
+        }
+    };
+
+    if new_note == item.meta.note {
+        println!("Note unchanged");
+        return Ok(());
+    }
+
+    let item = trash.annotate(&item.meta.id, new_note)?;
+    let short_id = &item.meta.id.to_string()[..8];
+    match &item.meta.note {
+        #[cfg(feature = "colors")]
+        Some(note) => println!("📝 {} {}: {}", short_id.dimmed(), item.meta.original_path.display(), note.cyan()),
+        #[cfg(not(feature = "colors"))]
+        Some(note) => println!("📝 {} {}: {}", short_id, item.meta.original_path.display(), note),
+        None => println!("Removed the note from {} {}", short_id, item.meta.original_path.display()),
+    }
+    if ctx.verbose {
+        println!("ID: {}", item.meta.id);
+    }
+
+    Ok(())
+}
+
+/// Current note followed by instructions, which [`parse_note`] strips again
+fn editor_template(item: &TrashItem) -> String {
+    format!(
+        "{}\n\n# Why was {} deleted?\n# Lines starting with '#' are ignored; an empty note removes it.\n",
+        item.meta.note.as_deref().unwrap_or(""),
+        item.meta.original_path.display()
+    )
+}
+
+/// Note text without comment lines and surrounding blank space, or None if nothing is left
+fn parse_note(text: &str) -> Option<String> {
+    let note = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let note = note.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note() {
+        assert_eq!(
+            parse_note("superseded by v2 schema\nsafe after release\n\n# Why was a.sql deleted?\n"),
+            Some("superseded by v2 schema\nsafe after release".to_string())
+        );
+        assert_eq!(parse_note("  \n# only comments\n"), None);
+        assert_eq!(parse_note("keep #hashtags inside lines"), Some("keep #hashtags inside lines".to_string()));
+    }
+}
@@ -0,0 +1,139 @@
+use super::Context;
+use anyhow::{anyhow, Result};
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+
+/// Bytes inspected to decide whether content is binary
+const SNIFF_BYTES: usize = 8192;
+
+/// Execute the cat command
+pub fn execute(ctx: &Context, id: String, path: Option<PathBuf>, raw: bool) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let item = trash.find_one(&id)?;
+
+    let reader = match &path {
+        Some(inner) => trash.store().open_inner(&item, inner)?,
+        None if item.is_directory() => {
+            return Err(anyhow!(
+                "{} is a directory; name a file inside it: rmz cat {} <path>",
+                item.meta.original_path.display(),
+                &item.meta.id.to_string()[..8]
+            ));
+        }
+        None => trash.store().open_content(&item)?,
+    };
+
+    let stdout = std::io::stdout().lock();
+    match write_content(reader, stdout, raw) {
+        // Stop quietly when piped into e.g. `head`
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Copy content to `out`, as a hexdump if it looks binary and `raw` is not set
+fn write_content(mut reader: impl Read, mut out: impl Write, raw: bool) -> std::io::Result<()> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    (&mut reader).take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+    let binary = !raw && is_binary(&head);
+    let mut content = std::io::Cursor::new(head).chain(reader);
+
+    if binary {
+        write_hexdump(&mut content, &mut out)?;
+    } else {
+        std::io::copy(&mut content, &mut out)?;
+    }
+    out.flush()
+}
+
+/// NUL bytes or invalid UTF-8 (other than a sequence cut off at the end) mean binary
+fn is_binary(head: &[u8]) -> bool {
+    head.contains(&0)
+        || std::str::from_utf8(head).is_err_and(|e| e.error_len().is_some())
+}
+
+/// Write content in the layout of `hexdump -C`
+fn write_hexdump(mut reader: impl Read, mut out: impl Write) -> std::io::Result<()> {
+    let mut offset = 0usize;
+    let mut line = [0u8; 16];
+
+    loop {
+        let len = read_full(&mut reader, &mut line)?;
+        if len == 0 {
+            break;
+        }
+
+        write!(out, "{:08x} ", offset)?;
+        for (i, byte) in line.iter().enumerate() {
+            if i == 8 {
+                write!(out, " ")?;
+            }
+            if i < len {
+                write!(out, " {:02x}", byte)?;
+            } else {
+                write!(out, "   ")?;
+            }
+        }
+        let ascii: String = line[..len]
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        writeln!(out, "  |{}|", ascii)?;
+
+        offset += len;
+        if len < line.len() {
+            break;
+        }
+    }
+
+    writeln!(out, "{:08x}", offset)
+}
+
+/// Fill `buf` unless the reader ends first, returning the bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(content: &[u8], raw: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_content(content, &mut out, raw).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_text_is_copied_verbatim() {
+        assert_eq!(render("héllo\nworld\n".as_bytes(), false), "héllo\nworld\n".as_bytes());
+    }
+
+    #[test]
+    fn test_binary_is_hexdumped_unless_raw() {
+        let content = b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00hello\n";
+        let expected = "\
+00000000  7f 45 4c 46 02 01 01 00  00 00 00 00 00 00 00 00  |.ELF............|
+00000010  68 65 6c 6c 6f 0a                                 |hello.|
+00000016
+";
+        assert_eq!(String::from_utf8(render(content, false)).unwrap(), expected);
+        assert_eq!(render(content, true), content);
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(b"plain text"));
+        assert!(is_binary(b"nul\0byte"));
+        assert!(is_binary(&[0xff, 0xfe, b'a']));
+        // A multi-byte character cut off by the sniff window is still text
+        assert!(!is_binary(&"é".as_bytes()[..1]));
+    }
+}
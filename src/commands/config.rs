@@ -1,24 +1,36 @@
+use super::Context;
 use crate::cli::ConfigAction;
+use crate::domain::config_layers::{find_project_file, validate_file, ConfigLayers, Origin, SYSTEM_CONFIG_PATH};
+use crate::domain::config_schema::{find_key, KEYS, SCHEMA_VERSION, UNSET};
 use crate::domain::Config;
+use crate::utils::size_parser::format_size;
 use anyhow::{anyhow, Result};
+use dialoguer::{Confirm, Editor};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 #[cfg(feature = "colors")]
 use colored::Colorize;
 
 /// Execute the config command
-pub fn execute(action: ConfigAction, verbose: bool) -> Result<()> {
+pub fn execute(ctx: &Context, action: ConfigAction) -> Result<()> {
     match action {
-        ConfigAction::Show => show_config(verbose),
-        ConfigAction::Set { key, value } => set_config_value(key, value, verbose),
-        ConfigAction::Reset => reset_config(verbose),
+        ConfigAction::Show { origin: false } => show_config(ctx),
+        ConfigAction::Show { origin: true } => show_origins(ctx),
+        ConfigAction::Get { key, origin } => get_config_value(ctx, &key, origin),
+        ConfigAction::Set { key, value } => set_config_value(ctx, key, value),
+        ConfigAction::Unset { key } => unset_config_value(ctx, &key),
+        ConfigAction::ListKeys => list_keys(ctx),
+        ConfigAction::Edit => edit_config(ctx),
+        ConfigAction::Reset => reset_config(ctx),
     }
 }
 
 /// Show current configuration
-fn show_config(verbose: bool) -> Result<()> {
-    let config = Config::load()?;
-    let config_path = Config::config_file_path();
+fn show_config(ctx: &Context) -> Result<()> {
+    let verbose = ctx.verbose;
+    let config = ctx.load_config()?;
+    let config_path = ctx.overrides.user_file();
     
     #[cfg(feature = "colors")]
     println!("{}", "Current Configuration:".bold().underline());
@@ -32,6 +44,15 @@ fn show_config(verbose: bool) -> Result<()> {
         println!("📁 Config file: {}", config_path.display().to_string().cyan());
         #[cfg(not(feature = "colors"))]
         println!("Config file: {}", config_path.display());
+        let project = std::env::current_dir().ok().and_then(|dir| find_project_file(&dir));
+        let system = PathBuf::from(SYSTEM_CONFIG_PATH);
+        for (label, path) in [("System file", Some(&system)), ("Project file", project.as_ref())] {
+            match path {
+                Some(path) if path.exists() => println!("{}: {}", label, path.display()),
+                _ => println!("{}: (none)", label),
+            }
+        }
+        println!("Run 'rmz config show --origin' to see which layer set each value");
         println!();
     }
     
@@ -46,7 +67,7 @@ fn show_config(verbose: bool) -> Result<()> {
         config.auto_clean_days.map_or("disabled".to_string(), |days| days.to_string())
     );
     println!("  max_trash_size: {}", 
-        config.max_trash_size.map_or("unlimited".to_string(), |size| format_size(size))
+        config.max_trash_size.map_or("unlimited".to_string(), format_size)
     );
     println!();
     
@@ -61,6 +82,80 @@ fn show_config(verbose: bool) -> Result<()> {
     println!("  use_fzf: {}", config.use_fzf);
     println!("  date_format: {}", config.date_format);
     println!();
+
+    // Storage settings
+    #[cfg(feature = "colors")]
+    println!("{}", "Storage Settings:".magenta().bold());
+    #[cfg(not(feature = "colors"))]
+    println!("Storage Settings:");
+
+    println!("  compression.enabled: {}", config.compression.enabled);
+    println!("  compression.level: {}", config.compression.level);
+    println!("  compression.min_age_days: {}", config.compression.min_age_days);
+    println!("  dedup.enabled: {}", config.dedup.enabled);
+    println!("  encryption.enabled: {}", config.encryption.enabled);
+    println!(
+        "  encryption.key_file: {}",
+        config
+            .encryption
+            .key_file
+            .as_ref()
+            .map_or("none".to_string(), |path| path.display().to_string())
+    );
+    println!("  encryption.encrypt_paths: {}", config.encryption.encrypt_paths);
+    println!(
+        "  gc.log_retention_days: {}",
+        config
+            .gc
+            .log_retention_days
+            .map_or("disabled".to_string(), |days| days.to_string())
+    );
+    println!(
+        "  notes.prompt_threshold: {}",
+        config
+            .notes
+            .prompt_threshold
+            .map_or("disabled".to_string(), |count| count.to_string())
+    );
+    println!(
+        "  log.max_size: {}",
+        config.log.max_size.map_or("unlimited".to_string(), format_size)
+    );
+    println!(
+        "  log.max_age_days: {}",
+        config.log.max_age_days.map_or("disabled".to_string(), |days| days.to_string())
+    );
+    println!(
+        "  log.keep_segments: {}",
+        config.log.keep_segments.map_or("unlimited".to_string(), |count| count.to_string())
+    );
+    println!("  log.hmac: {}", config.log.hmac);
+    if config.log.sinks.is_empty() {
+        println!("  log.sinks: none");
+    } else {
+        let sinks: Vec<String> = config.log.sinks.iter().map(ToString::to_string).collect();
+        println!("  log.sinks: {}", sinks.join(", "));
+    }
+    for (name, command) in [
+        ("pre_delete", &config.hooks.pre_delete),
+        ("post_delete", &config.hooks.post_delete),
+        ("pre_purge", &config.hooks.pre_purge),
+        ("post_restore", &config.hooks.post_restore),
+    ] {
+        println!("  hooks.{}: {}", name, command.as_deref().unwrap_or("none"));
+    }
+    println!(
+        "  hooks.dir: {}",
+        config
+            .hooks
+            .dir
+            .as_ref()
+            .map_or("none".to_string(), |path| path.display().to_string())
+    );
+    println!("  hooks.timeout_secs: {}", config.hooks.timeout_secs);
+    println!("  git.record: {}", config.git.record);
+    println!("  git.safeguard: {}", format!("{:?}", config.git.safeguard).to_lowercase());
+    println!();
     
     // Protected paths
     #[cfg(feature = "colors")]
@@ -96,79 +191,250 @@ fn show_config(verbose: bool) -> Result<()> {
 }
 
 /// Set a configuration value
-fn set_config_value(key: String, value: String, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
-    let original_value = value.clone(); // Clone for later display
-    
-    match key.as_str() {
-        "trash_path" => {
-            let new_path = PathBuf::from(&value);
-            if !new_path.is_absolute() {
-                return Err(anyhow!("trash_path must be an absolute path"));
-            }
-            config.trash_path = new_path;
-        }
-        "auto_clean_days" => {
-            if value.to_lowercase() == "disabled" || value.to_lowercase() == "none" {
-                config.auto_clean_days = None;
-            } else {
-                let days: u32 = value.parse()
-                    .map_err(|_| anyhow!("auto_clean_days must be a number or 'disabled'"))?;
-                config.auto_clean_days = Some(days);
-            }
-        }
-        "max_trash_size" => {
-            if value.to_lowercase() == "unlimited" || value.to_lowercase() == "none" {
-                config.max_trash_size = None;
-            } else {
-                let size = parse_size(&value)?;
-                config.max_trash_size = Some(size);
-            }
-        }
-        "colors" => {
-            config.colors = parse_bool(&value)?;
-        }
-        "require_confirmation" => {
-            config.require_confirmation = parse_bool(&value)?;
-        }
-        "use_fzf" => {
-            config.use_fzf = parse_bool(&value)?;
-        }
-        "date_format" => {
-            // Validate the date format by trying to format a test date
-            let test_date = chrono::Utc::now();
-            test_date.format(&value).to_string(); // This will panic if format is invalid
-            config.date_format = value;
-        }
-        _ => {
-            return Err(anyhow!("Unknown configuration key: {}", key));
-        }
+///
+/// For lists, the given paths replace the entries contributed by the file
+/// being written; entries from other layers stay.
+fn set_config_value(ctx: &Context, key: String, value: String) -> Result<()> {
+    let verbose = ctx.verbose;
+    let schema = find_key(&key).ok_or_else(|| anyhow!("Unknown configuration key: {} (see 'rmz config list-keys')", key))?;
+    let mut parsed = schema.parse(&value)?;
+
+    if let toml::Value::Array(items) = &parsed {
+        let resolved = ctx.overrides.resolve()?;
+        let mut list: Vec<toml::Value> = resolved
+            .entries(&key)
+            .iter()
+            .filter(|(_, origin)| !ctx.overrides.writes_to(origin))
+            .map(|(item, _)| item.clone())
+            .collect();
+        list.extend(items.iter().filter(|item| !list.contains(item)).cloned().collect::<Vec<_>>());
+        parsed = toml::Value::Array(list);
     }
-    
+
+    let mut config = ctx.load_config()?;
+    config.set_value(&key, Some(parsed.clone()))?;
+
     // Ensure directories exist with new config
     config.ensure_directories()?;
-    
+
     // Save the configuration
-    config.save()?;
-    
+    ctx.save_config(&config)?;
+
     if verbose {
         #[cfg(feature = "colors")]
-        println!("✅ Set {} = {}", key.green(), original_value.cyan());
+        println!("✅ Set {} = {}", key.green(), display_value(&parsed).cyan());
         #[cfg(not(feature = "colors"))]
-        println!("Set {} = {}", key, original_value);
+        println!("Set {} = {}", key, display_value(&parsed));
     }
-    
+
     #[cfg(feature = "colors")]
     println!("✅ Configuration updated successfully");
     #[cfg(not(feature = "colors"))]
     println!("Configuration updated successfully");
-    
+
+    // The file written sits below project files, the environment and the command line
+    let resolved = ctx.overrides.resolve()?;
+    let expected = (parsed.as_str() != Some(UNSET)).then_some(&parsed);
+    if !schema.is_additive() && resolved.value(&key) != expected {
+        if let Some(origin) = resolved.origin(&key) {
+            #[cfg(feature = "colors")]
+            println!("⚠️  {} is overridden by {}", key.yellow(), origin);
+            #[cfg(not(feature = "colors"))]
+            println!("Warning: {} is overridden by {}", key, origin);
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove a setting from the user file, or the selected profile in it
+fn unset_config_value(ctx: &Context, key: &str) -> Result<()> {
+    find_key(key).ok_or_else(|| anyhow!("Unknown configuration key: {} (see 'rmz config list-keys')", key))?;
+    let file = ctx.overrides.user_file();
+
+    if !ctx.overrides.unset(key)? {
+        println!("{} is not set in {}", key, file.display());
+        return Ok(());
+    }
+
+    let resolved = ctx.overrides.resolve()?;
+    let value = resolved.value(key).map_or(UNSET.to_string(), display_value);
+    #[cfg(feature = "colors")]
+    println!("✅ Removed {} from {}", key.green(), file.display());
+    #[cfg(not(feature = "colors"))]
+    println!("Removed {} from {}", key, file.display());
+    match resolved.origin(key) {
+        Some(origin) => print_with_origin(&format!("{} = {}", key, value), origin),
+        None => println!("{} = {}", key, value),
+    }
+    Ok(())
+}
+
+/// Print every setting with its type, default and description
+fn list_keys(ctx: &Context) -> Result<()> {
+    let defaults = ConfigLayers::default().resolve()?;
+    let rows: Vec<[String; 3]> = KEYS
+        .iter()
+        .map(|key| {
+            let default = defaults.value(key.name).map_or(UNSET.to_string(), display_value);
+            [key.name.to_string(), key.kind.describe(), default]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..3)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0).min(40))
+        .collect();
+
+    for (key, row) in KEYS.iter().zip(&rows) {
+        let default = if row[2].len() > 40 { format!("{}...", &row[2][..37]) } else { row[2].clone() };
+        #[cfg(feature = "colors")]
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0].cyan(),
+            row[1],
+            default.dimmed(),
+            key.description,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+        #[cfg(not(feature = "colors"))]
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            default,
+            key.description,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+        if ctx.verbose {
+            println!("{:<w0$}  env {}", "", key.env_var(), w0 = widths[0]);
+        }
+    }
     Ok(())
 }
 
+/// Edit the user file in an editor, reopening it until the content is valid
+fn edit_config(ctx: &Context) -> Result<()> {
+    let path = ctx.overrides.user_file();
+    let original = if path.exists() {
+        std::fs::read_to_string(&path)?
+    } else {
+        format!("# rmz configuration; run 'rmz config list-keys' for every setting\nversion = {}\n", SCHEMA_VERSION)
+    };
+
+    let mut text = original.clone();
+    loop {
+        let Some(edited) = Editor::new().extension(".toml").edit(&text)? else {
+            println!("Configuration unchanged (editor closed without saving)");
+            return Ok(());
+        };
+        match validate_file(Origin::User(path.clone()), &edited) {
+            Ok(()) => {
+                text = edited;
+                break;
+            }
+            // Without a terminal there is nobody to ask whether to edit again
+            Err(e) if !std::io::stdin().is_terminal() => return Err(e.context("Configuration not saved")),
+            Err(e) => {
+                #[cfg(feature = "colors")]
+                eprintln!("❌ {}", format!("{:#}", e).red());
+                #[cfg(not(feature = "colors"))]
+                eprintln!("Error: {:#}", e);
+                if !Confirm::new().with_prompt("Edit again?").default(true).interact()? {
+                    println!("Discarded changes");
+                    return Ok(());
+                }
+                text = edited;
+            }
+        }
+    }
+
+    if text == original {
+        println!("Configuration unchanged");
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, &text)?;
+
+    #[cfg(feature = "colors")]
+    println!("✅ Saved {}", path.display().to_string().green());
+    #[cfg(not(feature = "colors"))]
+    println!("Saved {}", path.display());
+    Ok(())
+}
+
+/// Print every setting with the layer that set it
+fn show_origins(ctx: &Context) -> Result<()> {
+    let resolved = ctx.overrides.resolve()?;
+
+    for key in KEYS {
+        if key.is_additive() {
+            println!("{}:", key.name);
+            for (value, origin) in resolved.entries(key.name) {
+                print_with_origin(&format!("  {}", display_value(value)), origin);
+            }
+            continue;
+        }
+
+        let value = resolved.value(key.name).map_or(UNSET.to_string(), display_value);
+        let line = format!("{} = {}", key.name, value);
+        match resolved.origin(key.name) {
+            Some(origin) => print_with_origin(&line, origin),
+            None => print_with_origin(&line, &Origin::Default),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_with_origin(line: &str, origin: &Origin) {
+    #[cfg(feature = "colors")]
+    println!("{:<48} {}", line, format!("# {}", origin).dimmed());
+    #[cfg(not(feature = "colors"))]
+    println!("{:<48} # {}", line, origin);
+}
+
+/// Print the effective value of one setting, lists one item per line
+fn get_config_value(ctx: &Context, name: &str, origin: bool) -> Result<()> {
+    let key = find_key(name).ok_or_else(|| anyhow!("Unknown configuration key: {}", name))?;
+    let resolved = ctx.overrides.resolve()?;
+
+    if key.is_additive() {
+        for (value, item_origin) in resolved.entries(key.name) {
+            if origin {
+                print_with_origin(&display_value(value), item_origin);
+            } else {
+                println!("{}", display_value(value));
+            }
+        }
+        return Ok(());
+    }
+
+    let value = resolved.value(key.name).map_or(UNSET.to_string(), display_value);
+    match resolved.origin(key.name) {
+        Some(key_origin) if origin => print_with_origin(&value, key_origin),
+        _ => println!("{}", value),
+    }
+    Ok(())
+}
+
+/// A value as users write it: strings unquoted, lists comma-separated
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Array(items) => items.iter().map(display_value).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
 /// Reset configuration to defaults
-fn reset_config(verbose: bool) -> Result<()> {
-    let config_path = Config::config_file_path();
+fn reset_config(ctx: &Context) -> Result<()> {
+    let verbose = ctx.verbose;
+    let config_path = ctx.overrides.user_file();
     
     if config_path.exists() {
         std::fs::remove_file(&config_path)?;
@@ -180,17 +446,8 @@ fn reset_config(verbose: bool) -> Result<()> {
         }
     }
     
-    // Create new default config
-    let default_config = Config::default();
-    default_config.ensure_directories()?;
-    default_config.save()?;
-    
-    if verbose {
-        #[cfg(feature = "colors")]
-        println!("📝 Created new default config: {}", config_path.display().to_string().green());
-        #[cfg(not(feature = "colors"))]
-        println!("Created new default config: {}", config_path.display());
-    }
+    // Without a user file the defaults apply, below any system, project or environment settings
+    ctx.load_config()?.ensure_directories()?;
     
     #[cfg(feature = "colors")]
     println!("✅ Configuration reset to defaults");
@@ -242,58 +499,6 @@ fn show_storage_info(config: &Config) -> Result<()> {
     Ok(())
 }
 
-/// Parse a boolean value from string
-fn parse_bool(value: &str) -> Result<bool> {
-    match value.to_lowercase().as_str() {
-        "true" | "yes" | "on" | "1" | "enabled" => Ok(true),
-        "false" | "no" | "off" | "0" | "disabled" => Ok(false),
-        _ => Err(anyhow!("Invalid boolean value: {}. Use true/false, yes/no, on/off, 1/0, or enabled/disabled", value)),
-    }
-}
-
-/// Parse size string (e.g., "100MB", "1GB") to bytes
-fn parse_size(size_str: &str) -> Result<u64> {
-    let size_str = size_str.to_uppercase();
-    
-    let (number_part, unit_part) = if size_str.ends_with("KB") {
-        (size_str.trim_end_matches("KB"), 1024u64)
-    } else if size_str.ends_with("MB") {
-        (size_str.trim_end_matches("MB"), 1024u64.pow(2))
-    } else if size_str.ends_with("GB") {
-        (size_str.trim_end_matches("GB"), 1024u64.pow(3))
-    } else if size_str.ends_with("TB") {
-        (size_str.trim_end_matches("TB"), 1024u64.pow(4))
-    } else if size_str.ends_with("B") {
-        (size_str.trim_end_matches("B"), 1u64)
-    } else {
-        // Assume bytes if no unit
-        (size_str.as_str(), 1u64)
-    };
-    
-    let number: f64 = number_part.parse()
-        .map_err(|_| anyhow!("Invalid size format: {}", size_str))?;
-    
-    Ok((number * unit_part as f64) as u64)
-}
-
-/// Format bytes to human readable size
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-    
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
-    }
-    
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
-    }
-}
-
 /// Calculate total size of a directory
 fn calculate_directory_size(dir: &PathBuf) -> Result<u64> {
     let mut total_size = 0u64;
@@ -338,52 +543,6 @@ fn count_files_in_directory(dir: &PathBuf) -> Result<usize> {
 mod tests {
     use super::*;
     
-    #[test]
-    fn test_parse_bool() {
-        // Test true values
-        assert_eq!(parse_bool("true").unwrap(), true);
-        assert_eq!(parse_bool("yes").unwrap(), true);
-        assert_eq!(parse_bool("on").unwrap(), true);
-        assert_eq!(parse_bool("1").unwrap(), true);
-        assert_eq!(parse_bool("enabled").unwrap(), true);
-        assert_eq!(parse_bool("TRUE").unwrap(), true); // Case insensitive
-        
-        // Test false values
-        assert_eq!(parse_bool("false").unwrap(), false);
-        assert_eq!(parse_bool("no").unwrap(), false);
-        assert_eq!(parse_bool("off").unwrap(), false);
-        assert_eq!(parse_bool("0").unwrap(), false);
-        assert_eq!(parse_bool("disabled").unwrap(), false);
-        assert_eq!(parse_bool("FALSE").unwrap(), false); // Case insensitive
-        
-        // Test invalid values
-        assert!(parse_bool("invalid").is_err());
-        assert!(parse_bool("").is_err());
-    }
-    
-    #[test]
-    fn test_parse_size() {
-        assert_eq!(parse_size("1024").unwrap(), 1024);
-        assert_eq!(parse_size("1KB").unwrap(), 1024);
-        assert_eq!(parse_size("1MB").unwrap(), 1024 * 1024);
-        assert_eq!(parse_size("1GB").unwrap(), 1024 * 1024 * 1024);
-        assert_eq!(parse_size("1.5MB").unwrap(), (1.5 * 1024.0 * 1024.0) as u64);
-        assert_eq!(parse_size("500B").unwrap(), 500);
-        
-        // Test invalid formats
-        assert!(parse_size("invalid").is_err());
-        assert!(parse_size("1.5.5MB").is_err());
-    }
-    
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-    }
-    
     #[test]
     fn test_calculate_directory_size() {
         use tempfile::TempDir;
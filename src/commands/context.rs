@@ -0,0 +1,71 @@
+use crate::cli::Cli;
+use crate::domain::config_layers::ConfigOverrides;
+use crate::domain::Config;
+use crate::infra::hooks::Hooks;
+use crate::infra::operation_logger::{init_operation_logger, log_trash_event};
+use crate::trash::{Trash, TrashEvent};
+use anyhow::Result;
+
+/// State shared by every command, built from the global options
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    /// Enable verbose output
+    pub verbose: bool,
+    /// `--config`, `--trash-dir` and `--profile`
+    pub overrides: ConfigOverrides,
+    /// `--no-hooks`
+    pub no_hooks: bool,
+}
+
+impl Context {
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self {
+            verbose: cli.verbose,
+            overrides: ConfigOverrides {
+                config_file: cli.config.clone(),
+                trash_dir: cli.trash_dir.clone(),
+                profile: cli.profile.clone(),
+            },
+            no_hooks: cli.no_hooks,
+        }
+    }
+
+    /// Load the configuration selected by the global options
+    pub fn load_config(&self) -> Result<Config> {
+        let config = self.overrides.load()?;
+        // Later log entries must land in the selected trash, not the default one
+        let _ = init_operation_logger(&config);
+        Ok(config)
+    }
+
+    /// Save changed settings to the selected user file and profile
+    pub fn save_config(&self, config: &Config) -> Result<()> {
+        self.overrides.save(config)
+    }
+
+    /// The hooks configured in `config`, unless `--no-hooks` was given
+    pub fn hooks(&self, config: &Config) -> Hooks {
+        if self.no_hooks {
+            Hooks::disabled()
+        } else {
+            Hooks::new(config.hooks.clone())
+        }
+    }
+
+    /// Open the trash selected by the global options, running its hooks and
+    /// logging what it deletes, restores and purges
+    pub fn open_trash(&self) -> Result<Trash> {
+        self.open_trash_with(self.load_config()?)
+    }
+
+    /// Like [`Context::open_trash`], for a configuration the command already loaded
+    pub fn open_trash_with(&self, config: Config) -> Result<Trash> {
+        let hooks = self.hooks(&config);
+        Ok(Trash::open(config)?.with_hooks(hooks).with_listener(|event| {
+            if let TrashEvent::Warning(message) = event {
+                eprintln!("Warning: {}", message);
+            }
+            log_trash_event(event);
+        }))
+    }
+}
@@ -1,25 +1,31 @@
-use crate::domain::{Config, FileMeta};
-use crate::infra::meta_store::MetaStoreInterface;
-use crate::infra::trash_store::TrashStoreInterface;
-use crate::infra::{ConfigManager, MetaStore, TrashStore};
+use super::Context;
+use crate::core::RmzError;
+use crate::domain::{Config, TrashItem};
+use crate::domain::operation_log::{OperationLog, OperationResult, OperationType};
+use crate::infra::operation_logger::log_operation;
+use crate::infra::trash_store::ENCRYPTED_PATH_PLACEHOLDER;
+use crate::infra::ConfigManager;
+use crate::trash::{DeleteOptions, Trash};
 use anyhow::Result;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 /// Execute delete command
+#[allow(clippy::too_many_arguments)]
 pub fn execute(
+    ctx: &Context,
     paths: Vec<PathBuf>,
     force: bool,
     dry_run: bool,
-    tag: Option<String>,
+    tags: Vec<String>,
+    note: Option<String>,
     interactive: bool,
     recursive: bool,
-    verbose: bool,
 ) -> Result<()> {
-    let config = ConfigManager::load()?;
+    let config = ctx.load_config()?;
     ConfigManager::initialize(&config)?;
 
-    let trash_store = TrashStore::new(config.trash_path.clone());
-    let meta_store = MetaStore::new(config.metadata_path());
+    let trash = ctx.open_trash_with(config)?;
 
     if dry_run {
         println!("DRY RUN: Would delete the following files:");
@@ -29,19 +35,31 @@ pub fn execute(
         return Ok(());
     }
 
+    let note = match note {
+        Some(note) => Some(note),
+        None => prompt_for_note(trash.config(), paths.len(), force)?,
+    };
+    let options = CliDeleteOptions {
+        force,
+        interactive,
+        recursive,
+        verbose: ctx.verbose,
+        tags,
+        note,
+    };
+
+    if let Err(e) = trash.check_delete(&paths, &options.to_trash_options()) {
+        let _ = log_operation(cancelled_entry(trash.config(), &paths, &e));
+        anyhow::bail!(e);
+    }
+
     let mut deleted_files = Vec::new();
 
     for path in paths {
-        let options = DeleteOptions {
-            force,
-            interactive,
-            recursive,
-            verbose,
-        };
-        match delete_path(&path, &config, &trash_store, &meta_store, &tag, &options) {
-            Ok(meta) => {
-                deleted_files.push(meta);
-                if verbose {
+        match delete_path(&path, &trash, &options) {
+            Ok(item) => {
+                deleted_files.push(item);
+                if ctx.verbose {
                     println!("✅ Moved to trash: {}", path.display());
                 }
             }
@@ -51,32 +69,81 @@ pub fn execute(
         }
     }
 
-    if !deleted_files.is_empty() {
+    trash.finish_delete(&deleted_files);
+
+    if !ctx.verbose && !deleted_files.is_empty() {
         println!(
             "Successfully moved {} file(s) to trash",
             deleted_files.len()
         );
     }
 
+    // Compress items that are old enough; failures are reported as warnings
+    let compressed = trash.compress_eligible();
+    if ctx.verbose && !compressed.is_empty() {
+        println!("Compressed {} item(s) in trash", compressed.len());
+    }
+
     Ok(())
 }
 
-struct DeleteOptions {
+/// Log entry for a deletion refused before anything was moved
+///
+/// With encrypted paths the paths, and the error naming them, stay out of the log.
+fn cancelled_entry(config: &Config, paths: &[PathBuf], error: &RmzError) -> OperationLog {
+    if config.encryption.enabled && config.encryption.encrypt_paths {
+        let hidden = vec![PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER); paths.len()];
+        OperationLog::new(OperationType::Delete, hidden, OperationResult::Cancelled)
+    } else {
+        OperationLog::new(OperationType::Delete, paths.to_vec(), OperationResult::Cancelled)
+            .with_context(error.to_string())
+    }
+}
+
+struct CliDeleteOptions {
     force: bool,
     interactive: bool,
     recursive: bool,
     verbose: bool,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+impl CliDeleteOptions {
+    fn to_trash_options(&self) -> DeleteOptions {
+        DeleteOptions {
+            recursive: self.recursive,
+            tags: self.tags.clone(),
+            note: self.note.clone(),
+        }
+    }
+}
+
+/// Ask why the files are being deleted when more than `notes.prompt_threshold` are given
+///
+/// Never prompts with `--force` or when stdin is not a terminal, so scripts are unaffected.
+fn prompt_for_note(config: &Config, count: usize, force: bool) -> Result<Option<String>> {
+    let Some(threshold) = config.notes.prompt_threshold else {
+        return Ok(None);
+    };
+    if force || count <= threshold || !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    let note: String = dialoguer::Input::new()
+        .with_prompt(format!("Deleting {} paths; note why (empty to skip)", count))
+        .allow_empty(true)
+        .interact_text()?;
+    let note = note.trim();
+    Ok((!note.is_empty()).then(|| note.to_string()))
 }
 
 /// Delete a path (file or directory)
 fn delete_path(
     path: &PathBuf,
-    config: &Config,
-    trash_store: &TrashStore,
-    meta_store: &MetaStore,
-    tag: &Option<String>,
-    options: &DeleteOptions,
-) -> Result<FileMeta> {
+    trash: &Trash,
+    options: &CliDeleteOptions,
+) -> Result<TrashItem> {
     // Check if file exists
     if !path.exists() {
         anyhow::bail!("Path does not exist: {}", path.display());
@@ -84,34 +151,24 @@ fn delete_path(
 
     // Check if it's a directory
     if path.is_dir() {
-        delete_directory(path, config, trash_store, meta_store, tag, options)
+        delete_directory(path, trash, options)
     } else {
-        delete_single_file(path, config, trash_store, meta_store, tag, options)
+        delete_single_file(path, trash, options)
     }
 }
 
 /// Delete a directory
 fn delete_directory(
     path: &PathBuf,
-    config: &Config,
-    trash_store: &TrashStore,
-    meta_store: &MetaStore,
-    tag: &Option<String>,
-    options: &DeleteOptions,
-) -> Result<FileMeta> {
+    trash: &Trash,
+    options: &CliDeleteOptions,
+) -> Result<TrashItem> {
     // Check if directory is empty
     let is_empty = path.read_dir()?.next().is_none();
-    
-    if !is_empty && !options.recursive {
-        anyhow::bail!(
-            "Cannot remove directory '{}': Directory not empty (use -r to delete recursively)", 
-            path.display()
-        );
-    }
 
-    // Check if path is protected
-    if config.is_protected(path) {
-        anyhow::bail!("Directory is protected from deletion: {}", path.display());
+    // Let the library reject non-empty or protected directories before prompting
+    if (!is_empty && !options.recursive) || trash.config().is_protected(path) {
+        return Ok(trash.delete_path(path, &options.to_trash_options())?);
     }
 
     // For non-empty directories, show warning and get confirmation
@@ -128,19 +185,6 @@ fn delete_directory(
         }
     }
 
-    // Create metadata for the directory
-    let mut meta = FileMeta::from_path(path.as_path())?;
-    
-    // For directories, calculate total size recursively
-    if path.is_dir() {
-        meta.size = calculate_directory_size(path)?;
-    }
-
-    // Add tag if provided
-    if let Some(tag_value) = tag {
-        meta.add_tag(tag_value.clone());
-    }
-
     if options.verbose {
         if is_empty {
             println!("Moving empty directory {} to trash...", path.display());
@@ -150,27 +194,21 @@ fn delete_directory(
     }
 
     // Move entire directory to trash
-    let _trash_item = trash_store.save(&meta, path)?;
-    meta_store.save_metadata(&meta)?;
-
-    Ok(meta)
+    Ok(trash.delete_path(path, &options.to_trash_options())?)
 }
 
 fn delete_single_file(
     path: &PathBuf,
-    config: &Config,
-    trash_store: &TrashStore,
-    meta_store: &MetaStore,
-    tag: &Option<String>,
-    options: &DeleteOptions,
-) -> Result<FileMeta> {
+    trash: &Trash,
+    options: &CliDeleteOptions,
+) -> Result<TrashItem> {
     // Check if file exists
     if !path.exists() {
         anyhow::bail!("File does not exist: {}", path.display());
     }
 
     // Check if path is protected
-    if config.is_protected(path) {
+    if trash.config().is_protected(path) {
         anyhow::bail!("Path is protected from deletion: {}", path.display());
     }
 
@@ -179,23 +217,12 @@ fn delete_single_file(
         anyhow::bail!("Deletion cancelled by user");
     }
 
-    // Create metadata
-    let mut meta = FileMeta::from_path(path.as_path())?;
-
-    // Add tag if provided
-    if let Some(tag_value) = tag {
-        meta.add_tag(tag_value.clone());
-    }
-
     if options.verbose {
         println!("Moving {} to trash...", path.display());
     }
 
     // Move to trash and save metadata
-    let _trash_item = trash_store.save(&meta, path)?;
-    meta_store.save_metadata(&meta)?;
-
-    Ok(meta)
+    Ok(trash.delete_path(path, &options.to_trash_options())?)
 }
 
 /// Count the total number of files and directories in a directory recursively
@@ -218,28 +245,6 @@ fn count_directory_contents(path: &std::path::Path) -> Result<usize> {
     Ok(count)
 }
 
-/// Calculate the total size of a directory recursively
-fn calculate_directory_size(path: &std::path::Path) -> Result<u64> {
-    let mut total_size = 0;
-    
-    fn calculate_recursive(path: &std::path::Path, total_size: &mut u64) -> Result<()> {
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
-            
-            if metadata.is_file() {
-                *total_size += metadata.len();
-            } else if metadata.is_dir() {
-                calculate_recursive(&entry.path(), total_size)?;
-            }
-        }
-        Ok(())
-    }
-    
-    calculate_recursive(path, &mut total_size)?;
-    Ok(total_size)
-}
-
 fn confirm_deletion(path: &std::path::Path) -> Result<bool> {
     use dialoguer::Confirm;
 
@@ -269,6 +274,7 @@ fn confirm_directory_deletion(path: &std::path::Path, file_count: usize) -> Resu
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::Config;
     use std::fs;
     use tempfile::{NamedTempFile, TempDir};
 
@@ -281,8 +287,7 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         // Create test file
         let test_file = NamedTempFile::new().unwrap();
@@ -290,18 +295,17 @@ mod tests {
         fs::write(&file_path, "test content").unwrap();
 
         // Delete file
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: false,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_single_file(
             &file_path,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -321,23 +325,21 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         let nonexistent_path = PathBuf::from("/nonexistent/file.txt");
 
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: false,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_single_file(
             &nonexistent_path,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -348,36 +350,53 @@ mod tests {
             .contains("File does not exist"));
     }
 
+    #[test]
+    fn test_cancelled_entry_hides_encrypted_paths() {
+        let paths = vec![PathBuf::from("/home/user/.ssh/id_ed25519")];
+        let error = RmzError::Protected { path: paths[0].clone() };
+
+        let mut config = Config::default();
+        let entry = cancelled_entry(&config, &paths, &error);
+        assert_eq!(entry.paths, paths);
+        assert!(entry.context.is_some());
+
+        config.encryption.enabled = true;
+        config.encryption.encrypt_paths = true;
+        let entry = cancelled_entry(&config, &paths, &error);
+        assert_eq!(entry.paths, vec![PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER)]);
+        assert!(entry.context.is_none());
+    }
+
     #[test]
     fn test_delete_protected_file() {
         let temp_dir = TempDir::new().unwrap();
-        let mut config = Config::default();
-        config.trash_path = temp_dir.path().join("trash");
+        let mut config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            ..Config::default()
+        };
 
         // Add a protected path
         let protected_dir = temp_dir.path().join("protected");
         fs::create_dir_all(&protected_dir).unwrap();
         config.add_protected_path(protected_dir.clone());
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         // Create test file in protected directory
         let protected_file = protected_dir.join("important.txt");
         fs::write(&protected_file, "important content").unwrap();
 
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: false,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_single_file(
             &protected_file,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -395,25 +414,23 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         // Create empty directory
         let empty_dir = temp_dir.path().join("empty_dir");
         fs::create_dir(&empty_dir).unwrap();
 
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: false,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_directory(
             &empty_dir,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -430,26 +447,24 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         // Create directory with file
         let dir_with_file = temp_dir.path().join("dir_with_file");
         fs::create_dir(&dir_with_file).unwrap();
         fs::write(dir_with_file.join("file.txt"), "content").unwrap();
 
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: false,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_directory(
             &dir_with_file,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -467,8 +482,7 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
-        let meta_store = MetaStore::new(config.metadata_path());
+        let trash = Trash::open(config).unwrap();
 
         // Create directory with nested structure
         let dir_with_files = temp_dir.path().join("dir_with_files");
@@ -479,18 +493,17 @@ mod tests {
         fs::create_dir(&subdir).unwrap();
         fs::write(subdir.join("file2.txt"), "content2").unwrap();
 
-        let options = DeleteOptions {
+        let options = CliDeleteOptions {
             force: true,
             interactive: false,
             recursive: true,
             verbose: false,
+            tags: Vec::new(),
+            note: None,
         };
         let result = delete_directory(
             &dir_with_files,
-            &config,
-            &trash_store,
-            &meta_store,
-            &None,
+            &trash,
             &options,
         );
 
@@ -515,22 +528,4 @@ mod tests {
         let count = count_directory_contents(&test_dir).unwrap();
         assert_eq!(count, 4); // 2 files + 1 subdir + 1 file in subdir
     }
-
-    #[test]
-    fn test_calculate_directory_size() {
-        let temp_dir = TempDir::new().unwrap();
-        let test_dir = temp_dir.path().join("test_dir");
-        fs::create_dir(&test_dir).unwrap();
-
-        // Create files with known sizes
-        fs::write(test_dir.join("file1.txt"), "12345").unwrap(); // 5 bytes
-        fs::write(test_dir.join("file2.txt"), "1234567890").unwrap(); // 10 bytes
-        
-        let subdir = test_dir.join("subdir");
-        fs::create_dir(&subdir).unwrap();
-        fs::write(subdir.join("file3.txt"), "123").unwrap(); // 3 bytes
-
-        let size = calculate_directory_size(&test_dir).unwrap();
-        assert_eq!(size, 18); // 5 + 10 + 3 = 18 bytes
-    }
 }
@@ -0,0 +1,110 @@
+use super::Context;
+use crate::domain::TrashItem;
+use crate::infra::diff::{self, ChangeKind, FileSummary, ItemDiff};
+use crate::trash::Trash;
+use crate::utils::size_parser::format_size;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Execute the diff command
+pub fn execute(ctx: &Context, id: String, path: Option<PathBuf>) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let item = trash.find_one(&id)?;
+    let current = path.unwrap_or_else(|| item.meta.original_path.clone());
+
+    print_diff(&trash, &item, &current, ctx.verbose)
+}
+
+/// Print how a trashed item differs from what is at `current`
+pub fn print_diff(trash: &Trash, item: &TrashItem, current: &Path, verbose: bool) -> Result<()> {
+    match diff::diff_item(trash.store(), item, current)? {
+        ItemDiff::Identical => {
+            println!("✅ No differences between the trashed copy and {}", current.display());
+        }
+        ItemDiff::Missing => {
+            println!("Nothing exists at {}; restoring there overwrites nothing", current.display());
+        }
+        ItemDiff::KindMismatch { trashed_is_dir } => {
+            let (trashed, existing) = if trashed_is_dir {
+                ("a directory", "a file")
+            } else {
+                ("a file", "a directory")
+            };
+            println!("⚠️  The trashed item is {} but {} is {}", trashed, current.display(), existing);
+        }
+        ItemDiff::Text(unified) => print_unified(&unified),
+        ItemDiff::Binary { trashed, current: existing } => {
+            println!("Binary files differ: trash:{} and {}", item.meta.original_path.display(), current.display());
+            print_summary(
+                "trashed",
+                &trashed,
+                &format!("deleted {}", item.meta.deleted_at.format("%Y-%m-%d %H:%M:%S")),
+                verbose,
+            );
+            let modified = existing
+                .modified
+                .map(|time| format!("modified {}", time.format("%Y-%m-%d %H:%M:%S")))
+                .unwrap_or_default();
+            print_summary("current", &existing, &modified, verbose);
+        }
+        ItemDiff::Directory(changes) => {
+            for change in &changes {
+                let name = format!("{}{}", change.path.display(), if change.is_dir { "/" } else { "" });
+                match change.kind {
+                    #[cfg(feature = "colors")]
+                    ChangeKind::OnlyInTrash => println!("{} {} (only in trash)", "-".red(), name.red()),
+                    #[cfg(feature = "colors")]
+                    ChangeKind::OnlyOnDisk => println!("{} {} (only on disk)", "+".green(), name.green()),
+                    #[cfg(feature = "colors")]
+                    ChangeKind::Changed => println!("{} {} (changed)", "~".yellow(), name.yellow()),
+                    #[cfg(not(feature = "colors"))]
+                    ChangeKind::OnlyInTrash => println!("- {} (only in trash)", name),
+                    #[cfg(not(feature = "colors"))]
+                    ChangeKind::OnlyOnDisk => println!("+ {} (only on disk)", name),
+                    #[cfg(not(feature = "colors"))]
+                    ChangeKind::Changed => println!("~ {} (changed)", name),
+                }
+            }
+
+            let count = |kind: ChangeKind| changes.iter().filter(|change| change.kind == kind).count();
+            println!(
+                "{} changed, {} only in trash, {} only on disk",
+                count(ChangeKind::Changed),
+                count(ChangeKind::OnlyInTrash),
+                count(ChangeKind::OnlyOnDisk)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_unified(unified: &str) {
+    for line in unified.lines() {
+        #[cfg(feature = "colors")]
+        {
+            if line.starts_with("---") || line.starts_with("+++") {
+                println!("{}", line.bold());
+            } else if line.starts_with("@@") {
+                println!("{}", line.cyan());
+            } else if line.starts_with('-') {
+                println!("{}", line.red());
+            } else if line.starts_with('+') {
+                println!("{}", line.green());
+            } else {
+                println!("{}", line);
+            }
+        }
+        #[cfg(not(feature = "colors"))]
+        println!("{}", line);
+    }
+}
+
+fn print_summary(label: &str, summary: &FileSummary, when: &str, verbose: bool) {
+    let hash = if verbose { &summary.sha256[..] } else { &summary.sha256[..16] };
+    println!("  {}: {} sha256 {} {}", label, format_size(summary.size), hash, when);
+}
+
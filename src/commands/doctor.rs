@@ -1,19 +1,26 @@
+use super::Context;
+use crate::domain::config_schema::{check_date_format, file_version, unknown_keys, SCHEMA_VERSION};
+use crate::domain::operation_log::{OperationLog, OperationResult, OperationType};
 use crate::domain::Config;
+use crate::infra::operation_logger::log_operation;
+use crate::infra::trash_repair::{self, Finding, RepairOptions};
 use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
 use anyhow::Result;
 use std::fs;
+use std::path::Path;
 use dialoguer::Confirm;
 
 #[cfg(feature = "colors")]
 use colored::Colorize;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DiagnosticCheck {
     TrashZone,
     Metadata,
     Permissions,
     Config,
     Dependencies,
+    Encryption,
     All,
 }
 
@@ -25,25 +32,61 @@ pub enum Severity {
     Critical,
 }
 
+/// Automatic fix for a diagnostic issue
+#[derive(Debug, Clone)]
+pub enum Fix {
+    CreateTrashDir,
+    MigrateConfig,
+    Repair(Finding),
+}
+
 #[derive(Debug, Clone)]
 pub struct DiagnosticIssue {
     pub check_type: DiagnosticCheck,
     pub severity: Severity,
     pub message: String,
     pub suggestion: Option<String>,
-    pub fixable: bool,
+    pub fix: Option<Fix>,
+}
+
+impl DiagnosticIssue {
+    pub fn fixable(&self) -> bool {
+        self.fix.is_some()
+    }
 }
 
 /// Execute the doctor command
 pub fn execute(
+    ctx: &Context,
     check: Option<DiagnosticCheck>,
     fix: bool,
+    dry_run: bool,
+    quarantine: bool,
     verbose: bool,
     force: bool,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let trash_root = config.trash_path.clone();
-    let trash_store = TrashStore::new(trash_root.clone());
+    let config = match ctx.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            // Nothing else can be checked without a configuration
+            let mut issues = check_config_files(ctx)?;
+            issues.push(DiagnosticIssue {
+                check_type: DiagnosticCheck::Config,
+                severity: Severity::Critical,
+                message: format!("Configuration cannot be loaded: {:#}", e),
+                suggestion: Some("Fix the value with 'rmz config edit' or 'rmz config unset'".to_string()),
+                fix: None,
+            });
+            return display_diagnostic_results(&issues, verbose);
+        }
+    };
+    let trash = ctx.open_trash_with(config.clone())?;
+    let trash_store = trash.store();
+    let findings = trash_repair::scan(trash_store, &config.metadata_path()).unwrap_or_else(|e| {
+        eprintln!("⚠️ Could not scan the trash for inconsistencies: {:#}", e);
+        Vec::new()
+    });
+    let options = RepairOptions { quarantine };
     
     let checks_to_run = match check {
         Some(DiagnosticCheck::All) | None => vec![
@@ -52,6 +95,7 @@ pub fn execute(
             DiagnosticCheck::Permissions,
             DiagnosticCheck::Config,
             DiagnosticCheck::Dependencies,
+            DiagnosticCheck::Encryption,
         ],
         Some(specific_check) => vec![specific_check],
     };
@@ -66,7 +110,7 @@ pub fn execute(
             println!("Running {:?} check...", check_type);
         }
         
-        let issues = run_diagnostic_check(&check_type, &trash_store, &config)?;
+        let issues = run_diagnostic_check(ctx, &check_type, trash_store, &config, &findings)?;
         all_issues.extend(issues);
     }
     
@@ -77,11 +121,11 @@ pub fn execute(
     if fix && !all_issues.is_empty() {
         let fixable_issues: Vec<_> = all_issues
             .iter()
-            .filter(|issue| issue.fixable)
+            .filter(|issue| issue.fixable())
             .collect();
             
         if !fixable_issues.is_empty() {
-            if !force {
+            if !force && !dry_run {
                 let msg = format!("Fix {} fixable issues automatically?", fixable_issues.len());
                 if !Confirm::new().with_prompt(msg).interact()? {
                     println!("Fix cancelled");
@@ -89,7 +133,7 @@ pub fn execute(
                 }
             }
             
-            fix_issues(&fixable_issues, &trash_store, &config, verbose)?;
+            fix_issues(ctx, &fixable_issues, trash_store, &options, dry_run)?;
         } else {
             println!("No fixable issues found");
         }
@@ -99,9 +143,11 @@ pub fn execute(
 }
 
 fn run_diagnostic_check(
+    ctx: &Context,
     check_type: &DiagnosticCheck,
     trash_store: &TrashStore,
     config: &Config,
+    findings: &[Finding],
 ) -> Result<Vec<DiagnosticIssue>> {
     let mut issues = Vec::new();
     
@@ -116,19 +162,56 @@ fn run_diagnostic_check(
             issues.extend(check_permissions(trash_store, config)?);
         }
         DiagnosticCheck::Config => {
-            issues.extend(check_config_validity(config)?);
+            issues.extend(check_config_files(ctx)?);
+            issues.extend(check_config_validity(config));
         }
         DiagnosticCheck::Dependencies => {
             issues.extend(check_dependencies()?);
         }
+        DiagnosticCheck::Encryption => {
+            issues.extend(check_encryption(trash_store)?);
+        }
         DiagnosticCheck::All => {
             // This case is handled in execute function
         }
     }
+
+    issues.extend(
+        findings
+            .iter()
+            .map(finding_issue)
+            .filter(|issue| &issue.check_type == check_type),
+    );
     
     Ok(issues)
 }
 
+/// Report a trash inconsistency found by the repair scan
+fn finding_issue(finding: &Finding) -> DiagnosticIssue {
+    let (check_type, severity) = match finding {
+        Finding::MissingPayload { .. } | Finding::OrphanedPayload { .. } | Finding::LeftoverPayload { .. } => {
+            (DiagnosticCheck::TrashZone, Severity::Warning)
+        }
+        Finding::MismatchedPayload { .. } | Finding::MisplacedPayload { .. } => {
+            (DiagnosticCheck::TrashZone, Severity::Error)
+        }
+        Finding::StaleFile { .. } => (DiagnosticCheck::TrashZone, Severity::Info),
+        Finding::CorruptMetadata { .. } => (DiagnosticCheck::Metadata, Severity::Error),
+        Finding::DuplicateMetadata { .. } | Finding::LegacyMetadata { .. } => {
+            (DiagnosticCheck::Metadata, Severity::Warning)
+        }
+        Finding::Permissions { .. } => (DiagnosticCheck::Permissions, Severity::Warning),
+    };
+
+    DiagnosticIssue {
+        check_type,
+        severity,
+        message: finding.to_string(),
+        suggestion: Some(finding.action(&RepairOptions::default())),
+        fix: Some(Fix::Repair(finding.clone())),
+    }
+}
+
 fn check_trash_zone(trash_store: &TrashStore) -> Result<Vec<DiagnosticIssue>> {
     let mut issues = Vec::new();
     let trash_root = trash_store.get_trash_root();
@@ -140,7 +223,7 @@ fn check_trash_zone(trash_store: &TrashStore) -> Result<Vec<DiagnosticIssue>> {
             severity: Severity::Error,
             message: format!("Trash directory does not exist: {}", trash_root.display()),
             suggestion: Some("Create the trash directory".to_string()),
-            fixable: true,
+            fix: Some(Fix::CreateTrashDir),
         });
     } else if !trash_root.is_dir() {
         issues.push(DiagnosticIssue {
@@ -148,26 +231,10 @@ fn check_trash_zone(trash_store: &TrashStore) -> Result<Vec<DiagnosticIssue>> {
             severity: Severity::Critical,
             message: format!("Trash path exists but is not a directory: {}", trash_root.display()),
             suggestion: Some("Remove the file and recreate as directory".to_string()),
-            fixable: false,
+            fix: None,
         });
     }
     
-    // Check for orphaned metadata files
-    if trash_root.exists() {
-        let items = trash_store.list()?;
-        for item in items {
-            if !item.trash_path.exists() {
-                issues.push(DiagnosticIssue {
-                    check_type: DiagnosticCheck::TrashZone,
-                    severity: Severity::Warning,
-                    message: format!("Orphaned metadata: {} (file missing)", item.meta.original_path.display()),
-                    suggestion: Some("Remove orphaned metadata".to_string()),
-                    fixable: true,
-                });
-            }
-        }
-    }
-    
     Ok(issues)
 }
 
@@ -183,7 +250,7 @@ fn check_metadata_integrity(trash_store: &TrashStore) -> Result<Vec<DiagnosticIs
                     severity: Severity::Warning,
                     message: format!("Empty original path in metadata: {}", item.meta.id),
                     suggestion: Some("Remove corrupted metadata".to_string()),
-                    fixable: true,
+                    fix: None,
                 });
             }
             
@@ -194,7 +261,7 @@ fn check_metadata_integrity(trash_store: &TrashStore) -> Result<Vec<DiagnosticIs
                     severity: Severity::Info,
                     message: format!("Missing SHA256 checksum for: {}", item.meta.original_path.display()),
                     suggestion: Some("Recalculate SHA256 checksums".to_string()),
-                    fixable: true,
+                    fix: None,
                 });
             }
         }
@@ -209,13 +276,13 @@ fn check_permissions(trash_store: &TrashStore, _config: &Config) -> Result<Vec<D
     
     if trash_root.exists() {
         // Check if we can write to trash directory
-        if let Err(_) = fs::metadata(&trash_root) {
+        if fs::metadata(trash_root).is_err() {
             issues.push(DiagnosticIssue {
                 check_type: DiagnosticCheck::Permissions,
                 severity: Severity::Error,
                 message: format!("Cannot access trash directory: {}", trash_root.display()),
                 suggestion: Some("Check file permissions".to_string()),
-                fixable: false,
+                fix: None,
             });
         }
     }
@@ -223,22 +290,113 @@ fn check_permissions(trash_store: &TrashStore, _config: &Config) -> Result<Vec<D
     Ok(issues)
 }
 
-fn check_config_validity(_config: &Config) -> Result<Vec<DiagnosticIssue>> {
+/// Report unreadable, outdated or misspelled configuration files
+fn check_config_files(ctx: &Context) -> Result<Vec<DiagnosticIssue>> {
     let mut issues = Vec::new();
-    
-    // Basic config validation
-    // Add more comprehensive checks as needed
-    issues.push(DiagnosticIssue {
-        check_type: DiagnosticCheck::Config,
-        severity: Severity::Info,
-        message: "Configuration appears valid".to_string(),
-        suggestion: None,
-        fixable: false,
-    });
-    
+    let user_file = ctx.overrides.user_file();
+
+    for origin in ctx.overrides.files() {
+        let Some(path) = origin.file().filter(|path| path.exists()) else {
+            continue;
+        };
+        let issue = |severity, message: String, suggestion: &str, fix| DiagnosticIssue {
+            check_type: DiagnosticCheck::Config,
+            severity,
+            message,
+            suggestion: Some(suggestion.to_string()),
+            fix,
+        };
+
+        let table = match read_table(path) {
+            Ok(table) => table,
+            Err(e) => {
+                issues.push(issue(Severity::Critical, format!("Cannot read {}: {}", path.display(), e), "Fix the TOML syntax", None));
+                continue;
+            }
+        };
+
+        match file_version(&table) {
+            Ok(version) if version < SCHEMA_VERSION => issues.push(issue(
+                Severity::Warning,
+                format!("{} uses configuration schema version {} (current: {})", path.display(), version, SCHEMA_VERSION),
+                "Run 'rmz doctor --fix' to migrate it",
+                (path == user_file).then_some(Fix::MigrateConfig),
+            )),
+            Ok(_) => {}
+            Err(e) => issues.push(issue(Severity::Error, format!("{}: {}", path.display(), e), "Upgrade rmz", None)),
+        }
+
+        let unknown = unknown_keys(&table);
+        if !unknown.is_empty() {
+            issues.push(issue(
+                Severity::Warning,
+                format!("Unknown keys in {}: {}", path.display(), unknown.join(", ")),
+                "Remove or rename them; 'rmz config list-keys' shows every setting",
+                None,
+            ));
+        }
+    }
+
     Ok(issues)
 }
 
+fn read_table(path: &Path) -> Result<toml::Table> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Check the effective settings for values that load but cannot work
+fn check_config_validity(config: &Config) -> Vec<DiagnosticIssue> {
+    let mut problems = Vec::new();
+    let trash = &config.trash_path;
+
+    if !trash.is_absolute() {
+        problems.push((Severity::Error, format!("trash_path is not absolute: {}", trash.display())));
+    }
+    if let Some(protected) = config.protected_paths.iter().find(|protected| trash.starts_with(protected)) {
+        problems.push((
+            Severity::Error,
+            format!("trash_path {} is inside protected path {}", trash.display(), protected.display()),
+        ));
+    }
+    if let Err(e) = check_writable(trash) {
+        problems.push((Severity::Error, format!("trash_path {} is not writable: {}", trash.display(), e)));
+    }
+    if let Err(e) = check_date_format(&config.date_format) {
+        problems.push((Severity::Error, format!("date_format: {}", e)));
+    }
+    if let Some(key_file) = config.encryption.key_file.as_ref().filter(|path| !path.is_file()) {
+        problems.push((Severity::Error, format!("encryption.key_file {} does not exist", key_file.display())));
+    }
+    for relative in config.protected_paths.iter().filter(|path| path.is_relative()) {
+        problems.push((
+            Severity::Warning,
+            format!("Protected path {} is relative and depends on the current directory", relative.display()),
+        ));
+    }
+
+    problems
+        .into_iter()
+        .map(|(severity, message)| DiagnosticIssue {
+            check_type: DiagnosticCheck::Config,
+            severity,
+            message,
+            suggestion: Some("Change the setting with 'rmz config set' or 'rmz config edit'".to_string()),
+            fix: None,
+        })
+        .collect()
+}
+
+/// Create and remove a probe file in `dir`, or in its nearest existing ancestor
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    let existing = dir.ancestors().find(|ancestor| ancestor.exists()).unwrap_or(dir);
+    if !existing.is_dir() {
+        return Err(std::io::Error::other(format!("{} is not a directory", existing.display())));
+    }
+    let probe = existing.join(format!(".rmz-doctor-{}", std::process::id()));
+    fs::File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
 fn check_dependencies() -> Result<Vec<DiagnosticIssue>> {
     let mut issues = Vec::new();
     
@@ -249,13 +407,52 @@ fn check_dependencies() -> Result<Vec<DiagnosticIssue>> {
             severity: Severity::Info,
             message: "fzf not found - interactive features may be limited".to_string(),
             suggestion: Some("Install fzf for enhanced interactive mode".to_string()),
-            fixable: false,
+            fix: None,
         });
     }
     
     Ok(issues)
 }
 
+fn check_encryption(trash_store: &TrashStore) -> Result<Vec<DiagnosticIssue>> {
+    let mut issues = Vec::new();
+
+    let encrypted: Vec<_> = trash_store
+        .list()?
+        .into_iter()
+        .filter(|item| item.meta.is_encrypted())
+        .collect();
+    if encrypted.is_empty() {
+        return Ok(issues);
+    }
+
+    // Only ask for the secret when there is something to check
+    if let Err(e) = trash_store.key() {
+        issues.push(DiagnosticIssue {
+            check_type: DiagnosticCheck::Encryption,
+            severity: Severity::Warning,
+            message: format!("Cannot unlock encryption key: {}", e),
+            suggestion: Some("Set RMZ_PASSPHRASE or encryption.key_file and re-run".to_string()),
+            fix: None,
+        });
+        return Ok(issues);
+    }
+
+    for item in encrypted {
+        if let Err(e) = trash_store.verify_decryptable(&item) {
+            issues.push(DiagnosticIssue {
+                check_type: DiagnosticCheck::Encryption,
+                severity: Severity::Error,
+                message: format!("Undecryptable item {}: {}", item.meta.id, e),
+                suggestion: Some("Restore the matching key or purge the item".to_string()),
+                fix: None,
+            });
+        }
+    }
+
+    Ok(issues)
+}
+
 fn display_diagnostic_results(issues: &[DiagnosticIssue], verbose: bool) -> Result<()> {
     if issues.is_empty() {
         #[cfg(feature = "colors")]
@@ -320,7 +517,7 @@ fn display_diagnostic_results(issues: &[DiagnosticIssue], verbose: bool) -> Resu
             if let Some(suggestion) = &issue.suggestion {
                 println!("   💡 {}", suggestion);
             }
-            if issue.fixable {
+            if issue.fixable() {
                 #[cfg(feature = "colors")]
                 println!("   🔧 {}", "Fixable".green());
                 #[cfg(not(feature = "colors"))]
@@ -334,49 +531,122 @@ fn display_diagnostic_results(issues: &[DiagnosticIssue], verbose: bool) -> Resu
 }
 
 fn fix_issues(
+    ctx: &Context,
     issues: &[&DiagnosticIssue],
     trash_store: &TrashStore,
-    _config: &Config,
-    verbose: bool,
+    options: &RepairOptions,
+    dry_run: bool,
 ) -> Result<()> {
     let mut fixed_count = 0;
     
     for issue in issues {
-        if verbose {
-            println!("🔧 Fixing: {}", issue.message);
+        let Some(fix) = &issue.fix else { continue };
+        let action = match fix {
+            Fix::CreateTrashDir => "Create the trash directory".to_string(),
+            Fix::MigrateConfig => "Migrate to the current schema version".to_string(),
+            Fix::Repair(finding) => finding.action(options),
+        };
+
+        if dry_run {
+            println!("🔧 Would fix: {}", issue.message);
+            println!("   ➜ {}", action);
+            continue;
         }
-        
-        match issue.check_type {
-            DiagnosticCheck::TrashZone => {
-                if issue.message.contains("does not exist") {
-                    fs::create_dir_all(trash_store.get_trash_root())?;
-                    fixed_count += 1;
-                } else if issue.message.contains("Orphaned metadata") {
-                    // Extract UUID from message or use other method to identify
-                    // For now, we'll skip this complex fix
-                    if verbose {
-                        println!("   ⚠️ Manual intervention required for orphaned metadata");
-                    }
-                }
-            }
-            DiagnosticCheck::Metadata => {
-                // Complex metadata fixes would go here
-                if verbose {
-                    println!("   ⚠️ Metadata fixes require manual intervention");
-                }
-            }
-            _ => {
-                if verbose {
-                    println!("   ⚠️ No automatic fix available");
-                }
+        println!("🔧 Fixing: {}", issue.message);
+
+        let result = match fix {
+            Fix::CreateTrashDir => fs::create_dir_all(trash_store.get_trash_root()).map_err(Into::into),
+            Fix::MigrateConfig => ctx.overrides.migrate_user_file().map(|_| ()),
+            Fix::Repair(finding) => trash_repair::repair(trash_store, finding, options),
+        };
+
+        let (path, file_ids) = match fix {
+            Fix::CreateTrashDir => (trash_store.get_trash_root().clone(), Vec::new()),
+            Fix::MigrateConfig => (ctx.overrides.user_file(), Vec::new()),
+            Fix::Repair(finding) => (finding.path().to_path_buf(), finding.id().into_iter().collect()),
+        };
+        match &result {
+            Ok(()) => {
+                println!("   ✅ {}", action);
+                fixed_count += 1;
             }
+            Err(e) => eprintln!("   ❌ {}: {:#}", action, e),
+        }
+
+        let log_entry = OperationLog::new(
+            OperationType::Doctor,
+            vec![path],
+            match &result {
+                Ok(()) => OperationResult::Success,
+                Err(e) => OperationResult::Failed(format!("{:#}", e)),
+            },
+        )
+        .with_context(format!("repair: {} ({})", action, issue.message))
+        .with_file_ids(file_ids);
+        // The repair has already happened, so a logging failure is reported but does not stop the rest
+        if let Err(e) = log_operation(log_entry) {
+            eprintln!("   ⚠️  Failed to log repair: {:#}", e);
         }
     }
     
+    if dry_run {
+        println!("Dry run: {} issue(s) would be fixed", issues.len());
+        return Ok(());
+    }
+
     #[cfg(feature = "colors")]
     println!("✅ Fixed {} issues", fixed_count.to_string().green().bold());
     #[cfg(not(feature = "colors"))]
     println!("✅ Fixed {} issues", fixed_count);
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::config_layers::ConfigOverrides;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_validity() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            ..Config::default()
+        };
+        config.protected_paths = vec![PathBuf::from("/etc")];
+        assert!(check_config_validity(&config).is_empty());
+
+        config.protected_paths.push(temp_dir.path().to_path_buf());
+        config.encryption.key_file = Some(temp_dir.path().join("missing.key"));
+        let messages: Vec<String> = check_config_validity(&config).into_iter().map(|issue| issue.message).collect();
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert!(messages[0].contains("inside protected path"));
+        assert!(messages[1].contains("missing.key"));
+    }
+
+    #[test]
+    fn test_config_files_report_old_schema_and_unknown_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_file = temp_dir.path().join("config.toml");
+        fs::write(&user_file, "use_fzf = false\ncolour = true\n").unwrap();
+        let ctx = Context {
+            verbose: false,
+            overrides: ConfigOverrides {
+                config_file: Some(user_file.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let issues = check_config_files(&ctx).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("schema version 1") && issues[0].fixable());
+        assert!(issues[1].message.contains("colour"));
+
+        assert!(ctx.overrides.migrate_user_file().unwrap());
+        assert!(fs::read_to_string(&user_file).unwrap().contains("version = 2"));
+        assert_eq!(check_config_files(&ctx).unwrap().len(), 1);
+    }
+}
@@ -1,5 +1,8 @@
-use crate::infra::{trash_store::TrashStoreInterface, TrashStore, create_selector};
-use crate::domain::{Config, TrashItem};
+use super::Context;
+use crate::domain::config_layers::ConfigOverrides;
+use crate::infra::{TrashStore, create_selector};
+use crate::domain::TrashItem;
+use crate::utils::size_parser::format_size;
 use anyhow::Result;
 use std::path::PathBuf;
 
@@ -7,32 +10,34 @@ use std::path::PathBuf;
 use colored::Colorize;
 
 /// Execute the extract command
+#[allow(clippy::too_many_arguments)]
 pub fn execute(
+    ctx: &Context,
     from: Option<String>,
     file: Option<String>,
     interactive: bool,
     all: bool,
     to: Option<PathBuf>,
     tree: bool,
-    verbose: bool,
+    tags: Vec<String>,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let trash_store = TrashStore::new(config.trash_path.clone());
+    let trash = ctx.open_trash()?;
+    let trash_store = trash.store();
 
     if let Some(from_id) = from {
         // Extract from specific directory ID
-        extract_from_directory(&trash_store, &from_id, file, to, tree, verbose)
+        extract_from_directory(trash_store, &from_id, file, to, tree, ctx.verbose)
     } else if interactive {
         // Interactive extraction
-        extract_interactive(&trash_store, file, to, verbose)
-    } else if all {
-        // Extract all files matching pattern
-        extract_all(&trash_store, file, to, verbose)
+        extract_interactive(trash_store, &ctx.overrides, file, &tags, to, ctx.verbose)
+    } else if all || (file.is_none() && !tags.is_empty()) {
+        // Extract all files matching pattern and tags
+        extract_all(trash_store, file, &tags, to, ctx.verbose)
     } else if let Some(filename) = file {
         // Extract specific file by name
-        extract_by_filename(&trash_store, &filename, to, verbose)
+        extract_by_filename(trash_store, &filename, &tags, to, ctx.verbose)
     } else {
-        anyhow::bail!("Must specify one of: --from, --file, --interactive, or --all");
+        anyhow::bail!("Must specify one of: --from, --file, --interactive, --tag, or --all");
     }
 }
 
@@ -45,7 +50,7 @@ fn extract_from_directory(
     verbose: bool,
 ) -> Result<()> {
     // Find the directory item
-    let items = trash_store.list()?;
+    let items = trash_store.list_revealed()?;
     let dir_item = items
         .iter()
         .find(|item| item.meta.id.to_string().starts_with(from_id))
@@ -108,24 +113,23 @@ fn extract_from_directory(
 
 fn extract_interactive(
     trash_store: &TrashStore,
+    overrides: &ConfigOverrides,
     filter: Option<String>,
+    tags: &[String],
     to: Option<PathBuf>,
     verbose: bool,
 ) -> Result<()> {
-    let mut items = trash_store.list()?;
+    let mut items = list_tagged(trash_store, tags)?;
 
     // Apply filter if provided
     if let Some(filter_pattern) = &filter {
         let pattern_lower = filter_pattern.to_lowercase();
-        items = items
-            .into_iter()
-            .filter(|item| {
-                item.meta.original_path
-                    .to_string_lossy()
-                    .to_lowercase()
-                    .contains(&pattern_lower)
-            })
-            .collect();
+        items.retain(|item| {
+            item.meta.original_path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&pattern_lower)
+        });
     }
 
     if items.is_empty() {
@@ -137,9 +141,9 @@ fn extract_interactive(
     }
 
     // Sort by deletion time (newest first)
-    items.sort_by(|a, b| b.meta.deleted_at.cmp(&a.meta.deleted_at));
+    items.sort_by_key(|item| std::cmp::Reverse(item.meta.deleted_at));
 
-    let selector = create_selector();
+    let selector = create_selector(trash_store, overrides);
 
     if verbose {
         #[cfg(feature = "colors")]
@@ -166,23 +170,21 @@ fn extract_interactive(
 fn extract_all(
     trash_store: &TrashStore,
     filter: Option<String>,
+    tags: &[String],
     to: Option<PathBuf>,
     verbose: bool,
 ) -> Result<()> {
-    let mut items = trash_store.list()?;
+    let mut items = list_tagged(trash_store, tags)?;
 
     // Apply filter if provided
     if let Some(filter_pattern) = &filter {
         let pattern_lower = filter_pattern.to_lowercase();
-        items = items
-            .into_iter()
-            .filter(|item| {
-                item.meta.original_path
-                    .to_string_lossy()
-                    .to_lowercase()
-                    .contains(&pattern_lower)
-            })
-            .collect();
+        items.retain(|item| {
+            item.meta.original_path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&pattern_lower)
+        });
     }
 
     if items.is_empty() {
@@ -205,10 +207,11 @@ fn extract_all(
 fn extract_by_filename(
     trash_store: &TrashStore,
     filename: &str,
+    tags: &[String],
     to: Option<PathBuf>,
     verbose: bool,
 ) -> Result<()> {
-    let items = trash_store.list()?;
+    let items = list_tagged(trash_store, tags)?;
     let matching_items: Vec<&TrashItem> = items
         .iter()
         .filter(|item| {
@@ -239,8 +242,15 @@ fn extract_by_filename(
     Ok(())
 }
 
+/// Trashed items carrying all of `tags`, with their original paths decrypted
+fn list_tagged(trash_store: &TrashStore, tags: &[String]) -> Result<Vec<TrashItem>> {
+    let mut items = trash_store.list_revealed()?;
+    items.retain(|item| tags.iter().all(|tag| item.meta.tags.contains(tag)));
+    Ok(items)
+}
+
 fn extract_single_file(
-    _trash_store: &TrashStore,
+    trash_store: &TrashStore,
     item: &TrashItem,
     to: Option<PathBuf>,
     verbose: bool,
@@ -276,7 +286,7 @@ fn extract_single_file(
     }
 
     // Copy file from trash to target location
-    std::fs::copy(&item.trash_path, &target_path)?;
+    trash_store.extract_to(item, &target_path)?;
 
     if verbose {
         #[cfg(feature = "colors")]
@@ -385,24 +395,6 @@ fn display_directory_contents(files: &[&TrashItem], verbose: bool) -> Result<()>
     Ok(())
 }
 
-/// Format bytes to human readable size
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-    
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
-    }
-    
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
-    }
-}
-
 /// Format relative time (e.g., "2 hours ago", "3 days ago")
 fn format_relative_time(datetime: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
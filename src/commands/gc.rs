@@ -0,0 +1,371 @@
+use super::Context;
+use crate::core::RmzError;
+use crate::domain::config_layers::ConfigOverrides;
+use crate::domain::{operation_log::OperationLogger, TrashItem};
+use crate::trash::{PurgeSelector, Trash};
+use crate::utils::shell::shell_quote;
+use crate::utils::size_parser::format_size;
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+const SERVICE_NAME: &str = "rmz-gc.service";
+const TIMER_NAME: &str = "rmz-gc.timer";
+
+/// Summary of a maintenance run
+#[derive(Debug, Default, PartialEq)]
+pub struct GcReport {
+    /// Items purged because they exceeded `auto_clean_days`
+    pub expired: usize,
+    /// Items purged to bring the trash under `max_trash_size`
+    pub over_quota: usize,
+    /// Bytes freed by purging items
+    pub freed: u64,
+    /// Metadata files removed because their payload was missing
+    pub orphaned_metadata: usize,
+    /// Temporary files left by interrupted runs
+    pub temp_files: usize,
+    /// Unreferenced blobs removed from the blob store
+    pub blobs: usize,
+    /// Operation log lines dropped while compacting
+    pub log_lines: usize,
+    /// Why the operation log was left uncompacted, if it was
+    pub log_error: Option<String>,
+    /// Why a `pre_purge` hook cancelled purging, if it did
+    pub vetoed: Option<String>,
+    /// Items that could not be purged and why; the run carries on without them
+    pub failed: Vec<(Uuid, String)>,
+}
+
+/// Execute the gc command
+pub fn execute(ctx: &Context, install_timer: bool, cron: bool) -> Result<()> {
+    if install_timer || cron {
+        let command = gc_args(&std::env::current_exe()?, &ctx.overrides);
+        if cron {
+            println!("{}", crontab_line(&command));
+            return Ok(());
+        }
+        return install_systemd_timer(&command, ctx.verbose);
+    }
+
+    let trash = ctx.open_trash()?;
+    let report = run(&trash, ctx.verbose)?;
+    display_report(&report);
+    Ok(())
+}
+
+/// Apply retention, remove orphans and compact the operation log without prompting
+///
+/// Expired and over-quota items are purged as one batch, so a `pre_purge`
+/// hook accepts or vetoes them together; the other maintenance steps run either way.
+pub fn run(trash: &Trash, verbose: bool) -> Result<GcReport> {
+    let config = trash.config();
+    let trash_store = trash.store();
+    let mut report = GcReport {
+        orphaned_metadata: trash_store.remove_orphaned_metadata()?.len(),
+        temp_files: trash_store.remove_temp_files()?,
+        ..Default::default()
+    };
+
+    let expired = match config.auto_clean_days {
+        Some(days) => trash.select(&PurgeSelector::OlderThan(Utc::now() - Duration::days(days as i64)))?,
+        None => Vec::new(),
+    };
+    let expired_ids: HashSet<Uuid> = expired.iter().map(|item| item.meta.id).collect();
+
+    // Expired items are the oldest, so the quota selection starts with them; skip those
+    let mut over_quota = match config.max_trash_size {
+        Some(max_size) => trash.select(&PurgeSelector::ExceedingSize(max_size))?,
+        None => Vec::new(),
+    };
+    over_quota.retain(|item| !expired_ids.contains(&item.meta.id));
+
+    let batch: Vec<TrashItem> = expired.into_iter().chain(over_quota).collect();
+    let outcome = trash.purge_items(&batch);
+
+    for item in &outcome.purged {
+        if expired_ids.contains(&item.meta.id) {
+            report.expired += 1;
+        } else {
+            report.over_quota += 1;
+        }
+        if verbose {
+            println!("Purged: {}", item.meta.original_path.display());
+        }
+    }
+    report.freed = outcome.freed;
+    for (item, error) in outcome.failed {
+        match error {
+            RmzError::Vetoed { reason, .. } => report.vetoed = Some(reason),
+            error => report.failed.push((item.meta.id, error.to_string())),
+        }
+    }
+
+    report.blobs = trash_store.blob_store().gc()?.0;
+
+    let keep_since = config
+        .gc
+        .log_retention_days
+        .map(|days| Utc::now() - Duration::days(days as i64));
+    let logger = OperationLogger::new(config.operation_log_path()).with_config(config.log.clone());
+    match logger.compact(keep_since) {
+        Ok((_, dropped)) => report.log_lines = dropped,
+        Err(error) => report.log_error = Some(error.to_string()),
+    }
+
+    Ok(report)
+}
+
+fn display_report(report: &GcReport) {
+    #[cfg(feature = "colors")]
+    println!("🧹 {}", "Garbage collection complete".green().bold());
+    #[cfg(not(feature = "colors"))]
+    println!("🧹 Garbage collection complete");
+
+    if let Some(reason) = &report.vetoed {
+        println!("  Purging skipped: cancelled by pre_purge hook: {}", reason);
+    }
+    println!("  Expired items purged: {}", report.expired);
+    println!("  Items purged for size limit: {}", report.over_quota);
+    println!("  Space freed: {}", format_size(report.freed));
+    for (id, error) in &report.failed {
+        println!("  Failed to purge {}: {}", id, error);
+    }
+    println!("  Orphaned metadata removed: {}", report.orphaned_metadata);
+    println!("  Temporary files removed: {}", report.temp_files);
+    println!("  Unreferenced blobs removed: {}", report.blobs);
+    println!("  Log lines compacted: {}", report.log_lines);
+    if let Some(error) = &report.log_error {
+        println!("  Log compaction skipped: {}", error);
+    }
+}
+
+/// Directory holding systemd user units, honouring `$XDG_CONFIG_HOME`
+pub fn systemd_user_dir() -> PathBuf {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(".config")
+        });
+    config_home.join("systemd").join("user")
+}
+
+/// Write the service and timer units for a daily `rmz gc`
+fn install_systemd_timer(command: &[String], verbose: bool) -> Result<()> {
+    let unit_dir = systemd_user_dir();
+    std::fs::create_dir_all(&unit_dir)?;
+
+    let service_path = unit_dir.join(SERVICE_NAME);
+    let timer_path = unit_dir.join(TIMER_NAME);
+    std::fs::write(&service_path, service_unit(command))?;
+    std::fs::write(&timer_path, timer_unit())?;
+
+    if verbose {
+        println!("Wrote {}", service_path.display());
+        println!("Wrote {}", timer_path.display());
+    }
+
+    println!("✅ Installed {} in {}", TIMER_NAME, unit_dir.display());
+    println!("Enable it with: systemctl --user daemon-reload && systemctl --user enable --now {}", TIMER_NAME);
+    Ok(())
+}
+
+/// Arguments of a scheduled run, keeping the global options it was installed with
+fn gc_args(rmz: &Path, overrides: &ConfigOverrides) -> Vec<String> {
+    let mut command = vec![rmz.display().to_string()];
+    command.extend(overrides.args());
+    command.push("gc".to_string());
+    command
+}
+
+fn service_unit(command: &[String]) -> String {
+    let exec_start: Vec<String> = command.iter().map(|arg| systemd_quote(arg)).collect();
+    format!(
+        "[Unit]\n\
+         Description=rmz trash maintenance\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart={}\n",
+        exec_start.join(" ")
+    )
+}
+
+/// Quote an argument for a systemd `ExecStart=` line, where `%` and `$` are expanded
+fn systemd_quote(value: &str) -> String {
+    let escaped = value.replace('%', "%%").replace('$', "$$");
+    if !escaped.is_empty() && escaped.chars().all(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '\\' | ';')) {
+        return escaped;
+    }
+    format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn timer_unit() -> String {
+    format!(
+        "[Unit]\n\
+         Description=Daily rmz trash maintenance\n\
+         \n\
+         [Timer]\n\
+         OnCalendar=daily\n\
+         Persistent=true\n\
+         Unit={}\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        SERVICE_NAME
+    )
+}
+
+/// Line for a crontab, whose command is run by `sh` and where a bare `%` ends the command
+fn crontab_line(command: &[String]) -> String {
+    let command: Vec<String> = command.iter().map(|arg| shell_quote(arg).replace('%', "\\%")).collect();
+    format!("0 3 * * * {} >/dev/null 2>&1", command.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Config, FileMeta};
+    use crate::infra::meta_store::MetaStoreInterface;
+    use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn trash_file(trash_store: &TrashStore, dir: &Path, name: &str, size: usize, age_days: i64) {
+        let path = dir.join(name);
+        fs::write(&path, "x".repeat(size)).unwrap();
+        let mut meta = FileMeta::from_path(&path).unwrap();
+        meta.deleted_at = Utc::now() - Duration::days(age_days);
+        trash_store.save(&meta, &path).unwrap();
+    }
+
+    #[test]
+    fn test_gc_applies_retention_and_quota() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            auto_clean_days: Some(30),
+            max_trash_size: Some(150),
+            ..Config::default()
+        };
+        let trash = Trash::open(config).unwrap();
+        let trash_store = trash.store();
+
+        trash_file(trash_store, temp_dir.path(), "ancient.txt", 10, 60);
+        trash_file(trash_store, temp_dir.path(), "old.txt", 100, 5);
+        trash_file(trash_store, temp_dir.path(), "new.txt", 100, 1);
+
+        let report = run(&trash, false).unwrap();
+        assert_eq!(report.expired, 1);
+        assert_eq!(report.over_quota, 1);
+        assert_eq!(report.freed, 110);
+
+        let remaining = trash_store.list().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].meta.filename(), Some("new.txt"));
+    }
+
+    #[test]
+    fn test_gc_continues_past_failed_purges() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            auto_clean_days: Some(30),
+            ..Config::default()
+        };
+        let trash = Trash::open(config).unwrap();
+        let trash_store = trash.store();
+
+        trash_file(trash_store, temp_dir.path(), "broken.txt", 10, 60);
+        trash_file(trash_store, temp_dir.path(), "fine.txt", 20, 50);
+
+        // A deduplicated item with an unreadable manifest cannot be purged
+        let mut broken = trash_store
+            .list()
+            .unwrap()
+            .into_iter()
+            .find(|item| item.meta.filename() == Some("broken.txt"))
+            .unwrap();
+        fs::remove_file(&broken.trash_path).unwrap();
+        broken.meta.deduplicated = true;
+        trash_store.meta_store().save_metadata(&broken.meta).unwrap();
+        fs::write(trash_store.payload_path(&broken.meta), "not a manifest").unwrap();
+
+        let report = run(&trash, false).unwrap();
+        assert_eq!(report.expired, 1);
+        assert_eq!(report.freed, 20);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, broken.meta.id);
+        assert_eq!(trash_store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_gc_purges_nothing_when_vetoed() {
+        use crate::domain::HooksConfig;
+        use crate::infra::hooks::Hooks;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            auto_clean_days: Some(30),
+            max_trash_size: Some(10),
+            ..Config::default()
+        };
+        let hooks = Hooks::new(HooksConfig {
+            pre_purge: Some("echo 'retention paused' >&2; exit 1".to_string()),
+            dir: None,
+            ..HooksConfig::default()
+        });
+        let trash = Trash::open(config).unwrap().with_hooks(hooks);
+
+        trash_file(trash.store(), temp_dir.path(), "ancient.txt", 10, 60);
+        trash_file(trash.store(), temp_dir.path(), "large.txt", 100, 1);
+
+        let report = run(&trash, false).unwrap();
+        assert_eq!(report.vetoed.as_deref(), Some("retention paused"));
+        assert_eq!((report.expired, report.over_quota, report.freed), (0, 0, 0));
+        assert!(report.failed.is_empty());
+        assert_eq!(trash.store().list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unit_contents() {
+        let command = gc_args(Path::new("/usr/local/bin/rmz"), &ConfigOverrides::default());
+        assert!(service_unit(&command).contains("ExecStart=/usr/local/bin/rmz gc\n"));
+        assert!(timer_unit().contains("OnCalendar=daily"));
+        assert!(timer_unit().contains("Unit=rmz-gc.service"));
+        assert_eq!(crontab_line(&command), "0 3 * * * /usr/local/bin/rmz gc >/dev/null 2>&1");
+
+        let overrides = ConfigOverrides {
+            trash_dir: Some(PathBuf::from("/srv/trash")),
+            profile: Some("work".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            gc_args(Path::new("/bin/rmz"), &overrides),
+            ["/bin/rmz", "--trash-dir", "/srv/trash", "--profile", "work", "gc"]
+        );
+    }
+
+    #[test]
+    fn test_scheduled_command_is_quoted() {
+        let overrides = ConfigOverrides {
+            trash_dir: Some(PathBuf::from("/srv/my trash/100%")),
+            ..Default::default()
+        };
+        let command = gc_args(Path::new("/opt/my tools/rmz"), &overrides);
+        assert!(service_unit(&command)
+            .contains("ExecStart=\"/opt/my tools/rmz\" --trash-dir \"/srv/my trash/100%%\" gc\n"));
+        assert_eq!(
+            crontab_line(&command),
+            "0 3 * * * '/opt/my tools/rmz' --trash-dir '/srv/my trash/100\\%' gc >/dev/null 2>&1"
+        );
+        assert_eq!(systemd_quote("say \"hi\" $USER"), "\"say \\\"hi\\\" $$USER\"");
+    }
+}
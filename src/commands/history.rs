@@ -0,0 +1,90 @@
+use super::Context;
+use crate::domain::TrashItem;
+use crate::trash::Trash;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Execute the history command
+pub fn execute(ctx: &Context, path: PathBuf) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let items = trash.history(&path)?;
+
+    if items.is_empty() {
+        return Err(anyhow!("No trashed versions of {}", path.display()));
+    }
+
+    // Versions are numbered per original path, oldest first, as `restore --version` expects
+    let mut timelines: BTreeMap<&Path, Vec<&TrashItem>> = BTreeMap::new();
+    for item in &items {
+        timelines.entry(item.meta.original_path.as_path()).or_default().push(item);
+    }
+
+    for (index, (original_path, versions)) in timelines.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        print_timeline(&trash, original_path, versions, ctx.verbose);
+    }
+
+    if timelines.len() > 1 {
+        println!();
+        println!("{} version(s) of {} path(s)", items.len(), timelines.len());
+    }
+
+    Ok(())
+}
+
+fn print_timeline(trash: &Trash, original_path: &Path, versions: &[&TrashItem], verbose: bool) {
+    #[cfg(feature = "colors")]
+    println!("{} ({} version(s))", original_path.display().to_string().bold(), versions.len());
+    #[cfg(not(feature = "colors"))]
+    println!("{} ({} version(s))", original_path.display(), versions.len());
+
+    for (index, item) in versions.iter().enumerate() {
+        let number = index + 1;
+        let id = item.meta.id.to_string();
+        let id = if verbose { &id[..] } else { &id[..8] };
+        let line = format!(
+            "  v{:<3} {}  {:>9}  {:<16}  {}",
+            number,
+            item.meta.deleted_at.format("%Y-%m-%d %H:%M:%S"),
+            item.meta.human_readable_size(),
+            checksum(trash, item, verbose),
+            id
+        );
+
+        if number < versions.len() {
+            println!("{}", line);
+        } else {
+            #[cfg(feature = "colors")]
+            println!("{}  {}", line, "(latest)".green());
+            #[cfg(not(feature = "colors"))]
+            println!("{}  (latest)", line);
+        }
+    }
+}
+
+/// Recorded or computed SHA-256 of a file version, shortened unless verbose
+fn checksum(trash: &Trash, item: &TrashItem, verbose: bool) -> String {
+    if item.is_directory() {
+        return "-".to_string();
+    }
+    // Hashing an encrypted payload would prompt for the passphrase for every row
+    if item.meta.checksum.is_none() && item.meta.is_encrypted() {
+        return "(encrypted)".to_string();
+    }
+
+    let hash = match &item.meta.checksum {
+        Some(checksum) => Some(checksum.clone()),
+        None => trash.store().content_checksum(item).ok(),
+    };
+    match hash {
+        Some(hash) if !verbose => hash.chars().take(16).collect(),
+        Some(hash) => hash,
+        None => "(unreadable)".to_string(),
+    }
+}
@@ -0,0 +1,174 @@
+use super::Context;
+use crate::cli::KeyAction;
+use crate::domain::Config;
+use crate::infra::encryption::{self, NEW_PASSPHRASE_ENV};
+use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
+use anyhow::Result;
+use std::path::PathBuf;
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Execute the key command
+pub fn execute(ctx: &Context, action: KeyAction) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let trash_store = trash.store();
+    let config = trash.config().clone();
+
+    match action {
+        KeyAction::Init { force } => init_key(trash_store, force),
+        KeyAction::Rotate { new_key_file } => rotate_key(ctx, trash_store, config, new_key_file),
+        KeyAction::Status => show_key_status(trash_store, &config),
+    }
+}
+
+/// Set up the key record for this trash
+///
+/// Replacing an existing key would make encrypted items unreadable, so
+/// `--force` is refused while any exist; `rmz key rotate` re-encrypts them.
+fn init_key(trash_store: &TrashStore, force: bool) -> Result<()> {
+    let key_manager = trash_store.key_manager();
+    if key_manager.is_initialized() && !force {
+        anyhow::bail!("Encryption key already exists; use 'rmz key rotate' to change it");
+    }
+    if key_manager.pending_rotation()?.is_some() {
+        anyhow::bail!("A key rotation is unfinished; run 'rmz key rotate' to complete it");
+    }
+    let encrypted = trash_store.list()?.iter().filter(|item| item.meta.is_encrypted()).count();
+    if encrypted > 0 {
+        anyhow::bail!(
+            "{} encrypted item(s) in trash would become unreadable; use 'rmz key rotate' to change the key",
+            encrypted
+        );
+    }
+
+    let key = key_manager.initialize(&key_manager.read_secret(true)?)?;
+
+    #[cfg(feature = "colors")]
+    println!("🔐 Encryption key {} created", key.id().green());
+    #[cfg(not(feature = "colors"))]
+    println!("🔐 Encryption key {} created", key.id());
+
+    Ok(())
+}
+
+/// Switch to a new secret and re-encrypt all items, or resume an interrupted rotation
+///
+/// The new key record is stored before any item is touched, with the old key
+/// journaled alongside it, so an interruption never strands items.
+fn rotate_key(
+    ctx: &Context,
+    trash_store: &TrashStore,
+    mut config: Config,
+    new_key_file: Option<PathBuf>,
+) -> Result<()> {
+    let key_manager = trash_store.key_manager();
+    if !key_manager.is_initialized() {
+        anyhow::bail!("No encryption key set up; run 'rmz key init' first");
+    }
+
+    let (old_id, outcome) = match key_manager.pending_rotation()? {
+        Some(old_id) => {
+            // The record already holds the new key, so the current secret unlocks both
+            println!("Resuming rotation away from key {}", old_id);
+            (old_id, trash_store.rotate_key()?)
+        }
+        None => {
+            // Unlock with the current secret before asking for the new one
+            let old_key = trash_store.key()?;
+            let new_secret = match &new_key_file {
+                Some(path) => std::fs::read(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read key file {}: {}", path.display(), e))?,
+                None => encryption::read_passphrase(NEW_PASSPHRASE_ENV, "New trash passphrase", true)?,
+            };
+            let (new_key, record) = key_manager.generate(&new_secret)?;
+            key_manager.begin_rotation(old_key, &new_key, &record)?;
+
+            // The new secret is the one to ask for from now on
+            if new_key_file.is_some() || config.encryption.key_file.is_some() {
+                config.encryption.key_file = new_key_file;
+                ctx.save_config(&config)?;
+            }
+
+            let rotating_store = TrashStore::new(config.trash_path.clone())
+                .with_encryption(&config.encryption)
+                .with_key(new_key);
+            (old_key.id().to_string(), rotating_store.rotate_key()?)
+        }
+    };
+
+    for (item, e) in &outcome.failed {
+        #[cfg(feature = "colors")]
+        eprintln!("{} {}: {}", "⚠️  Skipped".yellow(), item.meta.id, e);
+        #[cfg(not(feature = "colors"))]
+        eprintln!("⚠️  Skipped {}: {}", item.meta.id, e);
+    }
+
+    // Items on some other, unknown key are reported but do not hold the rotation open
+    let stranded = outcome
+        .failed
+        .iter()
+        .filter(|(item, _)| item.meta.encryption.as_ref().is_some_and(|info| info.key_id == old_id))
+        .count();
+
+    if ctx.verbose {
+        println!("Key {} replaced by {}", old_id, key_manager.current_key_id()?.unwrap_or_default());
+    }
+
+    #[cfg(feature = "colors")]
+    println!("✅ Re-encrypted {} item(s) with the new key", outcome.processed.len().to_string().green());
+    #[cfg(not(feature = "colors"))]
+    println!("✅ Re-encrypted {} item(s) with the new key", outcome.processed.len());
+
+    if stranded > 0 {
+        anyhow::bail!(
+            "{} item(s) still use key {}; fix or purge them and run 'rmz key rotate' again to finish",
+            stranded,
+            old_id
+        );
+    }
+    key_manager.finish_rotation()?;
+
+    Ok(())
+}
+
+/// Show whether encryption is set up and how many items use it
+fn show_key_status(trash_store: &TrashStore, config: &Config) -> Result<()> {
+    let key_id = trash_store.key_manager().current_key_id()?;
+    let items = trash_store.list()?;
+    let encrypted = items.iter().filter(|item| item.meta.is_encrypted()).count();
+    let stale = items
+        .iter()
+        .filter_map(|item| item.meta.encryption.as_ref())
+        .filter(|info| Some(&info.key_id) != key_id.as_ref())
+        .count();
+
+    println!("🔐 Encryption Status");
+    println!("{}", "─".repeat(50));
+    println!("Enabled: {}", config.encryption.enabled);
+    println!("Key: {}", key_id.as_deref().unwrap_or("(not set up)"));
+    println!(
+        "Secret source: {}",
+        config
+            .encryption
+            .key_file
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "passphrase".to_string())
+    );
+    println!("Encrypted paths: {}", config.encryption.encrypt_paths);
+    println!("Encrypted items: {} of {}", encrypted, items.len());
+
+    if let Some(previous) = trash_store.key_manager().pending_rotation()? {
+        println!("Rotation: unfinished, away from key {}; run 'rmz key rotate' to resume", previous);
+    }
+
+    if stale > 0 {
+        #[cfg(feature = "colors")]
+        println!("⚠️  {} item(s) use a different key; run 'rmz doctor --check encryption'", stale.to_string().yellow());
+        #[cfg(not(feature = "colors"))]
+        println!("⚠️  {} item(s) use a different key; run 'rmz doctor --check encryption'", stale);
+    }
+
+    Ok(())
+}
@@ -1,43 +1,55 @@
-use crate::cli::GroupBy;
+use super::Context;
+use crate::cli::{GroupBy, ListColumn, SortBy};
 use crate::domain::TrashItem;
-use crate::infra::{trash_store::TrashStoreInterface, ConfigManager, TrashStore};
+use crate::trash::{ListFilter, SortKey};
+use crate::utils::time_expr::TimeRange;
+use crate::utils::size_parser::format_size;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// How `rmz list` orders and lays out items
+#[derive(Debug, Clone)]
+pub struct ListView {
+    pub group_by: Option<GroupBy>,
+    pub sort: SortBy,
+    pub reverse: bool,
+    /// Show a table of these fields instead of the default lines
+    pub columns: Option<Vec<ListColumn>>,
+    /// Arrange items by their original directories
+    pub tree: bool,
+    /// Only items deleted from the current directory or beneath it
+    pub here: bool,
+}
 
 /// Execute list command
 pub fn execute(
+    ctx: &Context,
     json: bool,
     filter: Option<String>,
-    since: Option<String>,
-    group_by: Option<GroupBy>,
+    tags: Vec<String>,
+    range: TimeRange,
     limit: Option<usize>,
-    verbose: bool,
+    view: ListView,
 ) -> Result<()> {
-    let config = ConfigManager::load()?;
-    let trash_store = TrashStore::new(config.trash_path.clone());
-
-    // Get all items from trash
-    let mut items = trash_store.list()?;
-
-    // Apply since filter
-    if let Some(since_str) = &since {
-        let since_date = parse_since_date(since_str)?;
-        items.retain(|item| item.meta.deleted_at >= since_date);
-    }
-
-    // Apply pattern filter
-    if let Some(pattern) = &filter {
-        items.retain(|item| item.meta.matches_pattern(pattern));
-    }
-
-    // Apply limit
-    if let Some(limit_count) = limit {
-        items.truncate(limit_count);
-    }
+    let trash = ctx.open_trash()?;
+
+    let items = trash.list(&ListFilter {
+        pattern: filter.clone(),
+        since: range.since,
+        before: range.before,
+        tags: tags.clone(),
+        under: if view.here { Some(std::env::current_dir()?) } else { None },
+        sort: Some(convert_sort(view.sort)),
+        reverse: view.reverse,
+        limit,
+        ..ListFilter::default()
+    })?;
 
     if items.is_empty() {
-        if filter.is_some() || since.is_some() {
+        if filter.is_some() || !tags.is_empty() || !range.is_unbounded() || view.here {
             println!("No files found matching the criteria");
         } else {
             println!("Trash is empty");
@@ -45,73 +57,26 @@ pub fn execute(
         return Ok(());
     }
 
-    if json {
+    if view.tree {
+        output_tree(&items, view.sort, view.reverse);
+    } else if let (true, Some(columns)) = (json, &view.columns) {
+        output_json_columns(&items, columns)?;
+    } else if json {
         // Output in JSON format
-        output_json(&items, verbose)?;
-    } else if let Some(group_type) = group_by {
+        output_json(&items, ctx.verbose)?;
+    } else if let Some(columns) = &view.columns {
+        output_table(&items, columns);
+    } else if let Some(group_type) = &view.group_by {
         // Group and display
-        output_grouped(&items, &group_type, verbose)?;
+        output_grouped(&items, group_type, ctx.verbose)?;
     } else {
         // Simple list format
-        output_simple(&items, verbose)?;
+        output_simple(&items, ctx.verbose)?;
     }
 
     Ok(())
 }
 
-fn parse_since_date(since_str: &str) -> Result<DateTime<Utc>> {
-    match since_str.to_lowercase().as_str() {
-        "today" => {
-            let now = Utc::now();
-            let today_start = now
-                .date_naive()
-                .and_hms_opt(0, 0, 0)
-                .ok_or_else(|| anyhow::anyhow!("Failed to create today's start time"))?
-                .and_utc();
-            Ok(today_start)
-        }
-        "yesterday" => {
-            let now = Utc::now();
-            let yesterday = now.date_naive() - chrono::Duration::days(1);
-            let yesterday_start = yesterday
-                .and_hms_opt(0, 0, 0)
-                .ok_or_else(|| anyhow::anyhow!("Failed to create yesterday's start time"))?
-                .and_utc();
-            Ok(yesterday_start)
-        }
-        "week" => {
-            let now = Utc::now();
-            let week_ago = now - chrono::Duration::weeks(1);
-            Ok(week_ago)
-        }
-        "month" => {
-            let now = Utc::now();
-            let month_ago = now - chrono::Duration::days(30);
-            Ok(month_ago)
-        }
-        _ => {
-            // Try to parse as ISO date (YYYY-MM-DD or YYYY-MM-DD HH:MM:SS)
-            if let Ok(date) = chrono::NaiveDate::parse_from_str(since_str, "%Y-%m-%d") {
-                let datetime = date
-                    .and_hms_opt(0, 0, 0)
-                    .ok_or_else(|| anyhow::anyhow!("Failed to create datetime"))?
-                    .and_utc();
-                Ok(datetime)
-            } else if let Ok(datetime) =
-                chrono::DateTime::parse_from_str(since_str, "%Y-%m-%d %H:%M:%S %z")
-            {
-                Ok(datetime.with_timezone(&Utc))
-            } else if let Ok(datetime) =
-                chrono::NaiveDateTime::parse_from_str(since_str, "%Y-%m-%d %H:%M:%S")
-            {
-                Ok(datetime.and_utc())
-            } else {
-                anyhow::bail!("Invalid date format: {}. Use 'today', 'yesterday', 'week', 'month', or YYYY-MM-DD", since_str);
-            }
-        }
-    }
-}
-
 fn output_json(items: &[TrashItem], verbose: bool) -> Result<()> {
     #[derive(serde::Serialize)]
     struct JsonItem<'a> {
@@ -123,6 +88,10 @@ fn output_json(items: &[TrashItem], verbose: bool) -> Result<()> {
         human_size: String,
         tags: &'a Vec<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        git: Option<&'a crate::domain::GitInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         permissions: Option<u32>,
         #[serde(skip_serializing_if = "Option::is_none")]
         deleted_by: Option<&'a str>,
@@ -140,6 +109,8 @@ fn output_json(items: &[TrashItem], verbose: bool) -> Result<()> {
             size: item.meta.size,
             human_size: item.meta.human_readable_size(),
             tags: &item.meta.tags,
+            note: item.meta.note.as_deref(),
+            git: item.meta.git.as_ref(),
             permissions: if verbose {
                 Some(item.meta.permissions)
             } else {
@@ -163,6 +134,200 @@ fn output_json(items: &[TrashItem], verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// Convert the CLI sort field to the library sort key
+fn convert_sort(sort: SortBy) -> SortKey {
+    match sort {
+        SortBy::Deleted => SortKey::Deleted,
+        SortBy::Size => SortKey::Size,
+        SortBy::Path => SortKey::Path,
+        SortBy::Name => SortKey::Name,
+        SortBy::Tag => SortKey::Tag,
+    }
+}
+
+fn column_title(column: ListColumn) -> &'static str {
+    match column {
+        ListColumn::Id => "ID",
+        ListColumn::Name => "NAME",
+        ListColumn::Path => "PATH",
+        ListColumn::Size => "SIZE",
+        ListColumn::Deleted => "DELETED",
+        ListColumn::Tags => "TAGS",
+        ListColumn::User => "USER",
+        ListColumn::Note => "NOTE",
+    }
+}
+
+fn column_value(item: &TrashItem, column: ListColumn) -> String {
+    match column {
+        ListColumn::Id => item.meta.id.to_string().chars().take(8).collect(),
+        ListColumn::Name => item.meta.filename().unwrap_or("(unknown)").to_string(),
+        ListColumn::Path => item.meta.original_path.display().to_string(),
+        ListColumn::Size => item.meta.human_readable_size(),
+        ListColumn::Deleted => item.meta.deleted_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ListColumn::Tags => item.meta.tags.join(","),
+        ListColumn::User => item.meta.deleted_by.clone(),
+        // Only the first line keeps table rows aligned
+        ListColumn::Note => item.meta.note.as_deref().and_then(|note| note.lines().next()).unwrap_or("").to_string(),
+    }
+}
+
+/// Aligned table of the chosen columns
+fn output_table(items: &[TrashItem], columns: &[ListColumn]) {
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| columns.iter().map(|&column| column_value(item, column)).collect())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, &column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(column_title(column).len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |cells: Vec<String>| {
+        let last = cells.len().saturating_sub(1);
+        cells
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| if i == last { cell } else { format!("{:<width$}", cell, width = widths[i]) })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    println!("{}", format_row(columns.iter().map(|&column| column_title(column).to_string()).collect()));
+    for row in rows {
+        println!("{}", format_row(row));
+    }
+}
+
+/// JSON objects holding only the chosen columns
+fn output_json_columns(items: &[TrashItem], columns: &[ListColumn]) -> Result<()> {
+    let objects: Vec<serde_json::Map<String, serde_json::Value>> = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|&column| {
+                    let value = match column {
+                        ListColumn::Id => serde_json::json!(item.meta.id),
+                        ListColumn::Size => serde_json::json!(item.meta.size),
+                        ListColumn::Deleted => serde_json::json!(item.meta.deleted_at),
+                        ListColumn::Tags => serde_json::json!(item.meta.tags),
+                        _ => serde_json::json!(column_value(item, column)),
+                    };
+                    (column_title(column).to_lowercase(), value)
+                })
+                .collect()
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&objects)?);
+    Ok(())
+}
+
+/// A directory level of the tree view
+#[derive(Default)]
+struct TreeNode<'a> {
+    /// Trashed versions of exactly this path
+    items: Vec<&'a TrashItem>,
+    children: BTreeMap<OsString, TreeNode<'a>>,
+}
+
+impl<'a> TreeNode<'a> {
+    fn insert(&mut self, path: &std::path::Path, item: &'a TrashItem) {
+        let mut node = self;
+        for component in path.components() {
+            node = node.children.entry(component.as_os_str().to_os_string()).or_default();
+        }
+        node.items.push(item);
+    }
+
+    fn size(&self) -> u64 {
+        self.items.iter().map(|item| item.meta.size).sum::<u64>()
+            + self.children.values().map(TreeNode::size).sum::<u64>()
+    }
+
+    fn count(&self) -> usize {
+        self.items.len() + self.children.values().map(TreeNode::count).sum::<usize>()
+    }
+
+    fn latest(&self) -> Option<DateTime<Utc>> {
+        self.items
+            .iter()
+            .map(|item| item.meta.deleted_at)
+            .chain(self.children.values().filter_map(TreeNode::latest))
+            .max()
+    }
+}
+
+/// Items arranged by original directory, with sizes summed per directory
+fn output_tree(items: &[TrashItem], sort: SortBy, reverse: bool) {
+    let mut root = TreeNode::default();
+    for item in items {
+        let path = std::path::absolute(&item.meta.original_path).unwrap_or_else(|_| item.meta.original_path.clone());
+        root.insert(&path, item);
+    }
+
+    // Start at the deepest directory shared by every item
+    let mut prefix = PathBuf::new();
+    let mut node = &root;
+    while node.items.is_empty() && node.children.len() == 1 {
+        let (name, child) = node.children.iter().next().expect("one child");
+        if child.children.is_empty() {
+            break;
+        }
+        prefix.push(name);
+        node = child;
+    }
+
+    println!("{} ({}, {} items)", prefix.display(), format_size(node.size()), node.count());
+    print_tree_children(node, "", sort, reverse);
+}
+
+fn print_tree_children(node: &TreeNode, indent: &str, sort: SortBy, reverse: bool) {
+    let mut children: Vec<(&OsString, &TreeNode)> = node.children.iter().collect();
+    match sort {
+        SortBy::Size => children.sort_by_key(|(_, child)| std::cmp::Reverse(child.size())),
+        SortBy::Deleted => children.sort_by_key(|(_, child)| std::cmp::Reverse(child.latest())),
+        _ => {}
+    }
+    if reverse {
+        children.reverse();
+    }
+
+    for (index, (name, child)) in children.iter().enumerate() {
+        let last = index + 1 == children.len();
+        let branch = if last { "└── " } else { "├── " };
+        let is_dir = !child.children.is_empty() || child.items.iter().any(|item| item.is_directory());
+
+        let mut label = format!(
+            "{}{}{}{} ({}",
+            indent,
+            branch,
+            name.to_string_lossy(),
+            if is_dir { "/" } else { "" },
+            format_size(child.size())
+        );
+        match (child.items.as_slice(), child.children.is_empty()) {
+            ([item], true) => {
+                label.push_str(&format!(") {}", item.meta.id.to_string().chars().take(8).collect::<String>()))
+            }
+            ([] | [_], false) => label.push_str(&format!(", {} items)", child.count())),
+            (versions, _) => label.push_str(&format!(", {} versions)", versions.len())),
+        }
+        println!("{}", label);
+
+        let child_indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+        print_tree_children(child, &child_indent, sort, reverse);
+    }
+}
+
 fn output_grouped(items: &[TrashItem], group_by: &GroupBy, verbose: bool) -> Result<()> {
     match group_by {
         GroupBy::Date => {
@@ -258,6 +423,9 @@ fn output_single_item(item: &TrashItem, verbose: bool, prefix: &str) {
         if !item.meta.tags.is_empty() {
             println!("{}   Tags: {}", prefix, item.meta.tags.join(", "));
         }
+        if let Some(note) = &item.meta.note {
+            println!("{}   Note: {}", prefix, note);
+        }
         println!("{}   Trash: {}", prefix, item.trash_path.display());
         println!();
     } else {
@@ -279,60 +447,14 @@ fn output_single_item(item: &TrashItem, verbose: bool, prefix: &str) {
     }
 }
 
-fn format_size(size: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size_f = size as f64;
-    let mut unit_index = 0;
-
-    while size_f >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size_f /= 1024.0;
-        unit_index += 1;
-    }
-
-    if unit_index == 0 {
-        format!("{} {}", size, UNITS[0])
-    } else {
-        format!("{:.1} {}", size_f, UNITS[unit_index])
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::{Config, FileMeta};
-    use crate::infra::trash_store::TrashStoreInterface;
+    use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
     use std::fs;
     use tempfile::{NamedTempFile, TempDir};
 
-    #[test]
-    fn test_parse_since_date() {
-        // Test relative dates
-        let today = parse_since_date("today").unwrap();
-        let yesterday = parse_since_date("yesterday").unwrap();
-        let week = parse_since_date("week").unwrap();
-        let month = parse_since_date("month").unwrap();
-
-        assert!(today > yesterday);
-        assert!(yesterday > week);
-        assert!(week > month);
-
-        // Test absolute date
-        let absolute = parse_since_date("2024-01-01").unwrap();
-        assert_eq!(absolute.format("%Y-%m-%d").to_string(), "2024-01-01");
-
-        // Test invalid date
-        assert!(parse_since_date("invalid").is_err());
-    }
-
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(100), "100 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-    }
-
     #[test]
     fn test_list_empty_trash() {
         let temp_dir = TempDir::new().unwrap();
@@ -364,8 +486,8 @@ mod tests {
         fs::write(test_file1.path(), "content1").unwrap();
         fs::write(test_file2.path(), "content2").unwrap();
 
-        let mut meta1 = FileMeta::from_path(&test_file1.path()).unwrap();
-        let mut meta2 = FileMeta::from_path(&test_file2.path()).unwrap();
+        let mut meta1 = FileMeta::from_path(test_file1.path()).unwrap();
+        let mut meta2 = FileMeta::from_path(test_file2.path()).unwrap();
 
         // Add tags for testing
         meta1.add_tag("test".to_string());
@@ -394,7 +516,7 @@ mod tests {
         // Create test file
         let test_file = NamedTempFile::new().unwrap();
         fs::write(test_file.path(), "test content").unwrap();
-        let meta = FileMeta::from_path(&test_file.path()).unwrap();
+        let meta = FileMeta::from_path(test_file.path()).unwrap();
 
         trash_store.save(&meta, test_file.path()).unwrap();
         let items = trash_store.list().unwrap();
@@ -406,4 +528,33 @@ mod tests {
         let result_verbose = output_json(&items, true);
         assert!(result_verbose.is_ok());
     }
+
+    #[test]
+    fn test_tree_aggregates_sizes() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+        let dir = temp_dir.path().join("project/src");
+        fs::create_dir_all(&dir).unwrap();
+
+        for (name, content) in [("main.rs", "fn main() {}"), ("lib.rs", "pub mod x;"), ("lib.rs", "pub mod y;")] {
+            let path = dir.join(name);
+            fs::write(&path, content).unwrap();
+            trash_store.save(&FileMeta::from_path(&path).unwrap(), &path).unwrap();
+        }
+        let items = trash_store.list().unwrap();
+
+        let mut root = TreeNode::default();
+        for item in &items {
+            root.insert(&item.meta.original_path, item);
+        }
+        assert_eq!(root.count(), 3);
+        assert_eq!(root.size(), 32);
+
+        let mut node = &root;
+        for component in dir.components() {
+            node = &node.children[component.as_os_str()];
+        }
+        assert_eq!(node.children[std::ffi::OsStr::new("lib.rs")].items.len(), 2);
+        assert_eq!(node.size(), 32);
+    }
 }
@@ -1,51 +1,211 @@
-use crate::cli::OperationType;
-use crate::domain::{operation_log::OperationLogger, Config};
+use super::Context;
+use crate::cli::{LogResult, OperationType};
+use crate::domain::operation_log::{OperationLog, OperationLogger, OperationResult};
+use crate::utils::glob::{glob_match, is_glob};
+use crate::utils::time_expr::TimeRange;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::time::Duration;
+
+/// How often `--follow` checks the log for new entries
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
 
 #[cfg(feature = "colors")]
 use colored::Colorize;
 
+/// Which operations `rmz log` shows
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub operation: Option<OperationType>,
+    /// A path, matching it and everything beneath it, or a glob
+    pub path: Option<String>,
+    /// Entry or file ID, or a prefix of one
+    pub id: Option<String>,
+    pub user: Option<String>,
+    pub result: Option<LogResult>,
+    /// Tags that must all be present
+    pub tags: Vec<String>,
+    pub range: TimeRange,
+}
+
+impl LogFilter {
+    /// Resolve a plain `path` against the current directory so it can be compared with logged paths
+    fn resolved(mut self) -> Result<Self> {
+        if let Some(path) = self.path.as_mut() {
+            if !is_glob(path) {
+                *path = std::path::absolute(&*path)?.to_string_lossy().into_owned();
+            }
+        }
+        Ok(self)
+    }
+
+    /// Check whether a log entry passes every criterion
+    pub fn matches(&self, log: &OperationLog) -> bool {
+        let operation = self.operation.clone().map(convert_operation_type);
+        if !log.matches_filter(operation.as_ref(), None) || !self.range.contains(log.timestamp) {
+            return false;
+        }
+
+        if let Some(pattern) = &self.path {
+            let matched = log.paths.iter().any(|path| {
+                if is_glob(pattern) {
+                    glob_match(pattern, &path.to_string_lossy())
+                } else {
+                    path.starts_with(pattern)
+                }
+            });
+            if !matched {
+                return false;
+            }
+        }
+
+        if let Some(id) = &self.id {
+            let prefix = id.to_lowercase();
+            let mut ids = std::iter::once(&log.id).chain(&log.file_ids);
+            if !ids.any(|id| id.to_string().starts_with(&prefix)) {
+                return false;
+            }
+        }
+
+        let result_matches = match self.result {
+            None => true,
+            Some(LogResult::Success) => log.result == OperationResult::Success,
+            Some(LogResult::Failed) => matches!(log.result, OperationResult::Failed(_)),
+            Some(LogResult::Cancelled) => log.result == OperationResult::Cancelled,
+        };
+
+        result_matches
+            && self.user.as_ref().is_none_or(|user| &log.user == user)
+            && self.tags.iter().all(|tag| log.tags.contains(tag))
+    }
+}
+
 /// Execute the log command
 pub fn execute(
+    ctx: &Context,
     detailed: bool,
-    operation: Option<OperationType>,
-    since: Option<String>,
-    verbose: bool,
+    filter: LogFilter,
+    limit: Option<usize>,
+    reverse: bool,
+    follow: bool,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let log_file_path = config.logs_path().join("operations.jsonl");
-    let logger = OperationLogger::new(log_file_path);
-    
-    // Parse since date if provided
-    let since_datetime = if let Some(since_str) = since {
-        Some(parse_since_date(&since_str)?)
+    let config = ctx.load_config()?;
+    let logger = OperationLogger::new(config.operation_log_path()).with_config(config.log.clone());
+    let filter = filter.resolved()?;
+
+    if follow {
+        return follow_logs(&logger, &filter, limit);
+    }
+
+    // Stream newest-first and stop at the limit or the start of the range
+    let logs = if reverse {
+        let mut logs = logger.read_where(filter.range.since, None, |log| filter.matches(log))?;
+        logs.reverse();
+        logs.truncate(limit.unwrap_or(usize::MAX));
+        logs
     } else {
-        None
+        logger.read_where(filter.range.since, limit, |log| filter.matches(log))?
     };
     
-    // Convert CLI OperationType to domain OperationType if needed
-    let operation_filter = operation.map(|op| convert_operation_type(op));
-    
-    // Read filtered logs
-    let logs = logger.read_filtered_logs(
-        operation_filter,
-        since_datetime,
-        None, // No limit for now
-    )?;
-    
     if logs.is_empty() {
         println!("No operations found");
         return Ok(());
     }
     
     if detailed {
-        display_detailed_logs(&logs, verbose)
+        display_detailed_logs(&logs, ctx.verbose)
     } else {
-        display_summary_logs(&logs, verbose)
+        display_summary_logs(&logs, ctx.verbose)
     }
 }
 
+/// Print the last `limit` matches, then every new match, one JSON object per line
+fn follow_logs(logger: &OperationLogger, filter: &LogFilter, limit: Option<usize>) -> Result<()> {
+    let mut follower = logger.follow()?;
+    let mut backlog = match limit {
+        Some(limit) => logger.read_where(filter.range.since, Some(limit), |log| filter.matches(log))?,
+        None => Vec::new(),
+    };
+    backlog.reverse();
+
+    let mut pending = backlog;
+    loop {
+        let mut stdout = std::io::stdout().lock();
+        for log in pending.iter().filter(|log| filter.matches(log)) {
+            // A closed pipe means the subscriber went away
+            if writeln!(stdout, "{}", serde_json::to_string(log)?).is_err() {
+                return Ok(());
+            }
+        }
+        if stdout.flush().is_err() {
+            return Ok(());
+        }
+        drop(stdout);
+
+        std::thread::sleep(FOLLOW_INTERVAL);
+        pending = follower.poll()?;
+    }
+}
+
+/// Check the hash chain across all segments and report the first break
+pub fn verify(ctx: &Context) -> Result<()> {
+    let config = ctx.load_config()?;
+    let logger = OperationLogger::new(config.operation_log_path()).with_config(config.log.clone());
+    let report = logger.verify()?;
+
+    if let Some(broken) = &report.first_break {
+        let record = match (broken.id, broken.timestamp) {
+            (Some(id), Some(time)) => {
+                format!("entry {} at {}", &id.to_string()[..8], time.format("%Y-%m-%d %H:%M:%S UTC"))
+            }
+            (None, Some(time)) => format!("checkpoint at {}", time.format("%Y-%m-%d %H:%M:%S UTC")),
+            _ => "unreadable record".to_string(),
+        };
+        #[cfg(feature = "colors")]
+        eprintln!(
+            "{} {}:{}: {}",
+            "❌ Log chain broken at".red().bold(),
+            broken.file.display(),
+            broken.line,
+            record
+        );
+        #[cfg(not(feature = "colors"))]
+        eprintln!("Log chain broken at {}:{}: {}", broken.file.display(), broken.line, record);
+        eprintln!("   {}", broken.reason);
+        anyhow::bail!("operation log failed verification");
+    }
+
+    #[cfg(feature = "colors")]
+    println!(
+        "{} {} entries in {} file(s)",
+        "✅ Log chain intact:".green().bold(),
+        report.entries,
+        report.files.len()
+    );
+    #[cfg(not(feature = "colors"))]
+    println!("Log chain intact: {} entries in {} file(s)", report.entries, report.files.len());
+
+    if report.legacy > 0 {
+        println!("   {} older entries predate the chain and cannot be verified", report.legacy);
+    }
+    if let Some(anchor) = report.anchor {
+        println!(
+            "   Verified from a checkpoint at {}; older history was rotated out or compacted",
+            anchor.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+    if report.unauthenticated {
+        println!(
+            "   Entries carry MACs but {} is missing, so edits to the newest entry cannot be ruled out",
+            logger.audit_key_path().display()
+        );
+    } else if !config.log.hmac {
+        println!("   Enable log.hmac to also protect the newest entry against edits");
+    }
+    Ok(())
+}
+
 /// Display logs in detailed format
 fn display_detailed_logs(logs: &[crate::domain::operation_log::OperationLog], verbose: bool) -> Result<()> {
     for (index, log) in logs.iter().enumerate() {
@@ -220,40 +380,6 @@ fn display_operation_stats(logs: &[crate::domain::operation_log::OperationLog])
     Ok(())
 }
 
-/// Parse since date string into DateTime<Utc>
-fn parse_since_date(since_str: &str) -> Result<DateTime<Utc>> {
-    match since_str.to_lowercase().as_str() {
-        "today" => {
-            let now = Utc::now();
-            Ok(now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc())
-        }
-        "yesterday" => {
-            let yesterday = Utc::now() - chrono::Duration::days(1);
-            Ok(yesterday.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc())
-        }
-        "week" | "this-week" => {
-            let week_ago = Utc::now() - chrono::Duration::weeks(1);
-            Ok(week_ago)
-        }
-        "month" | "this-month" => {
-            let month_ago = Utc::now() - chrono::Duration::days(30);
-            Ok(month_ago)
-        }
-        _ => {
-            // Try to parse as ISO date
-            if let Ok(date) = chrono::DateTime::parse_from_rfc3339(since_str) {
-                Ok(date.with_timezone(&Utc))
-            } else if let Ok(date) = chrono::NaiveDateTime::parse_from_str(since_str, "%Y-%m-%d %H:%M:%S") {
-                Ok(date.and_utc())
-            } else if let Ok(date) = chrono::NaiveDate::parse_from_str(since_str, "%Y-%m-%d") {
-                Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc())
-            } else {
-                anyhow::bail!("Invalid date format: {}", since_str);
-            }
-        }
-    }
-}
-
 /// Convert CLI OperationType to domain OperationType
 fn convert_operation_type(cli_op: OperationType) -> crate::domain::operation_log::OperationType {
     match cli_op {
@@ -263,6 +389,8 @@ fn convert_operation_type(cli_op: OperationType) -> crate::domain::operation_log
         OperationType::Status => crate::domain::operation_log::OperationType::Status,
         OperationType::Protect => crate::domain::operation_log::OperationType::Protect,
         OperationType::Doctor => crate::domain::operation_log::OperationType::Doctor,
+        OperationType::Tag => crate::domain::operation_log::OperationType::Tag,
+        OperationType::Annotate => crate::domain::operation_log::OperationType::Annotate,
     }
 }
 
@@ -294,31 +422,6 @@ fn format_relative_time(datetime: DateTime<Utc>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Datelike;
-    
-    #[test]
-    fn test_parse_since_date() {
-        // Test special keywords
-        let today = parse_since_date("today").unwrap();
-        let now = Utc::now();
-        assert_eq!(today.date_naive(), now.date_naive());
-        
-        let yesterday = parse_since_date("yesterday").unwrap();
-        let expected = (now - chrono::Duration::days(1)).date_naive();
-        assert_eq!(yesterday.date_naive(), expected);
-        
-        // Test ISO date
-        let iso_date = parse_since_date("2024-01-01T00:00:00Z").unwrap();
-        assert_eq!(iso_date.year(), 2024);
-        assert_eq!(iso_date.month(), 1);
-        assert_eq!(iso_date.day(), 1);
-        
-        // Test simple date
-        let simple_date = parse_since_date("2024-01-01").unwrap();
-        assert_eq!(simple_date.year(), 2024);
-        assert_eq!(simple_date.month(), 1);
-        assert_eq!(simple_date.day(), 1);
-    }
     
     #[test]
     fn test_convert_operation_type() {
@@ -336,6 +439,28 @@ mod tests {
         );
     }
     
+    #[test]
+    fn test_log_filter() {
+        let mut log = OperationLog::new(
+            crate::domain::operation_log::OperationType::Delete,
+            vec![std::path::PathBuf::from("/home/me/src/main.rs")],
+            OperationResult::Success,
+        )
+        .with_tags(vec!["work".to_string()]);
+        log.user = "me".to_string();
+        let filter = |filter: LogFilter| filter.resolved().unwrap().matches(&log);
+
+        assert!(filter(LogFilter::default()));
+        assert!(filter(LogFilter { path: Some("/home/me".to_string()), ..Default::default() }));
+        assert!(!filter(LogFilter { path: Some("/home/me/sr".to_string()), ..Default::default() }));
+        assert!(filter(LogFilter { path: Some("/home/**/*.rs".to_string()), ..Default::default() }));
+        assert!(filter(LogFilter { id: Some(log.id.to_string()[..8].to_uppercase()), ..Default::default() }));
+        assert!(!filter(LogFilter { user: Some("root".to_string()), ..Default::default() }));
+        assert!(!filter(LogFilter { result: Some(LogResult::Failed), ..Default::default() }));
+        assert!(!filter(LogFilter { tags: vec!["work".to_string(), "old".to_string()], ..Default::default() }));
+        assert!(!filter(LogFilter { operation: Some(OperationType::Purge), ..Default::default() }));
+    }
+
     #[test]
     fn test_format_relative_time() {
         let now = Utc::now();
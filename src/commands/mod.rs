@@ -1,17 +1,29 @@
+pub mod annotate;
+pub mod cat;
 pub mod completions;
 pub mod config;
+pub mod context;
 pub mod delete;
+pub mod diff;
 pub mod doctor;
 pub mod extract;
+pub mod gc;
+pub mod history;
+pub mod key;
 pub mod list;
 pub mod log;
 pub mod protect;
 pub mod purge;
 pub mod restore;
+pub mod show;
 pub mod status;
+pub mod tag;
+pub mod tui;
 
 use crate::cli::{Cli, Commands};
 
+pub use context::Context;
+
 pub fn execute_command(cli: Cli) -> anyhow::Result<()> {
     // Initialize colored output based on CLI flags
     #[cfg(feature = "colors")]
@@ -21,45 +33,101 @@ pub fn execute_command(cli: Cli) -> anyhow::Result<()> {
         }
     }
 
+    let ctx = Context::from_cli(&cli);
     match cli.command {
         Commands::Delete {
             paths,
             force,
             dry_run,
-            tag,
+            tags,
+            note,
             interactive,
             recursive,
-        } => delete::execute(paths, force, dry_run, tag, interactive, recursive, cli.verbose),
+        } => delete::execute(&ctx, paths, force, dry_run, tags, note, interactive, recursive),
         Commands::Restore {
             file,
             id,
             interactive,
             all,
             to,
-        } => restore::execute(file, id, interactive, all, to, cli.verbose),
+            range,
+            tags,
+            version,
+            at,
+            under,
+            as_of,
+            dry_run,
+            on_conflict,
+        } => match (under, as_of) {
+            (Some(dir), Some(as_of)) => restore::execute_tree(&ctx, dir, as_of, dry_run, on_conflict),
+            _ => {
+                let selection = restore::Selection { range: range.parse()?, tags };
+                restore::execute(&ctx, file, id, interactive, all, to, selection, version, at)
+            }
+        },
+        Commands::History { path } => history::execute(&ctx, path),
         Commands::List {
             json,
             filter,
-            since,
+            tags,
+            range,
             group_by,
             limit,
-        } => list::execute(json, filter, since, group_by, limit, cli.verbose),
-        Commands::Status { detailed } => status::execute(detailed, cli.verbose),
+            sort,
+            reverse,
+            columns,
+            tree,
+            here,
+        } => {
+            let view = list::ListView { group_by, sort, reverse, columns, tree, here };
+            list::execute(&ctx, json, filter, tags, range.parse()?, limit, view)
+        }
+        Commands::Status { detailed } => status::execute(&ctx, detailed),
         Commands::Purge {
             all,
             days,
             size,
             id,
             interactive,
-        } => purge::execute(all, days, size, id, interactive, cli.verbose),
+            range,
+            tags,
+        } => purge::execute(&ctx, all, days, size, id, interactive, range.parse()?, tags),
+        Commands::Tag { action } => tag::execute(&ctx, action),
+        Commands::Tags => tag::summary(&ctx),
+        Commands::Annotate { id, note, clear } => annotate::execute(&ctx, id, note, clear),
         Commands::Log {
+            action: Some(crate::cli::LogAction::Verify),
+            ..
+        } => log::verify(&ctx),
+        Commands::Log {
+            action: None,
             detailed,
             operation,
-            since,
-        } => log::execute(detailed, operation, since, cli.verbose),
-        Commands::Protect { action } => protect::execute(action, cli.verbose),
-        Commands::Config { action } => config::execute(action, cli.verbose),
-        Commands::Doctor { check, fix, verbose, force } => {
+            path,
+            id,
+            user,
+            result,
+            tags,
+            limit,
+            reverse,
+            follow,
+            range,
+        } => {
+            let filter = log::LogFilter {
+                operation,
+                path,
+                id,
+                user,
+                result,
+                tags,
+                range: range.parse()?,
+            };
+            log::execute(&ctx, detailed, filter, limit, reverse, follow)
+        }
+        Commands::Protect { action } => protect::execute(&ctx, action),
+        Commands::Key { action } => key::execute(&ctx, action),
+        Commands::Config { action } => config::execute(&ctx, action),
+        Commands::Doctor { check, fix, dry_run, quarantine, verbose, force } => {
             // Convert CLI DiagnosticCheck to doctor module's DiagnosticCheck
             let doctor_check = check.map(|c| match c {
                 crate::cli::DiagnosticCheck::TrashZone => crate::commands::doctor::DiagnosticCheck::TrashZone,
@@ -67,13 +135,19 @@ pub fn execute_command(cli: Cli) -> anyhow::Result<()> {
                 crate::cli::DiagnosticCheck::Permissions => crate::commands::doctor::DiagnosticCheck::Permissions,
                 crate::cli::DiagnosticCheck::Config => crate::commands::doctor::DiagnosticCheck::Config,
                 crate::cli::DiagnosticCheck::Dependencies => crate::commands::doctor::DiagnosticCheck::Dependencies,
+                crate::cli::DiagnosticCheck::Encryption => crate::commands::doctor::DiagnosticCheck::Encryption,
                 crate::cli::DiagnosticCheck::All => crate::commands::doctor::DiagnosticCheck::All,
             });
-            crate::commands::doctor::execute(doctor_check, fix, verbose, force)
+            crate::commands::doctor::execute(&ctx, doctor_check, fix, dry_run, quarantine, verbose, force)
         },
-        Commands::Completions { shell } => completions::execute(shell, cli.verbose),
-        Commands::Extract { from, file, interactive, all, to, tree } => {
-            extract::execute(from, file, interactive, all, to, tree, cli.verbose)
+        Commands::Show { query } => show::execute(&ctx, query),
+        Commands::Cat { id, path, raw } => cat::execute(&ctx, id, path, raw),
+        Commands::Diff { id, path } => diff::execute(&ctx, id, path),
+        Commands::Tui => tui::execute(&ctx),
+        Commands::Gc { install_timer, cron } => gc::execute(&ctx, install_timer, cron),
+        Commands::Completions { shell } => completions::execute(shell, ctx.verbose),
+        Commands::Extract { from, file, interactive, all, to, tree, tags } => {
+            extract::execute(&ctx, from, file, interactive, all, to, tree, tags)
         },
     }
 }
@@ -1,3 +1,4 @@
+use super::Context;
 use crate::cli::ProtectAction;
 use crate::domain::Config;
 use anyhow::Result;
@@ -7,17 +8,18 @@ use std::path::PathBuf;
 use colored::Colorize;
 
 /// Execute the protect command
-pub fn execute(action: ProtectAction, verbose: bool) -> Result<()> {
+pub fn execute(ctx: &Context, action: ProtectAction) -> Result<()> {
     match action {
-        ProtectAction::Add { paths } => add_protected_paths(paths, verbose),
-        ProtectAction::Remove { paths } => remove_protected_paths(paths, verbose),
-        ProtectAction::List => list_protected_paths(verbose),
+        ProtectAction::Add { paths } => add_protected_paths(ctx, paths),
+        ProtectAction::Remove { paths } => remove_protected_paths(ctx, paths),
+        ProtectAction::List => list_protected_paths(ctx),
     }
 }
 
 /// Add paths to the protection list
-fn add_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
+fn add_protected_paths(ctx: &Context, paths: Vec<PathBuf>) -> Result<()> {
+    let verbose = ctx.verbose;
+    let mut config = ctx.load_config()?;
     let mut added_count = 0;
     let mut already_protected = Vec::new();
     
@@ -45,7 +47,7 @@ fn add_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
     }
     
     // Save the updated configuration
-    config.save()?;
+    ctx.save_config(&config)?;
     
     // Show summary
     if added_count > 0 {
@@ -85,8 +87,9 @@ fn add_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
 }
 
 /// Remove paths from the protection list
-fn remove_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
+fn remove_protected_paths(ctx: &Context, paths: Vec<PathBuf>) -> Result<()> {
+    let verbose = ctx.verbose;
+    let mut config = ctx.load_config()?;
     let mut removed_count = 0;
     let mut not_protected = Vec::new();
     
@@ -116,7 +119,7 @@ fn remove_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
     }
     
     // Save the updated configuration
-    config.save()?;
+    ctx.save_config(&config)?;
     
     // Show summary
     if removed_count > 0 {
@@ -156,8 +159,9 @@ fn remove_protected_paths(paths: Vec<PathBuf>, verbose: bool) -> Result<()> {
 }
 
 /// List all protected paths
-fn list_protected_paths(verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+fn list_protected_paths(ctx: &Context) -> Result<()> {
+    let verbose = ctx.verbose;
+    let config = ctx.load_config()?;
     
     if config.protected_paths.is_empty() {
         println!("No paths are currently protected");
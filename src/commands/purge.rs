@@ -1,49 +1,53 @@
-use crate::domain::Config;
-use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
+use super::Context;
+use crate::domain::TrashItem;
+use crate::trash::{stored_size, PurgeOutcome, PurgeSelector, Trash};
+use crate::utils::time_expr::TimeRange;
+use crate::utils::size_parser::format_size;
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Local, Utc};
 use dialoguer::Confirm;
 use std::collections::HashSet;
-use std::path::PathBuf;
-use uuid::Uuid;
 
 #[cfg(feature = "colors")]
 use colored::Colorize;
 
 /// Execute the purge command
+#[allow(clippy::too_many_arguments)]
 pub fn execute(
+    ctx: &Context,
     all: bool,
     days: Option<u32>,
     size: Option<String>,
     id: Option<String>,
     interactive: bool,
-    verbose: bool,
+    range: TimeRange,
+    tags: Vec<String>,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let trash_root = config.trash_path.clone();
-    let trash_store = TrashStore::new(trash_root);
+    let trash = ctx.open_trash()?;
     
     if all {
-        purge_all(&trash_store, interactive, verbose)
+        purge_all(&trash, interactive, ctx.verbose)
     } else if let Some(days) = days {
-        purge_by_age(&trash_store, days, interactive, verbose)
+        purge_by_age(&trash, days, interactive, ctx.verbose)
     } else if let Some(size_limit) = size {
-        purge_by_size(&trash_store, &size_limit, interactive, verbose)
+        purge_by_size(&trash, &size_limit, interactive, ctx.verbose)
     } else if let Some(id_str) = id {
-        purge_by_id(&trash_store, &id_str, interactive, verbose)
+        purge_by_id(&trash, &id_str, interactive, ctx.verbose)
+    } else if !range.is_unbounded() || !tags.is_empty() {
+        purge_matching(&trash, range, &tags, interactive, ctx.verbose)
     } else {
         // Default behavior - interactive purge
-        interactive_purge(&trash_store, verbose)
+        interactive_purge(&trash, ctx.verbose)
     }
 }
 
 /// Purge all files from trash
 fn purge_all(
-    trash_store: &TrashStore,
+    trash: &Trash,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    let items = trash_store.list()?;
+    let items = trash.select(&PurgeSelector::All)?;
     
     if items.is_empty() {
         println!("Trash is already empty");
@@ -58,47 +62,26 @@ fn purge_all(
         }
     }
     
-    let mut purged_count = 0;
-    
-    for item in &items {
-        match trash_store.purge(&item.meta.id) {
-            Ok(()) => {
-                purged_count += 1;
-                if verbose {
-                    #[cfg(feature = "colors")]
-                    println!("🗑️  Purged: {}", item.meta.original_path.display().to_string().bright_red());
-                    #[cfg(not(feature = "colors"))]
-                    println!("Purged: {}", item.meta.original_path.display());
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to purge {}: {}", item.meta.original_path.display(), e);
-            }
-        }
-    }
+    let outcome = trash.purge_items(&items);
+    report_purged(&outcome, verbose);
     
     #[cfg(feature = "colors")]
-    println!("✅ Purged {} items", purged_count.to_string().green().bold());
+    println!("✅ Purged {} items", outcome.purged.len().to_string().green().bold());
     #[cfg(not(feature = "colors"))]
-    println!("Purged {} items", purged_count);
+    println!("Purged {} items", outcome.purged.len());
     
     Ok(())
 }
 
 /// Purge files older than specified days
 fn purge_by_age(
-    trash_store: &TrashStore,
+    trash: &Trash,
     days: u32,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
     let cutoff_date = Utc::now() - Duration::days(days as i64);
-    let items = trash_store.list()?;
-    
-    let old_items: Vec<_> = items
-        .into_iter()
-        .filter(|item| item.meta.deleted_at < cutoff_date)
-        .collect();
+    let old_items = trash.select(&PurgeSelector::OlderThan(cutoff_date))?;
     
     if old_items.is_empty() {
         println!("No files older than {} days found", days);
@@ -117,61 +100,99 @@ fn purge_by_age(
         }
     }
     
-    let mut purged_count = 0;
-    
-    for item in &old_items {
-        match trash_store.purge(&item.meta.id) {
-            Ok(()) => {
-                purged_count += 1;
-                if verbose {
-                    #[cfg(feature = "colors")]
-                    println!("🗑️  Purged: {} (deleted {})",
-                        item.meta.original_path.display().to_string().bright_red(),
-                        format_relative_time(item.meta.deleted_at).dimmed()
-                    );
-                    #[cfg(not(feature = "colors"))]
-                    println!("Purged: {} (deleted {})",
-                        item.meta.original_path.display(),
-                        format_relative_time(item.meta.deleted_at)
-                    );
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to purge {}: {}", item.meta.original_path.display(), e);
-            }
+    let outcome = trash.purge_items(&old_items);
+    if verbose {
+        for item in &outcome.purged {
+            #[cfg(feature = "colors")]
+            println!("🗑️  Purged: {} (deleted {})",
+                item.meta.original_path.display().to_string().bright_red(),
+                format_relative_time(item.meta.deleted_at).dimmed()
+            );
+            #[cfg(not(feature = "colors"))]
+            println!("Purged: {} (deleted {})",
+                item.meta.original_path.display(),
+                format_relative_time(item.meta.deleted_at)
+            );
         }
     }
+    report_failures(&outcome);
     
     #[cfg(feature = "colors")]
     println!("✅ Purged {} items older than {} days", 
-        purged_count.to_string().green().bold(), 
+        outcome.purged.len().to_string().green().bold(), 
         days.to_string().yellow()
     );
     #[cfg(not(feature = "colors"))]
-    println!("Purged {} items older than {} days", purged_count, days);
+    println!("Purged {} items older than {} days", outcome.purged.len(), days);
     
     Ok(())
 }
 
+/// Purge files deleted within a time range
+fn purge_matching(
+    trash: &Trash,
+    range: TimeRange,
+    tags: &[String],
+    interactive: bool,
+    verbose: bool,
+) -> Result<()> {
+    let mut items = trash.select(&PurgeSelector::DeletedWithin(range))?;
+    items.retain(|item| tags.iter().all(|tag| item.meta.tags.contains(tag)));
+    let description = describe_selection(&range, tags);
+
+    if items.is_empty() {
+        println!("No files {} found", description);
+        return Ok(());
+    }
+
+    if interactive {
+        let msg = format!("Permanently delete {} items {}?", items.len(), description);
+        if !Confirm::new().with_prompt(msg).interact()? {
+            println!("Purge cancelled");
+            return Ok(());
+        }
+    }
+
+    let outcome = trash.purge_items(&items);
+    report_purged(&outcome, verbose);
+
+    #[cfg(feature = "colors")]
+    println!("✅ Purged {} items {}", outcome.purged.len().to_string().green().bold(), description);
+    #[cfg(not(feature = "colors"))]
+    println!("Purged {} items {}", outcome.purged.len(), description);
+
+    Ok(())
+}
+
+/// Human-readable selection in local time, e.g. "deleted since 2024-01-01 00:00 tagged tmp"
+fn describe_selection(range: &TimeRange, tags: &[String]) -> String {
+    let format = |time: DateTime<Utc>| time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string();
+    let mut parts = Vec::new();
+    match (range.since, range.before) {
+        (Some(since), Some(before)) => parts.push(format!("deleted between {} and {}", format(since), format(before))),
+        (Some(since), None) => parts.push(format!("deleted since {}", format(since))),
+        (None, Some(before)) => parts.push(format!("deleted before {}", format(before))),
+        (None, None) => {}
+    }
+    if !tags.is_empty() {
+        parts.push(format!("tagged {}", tags.join(", ")));
+    }
+    parts.join(" ")
+}
+
 /// Purge files when trash exceeds size limit
 fn purge_by_size(
-    trash_store: &TrashStore,
+    trash: &Trash,
     size_limit: &str,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
     let target_bytes = parse_size(size_limit)?;
-    let items = trash_store.list()?;
-    
-    // Calculate current size
-    let mut total_size = 0u64;
-    let mut item_sizes = Vec::new();
-    
-    for item in items {
-        let size = calculate_item_size(&item.trash_path)?;
-        total_size += size;
-        item_sizes.push((item, size));
-    }
+    let total_size: u64 = trash
+        .select(&PurgeSelector::All)?
+        .iter()
+        .map(|item| stored_size(&item.trash_path))
+        .sum();
     
     if total_size <= target_bytes {
         #[cfg(feature = "colors")]
@@ -187,20 +208,9 @@ fn purge_by_size(
         return Ok(());
     }
     
-    // Sort by oldest first
-    item_sizes.sort_by_key(|(item, _)| item.meta.deleted_at);
-    
     let excess_bytes = total_size - target_bytes;
-    let mut to_purge = Vec::new();
-    let mut purge_size = 0u64;
-    
-    for (item, size) in item_sizes {
-        to_purge.push((item, size));
-        purge_size += size;
-        if purge_size >= excess_bytes {
-            break;
-        }
-    }
+    let to_purge = trash.select(&PurgeSelector::ExceedingSize(target_bytes))?;
+    let purge_size: u64 = to_purge.iter().map(|item| stored_size(&item.trash_path)).sum();
     
     if interactive {
         let msg = format!(
@@ -215,87 +225,44 @@ fn purge_by_size(
         }
     }
     
-    let mut purged_count = 0;
-    let mut freed_bytes = 0u64;
-    
-    for (item, size) in &to_purge {
-        match trash_store.purge(&item.meta.id) {
-            Ok(()) => {
-                purged_count += 1;
-                freed_bytes += size;
-                if verbose {
-                    #[cfg(feature = "colors")]
-                    println!("🗑️  Purged: {} ({})",
-                        item.meta.original_path.display().to_string().bright_red(),
-                        format_size(*size).dimmed()
-                    );
-                    #[cfg(not(feature = "colors"))]
-                    println!("Purged: {} ({})",
-                        item.meta.original_path.display(),
-                        format_size(*size)
-                    );
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to purge {}: {}", item.meta.original_path.display(), e);
-            }
-        }
-    }
+    let outcome = trash.purge_items(&to_purge);
+    report_purged(&outcome, verbose);
     
     #[cfg(feature = "colors")]
     println!("✅ Purged {} items, freed {}", 
-        purged_count.to_string().green().bold(),
-        format_size(freed_bytes).yellow()
+        outcome.purged.len().to_string().green().bold(),
+        format_size(outcome.freed).yellow()
     );
     #[cfg(not(feature = "colors"))]
-    println!("Purged {} items, freed {}", purged_count, format_size(freed_bytes));
+    println!("Purged {} items, freed {}", outcome.purged.len(), format_size(outcome.freed));
     
     Ok(())
 }
 
 /// Purge specific file by ID
 fn purge_by_id(
-    trash_store: &TrashStore,
+    trash: &Trash,
     id_str: &str,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    // Try to parse as full UUID first
-    let id = match Uuid::parse_str(id_str) {
-        Ok(uuid) => uuid,
-        Err(_) => {
-            // Try partial UUID matching
-            let items = trash_store.list()?;
-            let partial_id_lower = id_str.to_lowercase();
-            
-            let matches: Vec<_> = items
-                .into_iter()
-                .filter(|item| {
-                    item.meta.id.to_string().to_lowercase().starts_with(&partial_id_lower)
-                })
-                .collect();
-            
-            match matches.len() {
-                0 => return Err(anyhow!("No file found with ID starting with: {}", id_str)),
-                1 => matches[0].meta.id,
-                _ => {
-                    eprintln!("Multiple files match ID prefix '{}':", id_str);
-                    for item in matches {
-                        println!("  {} - {}", 
-                            &item.meta.id.to_string()[..8],
-                            item.meta.original_path.display()
-                        );
-                    }
-                    return Err(anyhow!("Please provide a more specific ID"));
-                }
+    let matches = trash.find(id_str)?;
+    
+    let item = match matches.len() {
+        0 => return Err(anyhow!("No file found with ID starting with: {}", id_str)),
+        1 => &matches[0],
+        _ => {
+            eprintln!("Multiple files match ID prefix '{}':", id_str);
+            for item in &matches {
+                println!("  {} - {}", 
+                    &item.meta.id.to_string()[..8],
+                    item.meta.original_path.display()
+                );
             }
+            return Err(anyhow!("Please provide a more specific ID"));
         }
     };
     
-    // Find the item
-    let item = trash_store.find_by_id(&id)?
-        .ok_or_else(|| anyhow!("File with ID {} not found", id))?;
-    
     if interactive {
         let msg = format!(
             "Permanently delete '{}'?",
@@ -307,7 +274,10 @@ fn purge_by_id(
         }
     }
     
-    trash_store.purge(&id)?;
+    let outcome = trash.purge_items(std::slice::from_ref(item));
+    if let Some((_, e)) = outcome.failed.into_iter().next() {
+        return Err(e.into());
+    }
     
     if verbose {
         #[cfg(feature = "colors")]
@@ -325,8 +295,8 @@ fn purge_by_id(
 }
 
 /// Interactive purge mode
-fn interactive_purge(trash_store: &TrashStore, verbose: bool) -> Result<()> {
-    let items = trash_store.list()?;
+fn interactive_purge(trash: &Trash, verbose: bool) -> Result<()> {
+    let items = trash.select(&PurgeSelector::All)?;
     
     if items.is_empty() {
         println!("Trash is empty");
@@ -365,35 +335,40 @@ fn interactive_purge(trash_store: &TrashStore, verbose: bool) -> Result<()> {
         return Ok(());
     }
     
-    let mut purged_count = 0;
-    
-    for item in &items {
-        if selected_ids.contains(&item.meta.id) {
-            match trash_store.purge(&item.meta.id) {
-                Ok(()) => {
-                    purged_count += 1;
-                    if verbose {
-                        #[cfg(feature = "colors")]
-                        println!("🗑️  Purged: {}", item.meta.original_path.display().to_string().bright_red());
-                        #[cfg(not(feature = "colors"))]
-                        println!("Purged: {}", item.meta.original_path.display());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to purge {}: {}", item.meta.original_path.display(), e);
-                }
-            }
-        }
-    }
+    let selected: Vec<TrashItem> = items
+        .into_iter()
+        .filter(|item| selected_ids.contains(&item.meta.id))
+        .collect();
+    let outcome = trash.purge_items(&selected);
+    report_purged(&outcome, verbose);
     
     #[cfg(feature = "colors")]
-    println!("✅ Purged {} items", purged_count.to_string().green().bold());
+    println!("✅ Purged {} items", outcome.purged.len().to_string().green().bold());
     #[cfg(not(feature = "colors"))]
-    println!("Purged {} items", purged_count);
+    println!("Purged {} items", outcome.purged.len());
     
     Ok(())
 }
 
+/// Print purged items when verbose, and any failures
+fn report_purged(outcome: &PurgeOutcome, verbose: bool) {
+    if verbose {
+        for item in &outcome.purged {
+            #[cfg(feature = "colors")]
+            println!("🗑️  Purged: {}", item.meta.original_path.display().to_string().bright_red());
+            #[cfg(not(feature = "colors"))]
+            println!("Purged: {}", item.meta.original_path.display());
+        }
+    }
+    report_failures(outcome);
+}
+
+fn report_failures(outcome: &PurgeOutcome) {
+    for (item, e) in &outcome.failed {
+        eprintln!("Failed to purge {}: {}", item.meta.original_path.display(), e);
+    }
+}
+
 /// Parse size string (e.g., "100MB", "1GB") to bytes
 fn parse_size(size_str: &str) -> Result<u64> {
     let size_str = size_str.to_uppercase();
@@ -419,41 +394,6 @@ fn parse_size(size_str: &str) -> Result<u64> {
     Ok((number * unit_part as f64) as u64)
 }
 
-/// Format bytes to human readable size
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-    
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
-    }
-    
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
-    }
-}
-
-/// Calculate size of a file or directory
-fn calculate_item_size(path: &PathBuf) -> Result<u64> {
-    if path.is_file() {
-        Ok(std::fs::metadata(path)?.len())
-    } else if path.is_dir() {
-        let mut total_size = 0u64;
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let entry_path = entry.path();
-            total_size += calculate_item_size(&entry_path)?;
-        }
-        Ok(total_size)
-    } else {
-        Ok(0)
-    }
-}
-
 /// Format relative time (e.g., "2 hours ago", "3 days ago")
 fn format_relative_time(datetime: DateTime<Utc>) -> String {
     let now = Utc::now();
@@ -492,13 +432,4 @@ mod tests {
         assert_eq!(parse_size("1.5MB").unwrap(), (1.5 * 1024.0 * 1024.0) as u64);
         assert_eq!(parse_size("500B").unwrap(), 500);
     }
-    
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-    }
 }
\ No newline at end of file
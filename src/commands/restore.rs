@@ -1,21 +1,28 @@
-use crate::infra::{
-    trash_store::TrashStoreInterface, 
-    ConfigManager, 
-    TrashStore, 
-    create_selector,
-    operation_logger::log_operation
-};
-use crate::domain::operation_log::{OperationType, OperationLog, OperationResult};
+use super::Context;
+use crate::domain::config_layers::ConfigOverrides;
+use crate::domain::{GitFileStatus, TrashItem};
+use crate::infra::create_selector;
+use crate::infra::git::GitRepo;
+use crate::cli::ConflictPolicy;
+use crate::trash::{DeleteOptions, ListFilter, RestoreOptions, Trash, VersionSelector};
 use anyhow::Result;
-use dialoguer::Confirm;
-use std::path::PathBuf;
+use crate::utils::time_expr::{parse_time, TimeRange};
+use chrono::{DateTime, Utc};
+use dialoguer::Select;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 #[cfg(feature = "colors")]
 use colored::Colorize;
 
 /// Check if file exists and get user confirmation for overwrite
-fn check_existing_file_and_confirm(restore_path: &PathBuf, interactive: bool) -> Result<bool> {
+///
+/// In interactive mode `show_diff` is offered before deciding.
+fn check_existing_file_and_confirm(
+    restore_path: &Path,
+    interactive: bool,
+    show_diff: &dyn Fn() -> Result<()>,
+) -> Result<bool> {
     if !restore_path.exists() {
         return Ok(true); // File doesn't exist, safe to restore
     }
@@ -47,53 +54,270 @@ fn check_existing_file_and_confirm(restore_path: &PathBuf, interactive: bool) ->
         restore_path.display()
     );
     
-    let confirmed = Confirm::new()
-        .with_prompt(prompt)
-        .default(false) // Default to "no" for safety
-        .interact()?;
-    
-    if !confirmed {
-        #[cfg(feature = "colors")]
-        println!("⚠️  Restore cancelled - file was not overwritten");
-        #[cfg(not(feature = "colors"))]
-        println!("Restore cancelled - file was not overwritten");
-        return Ok(false);
+    loop {
+        let choice = Select::new()
+            .with_prompt(&prompt)
+            .items(&["Overwrite", "Show diff", "Cancel"])
+            .default(2) // Default to "cancel" for safety
+            .interact()?;
+
+        match choice {
+            0 => return Ok(true),
+            1 => show_diff()?,
+            _ => {
+                #[cfg(feature = "colors")]
+                println!("⚠️  Restore cancelled - file was not overwritten");
+                #[cfg(not(feature = "colors"))]
+                println!("Restore cancelled - file was not overwritten");
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Which trashed items a restore picks from
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Only items deleted within this range
+    pub range: TimeRange,
+    /// Only items carrying all of these tags
+    pub tags: Vec<String>,
+}
+
+impl Selection {
+    /// Whether every item is selected
+    fn is_empty(&self) -> bool {
+        self.range.is_unbounded() && self.tags.is_empty()
     }
-    
-    Ok(true)
 }
 
 /// Execute restore command
+#[allow(clippy::too_many_arguments)]
 pub fn execute(
+    ctx: &Context,
     file: Option<String>,
     id: Option<String>,
     interactive: bool,
     all: bool,
     to: Option<PathBuf>,
-    verbose: bool,
+    selection: Selection,
+    version: Option<usize>,
+    at: Option<String>,
 ) -> Result<()> {
-    let config = ConfigManager::load()?;
-    let trash_store = TrashStore::new(config.trash_path.clone());
+    let trash = ctx.open_trash()?;
+
+    let selector = match (version, at) {
+        (Some(number), _) => Some(VersionSelector::Number(number)),
+        (None, Some(time)) => Some(VersionSelector::At(parse_time(&time)?)),
+        (None, None) => None,
+    };
+
+    if let (Some(selector), Some(path)) = (selector, &file) {
+        // Pick one version of an exact path
+        let item = trash.version(Path::new(path), selector)?;
+        return restore_version(&trash, &item, to, interactive, ctx.verbose);
+    }
 
     if let Some(id_str) = id {
         // Restore by specific ID
-        restore_by_id(&trash_store, &id_str, to, interactive, verbose)
+        restore_by_id(&trash, &id_str, to, interactive, ctx.verbose)
     } else if all {
         // Restore all files (with optional filter)
-        restore_all(&trash_store, file, to, interactive, verbose)
+        restore_all(&trash, file, &selection, to, interactive, ctx.verbose)
     } else if interactive {
         // Interactive restore using fuzzy finder
-        restore_interactive(&trash_store, file, to, verbose)
+        restore_interactive(&trash, &ctx.overrides, file, &selection, to, ctx.verbose)
     } else if let Some(pattern) = file {
         // Restore by file pattern
-        restore_by_pattern(&trash_store, &pattern, to, interactive, verbose)
+        restore_by_pattern(&trash, &pattern, &selection, to, interactive, ctx.verbose)
+    } else if !selection.is_empty() {
+        // Restore everything carrying the tags or deleted within the time range
+        restore_all(&trash, None, &selection, to, interactive, ctx.verbose)
     } else {
-        anyhow::bail!("Must specify one of: --id, --all, --interactive, or file pattern");
+        anyhow::bail!("Must specify one of: --id, --all, --interactive, --tag, a time range, or file pattern");
+    }
+}
+
+/// Execute a point-in-time restore of everything deleted beneath `dir`
+pub fn execute_tree(
+    ctx: &Context,
+    dir: PathBuf,
+    as_of: String,
+    dry_run: bool,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let as_of = parse_time(&as_of)?;
+
+    let plan = trash.versions_as_of(&dir, as_of)?;
+    if plan.is_empty() {
+        println!(
+            "Nothing under {} was deleted after {}",
+            dir.display(),
+            as_of.format("%Y-%m-%d %H:%M:%S")
+        );
+        return Ok(());
+    }
+
+    let conflicts = print_tree_plan(&dir, as_of, &plan, ctx.verbose);
+    if dry_run {
+        println!("Dry run - nothing was restored");
+        return Ok(());
+    }
+
+    let policy = match on_conflict {
+        Some(policy) => policy,
+        None if conflicts == 0 => ConflictPolicy::Skip,
+        None => match Select::new()
+            .with_prompt(format!("{} path(s) already exist. What should happen to them?", conflicts))
+            .items(&[
+                "Skip them",
+                "Move them to the trash and restore over them",
+                "Restore next to them with a .restored suffix",
+                "Cancel",
+            ])
+            .default(0)
+            .interact()?
+        {
+            0 => ConflictPolicy::Skip,
+            1 => ConflictPolicy::Overwrite,
+            2 => ConflictPolicy::Rename,
+            _ => {
+                println!("Restoration cancelled");
+                return Ok(());
+            }
+        },
+    };
+
+    if !confirm_restore_all(&plan)? {
+        println!("Restoration cancelled");
+        return Ok(());
+    }
+
+    let summary = restore_tree(&trash, &plan, policy);
+    for (item, path) in &summary.restored {
+        if ctx.verbose {
+            println!("✅ Restored {} -> {}", item.meta.original_path.display(), path.display());
+        }
     }
+    for (item, e) in &summary.failed {
+        eprintln!("❌ Failed to restore {}: {}", item.meta.original_path.display(), e);
+    }
+
+    println!(
+        "Restored {} item(s), skipped {}, failed {}",
+        summary.restored.len(),
+        summary.skipped,
+        summary.failed.len()
+    );
+    Ok(())
+}
+
+/// Print what a tree restore would do and return how many paths already exist
+fn print_tree_plan(dir: &Path, as_of: DateTime<Utc>, plan: &[TrashItem], verbose: bool) -> usize {
+    println!(
+        "Restore plan for {} as of {} ({} item(s)):",
+        dir.display(),
+        as_of.format("%Y-%m-%d %H:%M:%S"),
+        plan.len()
+    );
+
+    let mut conflicts = 0;
+    for item in plan {
+        let exists = item.meta.original_path.exists();
+        let line = format!(
+            "{} ({}, deleted {}{})",
+            item.meta.original_path.display(),
+            item.meta.human_readable_size(),
+            item.meta.deleted_at.format("%Y-%m-%d %H:%M:%S"),
+            if verbose { format!(", {}", item.meta.id) } else { String::new() }
+        );
+        if exists {
+            conflicts += 1;
+            #[cfg(feature = "colors")]
+            println!("  {} {} {}", "!".yellow(), line, "- already exists".yellow());
+            #[cfg(not(feature = "colors"))]
+            println!("  ! {} - already exists", line);
+        } else {
+            #[cfg(feature = "colors")]
+            println!("  {} {}", "+".green(), line);
+            #[cfg(not(feature = "colors"))]
+            println!("  + {}", line);
+        }
+    }
+
+    println!("{} to restore, {} already exist", plan.len() - conflicts, conflicts);
+    conflicts
+}
+
+/// Outcome of [`restore_tree`]
+#[derive(Debug, Default)]
+struct TreeRestoreSummary {
+    restored: Vec<(TrashItem, PathBuf)>,
+    skipped: usize,
+    failed: Vec<(TrashItem, anyhow::Error)>,
+}
+
+/// Restore planned items in order, resolving every conflict with one policy
+fn restore_tree(trash: &Trash, plan: &[TrashItem], policy: ConflictPolicy) -> TreeRestoreSummary {
+    let mut summary = TreeRestoreSummary::default();
+
+    for item in plan {
+        let original = &item.meta.original_path;
+        // Checked again here since restoring a parent may have recreated the path
+        let to = if original.exists() {
+            match policy {
+                ConflictPolicy::Skip => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                ConflictPolicy::Overwrite => {
+                    // The replaced path is trashed like any other deletion, hooks included
+                    let options = DeleteOptions {
+                        recursive: true,
+                        ..DeleteOptions::default()
+                    };
+                    let replaced = trash
+                        .check_delete(&[original], &options)
+                        .and_then(|()| trash.delete_path(original, &options));
+                    match replaced {
+                        Ok(replaced) => trash.finish_delete(&[replaced]),
+                        Err(e) => {
+                            summary.failed.push((item.clone(), e.into()));
+                            continue;
+                        }
+                    }
+                    None
+                }
+                ConflictPolicy::Rename => Some(restored_name(original)),
+            }
+        } else {
+            None
+        };
+
+        match trash.restore(&item.meta.id, &RestoreOptions { to, overwrite: false }) {
+            Ok(outcome) => summary.restored.push((item.clone(), outcome.path)),
+            Err(e) => summary.failed.push((item.clone(), e.into())),
+        }
+    }
+
+    summary
+}
+
+/// First free `<path>.restored`, `<path>.restored.2`, ... next to `path`
+fn restored_name(path: &Path) -> PathBuf {
+    let base = format!("{}.restored", path.display());
+    let mut candidate = PathBuf::from(&base);
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = PathBuf::from(format!("{}.{}", base, n));
+        n += 1;
+    }
+    candidate
 }
 
 fn restore_by_id(
-    trash_store: &TrashStore,
+    trash: &Trash,
     id_str: &str,
     to: Option<PathBuf>,
     interactive: bool,
@@ -104,48 +328,20 @@ fn restore_by_id(
         full_id
     } else if id_str.len() >= 8 {
         // Try to find by partial ID (minimum 8 characters for safety)
-        return restore_by_partial_id(trash_store, id_str, to, interactive, verbose);
+        return restore_by_partial_id(trash, id_str, to, interactive, verbose);
     } else {
         return Err(anyhow::anyhow!("ID must be at least 8 characters long: {}", id_str));
     };
 
-    if let Some(item) = trash_store.find_by_id(&id)? {
-        let final_restore_path = if let Some(ref to_path) = to {
-            // Restore to specific location
-            if to_path.is_dir() {
-                // If target is directory, use original filename
-                if let Some(filename) = item.meta.filename() {
-                    to_path.join(filename)
-                } else {
-                    anyhow::bail!("Cannot determine filename for restoration");
-                }
-            } else {
-                to_path.clone()
-            }
-        } else {
-            // Restore to original location
-            item.meta.original_path.clone()
-        };
+    if let Some(item) = trash.find(&id.to_string())?.pop() {
+        let final_restore_path = trash.restore_destination(&item, to.as_deref())?;
 
         // Check if target file already exists and get confirmation
-        if !check_existing_file_and_confirm(&final_restore_path, interactive)? {
+        if !confirm_restore(trash, &item, &final_restore_path, interactive, verbose)? {
             return Ok(()); // User cancelled restoration
         }
 
-        let restore_path = if to.is_some() {
-            // Restore to specific location
-            // Ensure parent directory exists
-            if let Some(parent) = final_restore_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-
-            // Move file to new location
-            std::fs::rename(&item.trash_path, &final_restore_path)?;
-            final_restore_path
-        } else {
-            // Restore to original location using TrashStore
-            trash_store.restore(&id)?
-        };
+        let restore_path = restore_confirmed(trash, &item, to)?;
 
         if verbose {
             println!("✅ Restored: {} -> {}", id, restore_path.display());
@@ -160,13 +356,13 @@ fn restore_by_id(
 }
 
 fn restore_by_partial_id(
-    trash_store: &TrashStore,
+    trash: &Trash,
     partial_id: &str,
     to: Option<PathBuf>,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    let items = trash_store.list()?;
+    let items = list_revealed(trash, &Selection::default())?;
     let partial_id_lower = partial_id.to_lowercase();
     
     // Find all items that start with the partial ID
@@ -180,36 +376,14 @@ fn restore_by_partial_id(
         1 => {
             // Exactly one match, restore it
             let item = &matches[0];
-            let final_restore_path = if let Some(ref to_path) = to {
-                if to_path.is_dir() {
-                    if let Some(filename) = item.meta.filename() {
-                        to_path.join(filename)
-                    } else {
-                        return Err(anyhow::anyhow!("Cannot determine filename for restoration"));
-                    }
-                } else {
-                    to_path.clone()
-                }
-            } else {
-                item.meta.original_path.clone()
-            };
+            let final_restore_path = trash.restore_destination(item, to.as_deref())?;
             
             // Check if target file already exists and get confirmation
-            if !check_existing_file_and_confirm(&final_restore_path, interactive)? {
+            if !confirm_restore(trash, item, &final_restore_path, interactive, verbose)? {
                 return Ok(()); // User cancelled restoration
             }
             
-            let actual_restore_path = if to.is_some() {
-                // Custom location restore with file check already done
-                if let Some(parent) = final_restore_path.parent() {
-                    std::fs::create_dir_all(parent)?;
-                }
-                std::fs::rename(&item.trash_path, &final_restore_path)?;
-                final_restore_path
-            } else {
-                // Original location restore using TrashStore
-                trash_store.restore(&item.meta.id)?
-            };
+            let actual_restore_path = restore_confirmed(trash, item, to)?;
             
             if verbose {
                 println!("✅ Restored {} -> {}", item.meta.original_path.display(), actual_restore_path.display());
@@ -240,13 +414,14 @@ fn restore_by_partial_id(
 }
 
 fn restore_all(
-    trash_store: &TrashStore,
+    trash: &Trash,
     filter: Option<String>,
+    selection: &Selection,
     to: Option<PathBuf>,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    let items = trash_store.list()?;
+    let items = list_revealed(trash, selection)?;
 
     if items.is_empty() {
         println!("No files in trash to restore");
@@ -277,7 +452,7 @@ fn restore_all(
 
     let mut restored_count = 0;
     for item in filtered_items {
-        match restore_single_item(trash_store, &item, to.clone(), interactive, verbose) {
+        match restore_single_item(trash, &item, to.clone(), interactive, verbose) {
             Ok(Some(path)) => {
                 restored_count += 1;
                 if verbose {
@@ -305,17 +480,27 @@ fn restore_all(
 }
 
 fn restore_by_pattern(
-    trash_store: &TrashStore,
+    trash: &Trash,
     pattern: &str,
+    selection: &Selection,
     to: Option<PathBuf>,
     interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    let items = trash_store.list()?;
-    let matching_items: Vec<_> = items
-        .into_iter()
-        .filter(|item| item.meta.matches_pattern(pattern))
-        .collect();
+    // An exact original path restores its latest version rather than every match
+    if selection.is_empty() {
+        if let Ok(item) = trash.version(Path::new(pattern), VersionSelector::Latest) {
+            return restore_version(trash, &item, to, interactive, verbose);
+        }
+    }
+
+    let items = list_revealed(trash, selection)?;
+    let matching_items = latest_versions(
+        items
+            .into_iter()
+            .filter(|item| item.meta.matches_pattern(pattern))
+            .collect(),
+    );
 
     if matching_items.is_empty() {
         println!("No files matching '{}' found in trash", pattern);
@@ -325,7 +510,7 @@ fn restore_by_pattern(
     if matching_items.len() == 1 {
         // Single match, restore directly
         let item = &matching_items[0];
-        match restore_single_item(trash_store, item, to, interactive, verbose)? {
+        match restore_single_item(trash, item, to, interactive, verbose)? {
             Some(path) => println!("Restored: {}", path.display()),
             None => println!("Restoration cancelled"),
         }
@@ -344,7 +529,7 @@ fn restore_by_pattern(
         // For now, restore all matching files
         // TODO: Add interactive selection
         for item in matching_items {
-            match restore_single_item(trash_store, &item, to.clone(), interactive, verbose) {
+            match restore_single_item(trash, &item, to.clone(), interactive, verbose) {
                 Ok(Some(path)) => {
                     if verbose {
                         println!("✅ Restored: {}", path.display());
@@ -370,14 +555,53 @@ fn restore_by_pattern(
     Ok(())
 }
 
+/// Restore one version picked from a path's history
+fn restore_version(
+    trash: &Trash,
+    item: &TrashItem,
+    to: Option<PathBuf>,
+    interactive: bool,
+    verbose: bool,
+) -> Result<()> {
+    match restore_single_item(trash, item, to, interactive, verbose)? {
+        Some(path) if verbose => println!(
+            "✅ Restored {} (deleted {}) -> {}",
+            item.meta.original_path.display(),
+            item.meta.deleted_at.format("%Y-%m-%d %H:%M:%S"),
+            path.display()
+        ),
+        Some(path) => println!("Restored: {}", path.display()),
+        None => println!("Restoration cancelled"),
+    }
+    Ok(())
+}
+
+/// Keep only the most recently deleted item for each original path
+fn latest_versions(items: Vec<TrashItem>) -> Vec<TrashItem> {
+    let mut latest: Vec<TrashItem> = Vec::new();
+    for item in items {
+        match latest
+            .iter_mut()
+            .find(|kept| kept.meta.original_path == item.meta.original_path)
+        {
+            Some(kept) if kept.meta.deleted_at < item.meta.deleted_at => *kept = item,
+            Some(_) => {}
+            None => latest.push(item),
+        }
+    }
+    latest
+}
+
 fn restore_interactive(
-    trash_store: &TrashStore,
+    trash: &Trash,
+    overrides: &ConfigOverrides,
     filter: Option<String>,
+    selection: &Selection,
     to: Option<PathBuf>,
     verbose: bool,
 ) -> Result<()> {
     // Get all items from trash
-    let mut items = trash_store.list()?;
+    let mut items = list_revealed(trash, selection)?;
     
     if items.is_empty() {
         #[cfg(feature = "colors")]
@@ -390,15 +614,12 @@ fn restore_interactive(
     // Apply filter if provided
     if let Some(filter_pattern) = &filter {
         let pattern_lower = filter_pattern.to_lowercase();
-        items = items
-            .into_iter()
-            .filter(|item| {
-                item.meta.original_path
-                    .to_string_lossy()
-                    .to_lowercase()
-                    .contains(&pattern_lower)
-            })
-            .collect();
+        items.retain(|item| {
+            item.meta.original_path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&pattern_lower)
+        });
         
         if items.is_empty() {
             #[cfg(feature = "colors")]
@@ -410,10 +631,10 @@ fn restore_interactive(
     }
     
     // Sort by deletion time (newest first)
-    items.sort_by(|a, b| b.meta.deleted_at.cmp(&a.meta.deleted_at));
+    items.sort_by_key(|item| std::cmp::Reverse(item.meta.deleted_at));
     
     // Create selector based on system capabilities
-    let selector = create_selector();
+    let selector = create_selector(trash.store(), overrides);
     
     if verbose {
         #[cfg(feature = "colors")]
@@ -426,7 +647,7 @@ fn restore_interactive(
     match selector.select_trash_item(&items)? {
         Some(selected_item) => {
             // Restore the selected item
-            let restored_path = restore_single_item(trash_store, &selected_item, to, true, verbose)?;
+            let restored_path = restore_single_item(trash, &selected_item, to, true, verbose)?;
             
             if let Some(path) = restored_path {
                 #[cfg(feature = "colors")]
@@ -436,14 +657,6 @@ fn restore_interactive(
                 );
                 #[cfg(not(feature = "colors"))]
                 println!("✅ Restored: {}", path.display());
-                
-                // Log the operation
-                let log_entry = OperationLog::new(
-                    OperationType::Restore,
-                    vec![selected_item.meta.original_path.clone()],
-                    OperationResult::Success,
-                ).with_file_ids(vec![selected_item.meta.id]);
-                let _ = log_operation(log_entry);
             } else {
                 #[cfg(feature = "colors")]
                 println!("⚠️  {}", "Restore cancelled".yellow());
@@ -463,54 +676,73 @@ fn restore_interactive(
 }
 
 fn restore_single_item(
-    trash_store: &TrashStore,
-    item: &crate::domain::TrashItem,
+    trash: &Trash,
+    item: &TrashItem,
     to: Option<PathBuf>,
     interactive: bool,
     _verbose: bool,
 ) -> Result<Option<PathBuf>> {
-    let final_restore_path = if let Some(ref to_path) = to {
-        // Restore to specific location
-        if to_path.is_dir() {
-            if let Some(filename) = item.meta.filename() {
-                to_path.join(filename)
-            } else {
-                anyhow::bail!("Cannot determine filename for restoration");
-            }
-        } else {
-            to_path.clone()
-        }
-    } else {
-        // Restore to original location
-        item.meta.original_path.clone()
-    };
+    let final_restore_path = trash.restore_destination(item, to.as_deref())?;
 
     // Check if target file already exists and get confirmation
-    if !check_existing_file_and_confirm(&final_restore_path, interactive)? {
+    if !confirm_restore(trash, item, &final_restore_path, interactive, false)? {
         return Ok(None); // User cancelled restoration
     }
 
-    if to.is_some() {
-        // Restore to specific location
-        // Ensure parent directory exists
-        if let Some(parent) = final_restore_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+    Ok(Some(restore_confirmed(trash, item, to)?))
+}
 
-        // Move file to new location
-        std::fs::rename(&item.trash_path, &final_restore_path)?;
+/// Restore an item once any overwrite has been confirmed
+fn restore_confirmed(trash: &Trash, item: &TrashItem, to: Option<PathBuf>) -> Result<PathBuf> {
+    let options = RestoreOptions {
+        to,
+        overwrite: true,
+    };
+    Ok(trash.restore(&item.meta.id, &options)?.path)
+}
 
-        // Remove metadata (manual cleanup since we're not using trash_store.restore())
-        // Note: This is a bit awkward - we should refactor TrashStore to handle this better
-        Ok(Some(final_restore_path))
-    } else {
-        // Restore to original location
-        let restored_path = trash_store.restore(&item.meta.id)?;
-        Ok(Some(restored_path))
+/// Point out a git alternative, then confirm any overwrite of `restore_path`
+fn confirm_restore(trash: &Trash, item: &TrashItem, restore_path: &Path, interactive: bool, verbose: bool) -> Result<bool> {
+    if let Some(hint) = checkout_hint(item, restore_path) {
+        println!("ℹ️  {}", hint);
+    }
+    check_existing_file_and_confirm(restore_path, interactive, &|| {
+        super::diff::print_diff(trash, item, restore_path, verbose)
+    })
+}
+
+/// Point out, before restoring, that git could bring the item back just as well
+fn checkout_hint(item: &TrashItem, restore_path: &Path) -> Option<String> {
+    let info = item.meta.git.as_ref().filter(|info| info.status == GitFileStatus::Tracked)?;
+    if std::path::absolute(restore_path).ok()? != item.meta.original_path {
+        return None;
     }
+    let repo = GitRepo::discover(restore_path).filter(|repo| repo.root() == info.repo_root)?;
+    let relative = repo.relative(restore_path).filter(|relative| !relative.is_empty())?;
+    // A moved HEAD may hold different content than what was deleted
+    let head = repo.head().ok()?.commit.map(|id| id.to_hex());
+    (head.is_some() && head == info.head).then(|| {
+        format!(
+            "{} was tracked and unchanged at HEAD when deleted; `git -C {} checkout -- {}` would also work",
+            restore_path.display(),
+            repo.root().display(),
+            relative
+        )
+    })
+}
+
+/// Selected items, with their original paths decrypted
+fn list_revealed(trash: &Trash, selection: &Selection) -> Result<Vec<TrashItem>> {
+    trash.list(&ListFilter {
+        since: selection.range.since,
+        before: selection.range.before,
+        tags: selection.tags.clone(),
+        reveal_paths: true,
+        ..ListFilter::default()
+    })
 }
 
-fn confirm_restore_all(items: &[crate::domain::TrashItem]) -> Result<bool> {
+fn confirm_restore_all(items: &[TrashItem]) -> Result<bool> {
     use dialoguer::Confirm;
 
     let confirmed = Confirm::new()
@@ -538,7 +770,8 @@ mod tests {
             ..Config::default()
         };
 
-        let trash_store = TrashStore::new(config.trash_path.clone());
+        let trash = Trash::open(config).unwrap();
+        let trash_store = trash.store();
 
         // Create and delete a test file
         let test_file = NamedTempFile::new().unwrap();
@@ -553,7 +786,7 @@ mod tests {
         assert!(!original_path.exists());
 
         // Restore by ID
-        let result = restore_by_id(&trash_store, &id.to_string(), None, false, false);
+        let result = restore_by_id(&trash, &id.to_string(), None, false, false);
         assert!(result.is_ok());
         assert!(original_path.exists());
         assert_eq!(fs::read_to_string(&original_path).unwrap(), "test content");
@@ -562,9 +795,9 @@ mod tests {
     #[test]
     fn test_restore_by_invalid_id() {
         let temp_dir = TempDir::new().unwrap();
-        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
 
-        let result = restore_by_id(&trash_store, "xyz", None, false, false);
+        let result = restore_by_id(&trash, "xyz", None, false, false);
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -575,10 +808,10 @@ mod tests {
     #[test]
     fn test_restore_by_nonexistent_id() {
         let temp_dir = TempDir::new().unwrap();
-        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
 
         let nonexistent_id = uuid::Uuid::new_v4();
-        let result = restore_by_id(&trash_store, &nonexistent_id.to_string(), None, false, false);
+        let result = restore_by_id(&trash, &nonexistent_id.to_string(), None, false, false);
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -590,7 +823,8 @@ mod tests {
     fn test_restore_to_specific_location() {
         let temp_dir = TempDir::new().unwrap();
         let restore_dir = TempDir::new().unwrap();
-        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        let trash_store = trash.store();
 
         // Create and delete a test file
         let test_file = NamedTempFile::new().unwrap();
@@ -606,7 +840,7 @@ mod tests {
         // Restore to specific directory
         let restore_target = restore_dir.path().to_path_buf();
         let result = restore_by_id(
-            &trash_store,
+            &trash,
             &id.to_string(),
             Some(restore_target.clone()),
             false,
@@ -620,10 +854,38 @@ mod tests {
         assert_eq!(fs::read_to_string(&expected_path).unwrap(), "test content");
     }
 
+    #[test]
+    fn test_restore_compressed_to_specific_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let restore_dir = TempDir::new().unwrap();
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        let trash_store = trash.store();
+
+        let original_path = temp_dir.path().join("app.log");
+        fs::write(&original_path, "log line\n".repeat(100)).unwrap();
+        let meta = FileMeta::from_path(&original_path).unwrap();
+        trash_store.save(&meta, &original_path).unwrap();
+        trash_store.compress(&meta.id, 3).unwrap();
+
+        let result = restore_by_id(
+            &trash,
+            &meta.id.to_string(),
+            Some(restore_dir.path().to_path_buf()),
+            false,
+            false,
+        );
+        assert!(result.is_ok());
+
+        let expected_path = restore_dir.path().join("app.log");
+        assert_eq!(fs::read_to_string(&expected_path).unwrap(), "log line\n".repeat(100));
+        assert!(trash_store.find_by_id(&meta.id).unwrap().is_none());
+    }
+
     #[test]
     fn test_restore_existing_file_non_interactive() {
         let temp_dir = TempDir::new().unwrap();
-        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        let trash_store = trash.store();
 
         // Create and delete a file
         let original_path = temp_dir.path().join("test_file.txt");
@@ -637,7 +899,7 @@ mod tests {
         fs::write(&original_path, "new content").unwrap();
 
         // Try to restore (should fail in non-interactive mode)
-        let result = restore_by_id(&trash_store, &id.to_string(), None, false, false);
+        let result = restore_by_id(&trash, &id.to_string(), None, false, false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("File already exists"));
 
@@ -651,7 +913,7 @@ mod tests {
         let test_path = temp_dir.path().join("no_conflict.txt");
 
         // File doesn't exist - should return true
-        let result = check_existing_file_and_confirm(&test_path, false).unwrap();
+        let result = check_existing_file_and_confirm(&test_path, false, &|| Ok(())).unwrap();
         assert!(result);
     }
 
@@ -662,8 +924,151 @@ mod tests {
         fs::write(&test_path, "existing content").unwrap();
 
         // File exists, non-interactive mode - should return error
-        let result = check_existing_file_and_confirm(&test_path, false);
+        let result = check_existing_file_and_confirm(&test_path, false, &|| Ok(()));
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("File already exists"));
     }
+
+    #[test]
+    fn test_restore_by_pattern_picks_latest_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::open(Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            ..Config::default()
+        })
+        .unwrap();
+
+        let path = temp_dir.path().join("app.toml");
+        for content in ["old", "new"] {
+            fs::write(&path, content).unwrap();
+            trash.delete_path(&path, &Default::default()).unwrap();
+        }
+
+        restore_by_pattern(&trash, path.to_str().unwrap(), &Selection::default(), None, false, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(trash.history(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_tree_conflict_policies() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::open(Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            ..Config::default()
+        })
+        .unwrap();
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let as_of = Utc::now() - chrono::Duration::seconds(1);
+        for name in ["a.txt", "b.txt"] {
+            fs::write(project.join(name), format!("trashed {}", name)).unwrap();
+            trash.delete_path(&project.join(name), &DeleteOptions::default()).unwrap();
+        }
+        fs::write(project.join("a.txt"), "recreated").unwrap();
+
+        let plan = trash.versions_as_of(&project, as_of).unwrap();
+        let summary = restore_tree(&trash, &plan, ConflictPolicy::Skip);
+        assert_eq!((summary.restored.len(), summary.skipped), (1, 1));
+        assert_eq!(fs::read_to_string(project.join("b.txt")).unwrap(), "trashed b.txt");
+        assert_eq!(fs::read_to_string(project.join("a.txt")).unwrap(), "recreated");
+
+        let plan = trash.versions_as_of(&project, as_of).unwrap();
+        let summary = restore_tree(&trash, &plan, ConflictPolicy::Rename);
+        assert_eq!(summary.restored.len(), 1);
+        assert_eq!(fs::read_to_string(project.join("a.txt.restored")).unwrap(), "trashed a.txt");
+
+        // Overwrite keeps the replaced file in the trash
+        fs::write(project.join("a.txt.restored"), "edited").unwrap();
+        trash.delete_path(&project.join("a.txt.restored"), &DeleteOptions::default()).unwrap();
+        fs::write(project.join("a.txt.restored"), "newer").unwrap();
+        let plan = trash.versions_as_of(&project, as_of).unwrap();
+        let summary = restore_tree(&trash, &plan, ConflictPolicy::Overwrite);
+        assert_eq!(summary.restored.len(), 1);
+        assert_eq!(fs::read_to_string(project.join("a.txt.restored")).unwrap(), "edited");
+        assert_eq!(trash.history(&project.join("a.txt.restored")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_tree_overwrite_runs_delete_hooks() {
+        use crate::domain::HooksConfig;
+        use crate::infra::hooks::Hooks;
+
+        let temp_dir = TempDir::new().unwrap();
+        let deleted_log = temp_dir.path().join("deleted.json");
+        let config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            ..Config::default()
+        };
+        let open = |hooks: HooksConfig| {
+            let hooks = HooksConfig { dir: None, ..hooks };
+            Trash::open(config.clone()).unwrap().with_hooks(Hooks::new(hooks))
+        };
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let as_of = Utc::now() - chrono::Duration::seconds(1);
+        let file = project.join("a.txt");
+        fs::write(&file, "trashed").unwrap();
+        open(HooksConfig::default()).delete_path(&file, &DeleteOptions::default()).unwrap();
+        fs::write(&file, "recreated").unwrap();
+
+        // A vetoing pre_delete hook keeps the existing file in place
+        let trash = open(HooksConfig {
+            pre_delete: Some("exit 1".to_string()),
+            ..HooksConfig::default()
+        });
+        let plan = trash.versions_as_of(&project, as_of).unwrap();
+        let summary = restore_tree(&trash, &plan, ConflictPolicy::Overwrite);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "recreated");
+
+        let trash = open(HooksConfig {
+            post_delete: Some(format!("cat > {}", deleted_log.display())),
+            ..HooksConfig::default()
+        });
+        let plan = trash.versions_as_of(&project, as_of).unwrap();
+        let summary = restore_tree(&trash, &plan, ConflictPolicy::Overwrite);
+        assert_eq!(summary.restored.len(), 1);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "trashed");
+        assert!(fs::read_to_string(&deleted_log).unwrap().contains("a.txt"));
+    }
+
+    #[test]
+    fn test_checkout_hint_before_restore() {
+        use std::process::Command;
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        let file = repo.join("a.txt");
+        fs::write(&file, "committed").unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(["-c", "user.name=rmz", "-c", "user.email=rmz@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .status()
+                .expect("git must be installed to run this test")
+                .success()
+        };
+        assert!(git(&["init", "-q"]) && git(&["add", "."]) && git(&["commit", "-q", "-m", "Add a"]));
+
+        let mut meta = FileMeta::from_path(&file).unwrap();
+        meta.git = Some(GitRepo::discover(&file).unwrap().info(&file).unwrap());
+        let item = TrashItem::new(meta, temp_dir.path().join("payload"));
+        fs::remove_file(&file).unwrap();
+
+        assert!(checkout_hint(&item, &file).unwrap().contains("checkout -- a.txt"));
+        assert!(checkout_hint(&item, &repo.join("b.txt")).is_none());
+
+        // Once HEAD moves the committed content may differ from the deleted one
+        assert!(git(&["commit", "-q", "--allow-empty", "-m", "Later"]));
+        assert!(checkout_hint(&item, &file).is_none());
+    }
 }
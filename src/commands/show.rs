@@ -0,0 +1,195 @@
+use super::Context;
+use crate::domain::{GitFileStatus, GitInfo, TrashItem};
+use crate::infra::preview;
+use crate::trash::{ListFilter, Trash};
+use anyhow::{anyhow, Result};
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Lines of file content shown below the metadata
+const PREVIEW_LINES: usize = 200;
+
+/// Entries of a directory tree shown below the metadata
+const TREE_LINES: usize = 1000;
+
+/// Shortest query treated as a possible ID prefix
+const MIN_ID_PREFIX: usize = 4;
+
+/// Execute the show command
+pub fn execute(ctx: &Context, query: String) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let items = resolve_items(&trash, &query)?;
+
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            println!("{}", "─".repeat(50));
+        }
+        show_metadata(item, ctx.verbose);
+        println!();
+        show_contents(&trash, item);
+    }
+
+    Ok(())
+}
+
+/// Items whose ID starts with `query`, or else whose name, path or tags contain it
+fn resolve_items(trash: &Trash, query: &str) -> Result<Vec<TrashItem>> {
+    // Very short queries like "a" are far more likely names than ID prefixes
+    if query.len() >= MIN_ID_PREFIX {
+        let by_id = trash.find(query)?;
+        if !by_id.is_empty() {
+            return Ok(by_id);
+        }
+    }
+
+    let items = trash.list(&ListFilter {
+        pattern: Some(query.to_string()),
+        reveal_paths: true,
+        ..ListFilter::default()
+    })?;
+    if items.is_empty() {
+        return Err(anyhow!("No file found matching: {}", query));
+    }
+    Ok(items)
+}
+
+fn show_metadata(item: &TrashItem, verbose: bool) {
+    let meta = &item.meta;
+    let is_directory = item.is_directory();
+
+    #[cfg(feature = "colors")]
+    println!("{} {}", if is_directory { "📁" } else { "📄" }, meta.original_path.display().to_string().bold());
+    #[cfg(not(feature = "colors"))]
+    println!("{} {}", if is_directory { "📁" } else { "📄" }, meta.original_path.display());
+
+    println!("ID:         {}", meta.id);
+    println!("Type:       {}", if is_directory { "directory" } else { "file" });
+    println!("Size:       {} ({} bytes)", meta.human_readable_size(), meta.size);
+    println!("Mode:       {}", format_mode(meta.permissions, is_directory));
+    println!("Deleted:    {}", meta.deleted_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    println!("Deleted by: {}", meta.deleted_by);
+    println!(
+        "Tags:       {}",
+        if meta.tags.is_empty() { "(none)".to_string() } else { meta.tags.join(", ") }
+    );
+    if let Some(note) = &meta.note {
+        println!("Note:       {}", note.replace('\n', "\n            "));
+    }
+    println!("Checksum:   {}", meta.checksum.as_deref().unwrap_or("(none)"));
+    println!("Storage:    {}", storage_description(item));
+    if let Some(git) = &meta.git {
+        println!("Git:        {}", git_description(git));
+    }
+    if verbose {
+        println!("Trash path: {}", item.trash_path.display());
+    }
+}
+
+/// Directory tree, or the first lines of a file
+fn show_contents(trash: &Trash, item: &TrashItem) {
+    let (title, limit) = if item.is_directory() {
+        ("Contents:", TREE_LINES)
+    } else {
+        ("Preview:", PREVIEW_LINES)
+    };
+
+    #[cfg(feature = "colors")]
+    println!("{}", title.cyan());
+    #[cfg(not(feature = "colors"))]
+    println!("{}", title);
+
+    for line in preview::preview_lines(trash.store(), item, limit) {
+        println!("  {}", line);
+    }
+}
+
+/// e.g. `modified in /home/me/project (main @ 1a2b3c4)`
+fn git_description(git: &GitInfo) -> String {
+    let status = match git.status {
+        GitFileStatus::Tracked => "tracked",
+        GitFileStatus::Modified => "modified",
+        GitFileStatus::Untracked => "untracked",
+    };
+    let head = match (&git.branch, &git.head) {
+        (Some(branch), Some(head)) => format!("{} @ {}", branch, &head[..head.len().min(7)]),
+        (Some(branch), None) => format!("{}, no commits", branch),
+        (None, Some(head)) => format!("detached @ {}", &head[..head.len().min(7)]),
+        (None, None) => "no HEAD".to_string(),
+    };
+    format!("{} in {} ({})", status, git.repo_root.display(), head)
+}
+
+fn storage_description(item: &TrashItem) -> String {
+    let meta = &item.meta;
+    if let Some(info) = &meta.encryption {
+        format!("encrypted ({}, key {})", info.kind.extension(), info.key_id)
+    } else if meta.deduplicated {
+        "deduplicated (blob store)".to_string()
+    } else if let Some(info) = &meta.compression {
+        format!(
+            "compressed ({}, {:.0}% of original)",
+            info.kind.extension(),
+            info.ratio() * 100.0
+        )
+    } else {
+        "plain".to_string()
+    }
+}
+
+/// Octal and `ls`-style rendering of a Unix mode, e.g. `0644 (-rw-r--r--)`
+fn format_mode(mode: u32, is_directory: bool) -> String {
+    let mut symbolic = String::from(if is_directory { "d" } else { "-" });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        symbolic.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        symbolic.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        symbolic.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    format!("{:04o} ({})", mode & 0o7777, symbolic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trash::DeleteOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_mode() {
+        assert_eq!(format_mode(0o100644, false), "0644 (-rw-r--r--)");
+        assert_eq!(format_mode(0o40755, true), "0755 (drwxr-xr-x)");
+    }
+
+    #[test]
+    fn test_git_description() {
+        let mut git = GitInfo {
+            repo_root: "/home/me/project".into(),
+            branch: Some("main".to_string()),
+            head: Some("1a2b3c4d5e6f".to_string()),
+            status: GitFileStatus::Modified,
+        };
+        assert_eq!(git_description(&git), "modified in /home/me/project (main @ 1a2b3c4)");
+        git.branch = None;
+        git.status = GitFileStatus::Tracked;
+        assert_eq!(git_description(&git), "tracked in /home/me/project (detached @ 1a2b3c4)");
+    }
+
+    #[test]
+    fn test_resolve_by_id_prefix_or_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        let file = temp_dir.path().join("report.pdf");
+        fs::write(&file, "pdf").unwrap();
+        let item = trash.delete_path(&file, &DeleteOptions::default()).unwrap();
+
+        let by_id = resolve_items(&trash, &item.meta.id.to_string()[..8]).unwrap();
+        assert_eq!(by_id[0].meta.id, item.meta.id);
+
+        let by_pattern = resolve_items(&trash, "report").unwrap();
+        assert_eq!(by_pattern[0].meta.id, item.meta.id);
+
+        assert!(resolve_items(&trash, "missing").is_err());
+    }
+}
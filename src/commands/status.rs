@@ -1,13 +1,16 @@
+use super::Context;
 use crate::domain::TrashItem;
-use crate::infra::{trash_store::TrashStoreInterface, ConfigManager, TrashStore};
+use crate::infra::{trash_store::TrashStoreInterface, TrashStore};
+use crate::utils::size_parser::format_size;
 use anyhow::Result;
 use chrono::{Duration, Utc};
 use std::collections::HashMap;
 
 /// Execute status command
-pub fn execute(detailed: bool, verbose: bool) -> Result<()> {
-    let config = ConfigManager::load()?;
-    let trash_store = TrashStore::new(config.trash_path.clone());
+pub fn execute(ctx: &Context, detailed: bool) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let trash_store = trash.store();
+    let config = trash.config();
 
     // Get all items from trash
     let items = trash_store.list()?;
@@ -40,23 +43,79 @@ pub fn execute(detailed: bool, verbose: bool) -> Result<()> {
     println!("📊 Files: {}", total_files);
     println!("💾 Total Size: {}", total_size_human);
 
+    if let Some((logical, physical)) = dedup_summary(&items, trash_store)? {
+        println!(
+            "🧬 Deduplicated: {} logical, {} physical",
+            format_size(logical),
+            format_size(physical)
+        );
+    }
+
+    if let Some((original, compressed, count)) = compression_summary(&items) {
+        println!(
+            "🗜️  Compressed: {} items, {} -> {} ({:.1}%)",
+            count,
+            format_size(original),
+            format_size(compressed),
+            compressed as f64 / original.max(1) as f64 * 100.0
+        );
+    }
+
     if let (Some(oldest), Some(newest)) = (oldest, newest) {
         println!("🕐 Oldest: {}", oldest.format("%Y-%m-%d %H:%M:%S"));
         println!("🕐 Newest: {}", newest.format("%Y-%m-%d %H:%M:%S"));
     }
 
     if detailed {
-        show_detailed_status(&items, &config, verbose)?;
+        show_detailed_status(&items, config, ctx.verbose)?;
     }
 
-    // Show configuration info if verbose
-    if verbose {
-        show_config_status(&config)?;
+    // Show configuration info if ctx.verbose
+    if ctx.verbose {
+        show_config_status(config)?;
     }
 
     Ok(())
 }
 
+/// Logical bytes of all items vs. bytes actually stored, when the blob store is in use
+fn dedup_summary(items: &[TrashItem], trash_store: &TrashStore) -> Result<Option<(u64, u64)>> {
+    if !items.iter().any(|item| item.meta.deduplicated) {
+        return Ok(None);
+    }
+
+    let logical = items.iter().map(|item| item.meta.size).sum();
+    let stored: u64 = items
+        .iter()
+        .filter(|item| !item.meta.deduplicated)
+        .map(|item| {
+            item.meta
+                .compression
+                .as_ref()
+                .map_or(item.meta.size, |info| info.compressed_size)
+        })
+        .sum();
+    let physical = stored + trash_store.blob_store().physical_size()?;
+
+    Ok(Some((logical, physical)))
+}
+
+/// Sum original and compressed sizes over compressed items
+fn compression_summary(items: &[TrashItem]) -> Option<(u64, u64, usize)> {
+    let compressed: Vec<_> = items
+        .iter()
+        .filter_map(|item| item.meta.compression.as_ref())
+        .collect();
+
+    if compressed.is_empty() {
+        return None;
+    }
+
+    let original = compressed.iter().map(|info| info.original_size).sum();
+    let stored = compressed.iter().map(|info| info.compressed_size).sum();
+    Some((original, stored, compressed.len()))
+}
+
 fn show_detailed_status(
     items: &[TrashItem],
     config: &crate::domain::Config,
@@ -152,7 +211,7 @@ fn show_file_type_breakdown(items: &[TrashItem]) -> Result<()> {
     if !type_counts.is_empty() {
         println!("\n📄 By File Type:");
         let mut sorted_types: Vec<_> = type_counts.iter().collect();
-        sorted_types.sort_by(|a, b| b.1 .0.cmp(&a.1 .0)); // Sort by count
+        sorted_types.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count)); // Sort by count
 
         for (ext, (count, size)) in sorted_types.iter().take(10) {
             println!("   {}: {} files ({})", ext, count, format_size(*size));
@@ -346,23 +405,6 @@ fn show_config_status(config: &crate::domain::Config) -> Result<()> {
     Ok(())
 }
 
-fn format_size(size: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size_f = size as f64;
-    let mut unit_index = 0;
-
-    while size_f >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size_f /= 1024.0;
-        unit_index += 1;
-    }
-
-    if unit_index == 0 {
-        format!("{} {}", size, UNITS[0])
-    } else {
-        format!("{:.1} {}", size_f, UNITS[unit_index])
-    }
-}
-
 fn format_time_ago(duration: Duration) -> String {
     let days = duration.num_days();
     let hours = duration.num_hours();
@@ -392,15 +434,6 @@ mod tests {
     use std::fs;
     use tempfile::{NamedTempFile, TempDir};
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(100), "100 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-    }
-
     #[test]
     fn test_format_time_ago() {
         let _now = Utc::now();
@@ -457,10 +490,10 @@ mod tests {
         let test_file1 = NamedTempFile::new().unwrap();
         let test_file2 = NamedTempFile::new().unwrap();
         fs::write(test_file1.path(), "small content").unwrap();
-        fs::write(test_file2.path(), &vec![0u8; 2048]).unwrap(); // 2KB file
+        fs::write(test_file2.path(), vec![0u8; 2048]).unwrap(); // 2KB file
 
-        let mut meta1 = FileMeta::from_path(&test_file1.path()).unwrap();
-        let mut meta2 = FileMeta::from_path(&test_file2.path()).unwrap();
+        let mut meta1 = FileMeta::from_path(test_file1.path()).unwrap();
+        let mut meta2 = FileMeta::from_path(test_file2.path()).unwrap();
 
         // Add different tags
         meta1.add_tag("test".to_string());
@@ -489,6 +522,46 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_dedup_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        for name in ["a.lock", "b.lock"] {
+            let file_path = temp_dir.path().join(name);
+            fs::write(&file_path, "x".repeat(100)).unwrap();
+            let meta = FileMeta::from_path(&file_path).unwrap();
+            trash_store.save(&meta, &file_path).unwrap();
+            trash_store.deduplicate(&meta.id).unwrap();
+        }
+
+        let items = trash_store.list().unwrap();
+        let (logical, physical) = dedup_summary(&items, &trash_store).unwrap().unwrap();
+        assert_eq!(logical, 200);
+        assert_eq!(physical, 100);
+    }
+
+    #[test]
+    fn test_compression_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file_path = temp_dir.path().join("output.log");
+        fs::write(&file_path, "x".repeat(4096)).unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+
+        let items = trash_store.list().unwrap();
+        assert!(compression_summary(&items).is_none());
+
+        trash_store.compress(&meta.id, 3).unwrap();
+        let items = trash_store.list().unwrap();
+        let (original, compressed, count) = compression_summary(&items).unwrap();
+        assert_eq!(original, 4096);
+        assert!(compressed < original);
+        assert_eq!(count, 1);
+    }
+
     #[test]
     fn test_show_config_status() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,212 @@
+use super::Context;
+use crate::cli::TagAction;
+use crate::domain::TrashItem;
+use crate::trash::{stored_size, ListFilter, Trash};
+use crate::utils::size_parser::format_size;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "colors")]
+use colored::Colorize;
+
+/// Label used in the summary for items without tags
+const UNTAGGED: &str = "(untagged)";
+
+/// Execute a tag subcommand
+pub fn execute(ctx: &Context, action: TagAction) -> Result<()> {
+    let trash = ctx.open_trash()?;
+
+    match action {
+        TagAction::Add { ids, tags } => {
+            for id in &ids {
+                let item = trash.find_one(id)?;
+                let (new_tags, unchanged) = add_tags(&item.meta.tags, &tags);
+                if ctx.verbose && !unchanged.is_empty() {
+                    println!("{} already tagged {}", short_id(&item), unchanged.join(", "));
+                }
+                retag(&trash, &item, new_tags)?;
+            }
+        }
+        TagAction::Remove { ids, tags } => {
+            for id in &ids {
+                let item = trash.find_one(id)?;
+                let (new_tags, missing) = remove_tags(&item.meta.tags, &tags);
+                if !missing.is_empty() {
+                    println!("{} was not tagged {}", short_id(&item), missing.join(", "));
+                }
+                retag(&trash, &item, new_tags)?;
+            }
+        }
+        TagAction::List { ids } => {
+            let items = if ids.is_empty() {
+                trash.list(&ListFilter {
+                    reveal_paths: true,
+                    ..Default::default()
+                })?
+            } else {
+                ids.iter().map(|id| trash.find_one(id)).collect::<Result<_>>()?
+            };
+            for item in &items {
+                print_tags(item);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Show every tag with the number and total size of items carrying it
+pub fn summary(ctx: &Context) -> Result<()> {
+    let trash = ctx.open_trash()?;
+    let items = trash.list(&ListFilter::default())?;
+
+    if items.is_empty() {
+        println!("Trash is empty");
+        return Ok(());
+    }
+
+    let rows = summarize(&items, |item| stored_size(&item.trash_path));
+    let width = rows.iter().map(|row| row.tag.len()).max().unwrap_or(0).max(3);
+
+    #[cfg(feature = "colors")]
+    println!("{:<width$}  {:>5}  {:>10}", "TAG".bold(), "ITEMS".bold(), "SIZE".bold());
+    #[cfg(not(feature = "colors"))]
+    println!("{:<width$}  {:>5}  {:>10}", "TAG", "ITEMS", "SIZE");
+
+    for row in &rows {
+        #[cfg(feature = "colors")]
+        println!("{:<width$}  {:>5}  {:>10}", row.tag.cyan(), row.count, format_size(row.size).yellow());
+        #[cfg(not(feature = "colors"))]
+        println!("{:<width$}  {:>5}  {:>10}", row.tag, row.count, format_size(row.size));
+    }
+
+    Ok(())
+}
+
+/// One line of the tag summary
+#[derive(Debug, PartialEq, Eq)]
+struct TagSummary {
+    tag: String,
+    count: usize,
+    size: u64,
+}
+
+/// Count and size items per tag, most used first
+///
+/// An item with several tags counts towards each of them.
+fn summarize(items: &[TrashItem], size_of: impl Fn(&TrashItem) -> u64) -> Vec<TagSummary> {
+    let mut totals: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+    for item in items {
+        let size = size_of(item);
+        let tags: Vec<&str> = if item.meta.tags.is_empty() {
+            vec![UNTAGGED]
+        } else {
+            item.meta.tags.iter().map(String::as_str).collect()
+        };
+        for tag in tags {
+            let total = totals.entry(tag).or_default();
+            total.0 += 1;
+            total.1 += size;
+        }
+    }
+
+    let mut rows: Vec<TagSummary> = totals
+        .into_iter()
+        .map(|(tag, (count, size))| TagSummary {
+            tag: tag.to_string(),
+            count,
+            size,
+        })
+        .collect();
+    // Stable sort keeps tags with equal counts in name order
+    rows.sort_by_key(|row| std::cmp::Reverse(row.count));
+    rows
+}
+
+/// Tags after adding `new`, and the ones that were already present
+fn add_tags(current: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut tags = current.to_vec();
+    let mut unchanged = Vec::new();
+    for tag in new {
+        if tags.contains(tag) {
+            unchanged.push(tag.clone());
+        } else {
+            tags.push(tag.clone());
+        }
+    }
+    (tags, unchanged)
+}
+
+/// Tags after removing `old`, and the ones that were not present
+fn remove_tags(current: &[String], old: &[String]) -> (Vec<String>, Vec<String>) {
+    let missing = old.iter().filter(|tag| !current.contains(tag)).cloned().collect();
+    let tags = current.iter().filter(|tag| !old.contains(tag)).cloned().collect();
+    (tags, missing)
+}
+
+fn retag(trash: &Trash, item: &TrashItem, tags: Vec<String>) -> Result<()> {
+    if tags == item.meta.tags {
+        return Ok(());
+    }
+    let item = trash.retag(&item.meta.id, tags)?;
+    print_tags(&item);
+    Ok(())
+}
+
+fn print_tags(item: &TrashItem) {
+    let tags = if item.meta.tags.is_empty() {
+        "(none)".to_string()
+    } else {
+        item.meta.tags.join(", ")
+    };
+
+    #[cfg(feature = "colors")]
+    println!("{} {}: {}", short_id(item).dimmed(), item.meta.original_path.display(), tags.cyan());
+    #[cfg(not(feature = "colors"))]
+    println!("{} {}: {}", short_id(item), item.meta.original_path.display(), tags);
+}
+
+fn short_id(item: &TrashItem) -> String {
+    item.meta.id.to_string()[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileMeta;
+    use std::path::PathBuf;
+    use tempfile::NamedTempFile;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn item(tags: &[&str]) -> TrashItem {
+        let file = NamedTempFile::new().unwrap();
+        let mut meta = FileMeta::from_path(file.path()).unwrap();
+        meta.tags = strings(tags);
+        TrashItem {
+            meta,
+            trash_path: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn test_add_and_remove_tags() {
+        let (tags, unchanged) = add_tags(&strings(&["a", "b"]), &strings(&["b", "c"]));
+        assert_eq!(tags, strings(&["a", "b", "c"]));
+        assert_eq!(unchanged, strings(&["b"]));
+
+        let (tags, missing) = remove_tags(&strings(&["a", "b", "c"]), &strings(&["b", "x"]));
+        assert_eq!(tags, strings(&["a", "c"]));
+        assert_eq!(missing, strings(&["x"]));
+    }
+
+    #[test]
+    fn test_summarize_counts_each_tag() {
+        let items = vec![item(&["tmp", "logs"]), item(&["tmp"]), item(&[])];
+        let rows = summarize(&items, |_| 10);
+        let summary: Vec<(&str, usize, u64)> = rows.iter().map(|row| (row.tag.as_str(), row.count, row.size)).collect();
+        assert_eq!(summary, vec![("tmp", 2, 20), ("(untagged)", 1, 10), ("logs", 1, 10)]);
+    }
+}
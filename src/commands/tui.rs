@@ -0,0 +1,13 @@
+use super::Context;
+use anyhow::Result;
+
+/// Execute the tui command
+#[cfg(feature = "tui")]
+pub fn execute(ctx: &Context) -> Result<()> {
+    crate::tui::run(ctx.open_trash()?)
+}
+
+#[cfg(not(feature = "tui"))]
+pub fn execute(_ctx: &Context) -> Result<()> {
+    anyhow::bail!("rmz was built without the 'tui' feature")
+}
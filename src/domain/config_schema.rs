@@ -0,0 +1,519 @@
+//! Declarative description of every setting
+//!
+//! [`KEYS`] drives `config set`, `config unset` and `config list-keys`, the
+//! parsing of `RMZ_*` variables, the validation of every configuration layer
+//! and the migration of files written by older versions.
+
+use super::LogSinkConfig;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Version written to the `version` key of saved files
+///
+/// 1. Unversioned files. `Config::save` used to write every setting, leaving
+///    out optional ones that were disabled.
+/// 2. Only changed settings are written; disabled optional ones are `"none"`.
+pub const SCHEMA_VERSION: i64 = 2;
+
+/// Top-level key holding the schema version of a file
+pub const VERSION_KEY: &str = "version";
+
+/// Value that unsets an optional setting, e.g. `auto_clean_days = "none"`
+pub const UNSET: &str = "none";
+
+/// Words accepted on the command line in place of [`UNSET`]
+const UNSET_ALIASES: &[&str] = &[UNSET, "disabled", "unlimited"];
+
+/// Type and allowed range of a setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Bool,
+    Integer { min: i64, max: i64 },
+    /// Byte count; accepts units such as `500MB` on the command line
+    Size,
+    Path,
+    /// Paths merged across layers instead of replaced
+    PathList,
+    /// chrono `strftime` pattern
+    DateFormat,
+    /// Shell command line
+    Command,
+    /// One of a fixed set of words
+    Choice(&'static [&'static str]),
+    /// Log sinks, merged across layers; `syslog` or `journald`, optionally `:SOCKET`, on the command line
+    SinkList,
+}
+
+impl ValueKind {
+    /// Short description for `config list-keys`
+    pub fn describe(&self) -> String {
+        match self {
+            ValueKind::Bool => "bool".to_string(),
+            ValueKind::Integer { min, max } if *max == i64::from(u32::MAX) => format!("integer >= {}", min),
+            ValueKind::Integer { min, max } => format!("integer {}-{}", min, max),
+            ValueKind::Size => "size".to_string(),
+            ValueKind::Path => "path".to_string(),
+            ValueKind::PathList => "path list".to_string(),
+            ValueKind::DateFormat => "date format".to_string(),
+            ValueKind::Command => "command".to_string(),
+            ValueKind::Choice(choices) => choices.join("|"),
+            ValueKind::SinkList => "sink list".to_string(),
+        }
+    }
+}
+
+/// A configurable setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigKey {
+    /// Dotted name, e.g. `compression.level`
+    pub name: &'static str,
+    pub kind: ValueKind,
+    /// Can be unset with [`UNSET`]
+    pub optional: bool,
+    pub description: &'static str,
+}
+
+const fn key(name: &'static str, kind: ValueKind, description: &'static str) -> ConfigKey {
+    ConfigKey { name, kind, optional: false, description }
+}
+
+const fn optional(name: &'static str, kind: ValueKind, description: &'static str) -> ConfigKey {
+    ConfigKey { optional: true, ..key(name, kind, description) }
+}
+
+const fn days(min: i64) -> ValueKind {
+    ValueKind::Integer { min, max: u32::MAX as i64 }
+}
+
+/// Every setting that layers may set
+pub const KEYS: &[ConfigKey] = &[
+    key("trash_path", ValueKind::Path, "Directory holding trashed items"),
+    key("protected_paths", ValueKind::PathList, "Paths that can never be deleted"),
+    optional("auto_clean_days", days(1), "Purge items older than this many days"),
+    optional("max_trash_size", ValueKind::Size, "Purge the oldest items once the trash grows past this size"),
+    key("colors", ValueKind::Bool, "Colour output"),
+    key("require_confirmation", ValueKind::Bool, "Ask before destructive operations"),
+    key("use_fzf", ValueKind::Bool, "Use fzf for interactive selection when installed"),
+    key("date_format", ValueKind::DateFormat, "strftime pattern for displayed dates"),
+    key("compression.enabled", ValueKind::Bool, "Compress items once they reach compression.min_age_days"),
+    key("compression.level", ValueKind::Integer { min: 1, max: 22 }, "zstd compression level"),
+    key("compression.min_age_days", days(0), "Only compress items at least this many days old"),
+    key("dedup.enabled", ValueKind::Bool, "Store identical file contents once"),
+    key("encryption.enabled", ValueKind::Bool, "Encrypt items at rest"),
+    optional("encryption.key_file", ValueKind::Path, "File holding the encryption secret instead of a passphrase"),
+    key("encryption.encrypt_paths", ValueKind::Bool, "Also encrypt original paths in metadata"),
+    optional("gc.log_retention_days", days(1), "Drop operation log entries older than this many days"),
+    optional("notes.prompt_threshold", days(1), "Ask for a note when deleting at least this many items"),
+    optional("log.max_size", ValueKind::Size, "Rotate the operation log once it grows past this size"),
+    optional("log.max_age_days", days(1), "Rotate the operation log once its oldest entry is this many days old"),
+    optional("log.keep_segments", days(1), "Number of rotated operation log segments to keep"),
+    key("log.hmac", ValueKind::Bool, "Authenticate operation log entries with a local secret key"),
+    key("log.sinks", ValueKind::SinkList, "Also send operation log entries to syslog or journald"),
+    optional("hooks.pre_delete", ValueKind::Command, "Run before deleting; a non-zero exit cancels the deletion"),
+    optional("hooks.post_delete", ValueKind::Command, "Run after items were moved to trash"),
+    optional("hooks.pre_purge", ValueKind::Command, "Run before purging; a non-zero exit cancels the purge"),
+    optional("hooks.post_restore", ValueKind::Command, "Run after an item was restored"),
+    optional("hooks.dir", ValueKind::Path, "Directory with a subdirectory of executables per hook"),
+    key("hooks.timeout_secs", ValueKind::Integer { min: 1, max: 3600 }, "Seconds a hook may run before it is killed"),
+    key("git.record", ValueKind::Bool, "Record repository, branch and file status of paths deleted in a git work tree"),
+    key("git.safeguard", ValueKind::Choice(&["off", "warn", "refuse"]), "Warn or refuse when deleting a repository root, .git or uncommitted changes"),
+];
+
+/// Look up a setting by its dotted name
+pub fn find_key(name: &str) -> Option<&'static ConfigKey> {
+    KEYS.iter().find(|key| key.name == name)
+}
+
+impl ConfigKey {
+    /// Holds paths; relative paths in files are resolved against the file's directory
+    pub fn is_path(&self) -> bool {
+        matches!(self.kind, ValueKind::Path | ValueKind::PathList)
+    }
+
+    /// Names commands or executables that rmz runs
+    pub fn runs_commands(&self) -> bool {
+        matches!(self.kind, ValueKind::Command) || self.name == "hooks.dir"
+    }
+
+    /// List merged across layers instead of replaced
+    pub fn is_additive(&self) -> bool {
+        matches!(self.kind, ValueKind::PathList | ValueKind::SinkList)
+    }
+
+    /// Environment variable overriding this setting, e.g. `RMZ_COMPRESSION_LEVEL`
+    pub fn env_var(&self) -> String {
+        format!("{}{}", super::config_layers::ENV_PREFIX, self.name.replace('.', "_").to_uppercase())
+    }
+
+    /// Parse a value typed on the command line or set in the environment
+    ///
+    /// Relative paths are resolved against the current directory and path lists
+    /// are separated like `$PATH`.
+    pub fn parse(&self, raw: &str) -> Result<Value> {
+        let raw = raw.trim();
+        if self.optional && UNSET_ALIASES.iter().any(|alias| raw.eq_ignore_ascii_case(alias)) {
+            return Ok(Value::String(UNSET.to_string()));
+        }
+
+        let value = match self.kind {
+            ValueKind::Bool => Value::Boolean(parse_bool(raw)?),
+            ValueKind::Integer { .. } => Value::Integer(
+                raw.parse()
+                    .map_err(|_| anyhow!("{} must be {}, got '{}'", self.name, self.kind.describe(), raw))?,
+            ),
+            ValueKind::Size => Value::Integer(
+                i64::try_from(parse_size(raw)?).map_err(|_| anyhow!("{} is too large", raw))?,
+            ),
+            ValueKind::Path => Value::String(absolute(Path::new(raw)).display().to_string()),
+            ValueKind::PathList => Value::Array(
+                std::env::split_paths(raw)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .map(|path| Value::String(absolute(&path).display().to_string()))
+                    .collect(),
+            ),
+            ValueKind::DateFormat | ValueKind::Command => Value::String(raw.to_string()),
+            ValueKind::Choice(_) => Value::String(raw.to_lowercase()),
+            ValueKind::SinkList => Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|sink| !sink.is_empty())
+                    .map(parse_sink)
+                    .collect::<Result<_>>()?,
+            ),
+        };
+        self.check(&value)?;
+        Ok(value)
+    }
+
+    /// Check the type and range of a value, e.g. one read from a file
+    pub fn check(&self, value: &Value) -> Result<()> {
+        if self.optional && value.as_str() == Some(UNSET) {
+            return Ok(());
+        }
+
+        let valid = match (self.kind, value) {
+            (ValueKind::Bool, Value::Boolean(_)) => true,
+            (ValueKind::Integer { min, max }, Value::Integer(number)) => {
+                if !(min..=max).contains(number) {
+                    return Err(anyhow!("{} must be {}, got {}", self.name, self.kind.describe(), number));
+                }
+                true
+            }
+            (ValueKind::Size, Value::Integer(bytes)) => *bytes >= 0,
+            (ValueKind::Path, Value::String(path)) => !path.is_empty(),
+            (ValueKind::Command, Value::String(command)) => !command.trim().is_empty(),
+            (ValueKind::Choice(choices), Value::String(choice)) => {
+                if !choices.contains(&choice.as_str()) {
+                    return Err(anyhow!("{} must be {}, got '{}'", self.name, self.kind.describe(), choice));
+                }
+                true
+            }
+            (ValueKind::PathList, Value::Array(items)) => items.iter().all(|item| item.is_str()),
+            (ValueKind::DateFormat, Value::String(format)) => {
+                check_date_format(format)?;
+                true
+            }
+            (ValueKind::SinkList, Value::Array(items)) => {
+                for item in items {
+                    item.clone()
+                        .try_into::<LogSinkConfig>()
+                        .map_err(|e| anyhow!("{} has an invalid sink {}: {}", self.name, item, e.message().trim()))?;
+                }
+                true
+            }
+            _ => false,
+        };
+
+        if valid {
+            Ok(())
+        } else if self.optional {
+            Err(anyhow!("{} must be a {} or \"{}\", got {}", self.name, self.kind.describe(), UNSET, value))
+        } else {
+            Err(anyhow!("{} must be a {}, got {}", self.name, self.kind.describe(), value))
+        }
+    }
+}
+
+/// Fail if `format` contains a specifier chrono cannot render
+pub fn check_date_format(format: &str) -> Result<()> {
+    use chrono::format::{Item, StrftimeItems};
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(anyhow!("'{}' is not a valid date format (see strftime)", format));
+    }
+    Ok(())
+}
+
+/// Parse `syslog`, `journald` or either followed by `:SOCKET`
+fn parse_sink(raw: &str) -> Result<Value> {
+    let (kind, socket) = match raw.split_once(':') {
+        Some((kind, socket)) => (kind, Some(socket)),
+        None => (raw, None),
+    };
+    let kind = kind.to_lowercase();
+    if kind != "syslog" && kind != "journald" {
+        return Err(anyhow!("Unknown log sink '{}': use syslog or journald, optionally followed by :SOCKET", raw));
+    }
+
+    let mut sink = Table::new();
+    sink.insert("type".to_string(), Value::String(kind));
+    if let Some(socket) = socket {
+        sink.insert("socket".to_string(), Value::String(absolute(Path::new(socket)).display().to_string()));
+    }
+    Ok(Value::Table(sink))
+}
+
+/// Parse a boolean value from string
+pub fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" | "enabled" => Ok(true),
+        "false" | "no" | "off" | "0" | "disabled" => Ok(false),
+        _ => Err(anyhow!("Invalid boolean value: {}. Use true/false, yes/no, on/off, 1/0, or enabled/disabled", value)),
+    }
+}
+
+/// Parse size string (e.g., "100MB", "1GB") to bytes
+pub fn parse_size(size_str: &str) -> Result<u64> {
+    let size_str = size_str.to_uppercase();
+
+    let (number_part, unit_part) = if size_str.ends_with("KB") {
+        (size_str.trim_end_matches("KB"), 1024u64)
+    } else if size_str.ends_with("MB") {
+        (size_str.trim_end_matches("MB"), 1024u64.pow(2))
+    } else if size_str.ends_with("GB") {
+        (size_str.trim_end_matches("GB"), 1024u64.pow(3))
+    } else if size_str.ends_with("TB") {
+        (size_str.trim_end_matches("TB"), 1024u64.pow(4))
+    } else if size_str.ends_with("B") {
+        (size_str.trim_end_matches("B"), 1u64)
+    } else {
+        // Assume bytes if no unit
+        (size_str.as_str(), 1u64)
+    };
+
+    let number: f64 = number_part.trim().parse()
+        .map_err(|_| anyhow!("Invalid size format: {}", size_str))?;
+    if number < 0.0 {
+        return Err(anyhow!("Invalid size format: {}", size_str));
+    }
+
+    Ok((number * unit_part as f64) as u64)
+}
+
+/// Schema version a file table was written with
+pub fn file_version(table: &Table) -> Result<i64> {
+    match table.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(Value::Integer(version)) if *version > SCHEMA_VERSION => Err(anyhow!(
+            "Configuration schema version {} is newer than this rmz supports ({})",
+            version,
+            SCHEMA_VERSION
+        )),
+        Some(Value::Integer(version)) if *version >= 1 => Ok(*version),
+        Some(other) => Err(anyhow!("{} must be a positive integer, got {}", VERSION_KEY, other)),
+    }
+}
+
+/// Bring a file table up to [`SCHEMA_VERSION`], returning the version it had
+pub fn migrate(table: &mut Table) -> Result<i64> {
+    let from = file_version(table)?;
+    if from < 2 {
+        unset_missing_optionals(table);
+    }
+    table.insert(VERSION_KEY.to_string(), Value::Integer(SCHEMA_VERSION));
+    Ok(from)
+}
+
+/// Version 1 files written by `Config::save` left out disabled optional settings
+///
+/// Now that files only hold overrides, a missing key means "use the default",
+/// so such files get an explicit [`UNSET`] to keep the setting disabled. Only
+/// sections present in the file are touched: a section missing entirely was
+/// written before the setting existed.
+fn unset_missing_optionals(table: &mut Table) {
+    const FULL_DUMP: &[&str] = &["trash_path", "protected_paths", "colors", "require_confirmation", "use_fzf"];
+    if !FULL_DUMP.iter().all(|name| table.contains_key(*name)) {
+        return;
+    }
+
+    for key in KEYS.iter().filter(|key| key.optional) {
+        let (section, leaf) = match key.name.split_once('.') {
+            Some((section, leaf)) => match table.get_mut(section).and_then(Value::as_table_mut) {
+                Some(section) => (section, leaf),
+                None => continue,
+            },
+            None => (&mut *table, key.name),
+        };
+        section
+            .entry(leaf.to_string())
+            .or_insert_with(|| Value::String(UNSET.to_string()));
+    }
+}
+
+/// Dotted names in a file table that are not settings, including inside profiles
+pub fn unknown_keys(table: &Table) -> Vec<String> {
+    let mut unknown = Vec::new();
+    for (name, value) in table {
+        match (name.as_str(), value) {
+            (VERSION_KEY, _) => {}
+            (super::config_layers::PROFILES_TABLE, Value::Table(profiles)) => {
+                for (profile, settings) in profiles.iter().filter_map(|(profile, settings)| Some((profile, settings.as_table()?))) {
+                    let shown = format!("{}.{}.", name, profile);
+                    for (setting, value) in settings {
+                        collect_unknown(setting.clone(), value, &shown, &mut unknown);
+                    }
+                }
+            }
+            _ => collect_unknown(name.clone(), value, "", &mut unknown),
+        }
+    }
+    unknown
+}
+
+/// Report `dotted`, or the names below it, unless they are settings; `shown` prefixes reported names
+fn collect_unknown(dotted: String, value: &Value, shown: &str, out: &mut Vec<String>) {
+    if find_key(&dotted).is_some() {
+        return;
+    }
+    match value {
+        Value::Table(inner) => {
+            for (name, value) in inner {
+                collect_unknown(format!("{}.{}", dotted, name), value, shown, out);
+            }
+        }
+        _ => out.push(format!("{}{}", shown, dotted)),
+    }
+}
+
+fn absolute(path: &Path) -> std::path::PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checks_types_and_ranges() {
+        let level = find_key("compression.level").unwrap();
+        assert_eq!(level.parse("9").unwrap(), Value::Integer(9));
+        assert!(level.parse("23").unwrap_err().to_string().contains("1-22"));
+        assert!(level.parse("fast").is_err());
+        assert!(level.check(&Value::String("9".to_string())).is_err());
+
+        let size = find_key("max_trash_size").unwrap();
+        assert_eq!(size.parse("2GB").unwrap(), Value::Integer(2 * 1024 * 1024 * 1024));
+        assert_eq!(size.parse("unlimited").unwrap(), Value::String(UNSET.to_string()));
+
+        assert_eq!(find_key("use_fzf").unwrap().parse("off").unwrap(), Value::Boolean(false));
+        assert!(find_key("use_fzf").unwrap().parse("none").is_err());
+        assert!(find_key("auto_clean_days").unwrap().parse("0").is_err());
+
+        let paths = find_key("protected_paths").unwrap().parse("/srv:/data").unwrap();
+        assert_eq!(paths, Value::Array(vec![Value::String("/srv".into()), Value::String("/data".into())]));
+
+        let date_format = find_key("date_format").unwrap();
+        assert!(date_format.parse("%Y-%m-%d").is_ok());
+        assert!(date_format.parse("%Y-%Q").is_err());
+    }
+
+    #[test]
+    fn test_parse_bool() {
+        // Test true values
+        assert!(parse_bool("true").unwrap());
+        assert!(parse_bool("yes").unwrap());
+        assert!(parse_bool("on").unwrap());
+        assert!(parse_bool("1").unwrap());
+        assert!(parse_bool("enabled").unwrap());
+        assert!(parse_bool("TRUE").unwrap()); // Case insensitive
+
+        // Test false values
+        assert!(!parse_bool("false").unwrap());
+        assert!(!parse_bool("no").unwrap());
+        assert!(!parse_bool("off").unwrap());
+        assert!(!parse_bool("0").unwrap());
+        assert!(!parse_bool("disabled").unwrap());
+        assert!(!parse_bool("FALSE").unwrap()); // Case insensitive
+
+        // Test invalid values
+        assert!(parse_bool("invalid").is_err());
+        assert!(parse_bool("").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("1KB").unwrap(), 1024);
+        assert_eq!(parse_size("1MB").unwrap(), 1024 * 1024);
+        assert_eq!(parse_size("1GB").unwrap(), 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5MB").unwrap(), (1.5 * 1024.0 * 1024.0) as u64);
+        assert_eq!(parse_size("500B").unwrap(), 500);
+
+        // Test invalid formats
+        assert!(parse_size("invalid").is_err());
+        assert!(parse_size("1.5.5MB").is_err());
+        assert!(parse_size("-1MB").is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_full_file() {
+        let mut legacy: Table = toml::from_str(
+            "trash_path = \"/t\"\nprotected_paths = []\ncolors = true\nrequire_confirmation = true\nuse_fzf = true\n\
+             max_trash_size = 10\n[compression]\nenabled = false\n[gc]\n",
+        )
+        .unwrap();
+        assert_eq!(migrate(&mut legacy).unwrap(), 1);
+        assert_eq!(legacy["auto_clean_days"].as_str(), Some(UNSET));
+        assert_eq!(legacy["max_trash_size"].as_integer(), Some(10));
+        assert_eq!(legacy["gc"]["log_retention_days"].as_str(), Some(UNSET));
+        assert!(!legacy.contains_key("notes") && !legacy.contains_key("encryption"));
+        assert_eq!(legacy[VERSION_KEY].as_integer(), Some(SCHEMA_VERSION));
+
+        // Sparse files already only hold overrides
+        let mut sparse: Table = toml::from_str("use_fzf = false\n").unwrap();
+        migrate(&mut sparse).unwrap();
+        assert!(!sparse.contains_key("auto_clean_days"));
+
+        let mut newer: Table = toml::from_str("version = 99\n").unwrap();
+        assert!(migrate(&mut newer).unwrap_err().to_string().contains("newer"));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let table: Table = toml::from_str(
+            "version = 2\ncolour = true\n[compression]\nlevel = 3\nspeed = 1\n[profiles.work]\nauto_clean_days = 7\nretention = 1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            unknown_keys(&table),
+            vec!["colour", "compression.speed", "profiles.work.retention"]
+        );
+    }
+
+    #[test]
+    fn test_sink_list() {
+        let key = find_key("log.sinks").unwrap();
+        let sinks: Vec<LogSinkConfig> = key.parse("syslog, journald:/tmp/journal.sock").unwrap().try_into().unwrap();
+        assert_eq!(
+            sinks,
+            vec![
+                LogSinkConfig::Syslog { socket: None, facility: Default::default() },
+                LogSinkConfig::Journald { socket: Some("/tmp/journal.sock".into()) },
+            ]
+        );
+        assert!(key.parse("kafka").is_err());
+
+        let table: Table = toml::from_str("sinks = [{ type = \"syslog\", facility = \"local3\" }]").unwrap();
+        assert!(key.check(&table["sinks"]).is_ok());
+        let table: Table = toml::from_str("sinks = [{ type = \"syslog\", facilty = \"local3\" }]").unwrap();
+        assert!(key.check(&table["sinks"]).is_err());
+    }
+
+    #[test]
+    fn test_choice() {
+        let key = find_key("git.safeguard").unwrap();
+        assert_eq!(key.parse("Refuse").unwrap(), Value::String("refuse".to_string()));
+        let error = key.parse("maybe").unwrap_err().to_string();
+        assert!(error.contains("off|warn|refuse"), "{}", error);
+    }
+}
@@ -24,11 +24,139 @@ pub struct FileMeta {
     /// Tags associated with this deletion
     pub tags: Vec<String>,
 
+    /// Free-text explanation of why the item was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+
     /// Optional checksum for integrity verification
     pub checksum: Option<String>,
 
     /// User who performed the deletion (for future multi-user support)
     pub deleted_by: String,
+
+    /// Compression applied to the stored payload (None = stored raw)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionInfo>,
+
+    /// File contents live in the content-addressed blob store
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deduplicated: bool,
+
+    /// Encryption applied to the stored payload (None = stored in plaintext)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionInfo>,
+
+    /// Git state when the path was deleted inside a work tree (None = not in a repository)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitInfo>,
+}
+
+/// Compression format of a trashed payload
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionKind {
+    /// Single file compressed with zstd
+    Zstd,
+    /// Directory packed as a tar archive and compressed with zstd
+    TarZstd,
+}
+
+impl CompressionKind {
+    /// File extension appended to the trash filename
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CompressionKind::Zstd => "zst",
+            CompressionKind::TarZstd => "tar.zst",
+        }
+    }
+}
+
+/// Sizes recorded when a trashed payload is compressed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompressionInfo {
+    /// Compression format used for the payload
+    pub kind: CompressionKind,
+
+    /// Size of the payload before compression in bytes
+    pub original_size: u64,
+
+    /// Size of the compressed payload in bytes
+    pub compressed_size: u64,
+}
+
+/// Layout of an encrypted payload
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptedKind {
+    /// Single file encrypted as-is
+    File,
+    /// Directory packed as a tar archive before encryption
+    Tar,
+}
+
+impl EncryptedKind {
+    /// File extension appended to the trash filename
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EncryptedKind::File => "enc",
+            EncryptedKind::Tar => "tar.enc",
+        }
+    }
+}
+
+/// Details recorded when a trashed payload is encrypted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptionInfo {
+    /// Layout of the encrypted payload
+    pub kind: EncryptedKind,
+
+    /// Identifier of the key the payload was encrypted with
+    pub key_id: String,
+
+    /// Hex-encoded encrypted original path when path encryption is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_path: Option<String>,
+}
+
+/// Whether a deleted path differed from what git has committed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitFileStatus {
+    /// Tracked and identical to HEAD; for a directory, everything in it is
+    Tracked,
+    /// Tracked with staged or unstaged changes; for a directory, anything in it is, or is untracked
+    Modified,
+    /// Not known to git
+    Untracked,
+}
+
+/// Repository state recorded when a path is deleted inside a git work tree
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitInfo {
+    /// Top-level directory of the work tree
+    pub repo_root: PathBuf,
+
+    /// Checked-out branch (None = detached HEAD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
+    /// Commit ID HEAD pointed at (None = no commits yet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+
+    /// Status of the path relative to HEAD
+    pub status: GitFileStatus,
+}
+
+impl CompressionInfo {
+    /// Compressed size as a fraction of the original size
+    pub fn ratio(&self) -> f64 {
+        if self.original_size == 0 {
+            1.0
+        } else {
+            self.compressed_size as f64 / self.original_size as f64
+        }
+    }
 }
 
 impl FileMeta {
@@ -59,8 +187,13 @@ impl FileMeta {
             size,
             permissions,
             tags: Vec::new(),
+            note: None,
             checksum: None,
             deleted_by,
+            compression: None,
+            deduplicated: false,
+            encryption: None,
+            git: None,
         })
     }
 
@@ -71,6 +204,23 @@ impl FileMeta {
         }
     }
 
+    /// Check if the stored payload is compressed
+    pub fn is_compressed(&self) -> bool {
+        self.compression.is_some()
+    }
+
+    /// Check if the stored payload is encrypted
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Check if the original path is hidden behind encryption
+    pub fn has_encrypted_path(&self) -> bool {
+        self.encryption
+            .as_ref()
+            .is_some_and(|info| info.encrypted_path.is_some())
+    }
+
     /// Get the original filename
     pub fn filename(&self) -> Option<&str> {
         self.original_path
@@ -91,6 +241,7 @@ impl FileMeta {
         filename.contains(pattern)
             || path_str.contains(pattern)
             || self.tags.iter().any(|tag| tag.contains(pattern))
+            || self.note.as_ref().is_some_and(|note| note.contains(pattern))
     }
 
     /// Format file size in human-readable format
@@ -160,6 +311,9 @@ mod tests {
         assert!(meta.matches_pattern("work"));
         assert!(meta.matches_pattern("tmp")); // Should match path
         assert!(!meta.matches_pattern("nonexistent"));
+
+        meta.note = Some("superseded by v2 schema".to_string());
+        assert!(meta.matches_pattern("v2 schema"));
     }
 
     #[test]
@@ -172,8 +326,32 @@ mod tests {
         let meta1 = FileMeta::from_path(&path).unwrap();
         assert_eq!(meta1.human_readable_size(), "1 B");
 
-        fs::write(&path, &vec![0u8; 1536]).unwrap(); // 1.5 KB
+        fs::write(&path, vec![0u8; 1536]).unwrap(); // 1.5 KB
         let meta2 = FileMeta::from_path(&path).unwrap();
         assert_eq!(meta2.human_readable_size(), "1.5 KB");
     }
+
+    #[test]
+    fn test_compression_info() {
+        let info = CompressionInfo {
+            kind: CompressionKind::TarZstd,
+            original_size: 1000,
+            compressed_size: 250,
+        };
+        assert_eq!(info.ratio(), 0.25);
+        assert_eq!(info.kind.extension(), "tar.zst");
+    }
+
+    #[test]
+    fn test_legacy_metadata_without_compression() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let meta = FileMeta::from_path(temp_file.path()).unwrap();
+
+        let mut value = serde_json::to_value(&meta).unwrap();
+        assert!(value.get("compression").is_none());
+        value.as_object_mut().unwrap().remove("compression");
+
+        let loaded: FileMeta = serde_json::from_value(value).unwrap();
+        assert!(!loaded.is_compressed());
+    }
 }
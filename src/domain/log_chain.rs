@@ -0,0 +1,460 @@
+//! Hash chain and optional HMAC that make the operation log tamper-evident
+//!
+//! Every record stores in `prev_hash` the SHA-256 of the entry before it, so
+//! deleting, reordering or editing an entry breaks the link of the next one.
+//! With `log.hmac` enabled every record also carries an HMAC-SHA256 of its own
+//! serialization keyed by a secret kept next to the log, which additionally
+//! protects the newest entry. A [`LogCheckpoint`] starts the active file after
+//! each rotation or compaction and carries the link forward, so a chain whose
+//! oldest segments were deleted by retention still verifies from there on.
+
+use super::operation_log::OperationLog;
+use anyhow::Result;
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// File next to the operation log holding the HMAC secret
+pub const AUDIT_KEY_FILE: &str = "audit.key";
+
+const AUDIT_KEY_SIZE: usize = 32;
+
+/// Link carried by the first record of a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Record that starts a log file and carries the chain over from the previous one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogCheckpoint {
+    /// When the checkpoint was written
+    pub checkpoint: DateTime<Utc>,
+
+    /// Why a new file was started: "rotation" or "compaction"
+    pub reason: String,
+
+    /// Link the next entry continues from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+
+    /// HMAC of this checkpoint without this field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+}
+
+impl LogCheckpoint {
+    pub fn new(reason: &str, prev_hash: Option<String>) -> Self {
+        Self {
+            checkpoint: Utc::now(),
+            reason: reason.to_string(),
+            prev_hash,
+            mac: None,
+        }
+    }
+}
+
+/// A line of the operation log
+#[derive(Debug, Clone)]
+pub enum LogRecord {
+    Entry(Box<OperationLog>),
+    Checkpoint(LogCheckpoint),
+}
+
+impl LogRecord {
+    /// Parse a line, or None if it is neither an entry nor a checkpoint
+    pub fn parse(line: &str) -> Option<Self> {
+        if let Ok(entry) = serde_json::from_str::<OperationLog>(line) {
+            return Some(LogRecord::Entry(Box::new(entry)));
+        }
+        serde_json::from_str::<LogCheckpoint>(line).ok().map(LogRecord::Checkpoint)
+    }
+
+    fn prev_hash(&self) -> Option<&str> {
+        match self {
+            LogRecord::Entry(entry) => entry.prev_hash.as_deref(),
+            LogRecord::Checkpoint(checkpoint) => checkpoint.prev_hash.as_deref(),
+        }
+    }
+
+    /// Entries written before chaining, which carry neither a link nor a MAC
+    fn is_legacy(&self) -> bool {
+        matches!(self, LogRecord::Entry(entry) if entry.prev_hash.is_none())
+    }
+
+    fn mac(&self) -> Option<&str> {
+        match self {
+            LogRecord::Entry(entry) => entry.mac.as_deref(),
+            LogRecord::Checkpoint(checkpoint) => checkpoint.mac.as_deref(),
+        }
+    }
+
+    /// Serialization covered by the MAC: the record without its `mac`
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            LogRecord::Entry(entry) => serde_json::to_vec(&OperationLog { mac: None, ..(**entry).clone() })?,
+            LogRecord::Checkpoint(checkpoint) => {
+                serde_json::to_vec(&LogCheckpoint { mac: None, ..checkpoint.clone() })?
+            }
+        })
+    }
+
+    fn describe(&self) -> (Option<Uuid>, DateTime<Utc>) {
+        match self {
+            LogRecord::Entry(entry) => (Some(entry.id), entry.timestamp),
+            LogRecord::Checkpoint(checkpoint) => (None, checkpoint.checkpoint),
+        }
+    }
+}
+
+/// SHA-256 of a log line, hex encoded
+pub fn line_hash(line: &str) -> String {
+    format!("{:x}", Sha256::digest(line.as_bytes()))
+}
+
+/// Value the record following `line` must carry in `prev_hash`
+///
+/// Checkpoints are transparent: the record after one links to whatever the
+/// checkpoint links to.
+pub fn link_after(line: &str) -> Option<String> {
+    match LogRecord::parse(line) {
+        Some(LogRecord::Checkpoint(checkpoint)) => checkpoint.prev_hash,
+        _ => Some(line_hash(line)),
+    }
+}
+
+/// Value the record written after `previous` must carry, starting from [`GENESIS_HASH`]
+pub fn link_from(previous: Option<&str>) -> Option<String> {
+    match previous {
+        Some(line) => link_after(line),
+        None => Some(GENESIS_HASH.to_string()),
+    }
+}
+
+/// Local secret that authenticates log records
+pub struct AuditKey(Vec<u8>);
+
+impl AuditKey {
+    /// Load the key from `path` if it exists
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(secret) if secret.len() == AUDIT_KEY_SIZE => Ok(Some(Self(secret))),
+            Ok(_) => anyhow::bail!("{} is not a valid audit key", path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load the key from `path`, generating it readable by the owner only on first use
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if let Some(key) = Self::load(path)? {
+            return Ok(key);
+        }
+        let mut secret = vec![0u8; AUDIT_KEY_SIZE];
+        OsRng.fill_bytes(&mut secret);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, &secret)?;
+        Ok(Self(secret))
+    }
+
+    fn hmac(&self) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length")
+    }
+
+    fn sign(&self, bytes: &[u8]) -> String {
+        let mut mac = self.hmac();
+        mac.update(bytes);
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+
+    fn verify(&self, bytes: &[u8], expected: &str) -> bool {
+        let Some(expected) = decode_hex(expected) else {
+            return false;
+        };
+        let mut mac = self.hmac();
+        mac.update(bytes);
+        mac.verify_slice(&expected).is_ok()
+    }
+
+    /// Set the MAC of an entry that already has its `prev_hash`
+    pub fn seal_entry(&self, entry: &mut OperationLog) -> Result<()> {
+        entry.mac = None;
+        entry.mac = Some(self.sign(&serde_json::to_vec(entry)?));
+        Ok(())
+    }
+
+    /// Set the MAC of a checkpoint
+    pub fn seal_checkpoint(&self, checkpoint: &mut LogCheckpoint) -> Result<()> {
+        checkpoint.mac = None;
+        checkpoint.mac = Some(self.sign(&serde_json::to_vec(checkpoint)?));
+        Ok(())
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Where and why the chain first fails to verify
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainBreak {
+    pub file: PathBuf,
+    /// 1-based line number within `file`
+    pub line: usize,
+    pub id: Option<Uuid>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub reason: String,
+}
+
+/// Outcome of [`ChainVerifier`]
+#[derive(Debug, Clone, Default)]
+pub struct ChainReport {
+    /// Entries checked
+    pub entries: usize,
+    /// Files checked, oldest first
+    pub files: Vec<PathBuf>,
+    /// Unchained entries written before the chain started
+    pub legacy: usize,
+    /// Time of the checkpoint the chain starts from when older history was removed
+    pub anchor: Option<DateTime<Utc>>,
+    /// Records carry MACs but the audit key was not available to check them
+    pub unauthenticated: bool,
+    pub first_break: Option<ChainBreak>,
+}
+
+/// Checks log lines fed oldest first
+pub struct ChainVerifier<'a> {
+    key: Option<&'a AuditKey>,
+    report: ChainReport,
+    expected: Option<String>,
+    seen_any: bool,
+    chained: bool,
+    saw_mac: bool,
+}
+
+impl<'a> ChainVerifier<'a> {
+    pub fn new(key: Option<&'a AuditKey>) -> Self {
+        Self {
+            key,
+            report: ChainReport::default(),
+            expected: None,
+            seen_any: false,
+            chained: false,
+            saw_mac: false,
+        }
+    }
+
+    /// Check every line of one file
+    pub fn feed(&mut self, file: &Path, content: &str) {
+        self.report.files.push(file.to_path_buf());
+        for (index, line) in content.lines().enumerate() {
+            if self.report.first_break.is_some() {
+                return;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Err(reason) = self.check(line) {
+                let (id, timestamp) = LogRecord::parse(line).map(|record| record.describe()).unzip();
+                self.report.first_break = Some(ChainBreak {
+                    file: file.to_path_buf(),
+                    line: index + 1,
+                    id: id.flatten(),
+                    timestamp,
+                    reason,
+                });
+            }
+        }
+    }
+
+    fn check(&mut self, line: &str) -> std::result::Result<(), String> {
+        let record = LogRecord::parse(line).ok_or("malformed line")?;
+
+        match (record.mac(), self.key) {
+            (Some(mac), Some(key)) => {
+                self.saw_mac = true;
+                let bytes = record.signed_bytes().map_err(|e| e.to_string())?;
+                if !key.verify(&bytes, mac) {
+                    return Err("record was modified (MAC mismatch)".to_string());
+                }
+            }
+            (Some(_), None) => self.report.unauthenticated = true,
+            // With a key only legacy entries, written before chaining, may go unsigned
+            (None, Some(_)) if self.saw_mac || !record.is_legacy() => {
+                return Err("MAC missing from an authenticated log".to_string())
+            }
+            (None, _) => {}
+        }
+
+        match record.prev_hash() {
+            None if self.chained => return Err("unchained record inside the chain".to_string()),
+            None => {
+                if let LogRecord::Entry(_) = record {
+                    self.report.legacy += 1;
+                }
+            }
+            Some(link) if self.seen_any => {
+                if self.expected.as_deref() != Some(link) {
+                    return Err("does not follow the previous record: entries were deleted, reordered or modified"
+                        .to_string());
+                }
+                self.chained = true;
+            }
+            Some(GENESIS_HASH) => self.chained = true,
+            Some(_) => match &record {
+                LogRecord::Checkpoint(checkpoint) => {
+                    self.report.anchor = Some(checkpoint.checkpoint);
+                    self.chained = true;
+                }
+                LogRecord::Entry(_) => return Err("earlier entries are missing".to_string()),
+            },
+        }
+
+        if let LogRecord::Entry(_) = record {
+            self.report.entries += 1;
+        }
+        self.expected = link_after(line);
+        self.seen_any = true;
+        Ok(())
+    }
+
+    pub fn finish(self) -> ChainReport {
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::operation_log::{OperationResult, OperationType};
+    use tempfile::TempDir;
+
+    fn chain(count: usize, key: Option<&AuditKey>) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for index in 0..count {
+            let mut entry = OperationLog::new(
+                OperationType::Delete,
+                vec![PathBuf::from(format!("/file{}", index))],
+                OperationResult::Success,
+            );
+            entry.prev_hash = link_from(lines.last().map(String::as_str));
+            if let Some(key) = key {
+                key.seal_entry(&mut entry).unwrap();
+            }
+            lines.push(serde_json::to_string(&entry).unwrap());
+        }
+        lines
+    }
+
+    fn verify(lines: &[String], key: Option<&AuditKey>) -> ChainReport {
+        let mut verifier = ChainVerifier::new(key);
+        verifier.feed(Path::new("operations.jsonl"), &lines.join("\n"));
+        verifier.finish()
+    }
+
+    #[test]
+    fn test_intact_chain() {
+        let report = verify(&chain(3, None), None);
+        assert_eq!((report.legacy, report.entries), (0, 3));
+        assert!(report.first_break.is_none());
+    }
+
+    #[test]
+    fn test_deleted_reordered_and_modified_entries() {
+        let lines = chain(4, None);
+
+        let mut deleted = lines.clone();
+        deleted.remove(1);
+        assert_eq!(verify(&deleted, None).first_break.unwrap().line, 2);
+
+        let mut reordered = lines.clone();
+        reordered.swap(1, 2);
+        assert_eq!(verify(&reordered, None).first_break.unwrap().line, 2);
+
+        let mut modified = lines.clone();
+        modified[1] = modified[1].replace("/file1", "/other");
+        let broken = verify(&modified, None).first_break.unwrap();
+        assert_eq!(broken.line, 3);
+        assert!(broken.reason.contains("does not follow"));
+
+        let truncated_head = lines[1..].to_vec();
+        assert_eq!(verify(&truncated_head, None).first_break.unwrap().reason, "earlier entries are missing");
+    }
+
+    #[test]
+    fn test_hmac_detects_edit_of_last_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = AuditKey::load_or_create(&temp_dir.path().join(AUDIT_KEY_FILE)).unwrap();
+        let mut lines = chain(2, Some(&key));
+        assert!(verify(&lines, Some(&key)).first_break.is_none());
+
+        lines[1] = lines[1].replace("/file1", "/other");
+        assert!(verify(&lines, None).first_break.is_none());
+        let broken = verify(&lines, Some(&key)).first_break.unwrap();
+        assert_eq!(broken.line, 2);
+        assert!(broken.reason.contains("MAC"));
+    }
+
+    #[test]
+    fn test_checkpoint_anchors_chain_and_legacy_prefix() {
+        let mut legacy = OperationLog::new(OperationType::Restore, vec![], OperationResult::Success);
+        legacy.prev_hash = None;
+        let legacy_line = serde_json::to_string(&legacy).unwrap();
+
+        let mut lines = vec![legacy_line.clone()];
+        let rest = chain(2, None);
+        let mut first: OperationLog = serde_json::from_str(&rest[0]).unwrap();
+        first.prev_hash = link_after(&legacy_line);
+        lines.push(serde_json::to_string(&first).unwrap());
+        let report = verify(&lines, None);
+        assert_eq!((report.legacy, report.entries), (1, 2));
+        assert!(report.first_break.is_none());
+
+        // After old history is removed a checkpoint carries the link forward
+        let checkpoint = LogCheckpoint::new("rotation", link_after(&lines[0]));
+        let anchored = vec![serde_json::to_string(&checkpoint).unwrap(), lines[1].clone()];
+        let report = verify(&anchored, None);
+        assert!(report.first_break.is_none());
+        assert_eq!(report.anchor, Some(checkpoint.checkpoint));
+    }
+
+    #[test]
+    fn test_key_requires_mac_on_checkpoints_and_chained_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = AuditKey::load_or_create(&temp_dir.path().join(AUDIT_KEY_FILE)).unwrap();
+        let lines = chain(3, Some(&key));
+
+        // Dropping the head behind an unsigned checkpoint hides the deletion without a key
+        let forged = LogCheckpoint::new("rotation", link_after(&lines[0]));
+        let anchored = vec![serde_json::to_string(&forged).unwrap(), lines[1].clone(), lines[2].clone()];
+        assert!(verify(&anchored, None).first_break.is_none());
+        let broken = verify(&anchored, Some(&key)).first_break.unwrap();
+        assert_eq!(broken.line, 1);
+        assert!(broken.reason.contains("MAC"));
+
+        let unsigned = chain(2, None);
+        assert_eq!(verify(&unsigned, Some(&key)).first_break.unwrap().line, 1);
+
+        // Legacy entries predate the key and stay exempt
+        let mut legacy = OperationLog::new(OperationType::Restore, vec![], OperationResult::Success);
+        legacy.prev_hash = None;
+        let legacy_line = serde_json::to_string(&legacy).unwrap();
+        let mut first: OperationLog = serde_json::from_str(&lines[0]).unwrap();
+        first.prev_hash = link_after(&legacy_line);
+        key.seal_entry(&mut first).unwrap();
+        let report = verify(&[legacy_line, serde_json::to_string(&first).unwrap()], Some(&key));
+        assert!(report.first_break.is_none());
+        assert_eq!(report.legacy, 1);
+    }
+}
@@ -1,9 +1,19 @@
+pub mod blob_manifest;
 pub mod config;
+pub mod config_layers;
+pub mod config_schema;
 pub mod file_meta;
+pub mod log_chain;
 pub mod operation_log;
 pub mod trash_item;
 
-pub use config::Config;
-pub use file_meta::FileMeta;
+pub use blob_manifest::{BlobManifest, ManifestEntry, ManifestEntryKind};
+pub use config::{
+    CompressionConfig, Config, DedupConfig, EncryptionConfig, GcConfig, GitConfig, GitSafeguard,
+    HooksConfig, LogConfig, LogSinkConfig, NotesConfig, SyslogFacility,
+};
+pub use file_meta::{
+    CompressionInfo, CompressionKind, EncryptedKind, EncryptionInfo, FileMeta, GitFileStatus, GitInfo,
+};
 pub use operation_log::OperationLog;
 pub use trash_item::TrashItem;
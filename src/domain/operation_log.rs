@@ -1,6 +1,10 @@
+use super::log_chain::{link_after, link_from, AuditKey, ChainReport, ChainVerifier, LogCheckpoint, LogRecord, AUDIT_KEY_FILE};
+use super::LogConfig;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// Types of operations that can be logged
@@ -14,6 +18,8 @@ pub enum OperationType {
     Status,
     Protect,
     Doctor,
+    Tag,
+    Annotate,
 }
 
 /// Result of an operation
@@ -50,6 +56,18 @@ pub struct OperationLog {
 
     /// File IDs involved (for restore/purge operations)
     pub file_ids: Vec<Uuid>,
+
+    /// Tags carried by the items involved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// SHA-256 of the previous record, linking the log into a hash chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+
+    /// HMAC of this entry without this field, when `log.hmac` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
 }
 
 impl OperationLog {
@@ -68,6 +86,9 @@ impl OperationLog {
             user,
             context: None,
             file_ids: Vec::new(),
+            tags: Vec::new(),
+            prev_hash: None,
+            mac: None,
         }
     }
 
@@ -83,6 +104,12 @@ impl OperationLog {
         self
     }
 
+    /// Add the tags of the items involved
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
     /// Check if this log entry matches a filter
     pub fn matches_filter(
         &self,
@@ -124,6 +151,8 @@ impl OperationLog {
             OperationType::Status => "Status",
             OperationType::Protect => "Protect",
             OperationType::Doctor => "Doctor",
+            OperationType::Tag => "Tag",
+            OperationType::Annotate => "Annotate",
         };
 
         let paths_str = if self.paths.len() == 1 {
@@ -141,26 +170,56 @@ impl OperationLog {
 }
 
 /// Manager for operation logs
+///
+/// Entries are appended to the active file. Once it outgrows the [`LogConfig`]
+/// limits it is compressed to `<name>.1.zst`, older segments move up by one
+/// and those beyond `keep_segments` are deleted. Reads stream newest-first
+/// from the end of the active file through the segments, so a limited read
+/// never touches old history.
 pub struct OperationLogger {
     log_file_path: PathBuf,
+    config: LogConfig,
 }
 
 impl OperationLogger {
     /// Create a new operation logger
     pub fn new(log_file_path: PathBuf) -> Self {
-        Self { log_file_path }
+        Self {
+            log_file_path,
+            config: LogConfig::default(),
+        }
+    }
+
+    /// Use the given rotation, retention and authentication settings
+    pub fn with_config(mut self, config: LogConfig) -> Self {
+        self.config = config;
+        self
     }
 
     /// Log an operation
-    pub fn log(&self, entry: OperationLog) -> anyhow::Result<()> {
+    ///
+    /// The entry is linked to the previous record and, with `log.hmac`,
+    /// authenticated before it is appended.
+    pub fn log(&self, mut entry: OperationLog) -> anyhow::Result<()> {
         // Ensure log directory exists
         if let Some(parent) = self.log_file_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
+        let _lock = self.lock()?;
 
-        // Append to log file as JSON lines
-        let json_line = serde_json::to_string(&entry)? + "\n";
+        if self.needs_rotation() {
+            self.rotate_locked()?;
+        }
+
+        entry.prev_hash = link_from(self.last_line()?.as_deref());
+        entry.mac = None;
+        if let Some(key) = self.signing_key()? {
+            key.seal_entry(&mut entry)?;
+        }
+        self.append(&serde_json::to_string(&entry)?)
+    }
 
+    fn append(&self, line: &str) -> anyhow::Result<()> {
         use std::fs::OpenOptions;
         use std::io::Write;
 
@@ -169,37 +228,287 @@ impl OperationLogger {
             .append(true)
             .open(&self.log_file_path)?;
 
-        file.write_all(json_line.as_bytes())?;
-        file.flush()?;
+        file.write_all(format!("{}\n", line).as_bytes())?;
+        file.sync_all()?;
 
         Ok(())
     }
 
+    /// Serialize writers across processes; released when the returned file is dropped
+    fn lock(&self) -> anyhow::Result<std::fs::File> {
+        let file = open_lock_file(&self.log_file_path)?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    /// Path of the HMAC secret next to the log
+    pub fn audit_key_path(&self) -> PathBuf {
+        self.log_file_path.with_file_name(AUDIT_KEY_FILE)
+    }
+
+    fn signing_key(&self) -> anyhow::Result<Option<AuditKey>> {
+        if !self.config.hmac {
+            return Ok(None);
+        }
+        AuditKey::load_or_create(&self.audit_key_path()).map(Some)
+    }
+
+    /// Last record written, which may be in the newest segment
+    fn last_line(&self) -> anyhow::Result<Option<String>> {
+        let source: Box<dyn ReadSeek> = match std::fs::File::open(&self.log_file_path) {
+            Ok(file) => Box::new(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => match self.segments().first() {
+                Some(segment) => Box::new(read_segment(segment)?),
+                None => return Ok(None),
+            },
+            Err(e) => return Err(e.into()),
+        };
+        Ok(ReverseLines::new(source).next().transpose()?)
+    }
+
+    /// Path of rotated segment `index`; 1 is the most recent
+    pub fn segment_path(&self, index: usize) -> PathBuf {
+        let mut name = self.log_file_path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}.zst", index));
+        self.log_file_path.with_file_name(name)
+    }
+
+    /// Rotated segments from newest to oldest
+    pub fn segments(&self) -> Vec<PathBuf> {
+        (1..).map(|index| self.segment_path(index)).take_while(|path| path.exists()).collect()
+    }
+
+    /// Whether the active file has outgrown its size or age limit
+    fn needs_rotation(&self) -> bool {
+        let Ok(metadata) = std::fs::metadata(&self.log_file_path) else {
+            return false;
+        };
+        let too_big = self.config.max_size.is_some_and(|max| metadata.len() >= max);
+        if !too_big && self.config.max_age_days.is_none() {
+            return false;
+        }
+
+        // Entries are appended in order, so the first one is the oldest
+        let Ok(file) = std::fs::File::open(&self.log_file_path) else {
+            return false;
+        };
+        let oldest = std::io::BufRead::lines(std::io::BufReader::new(file))
+            .take(2)
+            .map_while(Result::ok)
+            .find_map(|line| serde_json::from_str::<OperationLog>(&line).ok());
+        // A file holding only its checkpoint is never rotated
+        let Some(oldest) = oldest else {
+            return false;
+        };
+        too_big
+            || self
+                .config
+                .max_age_days
+                .is_some_and(|days| oldest.timestamp < Utc::now() - chrono::Duration::days(days as i64))
+    }
+
+    /// Compress the active file into segment 1, shifting older segments up
+    ///
+    /// The new active file starts with a checkpoint that continues the hash
+    /// chain. Returns false when there was nothing to rotate.
+    pub fn rotate(&self) -> anyhow::Result<bool> {
+        let _lock = self.lock()?;
+        self.rotate_locked()
+    }
+
+    fn rotate_locked(&self) -> anyhow::Result<bool> {
+        // Move the active file aside first so an interrupted rotation never mixes files
+        let rotating = self.log_file_path.with_extension("jsonl.rotating");
+        match std::fs::rename(&self.log_file_path, &rotating) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+        let link = ReverseLines::new(std::fs::File::open(&rotating)?)
+            .next()
+            .transpose()?
+            .and_then(|line| link_after(&line));
+
+        let segments = self.segments();
+        for (index, segment) in segments.iter().enumerate().rev() {
+            std::fs::rename(segment, self.segment_path(index + 2))?;
+        }
+
+        let compressed = self.segment_path(1).with_extension("zst.tmp");
+        let mut encoder = zstd::Encoder::new(std::fs::File::create(&compressed)?, 3)?;
+        std::io::copy(&mut std::fs::File::open(&rotating)?, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        std::fs::rename(&compressed, self.segment_path(1))?;
+        std::fs::remove_file(&rotating)?;
+
+        self.append(&self.checkpoint("rotation", link)?)?;
+        self.prune_segments()?;
+        Ok(true)
+    }
+
+    /// Serialized checkpoint carrying `link` into a new file
+    fn checkpoint(&self, reason: &str, link: Option<String>) -> anyhow::Result<String> {
+        let mut checkpoint = LogCheckpoint::new(reason, link);
+        if let Some(key) = self.signing_key()? {
+            key.seal_checkpoint(&mut checkpoint)?;
+        }
+        Ok(serde_json::to_string(&checkpoint)?)
+    }
+
+    /// Delete segments beyond `keep_segments`
+    fn prune_segments(&self) -> anyhow::Result<usize> {
+        let Some(keep) = self.config.keep_segments else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        for segment in self.segments().into_iter().skip(keep as usize) {
+            std::fs::remove_file(segment)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Stream entries newest-first across the active file and its segments
+    pub fn entries(&self) -> anyhow::Result<LogEntries> {
+        let mut sources = vec![self.log_file_path.clone()];
+        sources.extend(self.segments());
+        Ok(LogEntries {
+            sources: sources.into_iter().collect(),
+            current: None,
+            malformed: 0,
+        })
+    }
+
+    /// Follow entries appended from now on, see [`LogFollower::poll`]
+    pub fn follow(&self) -> anyhow::Result<LogFollower> {
+        if let Some(parent) = self.log_file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut follower = LogFollower {
+            log_file_path: self.log_file_path.clone(),
+            segment_path: self.segment_path(1),
+            position: 0,
+            first_line: None,
+        };
+        // Start after the last complete line; anything written later is new
+        follower.poll()?;
+        Ok(follower)
+    }
+
     /// Read all log entries
     pub fn read_logs(&self) -> anyhow::Result<Vec<OperationLog>> {
+        self.read_where(None, None, |_| true)
+    }
+
+    /// Rewrite the log without blank lines and entries older than `keep_since`
+    ///
+    /// Segments whose newest entry is older than `keep_since`, or beyond
+    /// `keep_segments`, are deleted. When leading lines of the active file are
+    /// dropped a checkpoint takes their place so the hash chain still
+    /// verifies. Returns the number of entries kept in the active file and
+    /// lines dropped overall.
+    ///
+    /// Fails without changing anything while [`verify`](Self::verify) reports
+    /// a break, e.g. a malformed or modified line, as the new checkpoint would
+    /// otherwise hide it.
+    pub fn compact(&self, keep_since: Option<DateTime<Utc>>) -> anyhow::Result<(usize, usize)> {
+        if !self.log_file_path.exists() && self.segments().is_empty() {
+            return Ok((0, 0));
+        }
+        let _lock = self.lock()?;
+
+        if let Some(broken) = self.verify()?.first_break {
+            anyhow::bail!(
+                "Not compacting the operation log: line {} of {} {}; check it with `rmz log verify`",
+                broken.line,
+                broken.file.display(),
+                broken.reason
+            );
+        }
+
+        let mut dropped = 0;
+        if let Some(cutoff) = keep_since {
+            for segment in self.segments().into_iter().rev() {
+                let lines = ReverseLines::new(read_segment(&segment)?).collect::<std::io::Result<Vec<_>>>()?;
+                let newest = lines.iter().find_map(|line| serde_json::from_str::<OperationLog>(line).ok());
+                if newest.is_some_and(|log| log.timestamp >= cutoff) {
+                    break;
+                }
+                dropped += lines.len();
+                std::fs::remove_file(&segment)?;
+            }
+        }
+        self.prune_segments()?;
+
         if !self.log_file_path.exists() {
-            return Ok(Vec::new());
+            return Ok((0, dropped));
         }
 
         let content = std::fs::read_to_string(&self.log_file_path)?;
-        let mut logs = Vec::new();
+        let lines: Vec<&str> = content.lines().collect();
+        let keep = |line: &str| match LogRecord::parse(line) {
+            Some(LogRecord::Entry(log)) => keep_since.is_none_or(|cutoff| log.timestamp >= cutoff),
+            Some(LogRecord::Checkpoint(_)) => true,
+            None => false,
+        };
+        let is_entry = |line: &str| matches!(LogRecord::parse(line), Some(LogRecord::Entry(_)));
 
-        for line in content.lines() {
-            if !line.trim().is_empty() {
-                match serde_json::from_str::<OperationLog>(line) {
-                    Ok(log) => logs.push(log),
-                    Err(e) => {
-                        eprintln!("Warning: Failed to parse log line: {}", e);
-                        // Continue parsing other lines
-                    }
+        // Lines before the first kept entry are replaced by a single checkpoint
+        let first_kept = lines
+            .iter()
+            .position(|line| is_entry(line) && keep(line))
+            .unwrap_or(lines.len());
+        let (prefix, rest) = lines.split_at(first_kept);
+        let prefix_dropped = prefix.iter().filter(|line| !keep(line)).count();
+
+        let mut kept = String::new();
+        let mut kept_count = 0;
+        let mut dropped_lines = 0;
+        if prefix_dropped > 0 {
+            kept.push_str(&self.checkpoint("compaction", prefix.last().and_then(|line| link_after(line)))?);
+            kept.push('\n');
+            dropped_lines += prefix.len();
+        } else {
+            for line in prefix {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+
+        for line in rest {
+            if keep(line) {
+                kept.push_str(line);
+                kept.push('\n');
+                if is_entry(line) {
+                    kept_count += 1;
                 }
+            } else {
+                dropped_lines += 1;
             }
         }
 
-        // Sort by timestamp (newest first)
-        logs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        if dropped_lines > 0 {
+            // Write next to the log and rename so a crash never truncates it
+            let temp_path = self.log_file_path.with_extension("jsonl.tmp");
+            std::fs::write(&temp_path, kept)?;
+            std::fs::rename(&temp_path, &self.log_file_path)?;
+        }
 
-        Ok(logs)
+        Ok((kept_count, dropped + dropped_lines))
+    }
+
+    /// Check the hash chain, and the MACs when the audit key exists, oldest record first
+    pub fn verify(&self) -> anyhow::Result<ChainReport> {
+        let key = AuditKey::load(&self.audit_key_path())?;
+        let mut verifier = ChainVerifier::new(key.as_ref());
+        for segment in self.segments().into_iter().rev() {
+            let content = String::from_utf8_lossy(read_segment(&segment)?.get_ref()).into_owned();
+            verifier.feed(&segment, &content);
+        }
+        if self.log_file_path.exists() {
+            verifier.feed(&self.log_file_path, &std::fs::read_to_string(&self.log_file_path)?);
+        }
+        Ok(verifier.finish())
     }
 
     /// Read logs with filters
@@ -209,15 +518,272 @@ impl OperationLogger {
         since: Option<DateTime<Utc>>,
         limit: Option<usize>,
     ) -> anyhow::Result<Vec<OperationLog>> {
-        let all_logs = self.read_logs()?;
+        self.read_where(since, limit, |log| log.matches_filter(operation_type.as_ref(), None))
+    }
 
-        let filtered: Vec<OperationLog> = all_logs
-            .into_iter()
-            .filter(|log| log.matches_filter(operation_type.as_ref(), since.as_ref()))
-            .take(limit.unwrap_or(usize::MAX))
-            .collect();
+    /// Read up to `limit` entries newer than `since` that match `filter`, newest first
+    ///
+    /// Reading stops at the first entry older than `since`. Malformed lines
+    /// are skipped and reported in a single warning.
+    pub fn read_where(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+        mut filter: impl FnMut(&OperationLog) -> bool,
+    ) -> anyhow::Result<Vec<OperationLog>> {
+        let mut entries = self.entries()?;
+        let mut logs = Vec::new();
+
+        for entry in entries.by_ref() {
+            let log = entry?;
+            if since.is_some_and(|since| log.timestamp < since) {
+                break;
+            }
+            if filter(&log) {
+                logs.push(log);
+                if limit.is_some_and(|limit| logs.len() >= limit) {
+                    break;
+                }
+            }
+        }
+
+        if entries.malformed() > 0 {
+            eprintln!(
+                "Warning: Skipped {} malformed line(s) in {}",
+                entries.malformed(),
+                self.log_file_path.display()
+            );
+        }
+
+        Ok(logs)
+    }
+}
 
-        Ok(filtered)
+/// Newest-first iterator over log entries, see [`OperationLogger::entries`]
+pub struct LogEntries {
+    sources: VecDeque<PathBuf>,
+    current: Option<ReverseLines<Box<dyn ReadSeek>>>,
+    malformed: usize,
+}
+
+impl LogEntries {
+    /// Lines skipped so far because they are not valid entries
+    pub fn malformed(&self) -> usize {
+        self.malformed
+    }
+
+    fn open_next(&mut self) -> anyhow::Result<bool> {
+        while let Some(source) = self.sources.pop_front() {
+            let reader: Box<dyn ReadSeek> = if source.extension().is_some_and(|ext| ext == "zst") {
+                Box::new(read_segment(&source)?)
+            } else {
+                match std::fs::File::open(&source) {
+                    Ok(file) => Box::new(file),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e.into()),
+                }
+            };
+            self.current = Some(ReverseLines::new(reader));
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl Iterator for LogEntries {
+    type Item = anyhow::Result<OperationLog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(lines) = self.current.as_mut() else {
+                match self.open_next() {
+                    Ok(true) => continue,
+                    Ok(false) => return None,
+                    Err(e) => return Some(Err(e)),
+                }
+            };
+            match lines.next() {
+                Some(Ok(line)) => match LogRecord::parse(&line) {
+                    Some(LogRecord::Entry(log)) => return Some(Ok(*log)),
+                    Some(LogRecord::Checkpoint(_)) => {}
+                    None => self.malformed += 1,
+                },
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => self.current = None,
+            }
+        }
+    }
+}
+
+/// Oldest-first reader of entries as they are appended, see [`OperationLogger::follow`]
+pub struct LogFollower {
+    log_file_path: PathBuf,
+    segment_path: PathBuf,
+    /// Bytes of the active file already read
+    position: u64,
+    /// First line of the active file, which changes when it is rotated
+    first_line: Option<Vec<u8>>,
+}
+
+impl LogFollower {
+    /// Entries written since the last poll, oldest first
+    ///
+    /// Entries appended just before a rotation are read back from the new
+    /// segment. Several rotations between two polls lose the entries of all
+    /// but the newest segment.
+    pub fn poll(&mut self) -> anyhow::Result<Vec<OperationLog>> {
+        use std::io::BufRead;
+
+        let lock = open_lock_file(&self.log_file_path)?;
+        lock.lock_shared()?;
+
+        let mut reader = match std::fs::File::open(&self.log_file_path) {
+            Ok(file) => Some(std::io::BufReader::new(file)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let mut current_first = None;
+        if let Some(reader) = reader.as_mut() {
+            let mut line = Vec::new();
+            reader.read_until(b'\n', &mut line)?;
+            current_first = line.ends_with(b"\n").then(|| line[..line.len() - 1].to_vec());
+        }
+
+        let mut logs = Vec::new();
+        if self.first_line.is_some() && current_first != self.first_line {
+            if let Ok(segment) = read_segment(&self.segment_path) {
+                let segment = segment.into_inner();
+                if first_line(&segment) == self.first_line {
+                    logs.extend(complete_entries(&segment, &mut self.position));
+                }
+            }
+            self.position = 0;
+            self.first_line = None;
+        }
+
+        if let Some(mut reader) = reader {
+            // Compaction may have shortened the file; only newer lines are of interest then
+            let len = reader.get_ref().metadata()?.len();
+            self.position = self.position.min(len);
+            reader.seek(SeekFrom::Start(self.position))?;
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            let mut consumed = 0;
+            logs.extend(complete_entries(&content, &mut consumed));
+            self.position += consumed;
+            if self.position > 0 {
+                self.first_line = current_first;
+            }
+        }
+        Ok(logs)
+    }
+}
+
+fn open_lock_file(log_file_path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(log_file_path.with_extension("jsonl.lock"))
+}
+
+fn first_line(content: &[u8]) -> Option<Vec<u8>> {
+    let end = content.iter().position(|&b| b == b'\n')?;
+    Some(content[..end].to_vec())
+}
+
+/// Entries in the complete lines of `content` after `position`, advancing it past them
+fn complete_entries(content: &[u8], position: &mut u64) -> Vec<OperationLog> {
+    let start = (*position).min(content.len() as u64) as usize;
+    let Some(end) = content[start..].iter().rposition(|&b| b == b'\n') else {
+        return Vec::new();
+    };
+    *position = (start + end + 1) as u64;
+    String::from_utf8_lossy(&content[start..start + end])
+        .lines()
+        .filter_map(|line| match LogRecord::parse(line) {
+            Some(LogRecord::Entry(log)) => Some(*log),
+            _ => None,
+        })
+        .collect()
+}
+
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Decompress a rotated segment; segments are bounded by `log.max_size`
+fn read_segment(path: &Path) -> anyhow::Result<Cursor<Vec<u8>>> {
+    let mut content = Vec::new();
+    zstd::Decoder::new(std::fs::File::open(path)?)?.read_to_end(&mut content)?;
+    Ok(Cursor::new(content))
+}
+
+/// Reads non-empty lines from the end of a seekable source towards its start
+struct ReverseLines<R> {
+    reader: R,
+    /// Bytes before this offset have not been read yet
+    position: Option<u64>,
+    /// Read bytes that have not been returned, starting at `position`
+    pending: Vec<u8>,
+}
+
+impl<R: Read + Seek> ReverseLines<R> {
+    const CHUNK: u64 = 64 * 1024;
+
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            position: None,
+            pending: Vec::new(),
+        }
+    }
+
+    fn read_chunk(&mut self) -> std::io::Result<bool> {
+        let position = match self.position {
+            Some(position) => position,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+        if position == 0 {
+            self.position = Some(0);
+            return Ok(false);
+        }
+        let start = position.saturating_sub(Self::CHUNK);
+        let mut chunk = vec![0; (position - start) as usize];
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.reader.read_exact(&mut chunk)?;
+        chunk.append(&mut self.pending);
+        self.pending = chunk;
+        self.position = Some(start);
+        Ok(true)
+    }
+}
+
+impl<R: Read + Seek> Iterator for ReverseLines<R> {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(newline) = self.pending.iter().rposition(|&byte| byte == b'\n') {
+                let line = self.pending.split_off(newline + 1);
+                self.pending.truncate(newline);
+                if !line.iter().all(u8::is_ascii_whitespace) {
+                    return Some(Ok(String::from_utf8_lossy(&line).into_owned()));
+                }
+                continue;
+            }
+            match self.read_chunk() {
+                Ok(true) => {}
+                Ok(false) => {
+                    let line = std::mem::take(&mut self.pending);
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        return None;
+                    }
+                    return Some(Ok(String::from_utf8_lossy(&line).into_owned()));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
     }
 }
 
@@ -299,4 +865,211 @@ mod tests {
         assert_eq!(delete_logs.len(), 1);
         assert_eq!(delete_logs[0].operation, OperationType::Delete);
     }
+
+    #[test]
+    fn test_compact_drops_expired_entries() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let logger = OperationLogger::new(temp_file.path().to_path_buf());
+
+        let mut old = OperationLog::new(
+            OperationType::Purge,
+            vec![PathBuf::from("/old.txt")],
+            OperationResult::Success,
+        );
+        old.timestamp = Utc::now() - chrono::Duration::days(400);
+        logger.log(old).unwrap();
+        logger
+            .log(OperationLog::new(
+                OperationType::Delete,
+                vec![PathBuf::from("/new.txt")],
+                OperationResult::Success,
+            ))
+            .unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(365);
+        assert_eq!(logger.compact(Some(cutoff)).unwrap(), (1, 1));
+        assert_eq!(logger.read_logs().unwrap().len(), 1);
+        assert_eq!(logger.compact(Some(cutoff)).unwrap(), (1, 0));
+        assert!(logger.verify().unwrap().first_break.is_none());
+    }
+
+    #[test]
+    fn test_compact_refuses_a_broken_chain() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let logger = OperationLogger::new(temp_file.path().to_path_buf());
+        logger.log(delete_entry("/a.txt")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(temp_file.path())
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"not json\n"))
+            .unwrap();
+        logger.log(delete_entry("/b.txt")).unwrap();
+        let before = std::fs::read_to_string(temp_file.path()).unwrap();
+
+        let error = logger.compact(Some(Utc::now() + chrono::Duration::days(1))).unwrap_err();
+        assert!(error.to_string().contains("malformed line"), "{}", error);
+        assert_eq!(std::fs::read_to_string(temp_file.path()).unwrap(), before);
+    }
+
+    fn delete_entry(path: &str) -> OperationLog {
+        OperationLog::new(OperationType::Delete, vec![PathBuf::from(path)], OperationResult::Success)
+    }
+
+    #[test]
+    fn test_reverse_lines() {
+        let text = "first\n\nsecond\nthird";
+        let lines: Vec<String> = ReverseLines::new(Cursor::new(text)).map(Result::unwrap).collect();
+        assert_eq!(lines, vec!["third", "second", "first"]);
+
+        // Lines longer than a chunk are stitched together
+        let long = "x".repeat(ReverseLines::<Cursor<&[u8]>>::CHUNK as usize * 2 + 5);
+        let text = format!("{}\nshort\n", long);
+        let lines: Vec<String> = ReverseLines::new(Cursor::new(text)).map(Result::unwrap).collect();
+        assert_eq!(lines, vec!["short".to_string(), long]);
+    }
+
+    #[test]
+    fn test_rotation_and_streaming_reads() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let logger = OperationLogger::new(temp_dir.path().join("operations.jsonl")).with_config(LogConfig {
+            max_size: Some(1),
+            max_age_days: None,
+            keep_segments: Some(2),
+            hmac: false,
+            ..LogConfig::default()
+        });
+
+        // Every write after the first rotates the previous entry into a segment
+        for index in 0..4 {
+            logger.log(delete_entry(&format!("/file{}", index))).unwrap();
+        }
+        assert_eq!(logger.segments().len(), 2);
+        assert!(logger.segment_path(1).exists() && !logger.segment_path(3).exists());
+
+        let logs = logger.read_logs().unwrap();
+        let paths: Vec<_> = logs.iter().map(|log| log.paths[0].clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("/file3"), PathBuf::from("/file2"), PathBuf::from("/file1")]);
+
+        let limited = logger.read_filtered_logs(None, None, Some(2)).unwrap();
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].paths, vec![PathBuf::from("/file3")]);
+    }
+
+    #[test]
+    fn test_age_based_rotation_and_segment_retention() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let logger = OperationLogger::new(temp_dir.path().join("operations.jsonl")).with_config(LogConfig {
+            max_size: None,
+            max_age_days: Some(30),
+            keep_segments: None,
+            hmac: false,
+            ..LogConfig::default()
+        });
+
+        let mut old = delete_entry("/old");
+        old.timestamp = Utc::now() - chrono::Duration::days(400);
+        logger.log(old).unwrap();
+        logger.log(delete_entry("/new")).unwrap();
+        assert_eq!(logger.segments().len(), 1);
+        logger.log(delete_entry("/newer")).unwrap();
+        assert_eq!(logger.segments().len(), 1);
+
+        let cutoff = Utc::now() - chrono::Duration::days(365);
+        assert_eq!(logger.compact(Some(cutoff)).unwrap(), (2, 1));
+        assert!(logger.segments().is_empty());
+        assert_eq!(logger.read_logs().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_malformed_lines_are_skipped() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let logger = OperationLogger::new(temp_file.path().to_path_buf());
+        logger.log(delete_entry("/a")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(temp_file.path())
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"not json\n{\n"))
+            .unwrap();
+        logger.log(delete_entry("/b")).unwrap();
+
+        let mut entries = logger.entries().unwrap();
+        assert_eq!(entries.by_ref().map(Result::unwrap).count(), 2);
+        assert_eq!(entries.malformed(), 2);
+    }
+
+    #[test]
+    fn test_chain_survives_rotation_and_compaction() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let logger = OperationLogger::new(temp_dir.path().join("operations.jsonl")).with_config(LogConfig {
+            max_size: Some(1),
+            max_age_days: None,
+            keep_segments: Some(2),
+            hmac: true,
+            ..LogConfig::default()
+        });
+        for index in 0..4 {
+            logger.log(delete_entry(&format!("/file{}", index))).unwrap();
+        }
+
+        // The oldest segment was pruned, so the chain starts at a checkpoint
+        let report = logger.verify().unwrap();
+        assert!(report.first_break.is_none(), "{:?}", report.first_break);
+        assert_eq!((report.entries, report.files.len()), (3, 3));
+        assert!(report.anchor.is_some());
+        let mut entries = logger.entries().unwrap();
+        assert_eq!(entries.by_ref().count(), 3);
+        assert_eq!(entries.malformed(), 0);
+
+        let mut old = delete_entry("/old");
+        old.timestamp = Utc::now() - chrono::Duration::days(400);
+        let logger = OperationLogger::new(temp_dir.path().join("compacted.jsonl")).with_config(LogConfig {
+            hmac: true,
+            ..LogConfig::default()
+        });
+        logger.log(old).unwrap();
+        logger.log(delete_entry("/new")).unwrap();
+        assert_eq!(logger.compact(Some(Utc::now() - chrono::Duration::days(365))).unwrap(), (1, 1));
+        assert!(logger.verify().unwrap().first_break.is_none());
+    }
+
+    #[test]
+    fn test_verify_reports_first_break() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let logger = OperationLogger::new(temp_file.path().to_path_buf());
+        for index in 0..3 {
+            logger.log(delete_entry(&format!("/file{}", index))).unwrap();
+        }
+        assert!(logger.verify().unwrap().first_break.is_none());
+
+        let content = std::fs::read_to_string(temp_file.path()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        std::fs::write(temp_file.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let report = logger.verify().unwrap();
+        let broken = report.first_break.unwrap();
+        assert_eq!(broken.line, 2);
+        assert_eq!(broken.id, serde_json::from_str::<OperationLog>(lines[2]).ok().map(|log| log.id));
+    }
+
+    #[test]
+    fn test_follow_across_rotation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let logger = OperationLogger::new(temp_dir.path().join("operations.jsonl"));
+        logger.log(delete_entry("/before")).unwrap();
+
+        let mut follower = logger.follow().unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        logger.log(delete_entry("/a")).unwrap();
+        logger.log(delete_entry("/b")).unwrap();
+        let paths: Vec<_> = follower.poll().unwrap().into_iter().map(|log| log.paths[0].clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+
+        // Entries written just before a rotation are read back from the segment
+        logger.log(delete_entry("/c")).unwrap();
+        logger.rotate().unwrap();
+        logger.log(delete_entry("/d")).unwrap();
+        let paths: Vec<_> = follower.poll().unwrap().into_iter().map(|log| log.paths[0].clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("/c"), PathBuf::from("/d")]);
+        assert!(follower.poll().unwrap().is_empty());
+    }
 }
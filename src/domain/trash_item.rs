@@ -61,6 +61,23 @@ impl TrashItem {
         Ok(format!("{:x}", hasher.finalize()))
     }
 
+    /// Whether the item was a directory, whatever form its payload is stored in
+    pub fn is_directory(&self) -> bool {
+        use crate::domain::{CompressionKind, EncryptedKind};
+
+        if let Some(info) = &self.meta.encryption {
+            info.kind == EncryptedKind::Tar
+        } else if self.meta.deduplicated {
+            crate::domain::BlobManifest::load(&self.trash_path)
+                .map(|manifest| manifest.single_file().is_none())
+                .unwrap_or(false)
+        } else if let Some(info) = &self.meta.compression {
+            info.kind == CompressionKind::TarZstd
+        } else {
+            self.trash_path.is_dir()
+        }
+    }
+
     /// Get the trash subdirectory (based on deletion date)
     pub fn trash_subdirectory(&self) -> String {
         self.meta.deleted_at.format("%Y-%m-%d").to_string()
@@ -0,0 +1,248 @@
+use crate::domain::BlobManifest;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Content-addressed blob store with reference counting
+///
+/// File contents are stored once under `<root>/<first two hex chars>/<sha256>`.
+/// Reference counts live in `<root>/refs.json`; a blob whose count drops to
+/// zero is removed by [`BlobStore::gc`]. Every read-modify-write of the counts
+/// holds an exclusive lock on `<root>/refs.lock`, so concurrent processes
+/// neither lose updates nor collect a blob another one is referencing.
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the path of the blob with the given hash
+    pub fn blob_path(&self, hash: &str) -> PathBuf {
+        BlobManifest::blob_path(&self.root, hash)
+    }
+
+    fn refs_path(&self) -> PathBuf {
+        self.root.join("refs.json")
+    }
+
+    fn load_refs(&self) -> Result<HashMap<String, u64>> {
+        let path = self.refs_path();
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn save_refs(&self, refs: &HashMap<String, u64>) -> Result<()> {
+        // Replace the file atomically so a crash never leaves truncated counts
+        let temp_path = self.root.join("refs.json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string_pretty(refs)?)?;
+        std::fs::rename(temp_path, self.refs_path())?;
+        Ok(())
+    }
+
+    /// Serialize reference count updates across processes; released when the returned file is dropped
+    fn lock(&self) -> Result<File> {
+        std::fs::create_dir_all(&self.root)?;
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.root.join("refs.lock"))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    /// Store the content of `source` (left in place) and take a reference to it
+    pub fn ingest(&self, source: &Path) -> Result<String> {
+        let hash = hash_file(source)?;
+        let blob_path = self.blob_path(&hash);
+
+        // Hold the lock from storing the blob until it is referenced, so gc cannot remove it in between
+        let _lock = self.lock()?;
+        if !blob_path.exists() {
+            if let Some(parent) = blob_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Hard link when possible; the source is removed by the caller later
+            if std::fs::hard_link(source, &blob_path).is_err() {
+                std::fs::copy(source, &blob_path)?;
+            }
+        }
+
+        let mut refs = self.load_refs()?;
+        *refs.entry(hash.clone()).or_insert(0) += 1;
+        self.save_refs(&refs)?;
+
+        Ok(hash)
+    }
+
+    /// Drop one reference for each of the given hashes
+    pub fn release(&self, hashes: &[String]) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut refs = self.load_refs()?;
+        for hash in hashes {
+            if let Some(count) = refs.get_mut(hash) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        self.save_refs(&refs)
+    }
+
+    /// Current reference count of a blob
+    pub fn ref_count(&self, hash: &str) -> Result<u64> {
+        Ok(self.load_refs()?.get(hash).copied().unwrap_or(0))
+    }
+
+    /// Remove unreferenced blobs, returning the number removed and bytes freed
+    pub fn gc(&self) -> Result<(usize, u64)> {
+        let _lock = self.lock()?;
+        let mut refs = self.load_refs()?;
+        refs.retain(|_, count| *count > 0);
+
+        let mut removed = 0;
+        let mut freed = 0;
+        for (hash, path) in self.list_blobs()? {
+            if !refs.contains_key(&hash) {
+                freed += std::fs::metadata(&path)?.len();
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+
+        self.save_refs(&refs)?;
+        Ok((removed, freed))
+    }
+
+    /// Total bytes occupied by stored blobs
+    pub fn physical_size(&self) -> Result<u64> {
+        let mut total = 0;
+        for (_, path) in self.list_blobs()? {
+            total += std::fs::metadata(&path)?.len();
+        }
+        Ok(total)
+    }
+
+    fn list_blobs(&self) -> Result<Vec<(String, PathBuf)>> {
+        let mut blobs = Vec::new();
+        if !self.root.exists() {
+            return Ok(blobs);
+        }
+
+        for shard in std::fs::read_dir(&self.root)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&shard)? {
+                let path = entry?.path();
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    blobs.push((name.to_string(), path.clone()));
+                }
+            }
+        }
+
+        Ok(blobs)
+    }
+}
+
+/// Calculate the SHA-256 of a file
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let bytes_read = reader.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ingest_deduplicates_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        fs::write(&a, "same content").unwrap();
+        fs::write(&b, "same content").unwrap();
+
+        let hash_a = store.ingest(&a).unwrap();
+        let hash_b = store.ingest(&b).unwrap();
+
+        assert_eq!(hash_a, hash_b);
+        assert_eq!(store.ref_count(&hash_a).unwrap(), 2);
+        assert_eq!(store.physical_size().unwrap(), 12);
+    }
+
+    #[test]
+    fn test_release_and_gc() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BlobStore::new(temp_dir.path().join("blobs"));
+
+        let a = temp_dir.path().join("a.txt");
+        fs::write(&a, "content").unwrap();
+        let hash = store.ingest(&a).unwrap();
+        store.ingest(&a).unwrap();
+
+        store.release(std::slice::from_ref(&hash)).unwrap();
+        assert_eq!(store.gc().unwrap(), (0, 0));
+        assert!(store.blob_path(&hash).exists());
+
+        store.release(std::slice::from_ref(&hash)).unwrap();
+        assert_eq!(store.gc().unwrap(), (1, 7));
+        assert!(!store.blob_path(&hash).exists());
+    }
+
+    #[test]
+    fn test_concurrent_ingest_keeps_every_reference() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("blobs");
+        let source = temp_dir.path().join("shared.txt");
+        fs::write(&source, "shared content").unwrap();
+
+        // Separate stores stand in for separate processes; each opens its own lock file
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let (root, source) = (root.clone(), source.clone());
+                std::thread::spawn(move || {
+                    let store = BlobStore::new(root);
+                    for _ in 0..10 {
+                        store.ingest(&source).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let store = BlobStore::new(root);
+        let hash = hash_file(&source).unwrap();
+        assert_eq!(store.ref_count(&hash).unwrap(), 80);
+        assert_eq!(store.gc().unwrap(), (0, 0));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::domain::CompressionKind;
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::Path;
+
+/// Compress a file or directory into `destination`, returning the compressed size
+pub fn compress(source: &Path, destination: &Path, level: i32) -> Result<(CompressionKind, u64)> {
+    let kind = if source.is_dir() {
+        CompressionKind::TarZstd
+    } else {
+        CompressionKind::Zstd
+    };
+
+    let output = BufWriter::new(File::create(destination)?);
+    let mut encoder = zstd::Encoder::new(output, level)?;
+
+    match kind {
+        CompressionKind::Zstd => {
+            let mut input = BufReader::new(File::open(source)?);
+            std::io::copy(&mut input, &mut encoder)?;
+        }
+        CompressionKind::TarZstd => {
+            let mut builder = tar::Builder::new(&mut encoder);
+            builder.follow_symlinks(false);
+            builder.append_dir_all(".", source)?;
+            builder.finish()?;
+        }
+    }
+
+    let mut output = encoder.finish()?;
+    std::io::Write::flush(&mut output)?;
+    drop(output);
+
+    let compressed_size = std::fs::metadata(destination)?.len();
+    Ok((kind, compressed_size))
+}
+
+/// Decompress a payload to `destination`, recreating the original file or directory
+pub fn decompress(payload: &Path, kind: CompressionKind, destination: &Path) -> Result<()> {
+    let decoder = zstd::Decoder::new(File::open(payload)?)?;
+
+    match kind {
+        CompressionKind::Zstd => {
+            let mut reader = decoder;
+            let mut output = BufWriter::new(File::create(destination)?);
+            std::io::copy(&mut reader, &mut output)?;
+        }
+        CompressionKind::TarZstd => {
+            std::fs::create_dir_all(destination)?;
+            let mut archive = tar::Archive::new(decoder);
+            archive.set_preserve_permissions(true);
+            archive.unpack(destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Open a reader over the decompressed content of a single-file payload
+pub fn open_reader(payload: &Path, kind: CompressionKind) -> Result<Box<dyn Read>> {
+    match kind {
+        CompressionKind::Zstd => Ok(Box::new(zstd::Decoder::new(File::open(payload)?)?)),
+        CompressionKind::TarZstd => {
+            anyhow::bail!("Cannot read directory archive as a single file: {}", payload.display())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compress_and_decompress_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("log.txt");
+        fs::write(&source, "line\n".repeat(1000)).unwrap();
+
+        let payload = temp_dir.path().join("log.txt.zst");
+        let (kind, compressed_size) = compress(&source, &payload, 3).unwrap();
+        assert_eq!(kind, CompressionKind::Zstd);
+        assert!(compressed_size < 5000);
+
+        let restored = temp_dir.path().join("restored.txt");
+        decompress(&payload, kind, &restored).unwrap();
+        assert_eq!(fs::read_to_string(&restored).unwrap(), "line\n".repeat(1000));
+
+        let mut content = String::new();
+        open_reader(&payload, kind).unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content.len(), 5000);
+    }
+
+    #[test]
+    fn test_compress_and_decompress_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("build");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.txt"), "alpha").unwrap();
+        fs::write(source.join("nested/b.txt"), "beta").unwrap();
+
+        let payload = temp_dir.path().join("build.tar.zst");
+        let (kind, _) = compress(&source, &payload, 3).unwrap();
+        assert_eq!(kind, CompressionKind::TarZstd);
+        assert!(open_reader(&payload, kind).is_err());
+
+        let restored = temp_dir.path().join("restored");
+        decompress(&payload, kind, &restored).unwrap();
+        assert_eq!(fs::read_to_string(restored.join("a.txt")).unwrap(), "alpha");
+        assert_eq!(fs::read_to_string(restored.join("nested/b.txt")).unwrap(), "beta");
+    }
+}
@@ -0,0 +1,378 @@
+use crate::domain::TrashItem;
+use crate::infra::TrashStore;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Files larger than this are summarised instead of diffed line by line
+const MAX_TEXT_DIFF_BYTES: usize = 8 * 1024 * 1024;
+
+/// Lines of context around each change in a unified diff
+const CONTEXT_LINES: usize = 3;
+
+/// Difference between a trashed item and what is currently on disk
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemDiff {
+    /// Contents are the same
+    Identical,
+    /// Nothing exists at the current path
+    Missing,
+    /// One side is a file and the other a directory
+    KindMismatch { trashed_is_dir: bool },
+    /// Unified diff of two text files, trashed version first
+    Text(String),
+    /// Summary of two files that cannot be diffed as text
+    Binary { trashed: FileSummary, current: FileSummary },
+    /// Entries that differ between two directories
+    Directory(Vec<EntryChange>),
+}
+
+/// Size and fingerprint of one side of a binary comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSummary {
+    pub size: u64,
+    pub sha256: String,
+    /// Last modification time, when known
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// How an entry differs between the trashed and current directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    OnlyInTrash,
+    OnlyOnDisk,
+    Changed,
+}
+
+/// One differing entry of a directory comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    /// Path relative to the compared directories
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    pub is_dir: bool,
+}
+
+/// Compare a trashed item with the file or directory at `current`
+pub fn diff_item(store: &TrashStore, item: &TrashItem, current: &Path) -> Result<ItemDiff> {
+    let Ok(current_metadata) = std::fs::symlink_metadata(current) else {
+        return Ok(ItemDiff::Missing);
+    };
+
+    let trashed_is_dir = item.is_directory();
+    if trashed_is_dir != current_metadata.is_dir() {
+        return Ok(ItemDiff::KindMismatch { trashed_is_dir });
+    }
+
+    if trashed_is_dir {
+        // Directories are compared after materialising the trashed copy next to the trash
+        let scratch = ScratchDir::new(store.get_trash_root())?;
+        let trashed = scratch.path().join("item");
+        store.extract_to(item, &trashed)?;
+        let changes = diff_directories(&trashed, current)?;
+        return Ok(if changes.is_empty() {
+            ItemDiff::Identical
+        } else {
+            ItemDiff::Directory(changes)
+        });
+    }
+
+    let current_modified = current_metadata.modified().ok().map(DateTime::<Utc>::from);
+    if item.meta.size.max(current_metadata.len()) > MAX_TEXT_DIFF_BYTES as u64 {
+        // Too large to diff as text, so both sides are fingerprinted without loading them
+        let trashed = summarize_reader(store.open_content(item)?, None)?;
+        let current = summarize_reader(std::fs::File::open(current)?, current_modified)?;
+        return Ok(if trashed.size == current.size && trashed.sha256 == current.sha256 {
+            ItemDiff::Identical
+        } else {
+            ItemDiff::Binary { trashed, current }
+        });
+    }
+
+    let mut trashed = Vec::new();
+    store.open_content(item)?.read_to_end(&mut trashed)?;
+    let current_content = std::fs::read(current)?;
+
+    let labels = (
+        format!("trash:{}", item.meta.original_path.display()),
+        current.display().to_string(),
+    );
+    Ok(diff_contents(
+        &trashed,
+        &current_content,
+        labels,
+        current_modified,
+    ))
+}
+
+/// Compare two file contents, trashed version first
+pub fn diff_contents(
+    trashed: &[u8],
+    current: &[u8],
+    labels: (String, String),
+    current_modified: Option<DateTime<Utc>>,
+) -> ItemDiff {
+    if trashed == current {
+        return ItemDiff::Identical;
+    }
+
+    match (as_text(trashed), as_text(current)) {
+        (Some(old), Some(new)) => {
+            let diff = TextDiff::from_lines(old, new);
+            ItemDiff::Text(
+                diff.unified_diff()
+                    .context_radius(CONTEXT_LINES)
+                    .header(&labels.0, &labels.1)
+                    .to_string(),
+            )
+        }
+        _ => ItemDiff::Binary {
+            trashed: summarize(trashed, None),
+            current: summarize(current, current_modified),
+        },
+    }
+}
+
+/// List entries that differ between two directories
+///
+/// Entries below a directory that exists on one side only are not listed separately.
+pub fn diff_directories(trashed: &Path, current: &Path) -> Result<Vec<EntryChange>> {
+    let mut old = BTreeMap::new();
+    let mut new = BTreeMap::new();
+    collect_entries(trashed, Path::new(""), &mut old)?;
+    collect_entries(current, Path::new(""), &mut new)?;
+
+    let mut paths: Vec<&PathBuf> = old.keys().chain(new.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut changes: Vec<EntryChange> = Vec::new();
+    for path in paths {
+        let collapsed = changes
+            .iter()
+            .any(|change| change.is_dir && change.kind != ChangeKind::Changed && path.starts_with(&change.path));
+        if collapsed {
+            continue;
+        }
+
+        let change = match (old.get(path), new.get(path)) {
+            (Some(entry), None) => Some((ChangeKind::OnlyInTrash, entry.is_dir())),
+            (None, Some(entry)) => Some((ChangeKind::OnlyOnDisk, entry.is_dir())),
+            (Some(a), Some(b)) if a.is_dir() != b.is_dir() => Some((ChangeKind::Changed, false)),
+            (Some(Entry::File(size_a)), Some(Entry::File(size_b))) => {
+                let differs = size_a != size_b
+                    || std::fs::read(trashed.join(path))? != std::fs::read(current.join(path))?;
+                differs.then_some((ChangeKind::Changed, false))
+            }
+            (Some(a), Some(b)) => (a != b).then_some((ChangeKind::Changed, false)),
+            (None, None) => None,
+        };
+
+        if let Some((kind, is_dir)) = change {
+            changes.push(EntryChange {
+                path: path.clone(),
+                kind,
+                is_dir,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+#[derive(Debug, PartialEq)]
+enum Entry {
+    Directory,
+    File(u64),
+    Symlink(PathBuf),
+}
+
+impl Entry {
+    fn is_dir(&self) -> bool {
+        matches!(self, Entry::Directory)
+    }
+}
+
+fn collect_entries(root: &Path, relative: &Path, entries: &mut BTreeMap<PathBuf, Entry>) -> Result<()> {
+    for dir_entry in std::fs::read_dir(root.join(relative))? {
+        let dir_entry = dir_entry?;
+        let path = relative.join(dir_entry.file_name());
+        let metadata = std::fs::symlink_metadata(dir_entry.path())?;
+
+        if metadata.is_symlink() {
+            entries.insert(path, Entry::Symlink(std::fs::read_link(dir_entry.path())?));
+        } else if metadata.is_dir() {
+            entries.insert(path.clone(), Entry::Directory);
+            collect_entries(root, &path, entries)?;
+        } else {
+            entries.insert(path, Entry::File(metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Content as text, or None if it contains NUL bytes, invalid UTF-8 or is too large
+fn as_text(content: &[u8]) -> Option<&str> {
+    if content.len() > MAX_TEXT_DIFF_BYTES || content.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(content).ok()
+}
+
+fn summarize(content: &[u8], modified: Option<DateTime<Utc>>) -> FileSummary {
+    FileSummary {
+        size: content.len() as u64,
+        sha256: format!("{:x}", Sha256::digest(content)),
+        modified,
+    }
+}
+
+fn summarize_reader(mut reader: impl Read, modified: Option<DateTime<Utc>>) -> Result<FileSummary> {
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut reader, &mut hasher)?;
+    Ok(FileSummary {
+        size,
+        sha256: format!("{:x}", hasher.finalize()),
+        modified,
+    })
+}
+
+/// Temporary directory in the trash root, removed when dropped
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(trash_root: &Path) -> Result<Self> {
+        let path = trash_root.join(format!(".rmz-diff-{}.tmp", Uuid::new_v4()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileMeta;
+    use crate::infra::trash_store::TrashStoreInterface;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn labels() -> (String, String) {
+        ("trash:a.txt".to_string(), "a.txt".to_string())
+    }
+
+    #[test]
+    fn test_diff_contents() {
+        assert_eq!(diff_contents(b"same\n", b"same\n", labels(), None), ItemDiff::Identical);
+
+        let ItemDiff::Text(unified) = diff_contents(b"one\ntwo\n", b"one\n2\n", labels(), None) else {
+            panic!("expected a text diff");
+        };
+        assert!(unified.starts_with("--- trash:a.txt\n+++ a.txt\n"));
+        assert!(unified.contains("-two\n+2\n"));
+
+        let ItemDiff::Binary { trashed, current } = diff_contents(b"\0\x01", b"\0\x02\x03", labels(), None) else {
+            panic!("expected a binary summary");
+        };
+        assert_eq!((trashed.size, current.size), (2, 3));
+        assert_ne!(trashed.sha256, current.sha256);
+    }
+
+    #[test]
+    fn test_diff_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let (old, new) = (temp_dir.path().join("old"), temp_dir.path().join("new"));
+        for dir in [&old, &new] {
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(dir.join("src/same.rs"), "same").unwrap();
+        }
+        fs::write(old.join("src/lib.rs"), "old").unwrap();
+        fs::write(new.join("src/lib.rs"), "new").unwrap();
+        fs::create_dir_all(old.join("docs/guide")).unwrap();
+        fs::write(old.join("docs/guide/intro.md"), "intro").unwrap();
+        fs::write(new.join("NOTES"), "notes").unwrap();
+
+        let changes = diff_directories(&old, &new).unwrap();
+        let summary: Vec<(&str, ChangeKind)> = changes
+            .iter()
+            .map(|change| (change.path.to_str().unwrap(), change.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("NOTES", ChangeKind::OnlyOnDisk),
+                ("docs", ChangeKind::OnlyInTrash),
+                ("src/lib.rs", ChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_item_against_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let dir = temp_dir.path().join("project");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        let item = store.save(&FileMeta::from_path(&dir).unwrap(), &dir).unwrap();
+        let item = store.compress(&item.meta.id, 3).unwrap();
+
+        assert_eq!(diff_item(&store, &item, &dir).unwrap(), ItemDiff::Missing);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        assert_eq!(diff_item(&store, &item, &dir).unwrap(), ItemDiff::Identical);
+
+        fs::write(dir.join("main.rs"), "fn main() { run() }\n").unwrap();
+        assert!(matches!(diff_item(&store, &item, &dir).unwrap(), ItemDiff::Directory(changes) if changes.len() == 1));
+        assert!(matches!(
+            diff_item(&store, &item, &dir.join("main.rs")).unwrap(),
+            ItemDiff::KindMismatch { trashed_is_dir: true }
+        ));
+
+        // Scratch directories are cleaned up
+        let leftovers = fs::read_dir(store.get_trash_root())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(".rmz-diff"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_diff_item_summarises_large_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let path = temp_dir.path().join("large.log");
+        let content = "line\n".repeat(MAX_TEXT_DIFF_BYTES / 4);
+        fs::write(&path, &content).unwrap();
+        let item = store.save(&FileMeta::from_path(&path).unwrap(), &path).unwrap();
+
+        fs::write(&path, &content).unwrap();
+        assert_eq!(diff_item(&store, &item, &path).unwrap(), ItemDiff::Identical);
+
+        fs::write(&path, format!("{content}tail\n")).unwrap();
+        match diff_item(&store, &item, &path).unwrap() {
+            ItemDiff::Binary { trashed, current } => {
+                assert_eq!(trashed.size, content.len() as u64);
+                assert_eq!(current.size, content.len() as u64 + 5);
+            }
+            other => panic!("expected a summary, got {other:?}"),
+        }
+    }
+}
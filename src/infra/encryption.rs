@@ -0,0 +1,686 @@
+use crate::domain::{EncryptedKind, EncryptionConfig};
+use anyhow::{anyhow, Result};
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the passphrase for non-interactive use
+pub const PASSPHRASE_ENV: &str = "RMZ_PASSPHRASE";
+
+/// Environment variable holding the replacement passphrase during key rotation
+pub const NEW_PASSPHRASE_ENV: &str = "RMZ_NEW_PASSPHRASE";
+
+/// Plaintext bytes per encrypted chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Authentication tag appended to every chunk
+const TAG_SIZE: usize = 16;
+
+/// Header identifying an encrypted payload
+const MAGIC: &[u8; 5] = b"RMZE1";
+
+/// Nonce prefix length used by the STREAM construction with XChaCha20Poly1305
+const STREAM_NONCE_SIZE: usize = 19;
+
+/// Known plaintext encrypted into the key record to verify a secret
+const KEY_CHECK: &[u8] = b"rmz-key-check";
+
+/// Symmetric key derived from a passphrase or key file
+pub struct EncryptionKey {
+    key: chacha20poly1305::Key,
+    id: String,
+}
+
+impl EncryptionKey {
+    /// Derive a key from a secret and salt with Argon2id
+    pub fn derive(secret: &[u8], salt: &[u8], id: String) -> Result<Self> {
+        let mut key = chacha20poly1305::Key::default();
+        argon2::Argon2::default()
+            .hash_password_into(secret, salt, &mut key)
+            .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+        Ok(Self { key, id })
+    }
+
+    /// Identifier recorded in the metadata of items encrypted with this key
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Encrypt a small value (e.g. a path) into `nonce || ciphertext`
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = XChaCha20Poly1305::new(&self.key);
+        let mut nonce = XNonce::default();
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("Encryption failed"))?;
+
+        let mut output = nonce.to_vec();
+        output.extend_from_slice(&ciphertext);
+        Ok(output)
+    }
+
+    /// Decrypt a value produced by [`EncryptionKey::encrypt_bytes`]
+    pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < 24 {
+            anyhow::bail!("Encrypted value is truncated");
+        }
+        let (nonce, ciphertext) = data.split_at(24);
+        XChaCha20Poly1305::new(&self.key)
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Decryption failed: wrong key or corrupted data"))
+    }
+
+    /// Encrypt a stream in authenticated chunks
+    pub fn encrypt_stream(&self, mut reader: impl Read, writer: impl Write) -> Result<()> {
+        let mut encryptor = self.encrypting_writer(writer)?;
+        std::io::copy(&mut reader, &mut encryptor)?;
+        encryptor.finish()?;
+        Ok(())
+    }
+
+    /// Decrypt a stream produced by [`EncryptionKey::encrypt_stream`]
+    pub fn decrypt_stream(&self, reader: impl Read, mut writer: impl Write) -> Result<()> {
+        std::io::copy(&mut self.decrypting_reader(reader)?, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Wrap `writer` so everything written to it is encrypted; call [`EncryptWriter::finish`] when done
+    pub fn encrypting_writer<W: Write>(&self, mut writer: W) -> Result<EncryptWriter<W>> {
+        let mut nonce = [0u8; STREAM_NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&nonce)?;
+
+        Ok(EncryptWriter {
+            encryptor: EncryptorBE32::from_aead(XChaCha20Poly1305::new(&self.key), nonce.as_ref().into()),
+            writer,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    /// Wrap `reader` so reading yields the decrypted content, one authenticated chunk at a time
+    pub fn decrypting_reader<R: Read>(&self, mut reader: R) -> Result<DecryptReader<R>> {
+        let mut header = [0u8; MAGIC.len() + STREAM_NONCE_SIZE];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| anyhow!("Encrypted payload is truncated"))?;
+        if &header[..MAGIC.len()] != MAGIC {
+            anyhow::bail!("Not an rmz encrypted payload");
+        }
+
+        let nonce = &header[MAGIC.len()..];
+        Ok(DecryptReader {
+            decryptor: Some(DecryptorBE32::from_aead(XChaCha20Poly1305::new(&self.key), nonce.into())),
+            reader,
+            chunk: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Encrypt a file, or a directory packed as tar, into `destination`
+    ///
+    /// Directories are archived straight into the encrypting stream, so no
+    /// plaintext copy is ever written to disk.
+    pub fn encrypt_path(&self, source: &Path, destination: &Path) -> Result<EncryptedKind> {
+        let output = BufWriter::new(File::create(destination)?);
+
+        if source.is_dir() {
+            let mut builder = tar::Builder::new(self.encrypting_writer(output)?);
+            builder.follow_symlinks(false);
+            builder.append_dir_all(".", source)?;
+            builder.into_inner()?.finish()?;
+            Ok(EncryptedKind::Tar)
+        } else {
+            self.encrypt_stream(BufReader::new(File::open(source)?), output)?;
+            Ok(EncryptedKind::File)
+        }
+    }
+
+    /// Decrypt a payload to `destination`, recreating the original file or directory
+    pub fn decrypt_path(&self, payload: &Path, kind: EncryptedKind, destination: &Path) -> Result<()> {
+        let input = BufReader::new(File::open(payload)?);
+
+        match kind {
+            EncryptedKind::File => {
+                let temp = destination.with_extension("rmz-decrypt.tmp");
+                let result = self.decrypt_stream(input, BufWriter::new(File::create(&temp)?));
+                if let Err(e) = result {
+                    // Never leave partially decrypted plaintext behind
+                    std::fs::remove_file(&temp).ok();
+                    return Err(e);
+                }
+                std::fs::rename(&temp, destination)?;
+            }
+            EncryptedKind::Tar => {
+                // Unpack next to the destination so a corrupt archive or wrong key
+                // never touches a directory that is already there
+                let name = destination.file_name().ok_or_else(|| anyhow!("Invalid destination: {}", destination.display()))?;
+                let temp = destination.with_file_name(format!(".{}.rmz-decrypt.tmp", name.to_string_lossy()));
+                let mut archive = tar::Archive::new(self.decrypting_reader(input)?);
+                archive.set_preserve_permissions(true);
+                std::fs::create_dir_all(&temp)?;
+                let result = archive.unpack(&temp).map_err(anyhow::Error::from).and_then(|()| {
+                    if destination.is_dir() {
+                        move_into(&temp, destination)?;
+                        std::fs::remove_dir(&temp)?;
+                        Ok(())
+                    } else {
+                        Ok(std::fs::rename(&temp, destination)?)
+                    }
+                });
+                if let Err(e) = result {
+                    std::fs::remove_dir_all(&temp).ok();
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writer returned by [`EncryptionKey::encrypting_writer`]
+///
+/// Full chunks are encrypted as they fill up; the final, shorter chunk is
+/// written by [`EncryptWriter::finish`], without which the stream is invalid.
+pub struct EncryptWriter<W: Write> {
+    encryptor: EncryptorBE32<XChaCha20Poly1305>,
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    /// Encrypt the last chunk and return the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        let chunk = self
+            .encryptor
+            .encrypt_last(self.buffer.as_slice())
+            .map_err(|_| anyhow!("Encryption failed"))?;
+        self.writer.write_all(&chunk)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let taken = data.len().min(CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&data[..taken]);
+        if self.buffer.len() == CHUNK_SIZE {
+            // A full chunk is never the last one: the last is always shorter, possibly empty
+            let chunk = self
+                .encryptor
+                .encrypt_next(self.buffer.as_slice())
+                .map_err(|_| std::io::Error::other("Encryption failed"))?;
+            self.writer.write_all(&chunk)?;
+            self.buffer.clear();
+        }
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reader returned by [`EncryptionKey::decrypting_reader`]
+///
+/// Each chunk is authenticated before any of its plaintext is handed out.
+pub struct DecryptReader<R: Read> {
+    decryptor: Option<DecryptorBE32<XChaCha20Poly1305>>,
+    reader: R,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, output: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            let Some(decryptor) = self.decryptor.as_mut() else {
+                return Ok(0);
+            };
+
+            let mut buffer = vec![0u8; CHUNK_SIZE + TAG_SIZE];
+            let read = read_full(&mut self.reader, &mut buffer)?;
+            let decrypted = if read == buffer.len() {
+                decryptor.decrypt_next(buffer.as_slice())
+            } else {
+                let decryptor = self.decryptor.take().expect("decryptor checked above");
+                decryptor.decrypt_last(&buffer[..read])
+            };
+            self.chunk = decrypted.map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "Decryption failed: wrong key or corrupted data")
+            })?;
+            self.position = 0;
+        }
+
+        let count = output.len().min(self.chunk.len() - self.position);
+        output[..count].copy_from_slice(&self.chunk[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Key parameters persisted in the trash root; the key itself is never stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRecord {
+    version: u32,
+    key_id: String,
+    salt: String,
+    check: String,
+}
+
+/// Journal of a key rotation in progress, kept until every item is re-encrypted
+///
+/// The previous key is stored wrapped with the new one, so an interrupted
+/// rotation can be resumed, and old items read, with the new secret alone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RotationJournal {
+    previous_key_id: String,
+    key_id: String,
+    wrapped_key: String,
+}
+
+/// Derives, verifies and rotates the trash encryption key
+pub struct KeyManager {
+    trash_root: PathBuf,
+    config: EncryptionConfig,
+}
+
+impl KeyManager {
+    pub fn new(trash_root: PathBuf, config: EncryptionConfig) -> Self {
+        Self { trash_root, config }
+    }
+
+    fn record_path(&self) -> PathBuf {
+        self.trash_root.join("encryption.json")
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.trash_root.join("encryption.rotation.json")
+    }
+
+    /// Check whether a key has been set up for this trash
+    pub fn is_initialized(&self) -> bool {
+        self.record_path().exists()
+    }
+
+    /// Identifier of the current key, if one has been set up
+    pub fn current_key_id(&self) -> Result<Option<String>> {
+        if !self.is_initialized() {
+            return Ok(None);
+        }
+        Ok(Some(self.load_record()?.key_id))
+    }
+
+    fn load_record(&self) -> Result<KeyRecord> {
+        let content = std::fs::read_to_string(self.record_path())?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Read the secret from the key file, `RMZ_PASSPHRASE`, or an interactive prompt
+    pub fn read_secret(&self, confirm: bool) -> Result<Vec<u8>> {
+        if let Some(key_file) = &self.config.key_file {
+            return std::fs::read(key_file)
+                .map_err(|e| anyhow!("Failed to read key file {}: {}", key_file.display(), e));
+        }
+        read_passphrase(PASSPHRASE_ENV, "Trash passphrase", confirm)
+    }
+
+    /// Derive a fresh key with a new salt, without storing its record yet
+    pub fn generate(&self, secret: &[u8]) -> Result<(EncryptionKey, KeyRecord)> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let mut id_bytes = [0u8; 8];
+        OsRng.fill_bytes(&mut id_bytes);
+
+        let key = EncryptionKey::derive(secret, &salt, to_hex(&id_bytes))?;
+        let record = KeyRecord {
+            version: 1,
+            key_id: key.id().to_string(),
+            salt: to_hex(&salt),
+            check: to_hex(&key.encrypt_bytes(KEY_CHECK)?),
+        };
+        Ok((key, record))
+    }
+
+    /// Persist a key record, replacing the current one
+    pub fn store(&self, record: &KeyRecord) -> Result<()> {
+        write_atomically(&self.record_path(), &serde_json::to_string_pretty(record)?)
+    }
+
+    /// Switch the key record to `new_key`, journaling `old_key` first
+    ///
+    /// From here on the new secret unlocks the trash; items still encrypted
+    /// with the old key are read through [`KeyManager::previous_key`] until
+    /// [`KeyManager::finish_rotation`] is called.
+    pub fn begin_rotation(&self, old_key: &EncryptionKey, new_key: &EncryptionKey, record: &KeyRecord) -> Result<()> {
+        let journal = RotationJournal {
+            previous_key_id: old_key.id().to_string(),
+            key_id: new_key.id().to_string(),
+            wrapped_key: to_hex(&new_key.encrypt_bytes(old_key.key.as_slice())?),
+        };
+        write_atomically(&self.journal_path(), &serde_json::to_string_pretty(&journal)?)?;
+        self.store(record)
+    }
+
+    /// ID of the key being rotated away from, if a rotation to the current key is unfinished
+    pub fn pending_rotation(&self) -> Result<Option<String>> {
+        Ok(self.load_journal()?.map(|journal| journal.previous_key_id))
+    }
+
+    /// Unwrap the key being rotated away from with the current key
+    pub fn previous_key(&self, current: &EncryptionKey) -> Result<Option<EncryptionKey>> {
+        let Some(journal) = self.load_journal()? else {
+            return Ok(None);
+        };
+        if journal.key_id != current.id() {
+            return Ok(None);
+        }
+        let bytes = current.decrypt_bytes(&from_hex(&journal.wrapped_key)?)?;
+        if bytes.len() != 32 {
+            anyhow::bail!("Rotation journal holds an invalid key");
+        }
+        Ok(Some(EncryptionKey {
+            key: *chacha20poly1305::Key::from_slice(&bytes),
+            id: journal.previous_key_id,
+        }))
+    }
+
+    /// Drop the rotation journal once no item uses the previous key anymore
+    pub fn finish_rotation(&self) -> Result<()> {
+        match std::fs::remove_file(self.journal_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Load the journal if it belongs to the current key record
+    ///
+    /// A journal written just before an interruption, when the record had not
+    /// been switched yet, refers to a key that was never used and is ignored.
+    fn load_journal(&self) -> Result<Option<RotationJournal>> {
+        let path = self.journal_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let journal: RotationJournal = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        match self.current_key_id()? {
+            Some(key_id) if key_id == journal.key_id => Ok(Some(journal)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Create a new key record from a secret, replacing any existing one
+    pub fn initialize(&self, secret: &[u8]) -> Result<EncryptionKey> {
+        let (key, record) = self.generate(secret)?;
+        self.store(&record)?;
+        Ok(key)
+    }
+
+    /// Derive the key from a secret and verify it against the stored record
+    pub fn unlock(&self, secret: &[u8]) -> Result<EncryptionKey> {
+        let record = self.load_record()?;
+        let key = EncryptionKey::derive(secret, &from_hex(&record.salt)?, record.key_id)?;
+        match key.decrypt_bytes(&from_hex(&record.check)?) {
+            Ok(check) if check == KEY_CHECK => Ok(key),
+            _ => anyhow::bail!("Incorrect passphrase or key file for encrypted trash"),
+        }
+    }
+
+    /// Unlock the existing key, or set one up on first use
+    pub fn load_key(&self) -> Result<EncryptionKey> {
+        if self.is_initialized() {
+            self.unlock(&self.read_secret(false)?)
+        } else {
+            self.initialize(&self.read_secret(true)?)
+        }
+    }
+}
+
+/// Read a passphrase from an environment variable or prompt for it
+pub fn read_passphrase(env_var: &str, prompt: &str, confirm: bool) -> Result<Vec<u8>> {
+    if let Ok(passphrase) = std::env::var(env_var) {
+        return Ok(passphrase.into_bytes());
+    }
+
+    let mut input = dialoguer::Password::new().with_prompt(prompt);
+    if confirm {
+        input = input.with_confirmation("Confirm passphrase", "Passphrases do not match");
+    }
+    Ok(input.interact()?.into_bytes())
+}
+
+/// Encode bytes as lowercase hex
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode lowercase or uppercase hex
+pub fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        anyhow::bail!("Invalid hex string");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| anyhow!("Invalid hex string")))
+        .collect()
+}
+
+/// Replace a small file so that readers see either the old or the new content
+/// Move the entries of `source` into the existing directory `destination`, replacing what they collide with
+fn move_into(source: &Path, destination: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        let target_meta = std::fs::symlink_metadata(&target).ok();
+        if entry.file_type()?.is_dir() && target_meta.as_ref().is_some_and(|meta| meta.is_dir()) {
+            move_into(&entry.path(), &target)?;
+            std::fs::remove_dir(entry.path())?;
+            continue;
+        }
+        match target_meta {
+            Some(meta) if meta.is_dir() => std::fs::remove_dir_all(&target)?,
+            Some(_) => std::fs::remove_file(&target)?,
+            None => {}
+        }
+        std::fs::rename(entry.path(), &target)?;
+    }
+    Ok(())
+}
+
+fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    let mut file = File::create(&temp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buffer.len() {
+        let read = reader.read(&mut buffer[total..])?;
+        if read == 0 {
+            break;
+        }
+        total += read;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_key() -> EncryptionKey {
+        EncryptionKey::derive(b"correct horse", b"0123456789abcdef", "test".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_stream_roundtrip_across_chunks() {
+        let key = test_key();
+        for size in [0, 10, CHUNK_SIZE, CHUNK_SIZE * 2 + 7] {
+            let plaintext: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let mut encrypted = Vec::new();
+            key.encrypt_stream(plaintext.as_slice(), &mut encrypted).unwrap();
+
+            let mut decrypted = Vec::new();
+            key.decrypt_stream(encrypted.as_slice(), &mut decrypted).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let key = test_key();
+        let other = EncryptionKey::derive(b"wrong", b"0123456789abcdef", "other".to_string()).unwrap();
+
+        let mut encrypted = Vec::new();
+        key.encrypt_stream(b"SECRET=1".as_slice(), &mut encrypted).unwrap();
+        assert!(other.decrypt_stream(encrypted.as_slice(), Vec::new()).is_err());
+
+        let value = key.encrypt_bytes(b"/home/user/.env").unwrap();
+        assert!(other.decrypt_bytes(&value).is_err());
+        assert_eq!(key.decrypt_bytes(&value).unwrap(), b"/home/user/.env");
+    }
+
+    #[test]
+    fn test_encrypt_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join(".ssh");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("id_ed25519"), "PRIVATE KEY").unwrap();
+
+        let key = test_key();
+        let payload = temp_dir.path().join("payload.enc");
+        assert_eq!(key.encrypt_path(&source, &payload).unwrap(), EncryptedKind::Tar);
+        assert!(!fs::read(&payload).unwrap().windows(11).any(|w| w == b"PRIVATE KEY"));
+
+        let restored = temp_dir.path().join("restored");
+        key.decrypt_path(&payload, EncryptedKind::Tar, &restored).unwrap();
+        assert_eq!(fs::read_to_string(restored.join("id_ed25519")).unwrap(), "PRIVATE KEY");
+    }
+
+    #[test]
+    fn test_failed_directory_decrypt_keeps_existing_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("project");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("main.rs"), "trashed").unwrap();
+        let payload = temp_dir.path().join("payload.enc");
+        test_key().encrypt_path(&source, &payload).unwrap();
+
+        let destination = temp_dir.path().join("existing");
+        fs::create_dir_all(&destination).unwrap();
+        fs::write(destination.join("keep.txt"), "keep").unwrap();
+        let wrong_key = EncryptionKey::derive(b"wrong", b"0123456789abcdef", "other".to_string()).unwrap();
+        assert!(wrong_key.decrypt_path(&payload, EncryptedKind::Tar, &destination).is_err());
+        assert_eq!(fs::read_to_string(destination.join("keep.txt")).unwrap(), "keep");
+
+        // A successful unpack merges into the existing directory and leaves no scratch copy
+        test_key().decrypt_path(&payload, EncryptedKind::Tar, &destination).unwrap();
+        assert_eq!(fs::read_to_string(destination.join("main.rs")).unwrap(), "trashed");
+        assert_eq!(fs::read_to_string(destination.join("keep.txt")).unwrap(), "keep");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_writer_and_reader_match_stream_format() {
+        let key = test_key();
+        for size in [0, CHUNK_SIZE, CHUNK_SIZE * 3 + 1] {
+            let plaintext: Vec<u8> = (0..size).map(|i| (i % 241) as u8).collect();
+
+            // Small writes exercise chunk boundaries that do not line up with the input
+            let mut writer = key.encrypting_writer(Vec::new()).unwrap();
+            for piece in plaintext.chunks(1000) {
+                writer.write_all(piece).unwrap();
+            }
+            let encrypted = writer.finish().unwrap();
+
+            let mut decrypted = Vec::new();
+            key.decrypt_stream(encrypted.as_slice(), &mut decrypted).unwrap();
+            assert_eq!(decrypted, plaintext);
+
+            let mut streamed = Vec::new();
+            key.decrypting_reader(encrypted.as_slice()).unwrap().read_to_end(&mut streamed).unwrap();
+            assert_eq!(streamed, plaintext);
+
+            // Dropping the last chunk must not go unnoticed
+            let truncated = &encrypted[..encrypted.len() - TAG_SIZE];
+            let mut reader = key.decrypting_reader(truncated).unwrap();
+            assert!(reader.read_to_end(&mut Vec::new()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encrypt_directory_leaves_no_plaintext_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("secrets");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("token"), "TOKEN").unwrap();
+
+        let key = test_key();
+        let payloads = temp_dir.path().join("payloads");
+        fs::create_dir_all(&payloads).unwrap();
+        let payload = payloads.join("item.rmz.tar.enc");
+        key.encrypt_path(&source, &payload).unwrap();
+        key.decrypt_path(&payload, EncryptedKind::Tar, &temp_dir.path().join("restored")).unwrap();
+
+        let names: Vec<_> = fs::read_dir(&payloads).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("item.rmz.tar.enc")]);
+    }
+
+    #[test]
+    fn test_rotation_journal_unwraps_previous_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = KeyManager::new(temp_dir.path().to_path_buf(), EncryptionConfig::default());
+        let old_key = manager.initialize(b"old").unwrap();
+        let value = old_key.encrypt_bytes(b"/home/user/.env").unwrap();
+
+        let (new_key, record) = manager.generate(b"new").unwrap();
+        manager.begin_rotation(&old_key, &new_key, &record).unwrap();
+        assert_eq!(manager.pending_rotation().unwrap().as_deref(), Some(old_key.id()));
+
+        // The new secret alone recovers the previous key
+        let current = manager.unlock(b"new").unwrap();
+        let previous = manager.previous_key(&current).unwrap().unwrap();
+        assert_eq!(previous.id(), old_key.id());
+        assert_eq!(previous.decrypt_bytes(&value).unwrap(), b"/home/user/.env");
+
+        manager.finish_rotation().unwrap();
+        assert!(manager.pending_rotation().unwrap().is_none());
+        assert!(manager.previous_key(&current).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_manager_unlock() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = KeyManager::new(temp_dir.path().to_path_buf(), EncryptionConfig::default());
+        assert!(!manager.is_initialized());
+
+        let key = manager.initialize(b"passphrase").unwrap();
+        assert_eq!(manager.current_key_id().unwrap().as_deref(), Some(key.id()));
+        assert_eq!(manager.unlock(b"passphrase").unwrap().id(), key.id());
+        assert!(manager.unlock(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+        assert_eq!(from_hex("000fff").unwrap(), vec![0, 15, 255]);
+        assert!(from_hex("abc").is_err());
+    }
+}
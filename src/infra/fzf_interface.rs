@@ -1,7 +1,11 @@
 #[cfg(feature = "fzf")]
 use duct::cmd;
 
+use crate::domain::config_layers::ConfigOverrides;
 use crate::domain::TrashItem;
+use crate::infra::TrashStore;
+use crate::utils::shell::shell_quote;
+use crate::utils::size_parser::format_size;
 use anyhow::{anyhow, Result};
 
 #[cfg(feature = "colors")]
@@ -13,19 +17,28 @@ pub trait FzfInterface {
     fn select_multiple_trash_items(&self, items: &[TrashItem]) -> Result<Vec<TrashItem>>;
 }
 
+/// Separates the hidden item ID from the displayed line passed to fzf
+const FIELD_DELIMITER: char = '\t';
+
 /// FZF-based fuzzy finder implementation
+///
+/// Each line starts with the item's full ID as a hidden field, so the
+/// selection maps back to an item regardless of how the rest is formatted.
 pub struct FzfSelector {
     /// Whether fzf is available on the system
     available: bool,
+    /// Shell command fzf runs to preview the item ID in `{1}`
+    #[cfg_attr(not(feature = "fzf"), allow(dead_code))]
+    preview_command: Option<String>,
 }
 
 impl FzfSelector {
-    pub fn new() -> Self {
-        let available = Self::check_fzf_availability();
-        if !available {
-            eprintln!("Warning: fzf not found. Interactive mode will use basic prompts.");
+    /// Selector whose preview runs `rmz show` with the same global options as this process
+    pub fn new(overrides: &ConfigOverrides) -> Self {
+        Self {
+            available: Self::check_fzf_availability(),
+            preview_command: std::env::current_exe().ok().map(|exe| preview_command(&exe, overrides)),
         }
-        Self { available }
     }
 
     pub fn is_available(&self) -> bool {
@@ -42,64 +55,72 @@ impl FzfSelector {
         false
     }
 
-    /// Create a formatted display line for a trash item
-    fn format_item(&self, item: &TrashItem, index: usize) -> String {
+    /// Create an fzf input line: hidden ID field, then the display text
+    fn format_item(&self, item: &TrashItem) -> String {
         let relative_time = format_relative_time(item.meta.deleted_at);
         let size_str = format_size(item.meta.size);
         
         #[cfg(feature = "colors")]
         {
             format!(
-                "{}: {} {} {} {}",
-                format!("{:3}", index + 1).cyan(),
+                "{}{}{} {} {}",
+                item.meta.id,
+                FIELD_DELIMITER,
                 item.meta.original_path.display().to_string().white().bold(),
                 format!("({})", size_str).yellow(),
                 format!("deleted {}", relative_time).dimmed(),
-                item.meta.id.to_string()[..8].bright_black()
             )
         }
         #[cfg(not(feature = "colors"))]
         {
             format!(
-                "{:3}: {} ({}) deleted {} {}",
-                index + 1,
+                "{}{}{} ({}) deleted {}",
+                item.meta.id,
+                FIELD_DELIMITER,
                 item.meta.original_path.display(),
                 size_str,
                 relative_time,
-                &item.meta.id.to_string()[..8]
             )
         }
     }
 
-    /// Parse the selected line back to find the corresponding item
+    /// Find the item whose ID leads the selected line
     fn parse_selection(&self, selection: &str, items: &[TrashItem]) -> Result<TrashItem> {
-        // Extract the index from the beginning of the line
-        let parts: Vec<&str> = selection.split(':').collect();
-        if parts.is_empty() {
-            return Err(anyhow!("Invalid selection format"));
-        }
-        
-        let index_str = parts[0].trim();
-        let index: usize = index_str.parse()
-            .map_err(|_| anyhow!("Could not parse selection index: {}", index_str))?;
-        
-        if index == 0 || index > items.len() {
-            return Err(anyhow!("Selection index out of range: {}", index));
-        }
-        
-        Ok(items[index - 1].clone())
+        let id = selection
+            .split(FIELD_DELIMITER)
+            .next()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| anyhow!("Invalid selection format"))?;
+
+        items
+            .iter()
+            .find(|item| item.meta.id.to_string() == id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Selected item not found: {}", id))
     }
 
     #[cfg(feature = "fzf")]
     fn run_fzf(&self, input: &str, multi: bool) -> Result<String> {
-        let mut fzf_cmd = cmd!("fzf", "--ansi", "--height=40%", "--layout=reverse", "--border")
-            .stdin_bytes(input);
-        
+        let mut args = vec![
+            "--ansi".to_string(),
+            "--height=40%".to_string(),
+            "--layout=reverse".to_string(),
+            "--border".to_string(),
+            format!("--delimiter={}", FIELD_DELIMITER),
+            "--with-nth=2..".to_string(),
+        ];
+        if let Some(preview) = &self.preview_command {
+            args.push(format!("--preview={}", preview));
+            args.push("--preview-window=right,50%".to_string());
+        }
         if multi {
-            fzf_cmd = fzf_cmd.arg("--multi");
+            args.push("--multi".to_string());
         }
-        
-        let output = fzf_cmd.read()
+
+        let output = cmd("fzf", &args)
+            .stdin_bytes(input)
+            .read()
             .map_err(|e| anyhow!("fzf execution failed: {}", e))?;
         
         if output.trim().is_empty() {
@@ -128,8 +149,7 @@ impl FzfInterface for FzfSelector {
         // Format items for fzf display
         let formatted_items: Vec<String> = items
             .iter()
-            .enumerate()
-            .map(|(i, item)| self.format_item(item, i))
+            .map(|item| self.format_item(item))
             .collect();
         
         let input = formatted_items.join("\n");
@@ -161,8 +181,7 @@ impl FzfInterface for FzfSelector {
         // Format items for fzf display
         let formatted_items: Vec<String> = items
             .iter()
-            .enumerate()
-            .map(|(i, item)| self.format_item(item, i))
+            .map(|item| self.format_item(item))
             .collect();
         
         let input = formatted_items.join("\n");
@@ -189,9 +208,21 @@ impl FzfInterface for FzfSelector {
     }
 }
 
+impl Default for FzfSelector {
+    fn default() -> Self {
+        Self::new(&ConfigOverrides::default())
+    }
+}
+
 /// Fallback interactive selector using dialoguer
 pub struct DialoguerSelector;
 
+impl Default for DialoguerSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DialoguerSelector {
     pub fn new() -> Self {
         Self
@@ -271,33 +302,35 @@ impl FzfInterface for DialoguerSelector {
 }
 
 /// Create an appropriate selector based on system capabilities
-pub fn create_selector() -> Box<dyn FzfInterface> {
-    let fzf_selector = FzfSelector::new();
+///
+/// External fzf is used when built with the `fzf` feature and installed,
+/// then the built-in finder, then plain prompts.
+pub fn create_selector<'a>(store: &'a TrashStore, overrides: &ConfigOverrides) -> Box<dyn FzfInterface + 'a> {
+    let fzf_selector = FzfSelector::new(overrides);
     if fzf_selector.is_available() {
-        Box::new(fzf_selector)
-    } else {
-        Box::new(DialoguerSelector::new())
+        return Box::new(fzf_selector);
     }
-}
 
-/// Format bytes to human readable size
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-    
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
+    #[cfg(feature = "tui")]
+    {
+        Box::new(crate::tui::finder::FuzzySelector::new(store))
     }
-    
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
+    #[cfg(not(feature = "tui"))]
+    {
+        let _ = store;
+        eprintln!("Warning: fzf not found. Interactive mode will use basic prompts.");
+        Box::new(DialoguerSelector::new())
     }
 }
 
+/// Shell command previewing the item ID in fzf's `{1}` field, as fzf runs it through `sh -c`
+fn preview_command(exe: &std::path::Path, overrides: &ConfigOverrides) -> String {
+    let mut command = vec![shell_quote(&exe.to_string_lossy())];
+    command.extend(overrides.args().iter().map(|arg| shell_quote(arg)));
+    command.push("show {1}".to_string());
+    command.join(" ")
+}
+
 /// Format relative time (e.g., "2 hours ago", "3 days ago")
 fn format_relative_time(datetime: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
@@ -321,4 +354,45 @@ fn format_relative_time(datetime: chrono::DateTime<chrono::Utc>) -> String {
     } else {
         "unknown".to_string()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileMeta;
+    use std::path::PathBuf;
+
+    fn item(path: &str) -> TrashItem {
+        let mut meta = FileMeta::from_path(std::path::Path::new("Cargo.toml")).unwrap();
+        meta.id = uuid::Uuid::new_v4();
+        meta.original_path = PathBuf::from(path);
+        TrashItem::new(meta, PathBuf::from("/trash").join(path))
+    }
+
+    #[test]
+    fn test_selection_uses_hidden_id_field() {
+        let selector = FzfSelector::new(&ConfigOverrides::default());
+        let items = vec![item("/tmp/1: not an index.txt"), item("/tmp/b.txt")];
+
+        let line = selector.format_item(&items[0]);
+        assert!(line.starts_with(&format!("{}\t", items[0].meta.id)));
+
+        let selected = selector.parse_selection(&line, &items).unwrap();
+        assert_eq!(selected.meta.id, items[0].meta.id);
+        assert!(selector.parse_selection("garbage", &items).is_err());
+    }
+
+    #[test]
+    fn test_preview_command_keeps_global_options() {
+        let overrides = ConfigOverrides {
+            config_file: Some(PathBuf::from("/etc/rmz work.toml")),
+            profile: Some("work".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            preview_command(std::path::Path::new("/bin/rmz"), &overrides),
+            "/bin/rmz --config '/etc/rmz work.toml' --profile work show {1}"
+        );
+        assert_eq!(preview_command(std::path::Path::new("/bin/rmz"), &ConfigOverrides::default()), "/bin/rmz show {1}");
+    }
+}
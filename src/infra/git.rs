@@ -0,0 +1,986 @@
+//! Read-only access to git repositories, straight from the on-disk format
+//!
+//! Enough of git is understood to tell which work tree a path belongs to,
+//! what HEAD points at and whether the path matches what is committed: refs
+//! (loose and packed), the index (versions 2 to 4), `.gitignore` files,
+//! loose objects and pack files. Nothing is written and no `git` binary or
+//! network access is needed. Repositories using SHA-256 object names are
+//! treated as if there were no repository.
+
+use crate::domain::{GitFileStatus, GitInfo};
+use crate::utils::glob::glob_match;
+use anyhow::{anyhow, bail, Result};
+use flate2::read::ZlibDecoder;
+use sha1::{Digest, Sha1};
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const TREE_MODE: u32 = 0o40000;
+const GITLINK_MODE: u32 = 0o160000;
+
+const OBJ_COMMIT: u8 = 1;
+const OBJ_TREE: u8 = 2;
+const OBJ_OFS_DELTA: u8 = 6;
+const OBJ_REF_DELTA: u8 = 7;
+
+/// Longest delta chain followed before a pack is considered corrupt
+const MAX_DELTA_DEPTH: usize = 10_000;
+
+/// A SHA-1 object name
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId([u8; 20]);
+
+impl ObjectId {
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 40 {
+            return None;
+        }
+        let mut bytes = [0u8; 20];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self(bytes.try_into().ok()?))
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl std::fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl std::fmt::Debug for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ObjectId({})", self.to_hex())
+    }
+}
+
+/// What HEAD points at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Head {
+    /// Checked-out branch (None = detached HEAD)
+    pub branch: Option<String>,
+    /// Commit HEAD resolves to (None = no commits on the branch yet)
+    pub commit: Option<ObjectId>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    mtime: (u32, u32),
+    size: u32,
+    mode: u32,
+    id: ObjectId,
+    /// Non-zero while the path has unresolved merge conflicts
+    stage: u16,
+}
+
+/// Index entries keyed by `/`-separated path relative to the work tree
+type Index = BTreeMap<String, IndexEntry>;
+
+/// A work tree and the repository behind it
+pub struct GitRepo {
+    root: PathBuf,
+    git_dir: PathBuf,
+    /// Shared by all work trees of a repository; differs from `git_dir` in linked work trees
+    common_dir: PathBuf,
+    packs: OnceCell<Vec<PackIndex>>,
+}
+
+impl GitRepo {
+    /// The work tree containing `path`, if there is one
+    pub fn discover(path: &Path) -> Option<Self> {
+        let path = std::path::absolute(path).ok()?;
+        let start = if path.is_dir() { path.as_path() } else { path.parent()? };
+
+        for dir in start.ancestors() {
+            let dot_git = dir.join(".git");
+            let git_dir = if dot_git.is_dir() {
+                dot_git
+            } else if dot_git.is_file() {
+                // Linked work trees and submodules point at their git directory
+                let contents = fs::read_to_string(&dot_git).ok()?;
+                dir.join(contents.strip_prefix("gitdir:")?.trim())
+            } else {
+                continue;
+            };
+            if !git_dir.join("HEAD").is_file() {
+                continue;
+            }
+
+            let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+                Ok(common) => git_dir.join(common.trim()),
+                Err(_) => git_dir.clone(),
+            };
+            if uses_sha256(&common_dir) {
+                return None;
+            }
+            return Some(Self {
+                root: dir.to_path_buf(),
+                git_dir,
+                common_dir,
+                packs: OnceCell::new(),
+            });
+        }
+        None
+    }
+
+    /// Top-level directory of the work tree
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` relative to the work tree with `/` separators, empty for the root itself
+    pub fn relative(&self, path: &Path) -> Option<String> {
+        let path = std::path::absolute(path).ok()?;
+        let components = path
+            .strip_prefix(&self.root)
+            .ok()?
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+        Some(components.join("/"))
+    }
+
+    /// Repository, branch, HEAD and status of `path` as recorded in trash metadata
+    pub fn info(&self, path: &Path) -> Result<GitInfo> {
+        let head = self.head()?;
+        Ok(GitInfo {
+            repo_root: self.root.clone(),
+            branch: head.branch,
+            head: head.commit.map(|id| id.to_hex()),
+            status: self.status(path)?,
+        })
+    }
+
+    /// Resolve HEAD through symbolic refs to a branch and commit
+    pub fn head(&self) -> Result<Head> {
+        let mut target = fs::read_to_string(self.git_dir.join("HEAD"))?.trim().to_string();
+        let mut branch = None;
+
+        for _ in 0..5 {
+            let Some(name) = target.strip_prefix("ref:").map(str::trim) else {
+                let commit = ObjectId::from_hex(&target).ok_or_else(|| anyhow!("Invalid ref target '{}'", target))?;
+                return Ok(Head { branch, commit: Some(commit) });
+            };
+            if branch.is_none() {
+                branch = Some(name.strip_prefix("refs/heads/").unwrap_or(name).to_string());
+            }
+            match self.read_ref(name)? {
+                Some(next) => target = next,
+                None => return Ok(Head { branch, commit: None }),
+            }
+        }
+        bail!("Too many levels of symbolic refs from HEAD")
+    }
+
+    /// Contents of a loose ref, or the object ID packed-refs records for it
+    fn read_ref(&self, name: &str) -> Result<Option<String>> {
+        for dir in [&self.git_dir, &self.common_dir] {
+            if let Ok(contents) = fs::read_to_string(dir.join(name)) {
+                return Ok(Some(contents.trim().to_string()));
+            }
+        }
+        let packed = match fs::read_to_string(self.common_dir.join("packed-refs")) {
+            Ok(packed) => packed,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(packed
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .find_map(|line| {
+                let (id, ref_name) = line.split_once(' ')?;
+                (ref_name.trim() == name).then(|| id.to_string())
+            }))
+    }
+
+    /// Whether `path`, a file or directory in the work tree, matches HEAD
+    ///
+    /// A directory is [`GitFileStatus::Tracked`] only if every file in it is
+    /// committed and unchanged and nothing in it is untracked and not ignored.
+    pub fn status(&self, path: &Path) -> Result<GitFileStatus> {
+        let rel = self
+            .relative(path)
+            .ok_or_else(|| anyhow!("{} is outside {}", path.display(), self.root.display()))?;
+        if rel == ".git" || rel.starts_with(".git/") {
+            return Ok(GitFileStatus::Untracked);
+        }
+        let index = self.read_index()?;
+
+        if !fs::symlink_metadata(path)?.is_dir() {
+            return Ok(match index.get(&rel) {
+                None => GitFileStatus::Untracked,
+                Some(entry) if entry_clean(path, entry) && self.head_entry(&rel)? == Some((entry.mode, entry.id)) => {
+                    GitFileStatus::Tracked
+                }
+                Some(_) => GitFileStatus::Modified,
+            });
+        }
+
+        let prefix = if rel.is_empty() { String::new() } else { format!("{}/", rel) };
+        let tracked: Vec<(&String, &IndexEntry)> = index
+            .range(prefix.clone()..)
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .filter(|(_, entry)| entry.mode != GITLINK_MODE)
+            .collect();
+        let mut committed = HashMap::new();
+        if let Some((TREE_MODE, tree)) = self.head_entry(&rel)? {
+            self.collect_blobs(&tree, &prefix, &mut committed)?;
+        }
+        if tracked.is_empty() && committed.is_empty() {
+            return Ok(GitFileStatus::Untracked);
+        }
+
+        let clean = tracked.len() == committed.len()
+            && tracked.iter().all(|(name, entry)| {
+                committed.get(*name) == Some(&(entry.mode, entry.id)) && entry_clean(&self.root.join(name), entry)
+            })
+            && !self.has_untracked(path, &rel, &index)?;
+        Ok(if clean { GitFileStatus::Tracked } else { GitFileStatus::Modified })
+    }
+
+    fn read_index(&self) -> Result<Index> {
+        match fs::read(self.git_dir.join("index")) {
+            Ok(data) => parse_index(&data),
+            // A repository without any `git add` has no index yet
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Index::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Mode and object ID of the entry at `rel` in HEAD's tree
+    fn head_entry(&self, rel: &str) -> Result<Option<(u32, ObjectId)>> {
+        let Some(commit) = self.head()?.commit else {
+            return Ok(None);
+        };
+        let (kind, data) = self.read_object(&commit)?;
+        if kind != OBJ_COMMIT {
+            bail!("HEAD {} is not a commit", commit);
+        }
+        let tree = std::str::from_utf8(&data)
+            .ok()
+            .and_then(|text| text.strip_prefix("tree "))
+            .and_then(|text| ObjectId::from_hex(text.get(..40)?))
+            .ok_or_else(|| anyhow!("Commit {} has no tree", commit))?;
+
+        let mut entry = (TREE_MODE, tree);
+        for name in rel.split('/').filter(|name| !name.is_empty()) {
+            if entry.0 != TREE_MODE {
+                return Ok(None);
+            }
+            match self.read_tree(&entry.1)?.into_iter().find(|(entry_name, _, _)| entry_name == name) {
+                Some((_, mode, id)) => entry = (mode, id),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(entry))
+    }
+
+    /// Files and symlinks beneath a tree, keyed by path relative to the work tree
+    fn collect_blobs(&self, tree: &ObjectId, prefix: &str, blobs: &mut HashMap<String, (u32, ObjectId)>) -> Result<()> {
+        for (name, mode, id) in self.read_tree(tree)? {
+            let path = format!("{}{}", prefix, name);
+            match mode {
+                TREE_MODE => self.collect_blobs(&id, &format!("{}/", path), blobs)?,
+                GITLINK_MODE => {}
+                _ => {
+                    blobs.insert(path, (mode, id));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_tree(&self, id: &ObjectId) -> Result<Vec<(String, u32, ObjectId)>> {
+        let (kind, data) = self.read_object(id)?;
+        if kind != OBJ_TREE {
+            bail!("{} is not a tree", id);
+        }
+        parse_tree(&data)
+    }
+
+    /// Type and contents of an object, loose or packed
+    fn read_object(&self, id: &ObjectId) -> Result<(u8, Vec<u8>)> {
+        self.read_object_at_depth(id, 0)
+    }
+
+    fn read_object_at_depth(&self, id: &ObjectId, depth: usize) -> Result<(u8, Vec<u8>)> {
+        let hex = id.to_hex();
+        let loose = self.common_dir.join("objects").join(&hex[..2]).join(&hex[2..]);
+        match File::open(&loose) {
+            Ok(file) => return read_loose(file).map_err(|e| anyhow!("Cannot read object {}: {}", id, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        for pack in self.packs() {
+            if let Some(offset) = pack.find(id) {
+                return self.read_packed(&pack.pack_path, offset, depth);
+            }
+        }
+        bail!("Object {} not found", id)
+    }
+
+    fn packs(&self) -> &[PackIndex] {
+        self.packs.get_or_init(|| {
+            let Ok(entries) = fs::read_dir(self.common_dir.join("objects").join("pack")) else {
+                return Vec::new();
+            };
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "idx"))
+                .filter_map(|path| PackIndex::open(&path))
+                .collect()
+        })
+    }
+
+    /// Read the pack entry at `offset`, resolving deltas against their bases
+    fn read_packed(&self, pack: &Path, offset: u64, depth: usize) -> Result<(u8, Vec<u8>)> {
+        if depth > MAX_DELTA_DEPTH {
+            bail!("Delta chain in {} is too long", pack.display());
+        }
+        let mut file = File::open(pack)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut reader = BufReader::new(file);
+
+        let mut byte = read_byte(&mut reader)?;
+        let kind = (byte >> 4) & 0x7;
+        let mut size = u64::from(byte & 0x0f);
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            byte = read_byte(&mut reader)?;
+            size |= u64::from(byte & 0x7f).checked_shl(shift).ok_or_else(|| anyhow!("Corrupt pack entry"))?;
+            shift += 7;
+        }
+
+        match kind {
+            OBJ_OFS_DELTA => {
+                let distance = read_offset(&mut reader)?;
+                let base_offset = offset.checked_sub(distance).ok_or_else(|| anyhow!("Corrupt delta offset"))?;
+                let delta = inflate(&mut reader, size)?;
+                let (base_kind, base) = self.read_packed(pack, base_offset, depth + 1)?;
+                Ok((base_kind, apply_delta(&base, &delta)?))
+            }
+            OBJ_REF_DELTA => {
+                let mut base_id = [0u8; 20];
+                reader.read_exact(&mut base_id)?;
+                let delta = inflate(&mut reader, size)?;
+                let (base_kind, base) = self.read_object_at_depth(&ObjectId(base_id), depth + 1)?;
+                Ok((base_kind, apply_delta(&base, &delta)?))
+            }
+            1..=4 => Ok((kind, inflate(&mut reader, size)?)),
+            _ => bail!("Unknown object type {} in {}", kind, pack.display()),
+        }
+    }
+
+    /// Whether the directory `dir` holds files that are neither tracked nor ignored
+    pub fn has_untracked_files(&self, dir: &Path) -> Result<bool> {
+        let rel = self
+            .relative(dir)
+            .ok_or_else(|| anyhow!("{} is outside {}", dir.display(), self.root.display()))?;
+        self.has_untracked(dir, &rel, &self.read_index()?)
+    }
+
+    /// Whether a directory holds files that are neither tracked nor ignored
+    fn has_untracked(&self, dir: &Path, rel: &str, index: &Index) -> Result<bool> {
+        let mut ignore = Ignore::default();
+        ignore.load(&self.common_dir.join("info").join("exclude"), "");
+
+        // Rules from the directories above `dir`; `walk` loads the rest
+        let mut base = String::new();
+        for name in rel.split('/').filter(|name| !name.is_empty()) {
+            ignore.load(&self.root.join(&base).join(".gitignore"), &base);
+            base = join(&base, name);
+            if ignore.is_ignored(&base, true) {
+                return Ok(false);
+            }
+        }
+        self.walk_untracked(dir, rel, &mut ignore, index)
+    }
+
+    fn walk_untracked(&self, dir: &Path, rel: &str, ignore: &mut Ignore, index: &Index) -> Result<bool> {
+        let depth = ignore.patterns.len();
+        ignore.load(&dir.join(".gitignore"), rel);
+
+        let mut found = false;
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == ".git" {
+                continue;
+            }
+            let child = join(rel, &name);
+            let is_dir = entry.file_type()?.is_dir();
+            if ignore.is_ignored(&child, is_dir) {
+                continue;
+            }
+            found = if !is_dir {
+                !index.contains_key(&child)
+            } else if index.get(&child).is_some_and(|entry| entry.mode == GITLINK_MODE) {
+                // Submodules have their own status
+                false
+            } else if entry.path().join(".git").exists() {
+                // A nested repository git does not know about
+                true
+            } else {
+                self.walk_untracked(&entry.path(), &child, ignore, index)?
+            };
+            if found {
+                break;
+            }
+        }
+
+        ignore.patterns.truncate(depth);
+        Ok(found)
+    }
+}
+
+fn join(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", base, name)
+    }
+}
+
+fn uses_sha256(common_dir: &Path) -> bool {
+    fs::read_to_string(common_dir.join("config")).is_ok_and(|config| {
+        config.lines().any(|line| {
+            let line = line.trim().to_lowercase();
+            line.starts_with("objectformat") && line.contains("sha256")
+        })
+    })
+}
+
+/// Object ID git would store for the file or symlink at `path`
+pub fn hash_file(path: &Path) -> Result<ObjectId> {
+    let metadata = fs::symlink_metadata(path)?;
+    let mut hasher = Sha1::new();
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path)?;
+        let target = target.to_string_lossy();
+        hasher.update(format!("blob {}\0", target.len()));
+        hasher.update(target.as_bytes());
+    } else {
+        hasher.update(format!("blob {}\0", metadata.len()));
+        let mut file = File::open(path)?;
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+    }
+    Ok(ObjectId(hasher.finalize().into()))
+}
+
+/// Whether the work tree file still has the contents the index records
+fn entry_clean(path: &Path, entry: &IndexEntry) -> bool {
+    if entry.stage != 0 {
+        return false;
+    }
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    // The index truncates sizes to 32 bits
+    if metadata.len() as u32 != entry.size {
+        return false;
+    }
+    mtime_matches(&metadata, entry.mtime) || hash_file(path).is_ok_and(|id| id == entry.id)
+}
+
+#[cfg(unix)]
+fn mtime_matches(metadata: &fs::Metadata, (seconds, nanoseconds): (u32, u32)) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.mtime() as u32 == seconds && metadata.mtime_nsec() as u32 == nanoseconds
+}
+
+#[cfg(not(unix))]
+fn mtime_matches(_metadata: &fs::Metadata, _mtime: (u32, u32)) -> bool {
+    false
+}
+
+fn be16(data: &[u8], at: usize) -> Result<u16> {
+    let bytes = data.get(at..at + 2).ok_or_else(|| anyhow!("Unexpected end of data"))?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn be32(data: &[u8], at: usize) -> Result<u32> {
+    let bytes = data.get(at..at + 4).ok_or_else(|| anyhow!("Unexpected end of data"))?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Parse the entries of an index file, ignoring its extensions
+fn parse_index(data: &[u8]) -> Result<Index> {
+    if data.get(..4) != Some(b"DIRC") {
+        bail!("Not a git index");
+    }
+    let version = be32(data, 4)?;
+    if !(2..=4).contains(&version) {
+        bail!("Unsupported index version {}", version);
+    }
+    let count = be32(data, 8)?;
+
+    let mut index = Index::new();
+    let mut position = 12;
+    let mut previous: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let start = position;
+        let flags = be16(data, start + 60)?;
+        let entry = IndexEntry {
+            mtime: (be32(data, start + 16)?, be32(data, start + 20)?),
+            mode: be32(data, start + 24)?,
+            size: be32(data, start + 36)?,
+            id: ObjectId::from_bytes(&data[start + 40..start + 60]).ok_or_else(|| anyhow!("Truncated index"))?,
+            stage: (flags >> 12) & 0x3,
+        };
+        position = start + 62;
+        if version >= 3 && flags & 0x4000 != 0 {
+            position += 2;
+        }
+
+        let rest = data.get(position..).ok_or_else(|| anyhow!("Truncated index"))?;
+        let name = if version == 4 {
+            // Names are stored as a suffix of the previous entry's name
+            let mut cursor = rest;
+            let strip = read_offset(&mut cursor)? as usize;
+            position += rest.len() - cursor.len();
+            let end = cursor.iter().position(|&byte| byte == 0).ok_or_else(|| anyhow!("Truncated index"))?;
+            let keep = previous.len().checked_sub(strip).ok_or_else(|| anyhow!("Corrupt index path"))?;
+            let mut name = previous[..keep].to_vec();
+            name.extend_from_slice(&cursor[..end]);
+            position += end + 1;
+            name
+        } else {
+            let end = rest.iter().position(|&byte| byte == 0).ok_or_else(|| anyhow!("Truncated index"))?;
+            // Entries are padded with one to eight NULs to a multiple of eight bytes
+            position = start + (position - start + end + 8) / 8 * 8;
+            rest[..end].to_vec()
+        };
+
+        let path = String::from_utf8_lossy(&name).into_owned();
+        previous = name;
+        // Conflicted paths have one entry per stage; remember that there was a conflict
+        match index.get_mut(&path) {
+            Some(existing) => existing.stage = existing.stage.max(entry.stage),
+            None => {
+                index.insert(path, entry);
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Entries of a tree object as (name, mode, ID)
+fn parse_tree(mut data: &[u8]) -> Result<Vec<(String, u32, ObjectId)>> {
+    let mut entries = Vec::new();
+    while !data.is_empty() {
+        let space = data.iter().position(|&byte| byte == b' ').ok_or_else(|| anyhow!("Corrupt tree"))?;
+        let nul = data.iter().position(|&byte| byte == 0).ok_or_else(|| anyhow!("Corrupt tree"))?;
+        let mode = std::str::from_utf8(&data[..space])
+            .ok()
+            .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+            .ok_or_else(|| anyhow!("Corrupt tree entry mode"))?;
+        let name = String::from_utf8_lossy(&data[space + 1..nul]).into_owned();
+        let id = data
+            .get(nul + 1..nul + 21)
+            .and_then(ObjectId::from_bytes)
+            .ok_or_else(|| anyhow!("Corrupt tree"))?;
+        entries.push((name, mode, id));
+        data = &data[nul + 21..];
+    }
+    Ok(entries)
+}
+
+/// Inflate a loose object and split off its `<type> <size>\0` header
+fn read_loose(file: File) -> Result<(u8, Vec<u8>)> {
+    let mut data = Vec::new();
+    ZlibDecoder::new(BufReader::new(file)).read_to_end(&mut data)?;
+    let nul = data.iter().position(|&byte| byte == 0).ok_or_else(|| anyhow!("Missing object header"))?;
+    let kind = match data.split(|&byte| byte == b' ').next() {
+        Some(b"commit") => OBJ_COMMIT,
+        Some(b"tree") => OBJ_TREE,
+        Some(b"blob") => 3,
+        Some(b"tag") => 4,
+        _ => bail!("Unknown object type"),
+    };
+    Ok((kind, data.split_off(nul + 1)))
+}
+
+fn read_byte(reader: &mut impl Read) -> Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Big-endian base-128 number where each continuation adds one, as used for
+/// delta offsets and index v4 path prefixes
+fn read_offset(reader: &mut impl Read) -> Result<u64> {
+    let mut byte = read_byte(reader)?;
+    let mut value = u64::from(byte & 0x7f);
+    while byte & 0x80 != 0 {
+        byte = read_byte(reader)?;
+        value = value
+            .checked_add(1)
+            .and_then(|value| value.checked_mul(128))
+            .ok_or_else(|| anyhow!("Offset overflow"))?
+            | u64::from(byte & 0x7f);
+    }
+    Ok(value)
+}
+
+/// Little-endian base-128 size from a delta header
+fn read_size(data: &[u8], position: &mut usize) -> Result<usize> {
+    let mut value = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*position).ok_or_else(|| anyhow!("Truncated delta"))?;
+        *position += 1;
+        value |= usize::from(byte & 0x7f).checked_shl(shift).ok_or_else(|| anyhow!("Corrupt delta"))?;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+fn inflate(reader: &mut impl Read, size: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(usize::try_from(size).unwrap_or(0).min(1 << 24));
+    ZlibDecoder::new(reader).take(size).read_to_end(&mut data)?;
+    if data.len() as u64 != size {
+        bail!("Truncated pack entry");
+    }
+    Ok(data)
+}
+
+/// Rebuild an object from its delta base and a sequence of copy and insert instructions
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let mut position = 0;
+    if read_size(delta, &mut position)? != base.len() {
+        bail!("Delta does not apply to its base");
+    }
+    let target_size = read_size(delta, &mut position)?;
+    let mut target = Vec::with_capacity(target_size);
+
+    while position < delta.len() {
+        let instruction = delta[position];
+        position += 1;
+        if instruction & 0x80 != 0 {
+            let mut field = |bits: u8, count: usize| -> Result<usize> {
+                let mut value = 0usize;
+                for i in 0..count {
+                    if bits & (1 << i) != 0 {
+                        value |= usize::from(*delta.get(position).ok_or_else(|| anyhow!("Truncated delta"))?) << (8 * i);
+                        position += 1;
+                    }
+                }
+                Ok(value)
+            };
+            let offset = field(instruction & 0x0f, 4)?;
+            let size = match field((instruction >> 4) & 0x07, 3)? {
+                0 => 0x10000,
+                size => size,
+            };
+            target.extend_from_slice(base.get(offset..offset + size).ok_or_else(|| anyhow!("Delta copies past its base"))?);
+        } else if instruction != 0 {
+            let size = usize::from(instruction);
+            target.extend_from_slice(delta.get(position..position + size).ok_or_else(|| anyhow!("Truncated delta"))?);
+            position += size;
+        } else {
+            bail!("Invalid delta instruction");
+        }
+    }
+
+    if target.len() != target_size {
+        bail!("Delta produced {} bytes instead of {}", target.len(), target_size);
+    }
+    Ok(target)
+}
+
+/// A version 2 pack index, which maps object IDs to offsets in its pack
+struct PackIndex {
+    pack_path: PathBuf,
+    data: Vec<u8>,
+    count: usize,
+}
+
+impl PackIndex {
+    const HEADER: usize = 8;
+    const FANOUT: usize = 256 * 4;
+
+    fn open(path: &Path) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        if data.get(..4) != Some(b"\xfftOc") || be32(&data, 4).ok()? != 2 {
+            return None;
+        }
+        let count = be32(&data, Self::HEADER + Self::FANOUT - 4).ok()? as usize;
+        if data.len() < Self::HEADER + Self::FANOUT + count * 28 {
+            return None;
+        }
+        Some(Self {
+            pack_path: path.with_extension("pack"),
+            data,
+            count,
+        })
+    }
+
+    fn find(&self, id: &ObjectId) -> Option<u64> {
+        let fanout = |i: usize| be32(&self.data, Self::HEADER + i * 4).map(|count| count as usize);
+        let first = usize::from(id.0[0]);
+        let mut low = if first == 0 { 0 } else { fanout(first - 1).ok()? };
+        let mut high = fanout(first).ok()?.min(self.count);
+
+        let names = Self::HEADER + Self::FANOUT;
+        while low < high {
+            let middle = (low + high) / 2;
+            match self.data[names + middle * 20..names + middle * 20 + 20].cmp(&id.0) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return self.offset(middle),
+            }
+        }
+        None
+    }
+
+    fn offset(&self, position: usize) -> Option<u64> {
+        // Names, then CRCs, then 32-bit offsets whose top bit points into a table of 64-bit ones
+        let offsets = Self::HEADER + Self::FANOUT + self.count * 24;
+        let offset = be32(&self.data, offsets + position * 4).ok()?;
+        if offset & 0x8000_0000 == 0 {
+            return Some(u64::from(offset));
+        }
+        let large = offsets + self.count * 4 + (offset & 0x7fff_ffff) as usize * 8;
+        let bytes = self.data.get(large..large + 8)?;
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// A rule from a `.gitignore` or `info/exclude` file
+struct IgnorePattern {
+    pattern: String,
+    /// Directory the rule's file lives in, relative to the work tree
+    base: String,
+    negated: bool,
+    directory_only: bool,
+    /// Matched against the whole path below `base` rather than any file name
+    anchored: bool,
+}
+
+/// Ignore rules in effect for a directory, outermost first
+#[derive(Default)]
+struct Ignore {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl Ignore {
+    fn load(&mut self, file: &Path, base: &str) {
+        let Ok(contents) = fs::read_to_string(file) else {
+            return;
+        };
+        for line in contents.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (directory_only, line) = match line.strip_suffix('/') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            self.patterns.push(IgnorePattern {
+                pattern: line.strip_prefix('/').unwrap_or(line).to_string(),
+                base: base.to_string(),
+                negated,
+                directory_only,
+                anchored: line.contains('/'),
+            });
+        }
+    }
+
+    /// Whether the last rule matching `rel` ignores it
+    fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.patterns {
+            if rule.directory_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.base.is_empty() {
+                rel
+            } else {
+                match rel.strip_prefix(rule.base.as_str()).and_then(|rest| rest.strip_prefix('/')) {
+                    Some(subject) => subject,
+                    None => continue,
+                }
+            };
+            // An anchored rule without an inner `/` still only matches directly below its base
+            let matched = if rule.anchored && !rule.pattern.contains('/') {
+                !subject.contains('/') && glob_match(&rule.pattern, subject)
+            } else {
+                glob_match(&rule.pattern, subject)
+            };
+            if matched {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    /// Run git with a predictable configuration; these tests need the `git` binary
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=rmz", "-c", "user.email=rmz@example.com", "-c", "init.defaultBranch=main"])
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .output()
+            .expect("git must be installed to run the git tests");
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// A repository with one commit of `src/main.rs`, `README.md` and an ignored `target/`
+    fn repository(temp_dir: &TempDir) -> PathBuf {
+        let root = temp_dir.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("README.md"), "# Project\n").unwrap();
+        fs::write(root.join(".gitignore"), "/target/\n*.log\n").unwrap();
+        git(&root, &["init", "-q"]);
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "Initial commit"]);
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("target/debug/app"), "binary").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_discover_and_head() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = repository(&temp_dir);
+
+        let repo = GitRepo::discover(&root.join("src/main.rs")).unwrap();
+        assert_eq!(repo.root(), root);
+        assert_eq!(repo.relative(&root.join("src/main.rs")).unwrap(), "src/main.rs");
+        assert_eq!(repo.relative(&root).unwrap(), "");
+
+        let head = repo.head().unwrap();
+        assert_eq!(head.branch.as_deref(), Some("main"));
+        assert_eq!(head.commit.unwrap().to_hex(), git(&root, &["rev-parse", "HEAD"]));
+
+        git(&root, &["pack-refs", "--all"]);
+        assert_eq!(repo.head().unwrap(), head);
+        git(&root, &["checkout", "-q", "--detach"]);
+        assert_eq!(repo.head().unwrap().branch, None);
+
+        assert!(GitRepo::discover(temp_dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_file_and_directory_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = repository(&temp_dir);
+        let repo = GitRepo::discover(&root).unwrap();
+
+        assert_eq!(repo.status(&root.join("src/main.rs")).unwrap(), GitFileStatus::Tracked);
+        assert_eq!(repo.status(&root.join("src")).unwrap(), GitFileStatus::Tracked);
+        // Ignored build output does not make the tree dirty
+        assert_eq!(repo.status(&root).unwrap(), GitFileStatus::Tracked);
+        assert_eq!(repo.status(&root.join("target")).unwrap(), GitFileStatus::Untracked);
+        assert_eq!(repo.status(&root.join(".git")).unwrap(), GitFileStatus::Untracked);
+
+        fs::write(root.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        assert_eq!(repo.status(&root.join("src/lib.rs")).unwrap(), GitFileStatus::Untracked);
+        assert_eq!(repo.status(&root.join("src")).unwrap(), GitFileStatus::Modified);
+        fs::write(root.join("src/debug.log"), "ignored").unwrap();
+        fs::remove_file(root.join("src/lib.rs")).unwrap();
+        assert_eq!(repo.status(&root.join("src")).unwrap(), GitFileStatus::Tracked);
+
+        // Same size, different contents
+        fs::write(root.join("README.md"), "# Changed\n").unwrap();
+        assert_eq!(repo.status(&root.join("README.md")).unwrap(), GitFileStatus::Modified);
+        assert_eq!(repo.status(&root).unwrap(), GitFileStatus::Modified);
+
+        // Staged but not committed still differs from HEAD
+        git(&root, &["add", "README.md"]);
+        assert_eq!(repo.status(&root.join("README.md")).unwrap(), GitFileStatus::Modified);
+        git(&root, &["commit", "-q", "-m", "Update README"]);
+        assert_eq!(repo.status(&root.join("README.md")).unwrap(), GitFileStatus::Tracked);
+    }
+
+    #[test]
+    fn test_reads_packed_objects_and_index_v4() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = repository(&temp_dir);
+        // Similar versions of a file make git store deltas
+        let mut text: String = (0..200).map(|line| format!("line {}\n", line)).collect();
+        for round in 0..3 {
+            text.push_str(&format!("round {}\n", round));
+            fs::write(root.join("src/main.rs"), &text).unwrap();
+            git(&root, &["commit", "-q", "-am", "Grow"]);
+        }
+        git(&root, &["gc", "-q", "--aggressive"]);
+        git(&root, &["update-index", "--index-version", "4"]);
+        assert!(!root.join(".git/objects").read_dir().unwrap().any(|entry| entry.unwrap().file_name().len() == 2));
+
+        let repo = GitRepo::discover(&root).unwrap();
+        let (mode, id) = repo.head_entry("src/main.rs").unwrap().unwrap();
+        assert_eq!(mode, 0o100644);
+        assert_eq!(id, hash_file(&root.join("src/main.rs")).unwrap());
+        assert_eq!(repo.status(&root).unwrap(), GitFileStatus::Tracked);
+        assert_eq!(repo.status(&root.join("src/main.rs")).unwrap(), GitFileStatus::Tracked);
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("root"), "*.log\n!keep.log\n/build\ndocs/*.pdf\ncache/\n").unwrap();
+        fs::write(temp_dir.path().join("nested"), "*.tmp\n").unwrap();
+        let mut ignore = Ignore::default();
+        ignore.load(&temp_dir.path().join("root"), "");
+        ignore.load(&temp_dir.path().join("nested"), "src");
+
+        assert!(ignore.is_ignored("a/b/debug.log", false));
+        assert!(!ignore.is_ignored("keep.log", false));
+        assert!(ignore.is_ignored("build", true));
+        assert!(!ignore.is_ignored("src/build", true));
+        assert!(ignore.is_ignored("docs/manual.pdf", false));
+        assert!(!ignore.is_ignored("other/docs/manual.pdf", false));
+        assert!(ignore.is_ignored("src/cache", true));
+        assert!(!ignore.is_ignored("src/cache", false));
+        assert!(ignore.is_ignored("src/x/y.tmp", false));
+        assert!(!ignore.is_ignored("y.tmp", false));
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let base = b"hello world";
+        // Sizes 11 -> 12, copy "hello " (offset 0, size 6), insert "there!"
+        let delta = [11, 12, 0x90, 6, 6, b't', b'h', b'e', b'r', b'e', b'!'];
+        assert_eq!(apply_delta(base, &delta).unwrap(), b"hello there!");
+        assert!(apply_delta(b"short", &delta).is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! User-defined commands run around deletions, purges and restores
+//!
+//! A hook is either the shell command configured as `hooks.<name>` or an
+//! executable in `<hooks.dir>/<name>/`; the configured command runs first,
+//! then the directory entries in name order. Each hook receives a JSON
+//! document describing the affected items on stdin:
+//!
+//! ```json
+//! {"hook": "pre_delete", "items": [{"id": null, "original_path": "/home/me/big.iso", "size": 4700000000, "tags": []}]}
+//! ```
+//!
+//! A `pre_*` hook that exits non-zero, cannot be started or runs past
+//! `hooks.timeout_secs` cancels the operation, with its last line of stderr
+//! as the reason. Failures of `post_*` hooks are only reported.
+//!
+//! Hooks are only configured in the system and user files and `hooks.dir`;
+//! project files and `RMZ_*` variables cannot set them.
+
+use crate::domain::{HooksConfig, TrashItem};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How often a running hook is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long a failed hook's stderr may take to reach end of file
+const STDERR_GRACE: Duration = Duration::from_millis(200);
+
+/// Point in an operation at which hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    PreDelete,
+    PostDelete,
+    PrePurge,
+    PostRestore,
+}
+
+impl HookPoint {
+    /// Name used for the config key, the `hooks.d` subdirectory and `RMZ_HOOK`
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::PreDelete => "pre_delete",
+            HookPoint::PostDelete => "post_delete",
+            HookPoint::PrePurge => "pre_purge",
+            HookPoint::PostRestore => "post_restore",
+        }
+    }
+
+    /// Whether a failing hook cancels the operation
+    pub fn can_veto(self) -> bool {
+        matches!(self, HookPoint::PreDelete | HookPoint::PrePurge)
+    }
+
+    fn command(self, config: &HooksConfig) -> Option<&str> {
+        match self {
+            HookPoint::PreDelete => config.pre_delete.as_deref(),
+            HookPoint::PostDelete => config.post_delete.as_deref(),
+            HookPoint::PrePurge => config.pre_purge.as_deref(),
+            HookPoint::PostRestore => config.post_restore.as_deref(),
+        }
+    }
+}
+
+/// One affected item as described to a hook
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HookItem {
+    /// Trash ID, or `None` before the item has been moved to trash
+    pub id: Option<Uuid>,
+    pub original_path: PathBuf,
+    pub size: u64,
+    pub tags: Vec<String>,
+    /// Where the item was restored to, for `post_restore`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_to: Option<PathBuf>,
+}
+
+impl HookItem {
+    pub fn from_item(item: &TrashItem) -> Self {
+        Self {
+            id: Some(item.meta.id),
+            original_path: item.meta.original_path.clone(),
+            size: item.meta.size,
+            tags: item.meta.tags.clone(),
+            restored_to: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HookInput<'a> {
+    hook: &'static str,
+    items: &'a [HookItem],
+}
+
+/// A hook that exited non-zero, could not be started or timed out
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookFailure {
+    /// The configured command or the path of the executable
+    pub hook: String,
+    pub reason: String,
+}
+
+/// Runs the hooks configured for a trash
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    config: HooksConfig,
+    enabled: bool,
+}
+
+impl Hooks {
+    pub fn new(config: HooksConfig) -> Self {
+        Self { config, enabled: true }
+    }
+
+    /// Hooks that never run, e.g. for `--no-hooks` or embedded use
+    pub fn disabled() -> Self {
+        Self {
+            config: HooksConfig::default(),
+            enabled: false,
+        }
+    }
+
+    /// Commands and executables to run for `point`, in order
+    pub fn commands(&self, point: HookPoint) -> Vec<HookCommand> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut commands: Vec<HookCommand> = point
+            .command(&self.config)
+            .map(|command| HookCommand::Shell(command.to_string()))
+            .into_iter()
+            .collect();
+        if let Some(dir) = &self.config.dir {
+            commands.extend(executables(&dir.join(point.name())).into_iter().map(HookCommand::Executable));
+        }
+        commands
+    }
+
+    /// Run the hooks for `point` with `items` on stdin
+    ///
+    /// Nothing runs when there are no items. Hooks that can veto stop at the
+    /// first failure, so the result holds at most one entry for them.
+    pub fn run(&self, point: HookPoint, items: &[HookItem]) -> Vec<HookFailure> {
+        let commands = self.commands(point);
+        if items.is_empty() || commands.is_empty() {
+            return Vec::new();
+        }
+        // Newline-terminated so a shell hook can `read` it
+        let input = match serde_json::to_vec(&HookInput { hook: point.name(), items }) {
+            Ok(mut input) => {
+                input.push(b'\n');
+                input
+            }
+            Err(e) => {
+                return vec![HookFailure {
+                    hook: point.name().to_string(),
+                    reason: format!("could not describe items: {}", e),
+                }]
+            }
+        };
+        let total_size: u64 = items.iter().map(|item| item.size).sum();
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+
+        let mut failures = Vec::new();
+        for command in commands {
+            let mut process = command.process();
+            process
+                .env("RMZ_HOOK", point.name())
+                .env("RMZ_ITEM_COUNT", items.len().to_string())
+                .env("RMZ_TOTAL_SIZE", total_size.to_string());
+            if let Err(reason) = run_process(process, &input, timeout) {
+                failures.push(HookFailure {
+                    hook: command.to_string(),
+                    reason,
+                });
+                if point.can_veto() {
+                    break;
+                }
+            }
+        }
+        failures
+    }
+}
+
+/// A single hook to run
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookCommand {
+    /// Command line from the config file, run with `sh -c`
+    Shell(String),
+    /// Executable from the hooks directory
+    Executable(PathBuf),
+}
+
+impl HookCommand {
+    fn process(&self) -> Command {
+        match self {
+            HookCommand::Shell(command) => {
+                let mut process = Command::new("sh");
+                process.arg("-c").arg(command);
+                process
+            }
+            HookCommand::Executable(path) => Command::new(path),
+        }
+    }
+}
+
+impl std::fmt::Display for HookCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookCommand::Shell(command) => write!(f, "{}", command),
+            HookCommand::Executable(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Executables in `dir` sorted by name, skipping hidden files and editor backups
+fn executables(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            !name.starts_with('.') && !name.ends_with('~') && is_executable(path)
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Run a hook to completion, returning why it failed
+fn run_process(mut process: Command, input: &[u8], timeout: Duration) -> Result<(), String> {
+    let mut child = process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start: {}", e))?;
+
+    // Feed stdin and drain stderr on their own threads so a chatty or
+    // non-reading hook cannot block on a full pipe
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            // Hooks are free to ignore their input
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            let _ = stderr_tx.send(output);
+        });
+    }
+
+    let status = match wait_until(&mut child, Instant::now() + timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            // The stderr thread is left behind: a background process started
+            // by the hook may still hold the pipe open
+            return Err(format!("timed out after {}s", timeout.as_secs()));
+        }
+        Err(e) => return Err(format!("could not wait for hook: {}", e)),
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    // As on timeout the stderr thread is never joined, since a background
+    // process may hold the pipe open; its output is only used if it arrives soon
+    let output = stderr_rx.recv_timeout(STDERR_GRACE).unwrap_or_default();
+    let reason = output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string);
+    Err(reason.unwrap_or_else(|| match status.code() {
+        Some(code) => format!("exited with status {}", code),
+        None => "terminated by a signal".to_string(),
+    }))
+}
+
+fn wait_until(child: &mut Child, deadline: Instant) -> std::io::Result<Option<std::process::ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn item(path: &str, size: u64) -> HookItem {
+        HookItem {
+            id: None,
+            original_path: PathBuf::from(path),
+            size,
+            tags: vec!["ops".to_string()],
+            restored_to: None,
+        }
+    }
+
+    fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn hooks(temp_dir: &TempDir, config: HooksConfig) -> Hooks {
+        Hooks::new(HooksConfig {
+            dir: Some(temp_dir.path().join("hooks.d")),
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_hooks_receive_items_on_stdin() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("input.json");
+        let hooks = hooks(
+            &temp_dir,
+            HooksConfig {
+                post_delete: Some(format!("cat > {}; echo $RMZ_HOOK $RMZ_ITEM_COUNT $RMZ_TOTAL_SIZE >> {0}", output.display())),
+                ..HooksConfig::default()
+            },
+        );
+
+        let failures = hooks.run(HookPoint::PostDelete, &[item("/srv/a.iso", 700), item("/srv/b.iso", 300)]);
+        assert!(failures.is_empty(), "{:?}", failures);
+
+        let written = fs::read_to_string(&output).unwrap();
+        let (json, env) = written.split_once('\n').unwrap();
+        let input: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(input["hook"], "post_delete");
+        assert_eq!(input["items"][1]["original_path"], "/srv/b.iso");
+        assert_eq!(input["items"][0]["id"], serde_json::Value::Null);
+        assert_eq!(input["items"][0]["tags"][0], "ops");
+        assert_eq!(env.trim(), "post_delete 2 1000");
+
+        // No items, no hooks
+        fs::remove_file(&output).unwrap();
+        assert!(hooks.run(HookPoint::PostDelete, &[]).is_empty());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_pre_hook_veto_stops_at_first_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("hooks.d/pre_delete");
+        let marker = temp_dir.path().join("ran");
+        script(&dir, "10-refuse", "echo 'checking' >&2\necho 'file is open in vim' >&2\nexit 3");
+        script(&dir, "20-later", &format!("touch {}", marker.display()));
+        script(&dir, ".hidden", "exit 1");
+        script(&dir, "30-backup~", "exit 1");
+        fs::write(dir.join("40-not-executable"), "exit 1").unwrap();
+
+        let hooks = hooks(&temp_dir, HooksConfig::default());
+        assert_eq!(
+            hooks.commands(HookPoint::PreDelete),
+            vec![HookCommand::Executable(dir.join("10-refuse")), HookCommand::Executable(dir.join("20-later"))]
+        );
+
+        let failures = hooks.run(HookPoint::PreDelete, &[item("/srv/notes.txt", 10)]);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].reason, "file is open in vim");
+        assert!(!marker.exists());
+
+        assert!(Hooks::disabled().commands(HookPoint::PreDelete).is_empty());
+    }
+
+    #[test]
+    fn test_post_hook_failures_and_timeouts_are_collected() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("hooks.d/post_restore");
+        script(&dir, "slow", "sleep 5");
+        let hooks = hooks(
+            &temp_dir,
+            HooksConfig {
+                post_restore: Some("exit 2".to_string()),
+                timeout_secs: 1,
+                ..HooksConfig::default()
+            },
+        );
+
+        let started = Instant::now();
+        let failures = hooks.run(HookPoint::PostRestore, &[item("/srv/a", 1)]);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(
+            failures,
+            vec![
+                HookFailure {
+                    hook: "exit 2".to_string(),
+                    reason: "exited with status 2".to_string(),
+                },
+                HookFailure {
+                    hook: dir.join("slow").display().to_string(),
+                    reason: "timed out after 1s".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_hook_does_not_wait_for_background_processes() {
+        let temp_dir = TempDir::new().unwrap();
+        let hooks = hooks(
+            &temp_dir,
+            HooksConfig {
+                pre_purge: Some("sleep 5 & exit 4".to_string()),
+                ..HooksConfig::default()
+            },
+        );
+
+        let started = Instant::now();
+        let failures = hooks.run(HookPoint::PrePurge, &[item("/srv/a", 1)]);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(failures[0].reason, "exited with status 4");
+    }
+}
@@ -0,0 +1,300 @@
+//! Forward operation log entries to the local syslog or journald
+//!
+//! Sinks are fire-and-forget: an entry is always written to
+//! `operations.jsonl` first, and a sink that cannot be reached only produces
+//! a warning.
+
+use crate::domain::operation_log::{OperationLog, OperationResult};
+use crate::domain::{LogSinkConfig, SyslogFacility};
+use anyhow::Result;
+use chrono::SecondsFormat;
+use std::path::{Path, PathBuf};
+
+/// Default syslog datagram socket
+pub const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// Default journald native protocol socket
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+const APP_NAME: &str = "rmz";
+
+/// Structured data ID, using the enterprise number reserved for documentation (RFC 5612)
+const SD_ID: &str = "rmz@32473";
+
+/// Destination for operation log entries besides the log file
+pub trait LogSink: Send + Sync {
+    /// Name shown in warnings, e.g. `syslog:/dev/log`
+    fn name(&self) -> String;
+
+    fn emit(&self, entry: &OperationLog) -> Result<()>;
+}
+
+/// RFC 5424 messages over a Unix datagram socket
+pub struct SyslogSink {
+    socket: PathBuf,
+    facility: SyslogFacility,
+    hostname: String,
+}
+
+impl SyslogSink {
+    pub fn new(socket: PathBuf, facility: SyslogFacility) -> Self {
+        Self {
+            socket,
+            facility,
+            hostname: hostname(),
+        }
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn name(&self) -> String {
+        format!("syslog:{}", self.socket.display())
+    }
+
+    fn emit(&self, entry: &OperationLog) -> Result<()> {
+        send(&self.socket, syslog_message(entry, self.facility, &self.hostname).as_bytes())
+    }
+}
+
+/// journald native protocol over its Unix datagram socket
+pub struct JournaldSink {
+    socket: PathBuf,
+}
+
+impl JournaldSink {
+    pub fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+}
+
+impl LogSink for JournaldSink {
+    fn name(&self) -> String {
+        format!("journald:{}", self.socket.display())
+    }
+
+    fn emit(&self, entry: &OperationLog) -> Result<()> {
+        send(&self.socket, &journald_message(entry))
+    }
+}
+
+/// Sinks for the configured destinations, in order
+pub fn open_sinks(configs: &[LogSinkConfig]) -> Vec<Box<dyn LogSink>> {
+    configs
+        .iter()
+        .map(|config| -> Box<dyn LogSink> {
+            match config {
+                LogSinkConfig::Syslog { socket, facility } => Box::new(SyslogSink::new(
+                    socket.clone().unwrap_or_else(|| PathBuf::from(SYSLOG_SOCKET)),
+                    *facility,
+                )),
+                LogSinkConfig::Journald { socket } => Box::new(JournaldSink::new(
+                    socket.clone().unwrap_or_else(|| PathBuf::from(JOURNALD_SOCKET)),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Syslog severity: informational, notice for cancelled and error for failed operations
+fn severity(result: &OperationResult) -> u8 {
+    match result {
+        OperationResult::Success => 6,
+        OperationResult::Cancelled => 5,
+        OperationResult::Failed(_) => 3,
+    }
+}
+
+fn result_name(result: &OperationResult) -> &'static str {
+    match result {
+        OperationResult::Success => "success",
+        OperationResult::Cancelled => "cancelled",
+        OperationResult::Failed(_) => "failed",
+    }
+}
+
+/// Format an entry as an RFC 5424 message with its attributes as structured data
+pub fn syslog_message(entry: &OperationLog, facility: SyslogFacility, hostname: &str) -> String {
+    let mut params = vec![
+        ("id", entry.id.to_string()),
+        ("operation", format!("{:?}", entry.operation)),
+        ("user", entry.user.clone()),
+        ("result", result_name(&entry.result).to_string()),
+    ];
+    if let OperationResult::Failed(error) = &entry.result {
+        params.push(("error", error.clone()));
+    }
+    if !entry.file_ids.is_empty() {
+        let ids: Vec<String> = entry.file_ids.iter().map(ToString::to_string).collect();
+        params.push(("file_ids", ids.join(" ")));
+    }
+    if !entry.tags.is_empty() {
+        params.push(("tags", entry.tags.join(",")));
+    }
+    let structured: String = params
+        .iter()
+        .map(|(name, value)| format!(" {}=\"{}\"", name, escape_param(value)))
+        .collect();
+
+    format!(
+        "<{}>1 {} {} {} {} {:?} [{}{}] {}",
+        u16::from(facility.code()) * 8 + u16::from(severity(&entry.result)),
+        entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        hostname,
+        APP_NAME,
+        std::process::id(),
+        entry.operation,
+        SD_ID,
+        structured,
+        entry.description()
+    )
+}
+
+/// Escape the characters RFC 5424 reserves inside a PARAM-VALUE
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Encode an entry in the journald native protocol, one field per attribute
+///
+/// Paths and file IDs are repeated fields so `journalctl RMZ_FILE_ID=...` finds the entry.
+pub fn journald_message(entry: &OperationLog) -> Vec<u8> {
+    let mut fields = vec![
+        ("MESSAGE", entry.description()),
+        ("PRIORITY", severity(&entry.result).to_string()),
+        ("SYSLOG_IDENTIFIER", APP_NAME.to_string()),
+        ("RMZ_ID", entry.id.to_string()),
+        ("RMZ_OPERATION", format!("{:?}", entry.operation)),
+        ("RMZ_USER", entry.user.clone()),
+        ("RMZ_RESULT", result_name(&entry.result).to_string()),
+    ];
+    if let OperationResult::Failed(error) = &entry.result {
+        fields.push(("RMZ_ERROR", error.clone()));
+    }
+    fields.extend(entry.file_ids.iter().map(|id| ("RMZ_FILE_ID", id.to_string())));
+    fields.extend(entry.paths.iter().map(|path| ("RMZ_PATH", path.display().to_string())));
+    fields.extend(entry.tags.iter().map(|tag| ("RMZ_TAG", tag.clone())));
+    if let Some(context) = &entry.context {
+        fields.push(("RMZ_CONTEXT", context.clone()));
+    }
+
+    let mut payload = Vec::new();
+    for (name, value) in fields {
+        payload.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            // Values with newlines are sent length-prefixed
+            payload.push(b'\n');
+            payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            payload.push(b'=');
+        }
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(b'\n');
+    }
+    payload
+}
+
+#[cfg(unix)]
+fn send(socket: &Path, payload: &[u8]) -> Result<()> {
+    std::os::unix::net::UnixDatagram::unbound()?.send_to(payload, socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(socket: &Path, _payload: &[u8]) -> Result<()> {
+    anyhow::bail!("{} is a Unix socket, which this platform does not support", socket.display())
+}
+
+/// Host name for the HOSTNAME field, or the nil value "-"
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic()))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::operation_log::OperationType;
+    #[cfg(unix)]
+    use std::os::unix::net::UnixDatagram;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn entry(result: OperationResult) -> OperationLog {
+        OperationLog::new(OperationType::Delete, vec![PathBuf::from("/srv/app/old.log")], result)
+            .with_file_ids(vec![Uuid::nil()])
+            .with_tags(vec!["ops".to_string()])
+    }
+
+    #[test]
+    fn test_syslog_message() {
+        let message = syslog_message(
+            &entry(OperationResult::Failed("disk \"full\"]".to_string())),
+            SyslogFacility::Local3,
+            "web1",
+        );
+        // local3 (19) * 8 + error (3)
+        assert!(message.starts_with("<155>1 "), "{}", message);
+        assert!(message.contains(" web1 rmz "));
+        assert!(message.contains(" Delete [rmz@32473 id=\""));
+        assert!(message.contains(" result=\"failed\" error=\"disk \\\"full\\\"\\]\""));
+        assert!(message.contains(&format!(" file_ids=\"{}\"", Uuid::nil())));
+        assert!(message.ends_with("] Delete /srv/app/old.log"));
+    }
+
+    #[test]
+    fn test_journald_message() {
+        let mut logged = entry(OperationResult::Success);
+        logged.context = Some("first\nsecond".to_string());
+        let payload = journald_message(&logged);
+        let text = String::from_utf8_lossy(&payload);
+        assert!(text.contains("PRIORITY=6\n"));
+        assert!(text.contains("RMZ_OPERATION=Delete\n"));
+        assert!(text.contains(&format!("RMZ_FILE_ID={}\n", Uuid::nil())));
+        assert!(text.contains("RMZ_PATH=/srv/app/old.log\n"));
+
+        let mut binary = b"RMZ_CONTEXT\n".to_vec();
+        binary.extend_from_slice(&12u64.to_le_bytes());
+        binary.extend_from_slice(b"first\nsecond\n");
+        assert!(payload.ends_with(&binary));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sinks_send_to_local_socket() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("log.sock");
+        let listener = UnixDatagram::bind(&socket).unwrap();
+
+        let sinks = open_sinks(&[
+            LogSinkConfig::Syslog {
+                socket: Some(socket.clone()),
+                facility: SyslogFacility::Auth,
+            },
+            LogSinkConfig::Journald { socket: Some(socket.clone()) },
+        ]);
+        for sink in &sinks {
+            sink.emit(&entry(OperationResult::Success)).unwrap();
+        }
+
+        let mut buffer = [0u8; 4096];
+        let size = listener.recv(&mut buffer).unwrap();
+        assert!(String::from_utf8_lossy(&buffer[..size]).starts_with("<38>1 "));
+        let size = listener.recv(&mut buffer).unwrap();
+        assert!(String::from_utf8_lossy(&buffer[..size]).contains("SYSLOG_IDENTIFIER=rmz\n"));
+
+        let missing = JournaldSink::new(temp_dir.path().join("missing.sock"));
+        assert!(missing.emit(&entry(OperationResult::Success)).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::domain::FileMeta;
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// Interface for metadata storage operations
@@ -21,7 +21,12 @@ impl MetaStore {
         Self { metadata_dir }
     }
 
-    fn metadata_file_path(&self, id: &Uuid) -> PathBuf {
+    pub fn metadata_dir(&self) -> &Path {
+        &self.metadata_dir
+    }
+
+    /// Get the path of the JSON file holding the metadata for `id`
+    pub fn metadata_file_path(&self, id: &Uuid) -> PathBuf {
         self.metadata_dir.join(format!("{}.json", id))
     }
 }
@@ -73,7 +78,7 @@ impl MetaStoreInterface for MetaStore {
         }
 
         // Sort by deletion time (newest first)
-        metadata_list.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        metadata_list.sort_by_key(|meta| std::cmp::Reverse(meta.deleted_at));
 
         Ok(metadata_list)
     }
@@ -104,7 +109,7 @@ mod tests {
         // Create test metadata
         let test_file = NamedTempFile::new().unwrap();
         fs::write(test_file.path(), "content").unwrap();
-        let meta = FileMeta::from_path(&test_file.path().to_path_buf()).unwrap();
+        let meta = FileMeta::from_path(test_file.path()).unwrap();
 
         // Save metadata
         meta_store.save_metadata(&meta).unwrap();
@@ -130,8 +135,8 @@ mod tests {
         fs::write(test_file1.path(), "content1").unwrap();
         fs::write(test_file2.path(), "content2").unwrap();
 
-        let meta1 = FileMeta::from_path(&test_file1.path().to_path_buf()).unwrap();
-        let meta2 = FileMeta::from_path(&test_file2.path().to_path_buf()).unwrap();
+        let meta1 = FileMeta::from_path(test_file1.path()).unwrap();
+        let meta2 = FileMeta::from_path(test_file2.path()).unwrap();
 
         meta_store.save_metadata(&meta1).unwrap();
         meta_store.save_metadata(&meta2).unwrap();
@@ -151,7 +156,7 @@ mod tests {
 
         let test_file = NamedTempFile::new().unwrap();
         fs::write(test_file.path(), "content").unwrap();
-        let meta = FileMeta::from_path(&test_file.path().to_path_buf()).unwrap();
+        let meta = FileMeta::from_path(test_file.path()).unwrap();
 
         // Save and then delete
         meta_store.save_metadata(&meta).unwrap();
@@ -1,9 +1,19 @@
+pub mod blob_store;
+pub mod compression;
 pub mod config_manager;
+pub mod diff;
+pub mod encryption;
 pub mod fzf_interface;
+pub mod git;
+pub mod hooks;
+pub mod log_sinks;
 pub mod meta_store;
 pub mod operation_logger;
+pub mod preview;
+pub mod trash_repair;
 pub mod trash_store;
 
+pub use blob_store::BlobStore;
 pub use config_manager::ConfigManager;
 pub use fzf_interface::{FzfInterface, FzfSelector, DialoguerSelector, create_selector};
 pub use meta_store::MetaStore;
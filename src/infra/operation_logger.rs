@@ -1,7 +1,9 @@
-use crate::domain::operation_log::{OperationLog, OperationType};
+use crate::domain::operation_log::{OperationLog, OperationLogger, OperationResult, OperationType};
+use crate::domain::{Config, LogConfig};
+use crate::infra::log_sinks::{open_sinks, LogSink};
+use crate::infra::trash_store::ENCRYPTED_PATH_PLACEHOLDER;
+use crate::trash::TrashEvent;
 use anyhow::Result;
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::path::PathBuf;
 use std::sync::{Arc, OnceLock};
 
@@ -19,35 +21,27 @@ pub trait OperationLoggerInterface: Send + Sync {
 
 /// File-based operation logger implementation
 pub struct FileOperationLogger {
-    log_path: PathBuf,
+    logger: OperationLogger,
 }
 
 impl FileOperationLogger {
     pub fn new(log_path: PathBuf) -> Self {
-        Self { log_path }
+        Self {
+            logger: OperationLogger::new(log_path),
+        }
+    }
+
+    /// Use the given rotation, retention and authentication settings
+    pub fn with_config(self, config: LogConfig) -> Self {
+        Self {
+            logger: self.logger.with_config(config),
+        }
     }
 }
 
 impl OperationLoggerInterface for FileOperationLogger {
     fn log(&self, entry: OperationLog) -> Result<()> {
-        // Ensure parent directory exists
-        if let Some(parent) = self.log_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        // Serialize to JSON Lines format
-        let json_line = serde_json::to_string(&entry)?;
-        
-        // Append to log file
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.log_path)?;
-        
-        writeln!(file, "{}", json_line)?;
-        file.sync_all()?;
-        
-        Ok(())
+        self.logger.log(entry)
     }
 
     fn get_logs(&self, limit: Option<usize>) -> Result<Vec<OperationLog>> {
@@ -60,49 +54,72 @@ impl OperationLoggerInterface for FileOperationLogger {
         since: Option<chrono::DateTime<chrono::Utc>>,
         limit: Option<usize>,
     ) -> Result<Vec<OperationLog>> {
-        if !self.log_path.exists() {
-            return Ok(Vec::new());
-        }
+        self.logger.read_filtered_logs(operation_type, since, limit)
+    }
+}
 
-        let contents = std::fs::read_to_string(&self.log_path)?;
-        let mut logs = Vec::new();
+/// File logger that also forwards every entry to syslog, journald or other sinks
+pub struct MultiSinkLogger {
+    file: FileOperationLogger,
+    sinks: Vec<Box<dyn LogSink>>,
+}
 
-        for line in contents.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
+impl MultiSinkLogger {
+    pub fn new(file: FileOperationLogger, sinks: Vec<Box<dyn LogSink>>) -> Self {
+        Self { file, sinks }
+    }
 
-            match serde_json::from_str::<OperationLog>(line) {
-                Ok(log) => {
-                    // Apply filters
-                    if log.matches_filter(operation_type.as_ref(), since.as_ref()) {
-                        logs.push(log);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Warning: Failed to parse log line: {} ({})", line, e);
-                }
+    /// The log file and sinks configured in `config`
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            FileOperationLogger::new(config.operation_log_path()).with_config(config.log.clone()),
+            open_sinks(&config.log.sinks),
+        )
+    }
+}
+
+impl OperationLoggerInterface for MultiSinkLogger {
+    fn log(&self, entry: OperationLog) -> Result<()> {
+        let written = self.file.log(entry.clone());
+        // An unreachable sink must not fail the operation
+        for sink in &self.sinks {
+            if let Err(e) = sink.emit(&entry) {
+                eprintln!("Warning: Could not send log entry to {}: {}", sink.name(), e);
             }
         }
+        written
+    }
 
-        // Sort by timestamp (newest first)
-        logs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
-        // Apply limit if specified
-        if let Some(limit) = limit {
-            logs.truncate(limit);
-        }
+    fn get_logs(&self, limit: Option<usize>) -> Result<Vec<OperationLog>> {
+        self.file.get_logs(limit)
+    }
 
-        Ok(logs)
+    fn get_logs_filtered(
+        &self,
+        operation_type: Option<OperationType>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: Option<usize>,
+    ) -> Result<Vec<OperationLog>> {
+        self.file.get_logs_filtered(operation_type, since, limit)
     }
 }
 
 /// Global operation logger instance
 static OPERATION_LOGGER: OnceLock<Arc<dyn OperationLoggerInterface>> = OnceLock::new();
 
-/// Initialize the global operation logger
-pub fn init_operation_logger(log_path: PathBuf) -> Result<()> {
-    let logger = Arc::new(FileOperationLogger::new(log_path));
+/// Initialize the global operation logger for the given configuration
+pub fn init_operation_logger(config: &Config) -> Result<()> {
+    let log_path = config.operation_log_path();
+    // Older versions wrote the log inside the trash directory
+    let legacy_path = config.trash_path.join("operations.jsonl");
+    if legacy_path.exists() && !log_path.exists() {
+        if let Some(parent) = log_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&legacy_path, &log_path)?;
+    }
+
+    let logger = Arc::new(MultiSinkLogger::from_config(config));
     OPERATION_LOGGER.set(logger)
         .map_err(|_| anyhow::anyhow!("Operation logger already initialized"))?;
     Ok(())
@@ -122,9 +139,7 @@ pub fn log_operation(entry: OperationLog) -> Result<()> {
         logger.log(entry)?;
     } else {
         // Initialize with default path if not already initialized
-        let config = crate::domain::Config::load()?;
-        let log_path = config.trash_path.join("operations.jsonl");
-        init_operation_logger(log_path)?;
+        init_operation_logger(&Config::load()?)?;
         
         // Try again
         if let Ok(logger) = get_operation_logger() {
@@ -134,11 +149,48 @@ pub fn log_operation(entry: OperationLog) -> Result<()> {
     Ok(())
 }
 
+/// Log entry recording a deletion, restore or purge reported by a [`crate::trash::Trash`]
+///
+/// Items whose original path is stored encrypted are logged by ID only, with
+/// the placeholder in place of any path.
+pub fn event_entry(event: &TrashEvent) -> Option<OperationLog> {
+    let (operation, item) = match event {
+        TrashEvent::Deleted(item) => (OperationType::Delete, item),
+        TrashEvent::Restored { item, .. } => (OperationType::Restore, item),
+        TrashEvent::Purged(item) => (OperationType::Purge, item),
+        TrashEvent::Retagged(item) => (OperationType::Tag, item),
+        TrashEvent::Annotated(item) => (OperationType::Annotate, item),
+        TrashEvent::Warning(_) => return None,
+    };
+    let hidden = item.meta.has_encrypted_path();
+    let path = if hidden {
+        PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER)
+    } else {
+        item.meta.original_path.clone()
+    };
+    let mut entry = OperationLog::new(operation, vec![path], OperationResult::Success)
+        .with_file_ids(vec![item.meta.id])
+        .with_tags(item.meta.tags.clone());
+    if let TrashEvent::Restored { path, .. } = event {
+        if !hidden && path != &item.meta.original_path {
+            entry = entry.with_context(format!("restored to {}", path.display()));
+        }
+    }
+    Some(entry)
+}
+
+/// Trash listener that records deletions, restores, purges and metadata edits in the operation log
+pub fn log_trash_event(event: &TrashEvent) {
+    if let Some(entry) = event_entry(event) {
+        // Logging must never make the operation itself fail
+        let _ = log_operation(entry);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::operation_log::{OperationType, OperationResult};
-    use chrono::Utc;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
@@ -204,4 +256,91 @@ mod tests {
         let all_logs = logger.get_logs(None).unwrap();
         assert_eq!(all_logs.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_event_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+        let mut meta = crate::domain::FileMeta::from_path(&file).unwrap();
+        meta.add_tag("work".to_string());
+        let item = crate::domain::TrashItem::new(meta.clone(), temp_dir.path().join("payload"));
+
+        let deleted = event_entry(&TrashEvent::Deleted(item.clone())).unwrap();
+        assert_eq!(deleted.operation, OperationType::Delete);
+        assert_eq!((deleted.file_ids, deleted.tags), (vec![meta.id], vec!["work".to_string()]));
+
+        let restored = event_entry(&TrashEvent::Restored {
+            item: item.clone(),
+            path: temp_dir.path().join("elsewhere.txt"),
+        })
+        .unwrap();
+        assert_eq!(restored.paths, vec![file]);
+        assert!(restored.context.unwrap().contains("elsewhere.txt"));
+
+        let retagged = event_entry(&TrashEvent::Retagged(item.clone())).unwrap();
+        assert_eq!(retagged.operation, OperationType::Tag);
+        assert_eq!(retagged.tags, vec!["work".to_string()]);
+        let annotated = event_entry(&TrashEvent::Annotated(item)).unwrap();
+        assert_eq!(annotated.operation, OperationType::Annotate);
+
+        assert!(event_entry(&TrashEvent::Warning("compression failed".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_event_entry_hides_encrypted_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("id_ed25519");
+        std::fs::write(&file, "KEY").unwrap();
+        let mut meta = crate::domain::FileMeta::from_path(&file).unwrap();
+        meta.encryption = Some(crate::domain::EncryptionInfo {
+            kind: crate::domain::EncryptedKind::File,
+            key_id: "key".to_string(),
+            encrypted_path: Some("00ff".to_string()),
+        });
+        // Events carry revealed items, so the plaintext path is present here
+        let item = crate::domain::TrashItem::new(meta.clone(), temp_dir.path().join("payload"));
+
+        let restored = event_entry(&TrashEvent::Restored {
+            item,
+            path: temp_dir.path().join("elsewhere"),
+        })
+        .unwrap();
+        assert_eq!(restored.paths, vec![PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER)]);
+        assert_eq!(restored.file_ids, vec![meta.id]);
+        assert!(restored.context.is_none());
+
+        let journald = String::from_utf8(crate::infra::log_sinks::journald_message(&restored)).unwrap();
+        assert!(!journald.contains("id_ed25519"));
+        assert!(journald.contains(&format!("RMZ_PATH={}", ENCRYPTED_PATH_PLACEHOLDER)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_multi_sink_logger() {
+        use crate::domain::LogSinkConfig;
+        use std::os::unix::net::UnixDatagram;
+
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("journal.sock");
+        let listener = UnixDatagram::bind(&socket).unwrap();
+        let mut config = Config {
+            trash_path: temp_dir.path().join("trash"),
+            ..Config::default()
+        };
+        config.log.sinks = vec![
+            LogSinkConfig::Journald { socket: Some(socket) },
+            LogSinkConfig::Journald { socket: Some(temp_dir.path().join("missing.sock")) },
+        ];
+        let logger = MultiSinkLogger::from_config(&config);
+
+        // The unreachable second sink only warns
+        let entry = OperationLog::new(OperationType::Purge, vec![PathBuf::from("/old")], OperationResult::Success);
+        logger.log(entry.clone()).unwrap();
+
+        let mut buffer = [0u8; 4096];
+        let size = listener.recv(&mut buffer).unwrap();
+        assert!(String::from_utf8_lossy(&buffer[..size]).contains(&format!("RMZ_ID={}\n", entry.id)));
+        assert_eq!(logger.get_logs(None).unwrap()[0].id, entry.id);
+    }
+}
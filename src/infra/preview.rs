@@ -0,0 +1,157 @@
+use crate::domain::{BlobManifest, CompressionKind, EncryptedKind, ManifestEntryKind, TrashItem};
+use crate::infra::TrashStore;
+use anyhow::Result;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Bytes of file content read for a preview
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Lines shown in the preview pane: file contents or a directory tree
+pub fn preview_lines(store: &TrashStore, item: &TrashItem, max_lines: usize) -> Vec<String> {
+    match render(store, item, max_lines) {
+        Ok(lines) => lines,
+        Err(e) => vec![format!("Preview unavailable: {}", e)],
+    }
+}
+
+fn render(store: &TrashStore, item: &TrashItem, max_lines: usize) -> Result<Vec<String>> {
+    if let Some(info) = &item.meta.encryption {
+        if info.kind == EncryptedKind::Tar {
+            return Ok(vec!["Encrypted directory archive; extract it to browse".to_string()]);
+        }
+        return content_lines(store.open_content(item)?, max_lines);
+    }
+
+    if item.meta.deduplicated {
+        let manifest = BlobManifest::load(&item.trash_path)?;
+        if manifest.single_file().is_some() {
+            return content_lines(store.open_content(item)?, max_lines);
+        }
+        let mut lines: Vec<String> = manifest
+            .entries
+            .iter()
+            .filter(|entry| !entry.path.as_os_str().is_empty())
+            .map(|entry| tree_line(&entry.path, entry.kind == ManifestEntryKind::Directory))
+            .collect();
+        lines.truncate(max_lines);
+        return Ok(lines);
+    }
+
+    match item.meta.compression.as_ref().map(|info| info.kind) {
+        Some(CompressionKind::TarZstd) => archive_lines(&item.trash_path, max_lines),
+        Some(CompressionKind::Zstd) => content_lines(store.open_content(item)?, max_lines),
+        None if item.trash_path.is_dir() => {
+            let mut lines = Vec::new();
+            directory_lines(&item.trash_path, &item.trash_path, max_lines, &mut lines)?;
+            Ok(lines)
+        }
+        None => content_lines(store.open_content(item)?, max_lines),
+    }
+}
+
+/// Text lines of a file, or a note when the content is binary
+fn content_lines(reader: Box<dyn Read>, max_lines: usize) -> Result<Vec<String>> {
+    let mut content = Vec::new();
+    reader.take(PREVIEW_BYTES).read_to_end(&mut content)?;
+
+    if content.contains(&0) {
+        return Ok(vec![format!("Binary file ({} bytes shown)", content.len())]);
+    }
+    let text = String::from_utf8_lossy(&content);
+    Ok(text.lines().take(max_lines).map(|line| line.replace('\t', "    ")).collect())
+}
+
+/// Entries of a compressed directory archive, in path order
+fn archive_lines(path: &Path, max_lines: usize) -> Result<Vec<String>> {
+    let decoder = zstd::Decoder::new(std::fs::File::open(path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        // Archives are rooted at "." for the trashed directory itself
+        let relative: PathBuf = entry
+            .path()?
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect();
+        if !relative.as_os_str().is_empty() {
+            entries.push((relative, entry.header().entry_type().is_dir()));
+        }
+    }
+    entries.sort();
+    Ok(entries
+        .iter()
+        .take(max_lines)
+        .map(|(relative, is_dir)| tree_line(relative, *is_dir))
+        .collect())
+}
+
+/// Walk a plain directory depth first, in name order
+fn directory_lines(root: &Path, dir: &Path, max_lines: usize, lines: &mut Vec<String>) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if lines.len() >= max_lines {
+            break;
+        }
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+        lines.push(tree_line(path.strip_prefix(root)?, is_dir));
+        if is_dir {
+            directory_lines(root, &path, max_lines, lines)?;
+        }
+    }
+    Ok(())
+}
+
+fn tree_line(relative: &Path, is_dir: bool) -> String {
+    let depth = relative.components().count().saturating_sub(1);
+    let name = relative
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{}{}{}", "  ".repeat(depth), name, if is_dir { "/" } else { "" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileMeta;
+    use crate::infra::trash_store::TrashStoreInterface;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_and_directory_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "first\nsecond\nthird\n").unwrap();
+        let item = store.save(&FileMeta::from_path(&file).unwrap(), &file).unwrap();
+        assert_eq!(preview_lines(&store, &item, 2), vec!["first", "second"]);
+
+        let dir = temp_dir.path().join("project");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("README"), "readme").unwrap();
+        let item = store.save(&FileMeta::from_path(&dir).unwrap(), &dir).unwrap();
+        assert_eq!(preview_lines(&store, &item, 10), vec!["README", "src/", "  main.rs"]);
+
+        let compressed = store.compress(&item.meta.id, 3).unwrap();
+        assert_eq!(preview_lines(&store, &compressed, 10), vec!["README", "src/", "  main.rs"]);
+    }
+
+    #[test]
+    fn test_binary_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file = temp_dir.path().join("blob.bin");
+        fs::write(&file, [0u8, 159, 146, 150]).unwrap();
+        let item = store.save(&FileMeta::from_path(&file).unwrap(), &file).unwrap();
+        assert_eq!(preview_lines(&store, &item, 10), vec!["Binary file (4 bytes shown)"]);
+    }
+}
@@ -0,0 +1,663 @@
+//! Consistency checks and repairs for the on-disk trash layout
+//!
+//! [`scan`] walks the date directories, the metadata directory and the legacy
+//! metadata directory next to the trash, and reports every mismatch as a
+//! [`Finding`]. [`repair`] fixes a single finding so callers can preview, log
+//! or skip each step on its own.
+
+use crate::domain::{BlobManifest, CompressionInfo, CompressionKind, EncryptedKind, EncryptionInfo, FileMeta};
+use crate::infra::meta_store::MetaStoreInterface;
+use crate::infra::TrashStore;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+/// Directory inside the trash root that receives quarantined files
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Tag given to items adopted from payloads without metadata
+pub const RECOVERED_TAG: &str = "recovered";
+
+/// Temporary, lock and journal files untouched for this long are abandoned
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+const STALE_SUFFIXES: [&str; 3] = [".tmp", ".lock", ".journal"];
+
+/// A single inconsistency in the trash
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// Metadata whose payload is gone
+    MissingPayload { id: Uuid, metadata: PathBuf },
+    /// Metadata file that cannot be parsed
+    CorruptMetadata { metadata: PathBuf, error: String },
+    /// Payload that no metadata describes
+    OrphanedPayload { id: Uuid, payload: PathBuf },
+    /// Payload whose format differs from what its metadata records
+    MismatchedPayload { id: Uuid, payload: PathBuf },
+    /// Extra payload next to the one the metadata points to
+    LeftoverPayload { id: Uuid, payload: PathBuf },
+    /// Payload stored in a date directory that does not match its deletion time
+    MisplacedPayload { id: Uuid, payload: PathBuf, expected: PathBuf },
+    /// Metadata present in both the trash and the legacy metadata directory
+    DuplicateMetadata { id: Uuid, legacy: PathBuf, identical: bool },
+    /// Metadata present only in the legacy metadata directory
+    LegacyMetadata { id: Uuid, legacy: PathBuf },
+    /// Directory or file the owner cannot use, or that anyone may write to
+    Permissions { path: PathBuf, mode: u32, expected: u32 },
+    /// Abandoned temporary, lock or journal file
+    StaleFile { path: PathBuf },
+}
+
+/// How destructive repairs are carried out
+#[derive(Debug, Clone, Default)]
+pub struct RepairOptions {
+    /// Move broken metadata and leftover payloads to the quarantine directory instead of deleting them
+    pub quarantine: bool,
+}
+
+impl Finding {
+    /// The file or directory the finding is about
+    pub fn path(&self) -> &Path {
+        match self {
+            Finding::MissingPayload { metadata, .. } | Finding::CorruptMetadata { metadata, .. } => metadata,
+            Finding::OrphanedPayload { payload, .. }
+            | Finding::MismatchedPayload { payload, .. }
+            | Finding::LeftoverPayload { payload, .. }
+            | Finding::MisplacedPayload { payload, .. } => payload,
+            Finding::DuplicateMetadata { legacy, .. } | Finding::LegacyMetadata { legacy, .. } => legacy,
+            Finding::Permissions { path, .. } | Finding::StaleFile { path } => path,
+        }
+    }
+
+    /// The trash item the finding belongs to, if any
+    pub fn id(&self) -> Option<Uuid> {
+        match self {
+            Finding::MissingPayload { id, .. }
+            | Finding::OrphanedPayload { id, .. }
+            | Finding::MismatchedPayload { id, .. }
+            | Finding::LeftoverPayload { id, .. }
+            | Finding::MisplacedPayload { id, .. }
+            | Finding::DuplicateMetadata { id, .. }
+            | Finding::LegacyMetadata { id, .. } => Some(*id),
+            Finding::CorruptMetadata { .. } | Finding::Permissions { .. } | Finding::StaleFile { .. } => None,
+        }
+    }
+
+    /// Describe what [`repair`] would do about this finding
+    pub fn action(&self, options: &RepairOptions) -> String {
+        let discard = if options.quarantine { "Quarantine" } else { "Remove" };
+        match self {
+            Finding::MissingPayload { .. } | Finding::CorruptMetadata { .. } => format!("{} the metadata", discard),
+            Finding::OrphanedPayload { .. } => format!("Adopt with reconstructed metadata tagged '{}'", RECOVERED_TAG),
+            Finding::MismatchedPayload { .. } => "Rebuild the storage details in the metadata".to_string(),
+            Finding::LeftoverPayload { .. } => format!("{} the leftover payload", discard),
+            Finding::MisplacedPayload { expected, .. } => format!("Move to {}", expected.display()),
+            Finding::DuplicateMetadata { identical: true, .. } => "Remove the legacy copy".to_string(),
+            Finding::DuplicateMetadata { identical: false, .. } => "Quarantine the legacy copy".to_string(),
+            Finding::LegacyMetadata { .. } => "Move into the trash metadata directory".to_string(),
+            Finding::Permissions { expected, .. } => format!("Change mode to {:o}", expected),
+            Finding::StaleFile { .. } => "Remove the file".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::MissingPayload { id, metadata } => {
+                write!(f, "Metadata without payload: {} ({})", id, metadata.display())
+            }
+            Finding::CorruptMetadata { metadata, error } => {
+                write!(f, "Unreadable metadata {}: {}", metadata.display(), error)
+            }
+            Finding::OrphanedPayload { payload, .. } => write!(f, "Payload without metadata: {}", payload.display()),
+            Finding::MismatchedPayload { id, payload } => {
+                write!(f, "Payload {} does not match the format recorded for {}", payload.display(), id)
+            }
+            Finding::LeftoverPayload { id, payload } => {
+                write!(f, "Leftover payload for {}: {}", id, payload.display())
+            }
+            Finding::MisplacedPayload { payload, expected, .. } => write!(
+                f,
+                "Payload in the wrong date directory: {} (expected {})",
+                payload.display(),
+                expected.display()
+            ),
+            Finding::DuplicateMetadata { id, legacy, identical } => write!(
+                f,
+                "Metadata {} is duplicated in {}{}",
+                id,
+                legacy.display(),
+                if *identical { "" } else { " with different contents" }
+            ),
+            Finding::LegacyMetadata { id, legacy } => {
+                write!(f, "Metadata {} is only in the legacy directory: {}", id, legacy.display())
+            }
+            Finding::Permissions { path, mode, expected } => {
+                write!(f, "Wrong permissions on {}: {:o} (expected {:o})", path.display(), mode, expected)
+            }
+            Finding::StaleFile { path } => write!(f, "Stale file: {}", path.display()),
+        }
+    }
+}
+
+/// Find every inconsistency between payloads, metadata and permissions
+pub fn scan(store: &TrashStore, legacy_metadata_dir: &Path) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    let root = store.get_trash_root();
+    if !root.is_dir() {
+        return Ok(findings);
+    }
+    let metadata_dir = store.meta_store().metadata_dir();
+
+    let mut metas = BTreeMap::new();
+    for path in json_files(metadata_dir)? {
+        match read_meta(&path) {
+            Ok(meta) => {
+                metas.insert(meta.id, (meta, path));
+            }
+            Err(e) => findings.push(Finding::CorruptMetadata { metadata: path, error: e.to_string() }),
+        }
+    }
+
+    // Payloads described only by legacy metadata become visible once that metadata moves
+    let mut described: HashSet<Uuid> = metas.keys().copied().collect();
+    if legacy_metadata_dir != metadata_dir {
+        for legacy in json_files(legacy_metadata_dir)? {
+            let Some(id) = file_id(&legacy) else { continue };
+            match metas.get(&id) {
+                Some((meta, _)) => {
+                    let identical = read_meta(&legacy).is_ok_and(|copy| &copy == meta);
+                    findings.push(Finding::DuplicateMetadata { id, legacy, identical });
+                }
+                None => {
+                    described.insert(id);
+                    findings.push(Finding::LegacyMetadata { id, legacy });
+                }
+            }
+        }
+    }
+
+    let mut payloads: BTreeMap<Uuid, Vec<PathBuf>> = BTreeMap::new();
+    let mut stale = Vec::new();
+    for dir in date_dirs(root)? {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if is_stale(&path) {
+                stale.push(path);
+            } else if let Some(id) = payload_id(&path) {
+                payloads.entry(id).or_default().push(path);
+            }
+        }
+    }
+
+    for (id, (meta, metadata)) in &metas {
+        let expected = store.payload_path(meta);
+        let mut candidates = payloads.remove(id).unwrap_or_default();
+        candidates.sort_by_key(|path| path.file_name() != expected.file_name());
+
+        if expected.exists() {
+            candidates.retain(|path| path != &expected);
+        } else if candidates.first().is_some_and(|path| path.file_name() == expected.file_name()) {
+            let payload = candidates.remove(0);
+            findings.push(Finding::MisplacedPayload { id: *id, payload, expected });
+        } else if candidates.is_empty() {
+            findings.push(Finding::MissingPayload { id: *id, metadata: metadata.clone() });
+        } else {
+            let payload = candidates.remove(0);
+            findings.push(Finding::MismatchedPayload { id: *id, payload });
+        }
+
+        for payload in candidates {
+            findings.push(Finding::LeftoverPayload { id: *id, payload });
+        }
+    }
+
+    for (id, paths) in payloads.into_iter().filter(|(id, _)| !described.contains(id)) {
+        let mut paths = paths.into_iter();
+        if let Some(payload) = paths.next() {
+            findings.push(Finding::OrphanedPayload { id, payload });
+        }
+        for payload in paths {
+            findings.push(Finding::LeftoverPayload { id, payload });
+        }
+    }
+
+    for dir in [root.as_path(), metadata_dir, store.blob_store().root()] {
+        if dir.is_dir() {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if is_stale(&path) {
+                    stale.push(path);
+                }
+            }
+        }
+    }
+    findings.extend(stale.into_iter().map(|path| Finding::StaleFile { path }));
+
+    findings.extend(check_permissions(store)?);
+
+    Ok(findings)
+}
+
+/// Fix a single finding
+pub fn repair(store: &TrashStore, finding: &Finding, options: &RepairOptions) -> Result<()> {
+    match finding {
+        Finding::MissingPayload { metadata, .. } | Finding::CorruptMetadata { metadata, .. } => {
+            discard(store, metadata, options)
+        }
+        Finding::OrphanedPayload { id, payload } | Finding::MismatchedPayload { id, payload } => {
+            adopt(store, *id, payload)
+        }
+        Finding::LeftoverPayload { payload, .. } => {
+            if !options.quarantine && payload.to_string_lossy().ends_with(".manifest") {
+                let manifest = BlobManifest::load(payload)?;
+                store.blob_store().release(&manifest.blob_hashes())?;
+            }
+            discard(store, payload, options)
+        }
+        Finding::MisplacedPayload { payload, expected, .. } => move_into(payload, expected),
+        Finding::DuplicateMetadata { legacy, identical, .. } => {
+            let options = RepairOptions { quarantine: !identical };
+            discard(store, legacy, &options)
+        }
+        Finding::LegacyMetadata { id, legacy } => move_into(legacy, &store.meta_store().metadata_file_path(id)),
+        Finding::Permissions { path, expected, .. } => set_mode(path, *expected),
+        Finding::StaleFile { path } => Ok(fs::remove_file(path)?),
+    }
+}
+
+/// Write metadata for a payload, keeping what existing metadata knows about the item
+fn adopt(store: &TrashStore, id: Uuid, payload: &Path) -> Result<()> {
+    let existing = store.meta_store().load_metadata(&id)?;
+    let mut meta = match existing.clone() {
+        Some(meta) => meta,
+        None => recovered_meta(id, payload)?,
+    };
+
+    let name = payload.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let stored = crate::trash::stored_size(payload);
+    let previous_key = existing.and_then(|meta| meta.encryption);
+    meta.compression = None;
+    meta.encryption = None;
+    meta.deduplicated = false;
+
+    match payload_suffix(name) {
+        Some("") => {}
+        Some("manifest") => meta.deduplicated = true,
+        Some(suffix @ ("zst" | "tar.zst")) => {
+            let kind = if suffix == "zst" { CompressionKind::Zstd } else { CompressionKind::TarZstd };
+            meta.compression = Some(CompressionInfo {
+                kind,
+                original_size: meta.size,
+                compressed_size: stored,
+            });
+        }
+        Some(suffix @ ("enc" | "tar.enc")) => {
+            let kind = if suffix == "enc" { EncryptedKind::File } else { EncryptedKind::Tar };
+            meta.encryption = Some(match previous_key {
+                Some(info) => EncryptionInfo { kind, ..info },
+                None => EncryptionInfo {
+                    kind,
+                    key_id: store.key_manager().current_key_id()?.unwrap_or_default(),
+                    encrypted_path: None,
+                },
+            });
+        }
+        _ => anyhow::bail!("Unrecognized payload format: {}", payload.display()),
+    }
+
+    let expected = store.payload_path(&meta);
+    if expected != payload {
+        move_into(payload, &expected)?;
+    }
+    store.meta_store().save_metadata(&meta)
+}
+
+/// Reconstruct metadata from the payload itself and the directory it is stored in
+fn recovered_meta(id: Uuid, payload: &Path) -> Result<FileMeta> {
+    let metadata = fs::metadata(payload)?;
+    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+    let day = payload.parent().and_then(dir_date);
+    let deleted_at = match (modified, day) {
+        (Some(modified), Some(day)) if modified.date_naive() == day => modified,
+        (_, Some(day)) => day.and_time(NaiveTime::MIN).and_utc(),
+        (Some(modified), None) => modified,
+        (None, None) => Utc::now(),
+    };
+
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode()
+    };
+    #[cfg(not(unix))]
+    let permissions = 0o644;
+
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+    let deleted_by = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    Ok(FileMeta {
+        id,
+        original_path: home.join("rmz-recovered").join(id.to_string()),
+        deleted_at,
+        size: crate::trash::stored_size(payload),
+        permissions,
+        tags: vec![RECOVERED_TAG.to_string()],
+        note: Some("Adopted by rmz doctor from a payload without metadata".to_string()),
+        checksum: None,
+        deleted_by,
+        compression: None,
+        deduplicated: false,
+        encryption: None,
+        git: None,
+    })
+}
+
+/// Delete `path`, or move it to the quarantine directory
+fn discard(store: &TrashStore, path: &Path, options: &RepairOptions) -> Result<()> {
+    if options.quarantine {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let stamp = Utc::now().format("%Y%m%dT%H%M%S");
+        let target = store.get_trash_root().join(QUARANTINE_DIR).join(format!("{}-{}", stamp, name));
+        move_into(path, &target)
+    } else if path.is_dir() {
+        Ok(fs::remove_dir_all(path)?)
+    } else {
+        Ok(fs::remove_file(path)?)
+    }
+}
+
+/// Rename `source` to `target`, creating the parent and refusing to overwrite
+fn move_into(source: &Path, target: &Path) -> Result<()> {
+    if target.exists() {
+        anyhow::bail!("{} already exists", target.display());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(source, target)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_permissions(store: &TrashStore) -> Result<Vec<Finding>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = store.get_trash_root();
+    let mut dirs = vec![root.clone()];
+    let mut files = json_files(store.meta_store().metadata_dir())?;
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        } else {
+            files.push(path);
+        }
+    }
+    let blobs = store.blob_store().root();
+    if blobs.is_dir() {
+        for entry in fs::read_dir(blobs)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (paths, owner) in [(dirs, 0o700), (files, 0o600)] {
+        for path in paths {
+            let mode = fs::symlink_metadata(&path)?.permissions().mode() & 0o7777;
+            let expected = (mode | owner) & !0o002;
+            if mode != expected {
+                findings.push(Finding::Permissions { path, mode, expected });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_store: &TrashStore) -> Result<Vec<Finding>> {
+    Ok(Vec::new())
+}
+
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+    Ok(())
+}
+
+fn read_meta(path: &Path) -> Result<FileMeta> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Subdirectories of the trash root named after a deletion date
+fn date_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if path.is_dir() && dir_date(&path).is_some() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn dir_date(dir: &Path) -> Option<NaiveDate> {
+    let name = dir.file_name()?.to_str()?;
+    NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()
+}
+
+fn file_id(path: &Path) -> Option<Uuid> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+/// Format suffix after `<id>.rmz`: "", "zst", "tar.zst", "manifest", "enc" or "tar.enc"
+fn payload_suffix(name: &str) -> Option<&str> {
+    let (_, rest) = name.split_once(".rmz")?;
+    match rest.strip_prefix('.') {
+        None if rest.is_empty() => Some(""),
+        Some(suffix @ ("zst" | "tar.zst" | "manifest" | "enc" | "tar.enc")) => Some(suffix),
+        _ => None,
+    }
+}
+
+fn payload_id(path: &Path) -> Option<Uuid> {
+    let name = path.file_name()?.to_str()?;
+    payload_suffix(name)?;
+    name.split_once(".rmz")?.0.parse().ok()
+}
+
+fn is_stale(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if !path.is_file() || !STALE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return false;
+    }
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() >= STALE_AFTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::trash_store::TrashStoreInterface;
+    use chrono::Duration as ChronoDuration;
+    use tempfile::TempDir;
+
+    fn trash_file(store: &TrashStore, dir: &Path, name: &str) -> FileMeta {
+        let path = dir.join(name);
+        fs::write(&path, name).unwrap();
+        let meta = FileMeta::from_path(&path).unwrap();
+        store.save(&meta, &path).unwrap();
+        meta
+    }
+
+    fn age(path: &Path) {
+        let old = SystemTime::now() - STALE_AFTER * 2;
+        fs::File::options().write(true).open(path).unwrap().set_modified(old).unwrap();
+    }
+
+    #[test]
+    fn test_scan_and_repair() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("trash");
+        let legacy_dir = temp_dir.path().join("metadata");
+        let store = TrashStore::new(root.clone());
+        let options = RepairOptions::default();
+
+        let healthy = trash_file(&store, temp_dir.path(), "healthy.txt");
+        assert!(scan(&store, &legacy_dir).unwrap().is_empty());
+
+        // Metadata without payload
+        let missing = trash_file(&store, temp_dir.path(), "missing.txt");
+        fs::remove_file(store.payload_path(&missing)).unwrap();
+
+        // Payload without metadata
+        let orphan = trash_file(&store, temp_dir.path(), "orphan.txt");
+        store.meta_store().delete_metadata(&orphan.id).unwrap();
+
+        // Payload under the wrong date
+        let mut misplaced = trash_file(&store, temp_dir.path(), "misplaced.txt");
+        misplaced.deleted_at -= ChronoDuration::days(3);
+        store.meta_store().save_metadata(&misplaced).unwrap();
+
+        // Metadata copied into the legacy directory
+        fs::create_dir_all(&legacy_dir).unwrap();
+        fs::copy(
+            store.meta_store().metadata_file_path(&healthy.id),
+            legacy_dir.join(format!("{}.json", healthy.id)),
+        )
+        .unwrap();
+
+        let stale = root.join("operations.jsonl.lock");
+        fs::write(&stale, "").unwrap();
+        age(&stale);
+        fs::write(root.join("fresh.tmp"), "").unwrap();
+
+        let findings = scan(&store, &legacy_dir).unwrap();
+        assert_eq!(findings.len(), 5, "{:#?}", findings);
+        assert!(findings.contains(&Finding::MissingPayload {
+            id: missing.id,
+            metadata: store.meta_store().metadata_file_path(&missing.id),
+        }));
+        assert!(findings.iter().any(|f| matches!(f, Finding::OrphanedPayload { id, .. } if *id == orphan.id)));
+        assert!(findings.iter().any(|f| matches!(f, Finding::MisplacedPayload { id, .. } if *id == misplaced.id)));
+        assert!(findings.iter().any(|f| matches!(f, Finding::DuplicateMetadata { identical: true, .. })));
+        assert!(findings.contains(&Finding::StaleFile { path: stale }));
+
+        for finding in &findings {
+            repair(&store, finding, &options).unwrap();
+        }
+        assert!(scan(&store, &legacy_dir).unwrap().is_empty());
+
+        let items = store.list().unwrap();
+        assert_eq!(items.len(), 3);
+        let adopted = store.find_by_id(&orphan.id).unwrap().unwrap();
+        assert_eq!(adopted.meta.tags, vec![RECOVERED_TAG]);
+        assert_eq!(adopted.meta.size, "orphan.txt".len() as u64);
+        assert!(store.find_by_id(&misplaced.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_mismatched_payload_keeps_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+        let meta = trash_file(&store, temp_dir.path(), "notes.txt");
+        let item = store.compress(&meta.id, 3).unwrap();
+
+        // Compression finished but the metadata update was lost
+        store.meta_store().save_metadata(&meta).unwrap();
+        let findings = scan(&store, temp_dir.path()).unwrap();
+        assert_eq!(findings, vec![Finding::MismatchedPayload { id: meta.id, payload: item.trash_path.clone() }]);
+
+        repair(&store, &findings[0], &RepairOptions::default()).unwrap();
+        let repaired = store.find_by_id(&meta.id).unwrap().unwrap();
+        assert_eq!(repaired.meta.original_path, meta.original_path);
+        assert_eq!(repaired.meta.compression.unwrap().kind, CompressionKind::Zstd);
+    }
+
+    #[test]
+    fn test_quarantine_and_legacy_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+        let legacy_dir = temp_dir.path().join("metadata");
+        let options = RepairOptions { quarantine: true };
+
+        let broken = store.meta_store().metadata_dir().join(format!("{}.json", Uuid::new_v4()));
+        fs::create_dir_all(broken.parent().unwrap()).unwrap();
+        fs::write(&broken, "{").unwrap();
+
+        let moved = trash_file(&store, temp_dir.path(), "moved.txt");
+        fs::create_dir_all(&legacy_dir).unwrap();
+        let legacy = legacy_dir.join(format!("{}.json", moved.id));
+        fs::rename(store.meta_store().metadata_file_path(&moved.id), &legacy).unwrap();
+
+        let findings = scan(&store, &legacy_dir).unwrap();
+        assert_eq!(findings.len(), 2, "{:#?}", findings);
+        assert!(matches!(findings[0], Finding::CorruptMetadata { .. }));
+        assert_eq!(findings[1], Finding::LegacyMetadata { id: moved.id, legacy });
+        assert_eq!(findings[0].action(&options), "Quarantine the metadata");
+
+        for finding in &findings {
+            repair(&store, finding, &options).unwrap();
+        }
+        assert!(scan(&store, &legacy_dir).unwrap().is_empty());
+        assert!(store.find_by_id(&moved.id).unwrap().is_some());
+        assert_eq!(fs::read_dir(store.get_trash_root().join(QUARANTINE_DIR)).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+        let meta = trash_file(&store, temp_dir.path(), "file.txt");
+        let metadata = store.meta_store().metadata_file_path(&meta.id);
+        fs::set_permissions(&metadata, fs::Permissions::from_mode(0o446)).unwrap();
+
+        let findings = scan(&store, temp_dir.path()).unwrap();
+        assert_eq!(findings, vec![Finding::Permissions { path: metadata.clone(), mode: 0o446, expected: 0o644 }]);
+        repair(&store, &findings[0], &RepairOptions::default()).unwrap();
+        assert_eq!(fs::metadata(&metadata).unwrap().permissions().mode() & 0o7777, 0o644);
+    }
+
+    #[test]
+    fn test_payload_suffix() {
+        assert_eq!(payload_suffix("x.rmz"), Some(""));
+        assert_eq!(payload_suffix("x.rmz.tar.zst"), Some("tar.zst"));
+        assert_eq!(payload_suffix("x.rmz.tmp"), None);
+        assert_eq!(payload_suffix("x.rmzz"), None);
+    }
+}
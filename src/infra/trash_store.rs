@@ -1,13 +1,21 @@
-use crate::domain::{FileMeta, TrashItem};
-use crate::infra::{meta_store::MetaStoreInterface, MetaStore};
+use crate::domain::{
+    BlobManifest, CompressionConfig, CompressionInfo, CompressionKind, EncryptedKind, EncryptionConfig,
+    EncryptionInfo, FileMeta, ManifestEntry, ManifestEntryKind, TrashItem,
+};
+use crate::infra::encryption::{self, EncryptionKey, KeyManager};
+use crate::infra::{compression, meta_store::MetaStoreInterface, BlobStore, MetaStore};
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use chrono::{Duration, Utc};
+use std::cell::OnceCell;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use uuid::Uuid;
 
 /// Interface for trash storage operations
 pub trait TrashStoreInterface {
     fn save(&self, meta: &FileMeta, source_path: &Path) -> Result<TrashItem>;
     fn restore(&self, id: &Uuid) -> Result<PathBuf>;
+    fn restore_to(&self, id: &Uuid, destination: &Path) -> Result<PathBuf>;
     fn list(&self) -> Result<Vec<TrashItem>>;
     fn purge(&self, id: &Uuid) -> Result<()>;
     fn find_by_id(&self, id: &Uuid) -> Result<Option<TrashItem>>;
@@ -17,21 +25,78 @@ pub trait TrashStoreInterface {
 pub struct TrashStore {
     trash_root: PathBuf,
     meta_store: MetaStore,
+    blob_store: BlobStore,
+    key_manager: KeyManager,
+    encryption: EncryptionConfig,
+    key: OnceCell<EncryptionKey>,
+    previous_key: OnceCell<Option<EncryptionKey>>,
+}
+
+/// Placeholder shown instead of an original path that is stored encrypted
+pub const ENCRYPTED_PATH_PLACEHOLDER: &str = "[encrypted]";
+
+/// Result of a pass over many items where one failure must not stop the rest
+#[derive(Debug, Default)]
+pub struct SweepOutcome {
+    /// Items processed successfully, in their new form
+    pub processed: Vec<TrashItem>,
+    /// Items left untouched and why
+    pub failed: Vec<(TrashItem, anyhow::Error)>,
 }
 
 impl TrashStore {
     pub fn new(trash_root: PathBuf) -> Self {
         let meta_store = MetaStore::new(trash_root.join("metadata"));
+        let blob_store = BlobStore::new(trash_root.join("blobs"));
+        let key_manager = KeyManager::new(trash_root.clone(), EncryptionConfig::default());
         Self {
             trash_root,
             meta_store,
+            blob_store,
+            key_manager,
+            encryption: EncryptionConfig::default(),
+            key: OnceCell::new(),
+            previous_key: OnceCell::new(),
+        }
+    }
+
+    /// Use the given encryption settings for new items and key lookup
+    pub fn with_encryption(mut self, config: &EncryptionConfig) -> Self {
+        self.key_manager = KeyManager::new(self.trash_root.clone(), config.clone());
+        self.encryption = config.clone();
+        self
+    }
+
+    /// Use an already unlocked key instead of asking for the secret
+    pub fn with_key(self, key: EncryptionKey) -> Self {
+        let _ = self.key.set(key);
+        self
+    }
+
+    pub fn key_manager(&self) -> &KeyManager {
+        &self.key_manager
+    }
+
+    /// Get the encryption key, unlocking it on first use
+    pub fn key(&self) -> Result<&EncryptionKey> {
+        if self.key.get().is_none() {
+            let _ = self.key.set(self.key_manager.load_key()?);
         }
+        Ok(self.key.get().expect("key was just initialized"))
     }
 
     pub fn get_trash_root(&self) -> &PathBuf {
         &self.trash_root
     }
 
+    pub fn blob_store(&self) -> &BlobStore {
+        &self.blob_store
+    }
+
+    pub fn meta_store(&self) -> &MetaStore {
+        &self.meta_store
+    }
+
     /// Get the subdirectory for a given date
     fn get_date_subdir(&self, meta: &FileMeta) -> PathBuf {
         let date_str = meta.deleted_at.format("%Y-%m-%d").to_string();
@@ -40,7 +105,545 @@ impl TrashStore {
 
     /// Generate unique filename for trash
     fn generate_trash_filename(&self, meta: &FileMeta) -> String {
-        format!("{}.rmz", meta.id)
+        match &meta.compression {
+            Some(info) => format!("{}.rmz.{}", meta.id, info.kind.extension()),
+            None if meta.is_encrypted() => {
+                let kind = meta.encryption.as_ref().map(|info| info.kind);
+                format!("{}.rmz.{}", meta.id, kind.unwrap_or(EncryptedKind::File).extension())
+            }
+            None if meta.deduplicated => format!("{}.rmz.manifest", meta.id),
+            None => format!("{}.rmz", meta.id),
+        }
+    }
+
+    /// Get the path of the stored payload for the given metadata
+    pub fn payload_path(&self, meta: &FileMeta) -> PathBuf {
+        self.get_date_subdir(meta)
+            .join(self.generate_trash_filename(meta))
+    }
+
+    /// Copy an item out of trash without removing it, decompressing if needed
+    pub fn extract_to(&self, item: &TrashItem, destination: &Path) -> Result<()> {
+        if let Some(info) = &item.meta.encryption {
+            self.key_for(info)?.decrypt_path(&item.trash_path, info.kind, destination)?;
+            if info.kind == EncryptedKind::File {
+                set_mode(destination, item.meta.permissions);
+            }
+            Ok(())
+        } else if item.meta.deduplicated {
+            self.materialize(&BlobManifest::load(&item.trash_path)?, destination)
+        } else if let Some(info) = &item.meta.compression {
+            compression::decompress(&item.trash_path, info.kind, destination)
+        } else if item.trash_path.is_dir() {
+            copy_dir_recursive(&item.trash_path, destination)
+        } else {
+            std::fs::copy(&item.trash_path, destination)?;
+            Ok(())
+        }
+    }
+
+    /// Compress a single trashed item in place and update its metadata
+    pub fn compress(&self, id: &Uuid, level: i32) -> Result<TrashItem> {
+        let item = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("File with ID {} not found in trash", id))?;
+
+        if item.meta.is_compressed() || item.meta.deduplicated || item.meta.is_encrypted() {
+            return Ok(item);
+        }
+
+        let mut meta = item.meta.clone();
+        let temp_path = item.trash_path.with_extension("rmz.tmp");
+        let (kind, compressed_size) = match compression::compress(&item.trash_path, &temp_path, level) {
+            Ok(result) => result,
+            Err(e) => {
+                std::fs::remove_file(&temp_path).ok();
+                return Err(e);
+            }
+        };
+        meta.compression = Some(CompressionInfo {
+            kind,
+            original_size: meta.size,
+            compressed_size,
+        });
+
+        // Move the compressed payload into place before pointing metadata at it,
+        // so an interrupted run never leaves metadata without a payload
+        let compressed_path = self.payload_path(&meta);
+        std::fs::rename(&temp_path, &compressed_path)?;
+        self.meta_store.save_metadata(&meta)?;
+
+        if item.trash_path.is_dir() {
+            std::fs::remove_dir_all(&item.trash_path)?;
+        } else {
+            std::fs::remove_file(&item.trash_path)?;
+        }
+
+        Ok(TrashItem::new(meta, compressed_path))
+    }
+
+    /// Compress every uncompressed item that is old enough according to `config`
+    ///
+    /// An item that fails to compress stays uncompressed and is reported in
+    /// [`SweepOutcome::failed`]; the sweep carries on with the next one.
+    pub fn compress_eligible(&self, config: &CompressionConfig) -> Result<SweepOutcome> {
+        let cutoff = Utc::now() - Duration::days(config.min_age_days as i64);
+        let mut outcome = SweepOutcome::default();
+
+        for item in self.list()? {
+            if item.meta.is_compressed()
+                || item.meta.deduplicated
+                || item.meta.is_encrypted()
+                || item.meta.deleted_at > cutoff
+            {
+                continue;
+            }
+            match self.compress(&item.meta.id, config.level) {
+                Ok(compressed) => outcome.processed.push(compressed),
+                Err(e) => outcome.failed.push((item, e)),
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Move a trashed item's file contents into the blob store
+    ///
+    /// The payload is replaced by a manifest that references one blob per
+    /// file, so identical content deleted repeatedly is stored only once.
+    pub fn deduplicate(&self, id: &Uuid) -> Result<TrashItem> {
+        let item = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("File with ID {} not found in trash", id))?;
+
+        if item.meta.deduplicated || item.meta.is_compressed() || item.meta.is_encrypted() {
+            return Ok(item);
+        }
+
+        let mut manifest = BlobManifest::default();
+        collect_manifest_entries(&item.trash_path, Path::new(""), &mut manifest.entries)?;
+
+        let mut ingested = Vec::new();
+        for entry in manifest.entries.iter_mut() {
+            if entry.kind == ManifestEntryKind::File {
+                let source = entry_destination(&item.trash_path, entry);
+                match self.blob_store.ingest(&source) {
+                    Ok(hash) => {
+                        ingested.push(hash.clone());
+                        entry.blob = Some(hash);
+                    }
+                    Err(e) => {
+                        // Roll back references taken so far
+                        self.blob_store.release(&ingested)?;
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        let mut meta = item.meta.clone();
+        meta.deduplicated = true;
+
+        // Write the manifest and metadata before dropping the raw payload
+        let manifest_path = self.payload_path(&meta);
+        manifest.save(&manifest_path)?;
+        self.meta_store.save_metadata(&meta)?;
+
+        if item.trash_path.is_dir() {
+            std::fs::remove_dir_all(&item.trash_path)?;
+        } else {
+            std::fs::remove_file(&item.trash_path)?;
+        }
+
+        Ok(TrashItem::new(meta, manifest_path))
+    }
+
+    /// Encrypt a trashed item in place, optionally hiding its original path
+    pub fn encrypt(&self, id: &Uuid) -> Result<TrashItem> {
+        let item = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("File with ID {} not found in trash", id))?;
+
+        if item.meta.is_encrypted() || item.meta.is_compressed() || item.meta.deduplicated {
+            return Ok(item);
+        }
+
+        let key = self.key()?;
+        let temp_path = item.trash_path.with_extension("rmz.tmp");
+        let kind = match key.encrypt_path(&item.trash_path, &temp_path) {
+            Ok(kind) => kind,
+            Err(e) => {
+                std::fs::remove_file(&temp_path).ok();
+                return Err(e);
+            }
+        };
+
+        let mut meta = item.meta.clone();
+        let encrypted_path = if self.encryption.encrypt_paths {
+            // The repository root would reveal where the item came from
+            meta.git = None;
+            let path = std::mem::replace(&mut meta.original_path, PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER));
+            Some(encryption::to_hex(&key.encrypt_bytes(path.to_string_lossy().as_bytes())?))
+        } else {
+            None
+        };
+        meta.encryption = Some(EncryptionInfo {
+            kind,
+            key_id: key.id().to_string(),
+            encrypted_path,
+        });
+
+        // Same ordering as compression: payload first, then metadata, then plaintext removal
+        let encrypted_payload = self.payload_path(&meta);
+        std::fs::rename(&temp_path, &encrypted_payload)?;
+        self.meta_store.save_metadata(&meta)?;
+
+        if item.trash_path.is_dir() {
+            std::fs::remove_dir_all(&item.trash_path)?;
+        } else {
+            std::fs::remove_file(&item.trash_path)?;
+        }
+
+        Ok(TrashItem::new(meta, encrypted_payload))
+    }
+
+    /// Return the item with its original path decrypted, if it was encrypted
+    pub fn reveal(&self, item: &TrashItem) -> Result<TrashItem> {
+        let Some(encrypted_path) = item
+            .meta
+            .encryption
+            .as_ref()
+            .and_then(|info| info.encrypted_path.as_ref())
+        else {
+            return Ok(item.clone());
+        };
+
+        let info = item.meta.encryption.as_ref().expect("encrypted path implies encryption info");
+        let path = self.key_for(info)?.decrypt_bytes(&encryption::from_hex(encrypted_path)?)?;
+        let mut revealed = item.clone();
+        revealed.meta.original_path = PathBuf::from(String::from_utf8(path)?);
+        Ok(revealed)
+    }
+
+    /// List all items, along with the IDs whose metadata exists but whose file is missing
+    pub fn list_checked(&self) -> Result<(Vec<TrashItem>, Vec<Uuid>)> {
+        let mut items = Vec::new();
+        let mut missing = Vec::new();
+
+        if !self.trash_root.exists() {
+            return Ok((items, missing));
+        }
+
+        // Only include items where the actual file exists
+        for meta in self.meta_store.list_all_metadata()? {
+            let trash_path = self.payload_path(&meta);
+            if trash_path.exists() {
+                items.push(TrashItem::new(meta, trash_path));
+            } else {
+                missing.push(meta.id);
+            }
+        }
+
+        Ok((items, missing))
+    }
+
+    /// List all items with their original paths decrypted
+    ///
+    /// The key is only requested when at least one path is encrypted.
+    pub fn list_revealed(&self) -> Result<Vec<TrashItem>> {
+        self.list()?.iter().map(|item| self.reveal(item)).collect()
+    }
+
+    /// Open a reader over the content of a single-file item, decrypting if needed
+    pub fn open_content(&self, item: &TrashItem) -> Result<Box<dyn Read>> {
+        match &item.meta.encryption {
+            Some(info) if info.kind == EncryptedKind::File => {
+                let file = std::io::BufReader::new(std::fs::File::open(&item.trash_path)?);
+                Ok(Box::new(self.key_for(info)?.decrypting_reader(file)?))
+            }
+            Some(_) => anyhow::bail!("Cannot read directory archive as a single file: {}", item.trash_path.display()),
+            None if item.meta.deduplicated => {
+                let manifest = BlobManifest::load(&item.trash_path)?;
+                let hash = manifest
+                    .single_file()
+                    .and_then(|entry| entry.blob.as_ref())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Cannot read directory manifest as a single file: {}", item.trash_path.display())
+                    })?;
+                Ok(Box::new(std::io::BufReader::new(std::fs::File::open(self.blob_store.blob_path(hash))?)))
+            }
+            None => match &item.meta.compression {
+                Some(info) => compression::open_reader(&item.trash_path, info.kind),
+                None => Ok(Box::new(std::io::BufReader::new(std::fs::File::open(&item.trash_path)?))),
+            },
+        }
+    }
+
+    /// SHA-256 of a single-file item's original content, whatever form it is stored in
+    pub fn content_checksum(&self, item: &TrashItem) -> Result<String> {
+        use sha2::Digest;
+
+        let mut reader = self.open_content(item)?;
+        let mut hasher = sha2::Sha256::new();
+        std::io::copy(&mut reader, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Compare a single-file item's content against its recorded checksum (if any)
+    pub fn verify_integrity(&self, item: &TrashItem) -> Result<bool> {
+        match &item.meta.checksum {
+            Some(expected) => Ok(self.content_checksum(item)? == *expected),
+            None => Ok(true),
+        }
+    }
+
+    /// Open a reader over a file inside a trashed directory, decrypting if needed
+    ///
+    /// `inner` is relative to the trashed directory.
+    pub fn open_inner(&self, item: &TrashItem, inner: &Path) -> Result<Box<dyn Read>> {
+        if inner.as_os_str().is_empty() || inner.components().any(|c| !matches!(c, Component::Normal(_))) {
+            anyhow::bail!("Inner path must be relative to the trashed directory: {}", inner.display());
+        }
+        let not_found = || anyhow::anyhow!("No file '{}' in {}", inner.display(), item.meta.original_path.display());
+
+        if let Some(info) = &item.meta.encryption {
+            if info.kind != EncryptedKind::Tar {
+                anyhow::bail!("Not a directory: {}", item.meta.original_path.display());
+            }
+            let file = std::io::BufReader::new(std::fs::File::open(&item.trash_path)?);
+            read_from_archive(self.key_for(info)?.decrypting_reader(file)?, inner)?.ok_or_else(not_found)
+        } else if item.meta.deduplicated {
+            let manifest = BlobManifest::load(&item.trash_path)?;
+            let hash = manifest
+                .entries
+                .iter()
+                .find(|entry| entry.kind == ManifestEntryKind::File && entry.path == inner)
+                .and_then(|entry| entry.blob.as_ref())
+                .ok_or_else(not_found)?;
+            Ok(Box::new(std::io::BufReader::new(std::fs::File::open(self.blob_store.blob_path(hash))?)))
+        } else if let Some(info) = &item.meta.compression {
+            if info.kind != CompressionKind::TarZstd {
+                anyhow::bail!("Not a directory: {}", item.meta.original_path.display());
+            }
+            let decoder = zstd::Decoder::new(std::fs::File::open(&item.trash_path)?)?;
+            read_from_archive(decoder, inner)?.ok_or_else(not_found)
+        } else if item.trash_path.is_dir() {
+            let path = item.trash_path.join(inner);
+            if !path.is_file() {
+                return Err(not_found());
+            }
+            Ok(Box::new(std::io::BufReader::new(std::fs::File::open(path)?)))
+        } else {
+            anyhow::bail!("Not a directory: {}", item.meta.original_path.display())
+        }
+    }
+
+    /// Replace the tags recorded for an item
+    pub fn set_tags(&self, id: &Uuid, tags: Vec<String>) -> Result<TrashItem> {
+        let mut item = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("File with ID {} not found in trash", id))?;
+        item.meta.tags = tags;
+        self.meta_store.save_metadata(&item.meta)?;
+        Ok(item)
+    }
+
+    /// Replace the note recorded for an item
+    pub fn set_note(&self, id: &Uuid, note: Option<String>) -> Result<TrashItem> {
+        let mut item = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("File with ID {} not found in trash", id))?;
+        item.meta.note = note;
+        self.meta_store.save_metadata(&item.meta)?;
+        Ok(item)
+    }
+
+    /// Verify that an encrypted item can be decrypted with the current key
+    pub fn verify_decryptable(&self, item: &TrashItem) -> Result<()> {
+        let Some(info) = &item.meta.encryption else {
+            return Ok(());
+        };
+
+        let key = self.key_for(info)?;
+        key.decrypt_stream(
+            std::io::BufReader::new(std::fs::File::open(&item.trash_path)?),
+            std::io::sink(),
+        )?;
+        if let Some(encrypted_path) = &info.encrypted_path {
+            key.decrypt_bytes(&encryption::from_hex(encrypted_path)?)?;
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt items still on the previous key with the current key
+    ///
+    /// Run after [`KeyManager::begin_rotation`], and again to resume an
+    /// interrupted rotation. Items that cannot be decrypted, e.g. because they
+    /// use an unknown key, are reported in [`SweepOutcome::failed`] and left as is.
+    pub fn rotate_key(&self) -> Result<SweepOutcome> {
+        let key = self.key()?;
+        let mut outcome = SweepOutcome::default();
+
+        for item in self.list()? {
+            let Some(info) = &item.meta.encryption else {
+                continue;
+            };
+            if info.key_id == key.id() {
+                // Metadata is switched before the payload; finish an item interrupted in between
+                let temp_path = rotation_temp_path(&item.trash_path);
+                if temp_path.exists() {
+                    match std::fs::rename(&temp_path, &item.trash_path) {
+                        Ok(()) => outcome.processed.push(item),
+                        Err(e) => outcome.failed.push((item, e.into())),
+                    }
+                }
+                continue;
+            }
+
+            match self.rotate_item(&item, info, key) {
+                Ok(rotated) => outcome.processed.push(rotated),
+                Err(e) => outcome.failed.push((item, e)),
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Re-encrypt one item's payload and path with `new_key`
+    fn rotate_item(&self, item: &TrashItem, info: &EncryptionInfo, new_key: &EncryptionKey) -> Result<TrashItem> {
+        let old_key = self.key_for(info)?;
+
+        let mut info = info.clone();
+        if let Some(encrypted_path) = &info.encrypted_path {
+            let path = old_key.decrypt_bytes(&encryption::from_hex(encrypted_path)?)?;
+            info.encrypted_path = Some(encryption::to_hex(&new_key.encrypt_bytes(&path)?));
+        }
+        info.key_id = new_key.id().to_string();
+
+        // Stream old ciphertext through decryption straight into the new ciphertext
+        let temp_path = rotation_temp_path(&item.trash_path);
+        let written = (|| -> Result<()> {
+            let input = std::io::BufReader::new(std::fs::File::open(&item.trash_path)?);
+            let output = std::io::BufWriter::new(std::fs::File::create(&temp_path)?);
+            let mut output = new_key.encrypting_writer(output)?;
+            std::io::copy(&mut old_key.decrypting_reader(input)?, &mut output)?;
+            output.finish()?.into_inner()?.sync_all()?;
+            Ok(())
+        })();
+        if let Err(e) = written {
+            std::fs::remove_file(&temp_path).ok();
+            return Err(e);
+        }
+
+        // Metadata first: if interrupted before the rename, the next rotation
+        // finds the complete new payload next to the old one and moves it in
+        let mut meta = item.meta.clone();
+        meta.encryption = Some(info);
+        self.meta_store.save_metadata(&meta)?;
+        std::fs::rename(&temp_path, &item.trash_path)?;
+
+        Ok(TrashItem::new(meta, item.trash_path.clone()))
+    }
+
+    /// Key that decrypts `info`'s item: the current one, or the previous one during a rotation
+    fn key_for(&self, info: &EncryptionInfo) -> Result<&EncryptionKey> {
+        let key = self.key()?;
+        if info.key_id == key.id() {
+            return Ok(key);
+        }
+
+        if self.previous_key.get().is_none() {
+            let _ = self.previous_key.set(self.key_manager.previous_key(key)?);
+        }
+        match self.previous_key.get().and_then(Option::as_ref) {
+            Some(previous) if previous.id() == info.key_id => Ok(previous),
+            _ => anyhow::bail!(
+                "Item was encrypted with key {} but the current key is {}",
+                info.key_id,
+                key.id()
+            ),
+        }
+    }
+
+    /// Remove metadata whose payload no longer exists, returning the affected IDs
+    pub fn remove_orphaned_metadata(&self) -> Result<Vec<Uuid>> {
+        let mut removed = Vec::new();
+        for meta in self.meta_store.list_all_metadata()? {
+            if !self.payload_path(&meta).exists() {
+                self.meta_store.delete_metadata(&meta.id)?;
+                removed.push(meta.id);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Remove temporary payloads left behind by interrupted compression or encryption
+    ///
+    /// Nothing is removed while a key rotation is unfinished, since resuming it
+    /// may need the re-encrypted payloads waiting next to their items.
+    pub fn remove_temp_files(&self) -> Result<usize> {
+        let mut removed = 0;
+        if !self.trash_root.exists() || self.key_manager.pending_rotation()?.is_some() {
+            return Ok(removed);
+        }
+
+        for date_dir in std::fs::read_dir(&self.trash_root)? {
+            let date_dir = date_dir?.path();
+            if !date_dir.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&date_dir)? {
+                let path = entry?.path();
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                if name.contains(".rmz") && name.ends_with(".tmp") && path.is_file() {
+                    std::fs::remove_file(&path)?;
+                    removed += 1;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Recreate a deduplicated item at `destination` from its blobs
+    fn materialize(&self, manifest: &BlobManifest, destination: &Path) -> Result<()> {
+        for entry in &manifest.entries {
+            let target = entry_destination(destination, entry);
+            match entry.kind {
+                ManifestEntryKind::Directory => std::fs::create_dir_all(&target)?,
+                ManifestEntryKind::File => {
+                    let hash = entry
+                        .blob
+                        .as_ref()
+                        .ok_or_else(|| anyhow::anyhow!("Manifest entry without blob: {}", entry.path.display()))?;
+                    std::fs::copy(self.blob_store.blob_path(hash), &target)?;
+                    set_mode(&target, entry.mode);
+                }
+                ManifestEntryKind::Symlink => {
+                    #[cfg(unix)]
+                    if let Some(link_target) = &entry.target {
+                        std::os::unix::fs::symlink(link_target, &target)?;
+                    }
+                }
+            }
+        }
+
+        // Apply directory modes last so read-only directories can be populated first
+        for entry in manifest.entries.iter().rev() {
+            if entry.kind == ManifestEntryKind::Directory {
+                set_mode(&entry_destination(destination, entry), entry.mode);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop a deduplicated item's blob references and collect unreferenced blobs
+    fn release_blobs(&self, manifest_path: &Path) -> Result<()> {
+        let manifest = BlobManifest::load(manifest_path)?;
+        self.blob_store.release(&manifest.blob_hashes())?;
+        self.blob_store.gc()?;
+        Ok(())
     }
 }
 
@@ -86,60 +689,61 @@ impl TrashStoreInterface for TrashStore {
     fn restore(&self, id: &Uuid) -> Result<PathBuf> {
         if let Some(item) = self.find_by_id(id)? {
             // Restore to original location
-            let original_path = &item.meta.original_path;
+            let item = self.reveal(&item)?;
+            self.restore_to(id, &item.meta.original_path)
+        } else {
+            anyhow::bail!("File with ID {} not found in trash", id);
+        }
+    }
 
+    fn restore_to(&self, id: &Uuid, destination: &Path) -> Result<PathBuf> {
+        if let Some(item) = self.find_by_id(id)? {
             // Ensure parent directory exists
-            if let Some(parent) = original_path.parent() {
+            if let Some(parent) = destination.parent() {
                 std::fs::create_dir_all(parent)?;
             }
 
-            // Move file back
-            std::fs::rename(&item.trash_path, original_path)?;
+            if let Some(info) = &item.meta.encryption {
+                // Decrypt to the destination, then drop the ciphertext
+                self.key_for(info)?.decrypt_path(&item.trash_path, info.kind, destination)?;
+                if info.kind == EncryptedKind::File {
+                    set_mode(destination, item.meta.permissions);
+                }
+                std::fs::remove_file(&item.trash_path)?;
+            } else if item.meta.deduplicated {
+                // Rebuild from blobs, then drop the manifest and its references
+                self.materialize(&BlobManifest::load(&item.trash_path)?, destination)?;
+                self.release_blobs(&item.trash_path)?;
+                std::fs::remove_file(&item.trash_path)?;
+            } else if let Some(info) = &item.meta.compression {
+                // Unpack the payload, then drop the compressed copy
+                compression::decompress(&item.trash_path, info.kind, destination)?;
+                std::fs::remove_file(&item.trash_path)?;
+            } else {
+                // Move file back
+                std::fs::rename(&item.trash_path, destination)?;
+            }
 
             // Remove metadata after successful restore
             self.meta_store.delete_metadata(id)?;
 
-            Ok(original_path.clone())
+            Ok(destination.to_path_buf())
         } else {
             anyhow::bail!("File with ID {} not found in trash", id);
         }
     }
 
     fn list(&self) -> Result<Vec<TrashItem>> {
-        let mut items = Vec::new();
-
-        if !self.trash_root.exists() {
-            return Ok(items);
-        }
-
-        // Get all metadata from MetaStore
-        let all_metadata = self.meta_store.list_all_metadata()?;
-
-        // Convert metadata to TrashItems, checking if files exist
-        for meta in all_metadata {
-            let date_dir = self.get_date_subdir(&meta);
-            let filename = self.generate_trash_filename(&meta);
-            let trash_path = date_dir.join(&filename);
-
-            // Only include items where the actual file exists
-            if trash_path.exists() {
-                items.push(TrashItem::new(meta, trash_path));
-            } else {
-                // File is missing, optionally clean up metadata
-                eprintln!(
-                    "Warning: Metadata exists but file missing for ID: {}",
-                    meta.id
-                );
-            }
-        }
-
-        Ok(items)
+        Ok(self.list_checked()?.0)
     }
 
     fn purge(&self, id: &Uuid) -> Result<()> {
         if let Some(item) = self.find_by_id(id)? {
             // Remove the actual file or directory
-            if item.trash_path.is_dir() {
+            if item.meta.deduplicated {
+                self.release_blobs(&item.trash_path)?;
+                std::fs::remove_file(&item.trash_path)?;
+            } else if item.trash_path.is_dir() {
                 std::fs::remove_dir_all(&item.trash_path)?;
             } else {
                 std::fs::remove_file(&item.trash_path)?;
@@ -157,16 +761,12 @@ impl TrashStoreInterface for TrashStore {
     fn find_by_id(&self, id: &Uuid) -> Result<Option<TrashItem>> {
         // Load metadata for the specific ID
         if let Some(meta) = self.meta_store.load_metadata(id)? {
-            let date_dir = self.get_date_subdir(&meta);
-            let filename = self.generate_trash_filename(&meta);
-            let trash_path = date_dir.join(&filename);
+            let trash_path = self.payload_path(&meta);
 
-            // Check if the actual file exists
+            // Metadata whose file is missing is reported by `list_checked`
             if trash_path.exists() {
                 Ok(Some(TrashItem::new(meta, trash_path)))
             } else {
-                // Metadata exists but file is missing
-                eprintln!("Warning: Metadata exists but file missing for ID: {}", id);
                 Ok(None)
             }
         } else {
@@ -175,6 +775,128 @@ impl TrashStoreInterface for TrashStore {
     }
 }
 
+/// Where a re-encrypted payload is written before it replaces the current one
+fn rotation_temp_path(payload: &Path) -> PathBuf {
+    let mut path = payload.as_os_str().to_owned();
+    path.push(".tmp");
+    PathBuf::from(path)
+}
+
+/// Stream one file out of a tar archive
+///
+/// Headers are walked by hand so the matching entry can be handed out as a reader over the
+/// archive stream itself instead of being buffered in memory.
+fn read_from_archive<R: Read + 'static>(mut reader: R, inner: &Path) -> Result<Option<Box<dyn Read>>> {
+    const BLOCK: u64 = 512;
+    let mut long_name: Option<Vec<u8>> = None;
+    let mut pax_path: Option<Vec<u8>> = None;
+
+    loop {
+        let mut header = tar::Header::new_old();
+        match reader.read_exact(header.as_mut_bytes()) {
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        if header.as_bytes().iter().all(|&byte| byte == 0) {
+            return Ok(None);
+        }
+
+        let size = header.entry_size()?;
+        let padded = size.div_ceil(BLOCK) * BLOCK;
+        let entry_type = header.entry_type();
+
+        if entry_type.is_gnu_longname() || entry_type.is_pax_local_extensions() {
+            let mut data = Vec::new();
+            (&mut reader).take(size).read_to_end(&mut data)?;
+            std::io::copy(&mut (&mut reader).take(padded - size), &mut std::io::sink())?;
+            if entry_type.is_gnu_longname() {
+                let end = data.iter().position(|&byte| byte == 0).unwrap_or(data.len());
+                data.truncate(end);
+                long_name = Some(data);
+            } else {
+                for extension in tar::PaxExtensions::new(&data) {
+                    let extension = extension?;
+                    if extension.key_bytes() == b"path" {
+                        pax_path = Some(extension.value_bytes().to_vec());
+                    }
+                }
+            }
+            continue;
+        }
+
+        let raw = pax_path.take().or(long_name.take()).unwrap_or_else(|| header.path_bytes().into_owned());
+        let path: PathBuf = Path::new(String::from_utf8_lossy(&raw).as_ref())
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect();
+        if path == inner && entry_type.is_file() {
+            return Ok(Some(Box::new(reader.take(size))));
+        }
+        std::io::copy(&mut (&mut reader).take(padded), &mut std::io::sink())?;
+    }
+}
+
+/// Record `path` and everything below it as manifest entries
+fn collect_manifest_entries(path: &Path, relative: &Path, entries: &mut Vec<ManifestEntry>) -> Result<()> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    let file_type = metadata.file_type();
+
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode()
+    };
+    #[cfg(not(unix))]
+    let mode = 0o644;
+
+    let (kind, size, target) = if file_type.is_symlink() {
+        (ManifestEntryKind::Symlink, 0, Some(std::fs::read_link(path)?))
+    } else if file_type.is_dir() {
+        (ManifestEntryKind::Directory, 0, None)
+    } else {
+        (ManifestEntryKind::File, metadata.len(), None)
+    };
+
+    entries.push(ManifestEntry {
+        path: relative.to_path_buf(),
+        kind,
+        mode,
+        size,
+        blob: None,
+        target,
+    });
+
+    if kind == ManifestEntryKind::Directory {
+        let mut children: Vec<_> = std::fs::read_dir(path)?.collect::<std::io::Result<_>>()?;
+        children.sort_by_key(|entry| entry.file_name());
+        for child in children {
+            collect_manifest_entries(&child.path(), &relative.join(child.file_name()), entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve where a manifest entry is recreated below `destination`
+fn entry_destination(destination: &Path, entry: &ManifestEntry) -> PathBuf {
+    if entry.path.as_os_str().is_empty() {
+        destination.to_path_buf()
+    } else {
+        destination.join(&entry.path)
+    }
+}
+
+/// Apply a Unix mode to a restored file or directory (best effort)
+fn set_mode(path: &Path, mode: u32) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).ok();
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+}
+
 /// Recursively copy a directory and all its contents to a new location
 fn copy_dir_recursive(source: &Path, destination: &Path) -> Result<()> {
     // Create the destination directory
@@ -194,12 +916,8 @@ fn copy_dir_recursive(source: &Path, destination: &Path) -> Result<()> {
             std::fs::copy(&source_path, &dest_path)?;
             
             // Preserve file permissions
-            if let Ok(_metadata) = std::fs::metadata(&source_path) {
-                if let Ok(permissions) = std::fs::metadata(&dest_path) {
-                    let mut dest_permissions = permissions.permissions();
-                    dest_permissions.set_readonly(false);
-                    std::fs::set_permissions(&dest_path, dest_permissions).ok();
-                }
+            if let Ok(metadata) = std::fs::metadata(&source_path) {
+                std::fs::set_permissions(&dest_path, metadata.permissions()).ok();
             }
         }
     }
@@ -210,7 +928,7 @@ fn copy_dir_recursive(source: &Path, destination: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::FileMeta;
+    use crate::domain::{FileMeta, GitFileStatus, GitInfo};
     use std::fs;
     use tempfile::{NamedTempFile, TempDir};
 
@@ -245,7 +963,7 @@ mod tests {
         let trash_store = TrashStore::new(temp_dir.path().to_path_buf());
 
         let test_file = NamedTempFile::new().unwrap();
-        let meta = FileMeta::from_path(&test_file.path().to_path_buf()).unwrap();
+        let meta = FileMeta::from_path(test_file.path()).unwrap();
 
         let filename = trash_store.generate_trash_filename(&meta);
         assert!(filename.ends_with(".rmz"));
@@ -272,8 +990,8 @@ mod tests {
         fs::write(test_file1.path(), "content1").unwrap();
         fs::write(test_file2.path(), "content2").unwrap();
 
-        let meta1 = FileMeta::from_path(&test_file1.path().to_path_buf()).unwrap();
-        let meta2 = FileMeta::from_path(&test_file2.path().to_path_buf()).unwrap();
+        let meta1 = FileMeta::from_path(test_file1.path()).unwrap();
+        let meta2 = FileMeta::from_path(test_file2.path()).unwrap();
 
         trash_store.save(&meta1, test_file1.path()).unwrap();
         trash_store.save(&meta2, test_file2.path()).unwrap();
@@ -291,7 +1009,7 @@ mod tests {
         // Create and save test file
         let test_file = NamedTempFile::new().unwrap();
         fs::write(test_file.path(), "content").unwrap();
-        let meta = FileMeta::from_path(&test_file.path().to_path_buf()).unwrap();
+        let meta = FileMeta::from_path(test_file.path()).unwrap();
 
         trash_store.save(&meta, test_file.path()).unwrap();
 
@@ -314,6 +1032,392 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_compress_and_restore_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file_path = temp_dir.path().join("build.log");
+        fs::write(&file_path, "warning: unused variable\n".repeat(200)).unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+
+        let item = trash_store.compress(&meta.id, 3).unwrap();
+        let info = item.meta.compression.clone().unwrap();
+        assert_eq!(info.original_size, meta.size);
+        assert!(info.compressed_size < meta.size);
+        assert!(item.trash_path.to_string_lossy().ends_with(".rmz.zst"));
+
+        // The uncompressed payload is gone and lookups resolve the new path
+        let found = trash_store.find_by_id(&meta.id).unwrap().unwrap();
+        assert_eq!(found.trash_path, item.trash_path);
+        assert_eq!(trash_store.list().unwrap().len(), 1);
+
+        trash_store.restore(&meta.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "warning: unused variable\n".repeat(200)
+        );
+        assert!(!item.trash_path.exists());
+    }
+
+    #[test]
+    fn test_compress_directory_and_extract() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let dir_path = temp_dir.path().join("target");
+        fs::create_dir_all(dir_path.join("debug")).unwrap();
+        fs::write(dir_path.join("debug/app"), "binary").unwrap();
+        let meta = FileMeta::from_path(&dir_path).unwrap();
+        trash_store.save(&meta, &dir_path).unwrap();
+
+        let item = trash_store.compress(&meta.id, 3).unwrap();
+        assert!(item.trash_path.is_file());
+
+        let extracted = temp_dir.path().join("extracted");
+        trash_store.extract_to(&item, &extracted).unwrap();
+        assert_eq!(fs::read_to_string(extracted.join("debug/app")).unwrap(), "binary");
+        assert!(item.trash_path.exists());
+    }
+
+    #[test]
+    fn test_compress_eligible_respects_min_age() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file_path = temp_dir.path().join("fresh.txt");
+        fs::write(&file_path, "fresh").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+
+        let config = CompressionConfig {
+            enabled: true,
+            min_age_days: 7,
+            ..CompressionConfig::default()
+        };
+        assert!(trash_store.compress_eligible(&config).unwrap().processed.is_empty());
+
+        let config = CompressionConfig {
+            min_age_days: 0,
+            ..config
+        };
+        assert_eq!(trash_store.compress_eligible(&config).unwrap().processed.len(), 1);
+    }
+
+    #[test]
+    fn test_compress_eligible_continues_past_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let mut saved = Vec::new();
+        for name in ["first.log", "second.log"] {
+            let file_path = temp_dir.path().join(name);
+            fs::write(&file_path, "line\n".repeat(100)).unwrap();
+            let meta = FileMeta::from_path(&file_path).unwrap();
+            saved.push(trash_store.save(&meta, &file_path).unwrap());
+        }
+
+        // A directory where the temporary payload goes makes that item fail
+        fs::create_dir(saved[0].trash_path.with_extension("rmz.tmp")).unwrap();
+
+        let config = CompressionConfig {
+            enabled: true,
+            min_age_days: 0,
+            ..CompressionConfig::default()
+        };
+        let outcome = trash_store.compress_eligible(&config).unwrap();
+        assert_eq!(outcome.processed.len(), 1);
+        assert_eq!(outcome.processed[0].meta.id, saved[1].meta.id);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0.meta.id, saved[0].meta.id);
+        assert!(saved[0].trash_path.exists());
+    }
+
+    #[test]
+    fn test_deduplicate_identical_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let mut ids = Vec::new();
+        for name in ["vendor1", "vendor2"] {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir_all(dir.join("lib")).unwrap();
+            fs::write(dir.join("lib/mod.rs"), "pub fn vendored() {}").unwrap();
+            fs::write(dir.join("README"), "vendored copy").unwrap();
+            let mut meta = FileMeta::from_path(&dir).unwrap();
+            meta.size = 33;
+            trash_store.save(&meta, &dir).unwrap();
+            trash_store.deduplicate(&meta.id).unwrap();
+            ids.push(meta.id);
+        }
+
+        // Two logical copies, one physical copy of each file
+        assert_eq!(trash_store.blob_store().physical_size().unwrap(), 33);
+        assert_eq!(trash_store.list().unwrap().len(), 2);
+
+        // Purging one copy keeps the shared blobs alive
+        trash_store.purge(&ids[0]).unwrap();
+        assert_eq!(trash_store.blob_store().physical_size().unwrap(), 33);
+
+        // Restoring the other rebuilds the tree and releases the blobs
+        trash_store.restore(&ids[1]).unwrap();
+        let restored = temp_dir.path().join("vendor2");
+        assert_eq!(
+            fs::read_to_string(restored.join("lib/mod.rs")).unwrap(),
+            "pub fn vendored() {}"
+        );
+        assert_eq!(trash_store.blob_store().physical_size().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_deduplicated_file_extract_and_checksum() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file_path = temp_dir.path().join("artifact.bin");
+        fs::write(&file_path, "artifact").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        let raw = trash_store.save(&meta, &file_path).unwrap();
+        let checksum = raw.calculate_checksum().unwrap();
+
+        let item = trash_store.deduplicate(&meta.id).unwrap();
+        assert!(item.trash_path.to_string_lossy().ends_with(".rmz.manifest"));
+        assert_eq!(trash_store.content_checksum(&item).unwrap(), checksum);
+
+        let extracted = temp_dir.path().join("copy.bin");
+        trash_store.extract_to(&item, &extracted).unwrap();
+        assert_eq!(fs::read_to_string(&extracted).unwrap(), "artifact");
+    }
+
+    fn encrypted_store(root: &Path, encrypt_paths: bool) -> TrashStore {
+        let config = EncryptionConfig {
+            enabled: true,
+            key_file: None,
+            encrypt_paths,
+        };
+        let store = TrashStore::new(root.to_path_buf()).with_encryption(&config);
+        let key = store.key_manager().initialize(b"passphrase").unwrap();
+        store.with_key(key)
+    }
+
+    #[test]
+    fn test_encrypted_file_restore_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = encrypted_store(&temp_dir.path().join("trash"), true);
+
+        let file_path = temp_dir.path().join(".env");
+        fs::write(&file_path, "API_TOKEN=secret").unwrap();
+        let mut meta = FileMeta::from_path(&file_path).unwrap();
+        meta.git = Some(GitInfo {
+            repo_root: temp_dir.path().to_path_buf(),
+            branch: Some("main".to_string()),
+            head: None,
+            status: GitFileStatus::Tracked,
+        });
+        trash_store.save(&meta, &file_path).unwrap();
+
+        let item = trash_store.encrypt(&meta.id).unwrap();
+        assert!(item.meta.git.is_none());
+        assert!(item.trash_path.to_string_lossy().ends_with(".rmz.enc"));
+        assert!(!fs::read_to_string(&item.trash_path).unwrap_or_default().contains("secret"));
+
+        // The index keeps working without the key but hides the path
+        let listed = TrashStore::new(temp_dir.path().join("trash")).list().unwrap();
+        assert_eq!(listed[0].meta.original_path, PathBuf::from(ENCRYPTED_PATH_PLACEHOLDER));
+        assert!(listed[0].meta.git.is_none());
+        let stored = fs::read_dir(temp_dir.path().join("trash").join("metadata")).unwrap();
+        for entry in stored {
+            let json = fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(!json.contains(&*temp_dir.path().to_string_lossy()));
+        }
+        assert_eq!(trash_store.reveal(&listed[0]).unwrap().meta.original_path, file_path);
+
+        let mut content = String::new();
+        trash_store.open_content(&item).unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "API_TOKEN=secret");
+
+        trash_store.restore(&meta.id).unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "API_TOKEN=secret");
+        assert!(trash_store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_encrypted_directory_extract() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = encrypted_store(&temp_dir.path().join("trash"), false);
+
+        let dir = temp_dir.path().join(".ssh");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("id_ed25519"), "PRIVATE").unwrap();
+        let meta = FileMeta::from_path(&dir).unwrap();
+        trash_store.save(&meta, &dir).unwrap();
+
+        let item = trash_store.encrypt(&meta.id).unwrap();
+        assert!(item.trash_path.to_string_lossy().ends_with(".rmz.tar.enc"));
+        assert_eq!(item.meta.original_path, dir);
+
+        let extracted = temp_dir.path().join("copy");
+        trash_store.extract_to(&item, &extracted).unwrap();
+        assert_eq!(fs::read_to_string(extracted.join("id_ed25519")).unwrap(), "PRIVATE");
+        assert!(trash_store.find_by_id(&meta.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_rotate_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("trash");
+        let trash_store = encrypted_store(&root, true);
+
+        let file_path = temp_dir.path().join("id_rsa");
+        fs::write(&file_path, "KEY").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+        trash_store.encrypt(&meta.id).unwrap();
+
+        // An item on a key nobody has any more cannot be rotated
+        let lost_path = temp_dir.path().join("lost.txt");
+        fs::write(&lost_path, "lost").unwrap();
+        let lost = FileMeta::from_path(&lost_path).unwrap();
+        trash_store.save(&lost, &lost_path).unwrap();
+        let mut lost = trash_store.encrypt(&lost.id).unwrap().meta;
+        lost.encryption.as_mut().unwrap().key_id = "unknown".to_string();
+        trash_store.meta_store().save_metadata(&lost).unwrap();
+
+        let key_manager = trash_store.key_manager();
+        let (new_key, record) = key_manager.generate(b"new passphrase").unwrap();
+        key_manager.begin_rotation(trash_store.key().unwrap(), &new_key, &record).unwrap();
+
+        // Once the record is switched, old items stay readable through the journal
+        let rotating_store = TrashStore::new(root.clone()).with_key(new_key);
+        let item = rotating_store.find_by_id(&meta.id).unwrap().unwrap();
+        assert_eq!(rotating_store.reveal(&item).unwrap().meta.original_path, file_path);
+
+        let outcome = rotating_store.rotate_key().unwrap();
+        assert_eq!(outcome.processed.len(), 1);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0.meta.id, lost.id);
+        key_manager.finish_rotation().unwrap();
+
+        // The old key no longer unlocks the trash; the new one decrypts everything it can
+        assert!(key_manager.unlock(b"passphrase").is_err());
+        let new_key = key_manager.unlock(b"new passphrase").unwrap();
+        let rotated_store = TrashStore::new(root).with_key(new_key);
+        let item = rotated_store.find_by_id(&meta.id).unwrap().unwrap();
+        rotated_store.verify_decryptable(&item).unwrap();
+        assert_eq!(rotated_store.reveal(&item).unwrap().meta.original_path, file_path);
+    }
+
+    #[test]
+    fn test_resume_rotation_interrupted_before_payload_swap() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("trash");
+        let trash_store = encrypted_store(&root, false);
+
+        let file_path = temp_dir.path().join(".env");
+        fs::write(&file_path, "SECRET=1").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+        let item = trash_store.encrypt(&meta.id).unwrap();
+        let old_payload = fs::read(&item.trash_path).unwrap();
+
+        let (new_key, record) = trash_store.key_manager().generate(b"new passphrase").unwrap();
+        trash_store
+            .key_manager()
+            .begin_rotation(trash_store.key().unwrap(), &new_key, &record)
+            .unwrap();
+        let rotating_store = TrashStore::new(root.clone()).with_key(new_key);
+        rotating_store.rotate_key().unwrap();
+
+        // Recreate the state after the metadata was saved but before the rename
+        fs::rename(&item.trash_path, rotation_temp_path(&item.trash_path)).unwrap();
+        fs::write(&item.trash_path, old_payload).unwrap();
+        assert_eq!(rotating_store.remove_temp_files().unwrap(), 0);
+
+        let outcome = rotating_store.rotate_key().unwrap();
+        assert_eq!(outcome.processed.len(), 1);
+        assert!(outcome.failed.is_empty());
+        let item = rotating_store.find_by_id(&meta.id).unwrap().unwrap();
+        rotating_store.verify_decryptable(&item).unwrap();
+        assert!(!rotation_temp_path(&item.trash_path).exists());
+    }
+
+    #[test]
+    fn test_undecryptable_item_is_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("trash");
+        let trash_store = encrypted_store(&root, false);
+
+        let file_path = temp_dir.path().join("notes.txt");
+        fs::write(&file_path, "notes").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        trash_store.save(&meta, &file_path).unwrap();
+        let item = trash_store.encrypt(&meta.id).unwrap();
+
+        // Re-initializing the key orphans everything encrypted with the old one
+        let other_key = trash_store.key_manager().initialize(b"other").unwrap();
+        let other_store = TrashStore::new(root).with_key(other_key);
+        assert!(other_store.verify_decryptable(&item).is_err());
+        assert!(other_store.restore(&meta.id).is_err());
+        assert!(other_store.find_by_id(&meta.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_open_inner_for_every_storage_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = encrypted_store(&temp_dir.path().join("trash"), false);
+
+        // Longer than a ustar name field, so archives need an extended header for it
+        let long_path = Path::new("src").join("nested".repeat(20)).join("mod.rs");
+        let read_inner = |item: &TrashItem, inner: &Path| {
+            let mut content = String::new();
+            trash_store
+                .open_inner(item, inner)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+
+        let mut items = Vec::new();
+        for name in ["plain", "compressed", "deduplicated", "encrypted"] {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(dir.join("src/lib.rs"), name).unwrap();
+            fs::create_dir_all(dir.join(long_path.parent().unwrap())).unwrap();
+            fs::write(dir.join(&long_path), "long").unwrap();
+            items.push(trash_store.save(&FileMeta::from_path(&dir).unwrap(), &dir).unwrap());
+        }
+        items[1] = trash_store.compress(&items[1].meta.id, 3).unwrap();
+        items[2] = trash_store.deduplicate(&items[2].meta.id).unwrap();
+        items[3] = trash_store.encrypt(&items[3].meta.id).unwrap();
+
+        for (item, expected) in items.iter().zip(["plain", "compressed", "deduplicated", "encrypted"]) {
+            assert_eq!(read_inner(item, Path::new("src/lib.rs")), expected);
+            assert_eq!(read_inner(item, &long_path), "long");
+            assert!(trash_store.open_inner(item, Path::new("src/missing.rs")).is_err());
+        }
+        assert!(trash_store.open_inner(&items[0], Path::new("../plain/src/lib.rs")).is_err());
+    }
+
+    #[test]
+    fn test_remove_orphans() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash_store = TrashStore::new(temp_dir.path().join("trash"));
+
+        let file_path = temp_dir.path().join("gone.txt");
+        fs::write(&file_path, "gone").unwrap();
+        let meta = FileMeta::from_path(&file_path).unwrap();
+        let item = trash_store.save(&meta, &file_path).unwrap();
+
+        fs::remove_file(&item.trash_path).unwrap();
+        fs::write(item.trash_path.with_extension("rmz.tmp"), "partial").unwrap();
+
+        assert_eq!(trash_store.remove_orphaned_metadata().unwrap(), vec![meta.id]);
+        assert_eq!(trash_store.remove_temp_files().unwrap(), 1);
+        assert!(trash_store.meta_store.load_metadata(&meta.id).unwrap().is_none());
+    }
+
     #[test]
     fn test_copy_dir_recursive() {
         let temp_dir = TempDir::new().unwrap();
@@ -3,4 +3,9 @@ pub mod commands;
 pub mod core;
 pub mod domain;
 pub mod infra;
+pub mod trash;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod utils;
+
+pub use trash::Trash;
@@ -0,0 +1,1258 @@
+//! Embeddable trash API
+//!
+//! [`Trash`] performs the same operations as the CLI without printing,
+//! prompting or reading global state beyond what it is opened with.
+//! Results are returned as typed values and progress is reported through
+//! an optional [`TrashEvent`] listener.
+//!
+//! ```no_run
+//! use rmz::trash::{DeleteOptions, ListFilter, PurgeSelector, RestoreOptions, Trash};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let trash = Trash::open_root("/tmp/my-tool/trash")?
+//!     .with_listener(|event| eprintln!("{:?}", event));
+//!
+//! let outcome = trash.delete(&["build.log"], &DeleteOptions::default())?;
+//! let id = outcome.deleted[0].meta.id;
+//!
+//! let items = trash.list(&ListFilter::default())?;
+//! assert!(!items.is_empty());
+//!
+//! trash.restore(&id, &RestoreOptions::default())?;
+//! trash.purge(&PurgeSelector::All)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::core::RmzError;
+use crate::domain::{Config, FileMeta, GitFileStatus, GitInfo, GitSafeguard, TrashItem};
+use crate::infra::git::GitRepo;
+use crate::infra::hooks::{HookFailure, HookItem, HookPoint, Hooks};
+use crate::infra::meta_store::MetaStoreInterface;
+use crate::infra::{trash_store::TrashStoreInterface, ConfigManager, TrashStore};
+use crate::utils::time_expr::TimeRange;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Something that happened while performing a trash operation
+#[derive(Debug, Clone)]
+pub enum TrashEvent {
+    /// A path was moved into the trash
+    Deleted(TrashItem),
+    /// An item was restored to `path`
+    Restored { item: TrashItem, path: PathBuf },
+    /// An item was permanently removed
+    Purged(TrashItem),
+    /// An item's tags were replaced
+    Retagged(TrashItem),
+    /// An item's note was replaced or removed
+    Annotated(TrashItem),
+    /// A non-fatal problem, e.g. a failed compression or encryption pass
+    Warning(String),
+}
+
+/// Options for [`Trash::delete`]
+#[derive(Debug, Clone, Default)]
+pub struct DeleteOptions {
+    /// Allow deleting non-empty directories
+    pub recursive: bool,
+    /// Tags recorded on every deleted item
+    pub tags: Vec<String>,
+    /// Note recorded on every deleted item
+    pub note: Option<String>,
+}
+
+/// Result of [`Trash::delete`]
+#[derive(Debug, Default)]
+pub struct DeleteOutcome {
+    /// Items now in the trash, in the order their paths were given
+    pub deleted: Vec<TrashItem>,
+    /// Paths that could not be deleted and why
+    pub failed: Vec<(PathBuf, RmzError)>,
+}
+
+/// Options for [`Trash::restore`]
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// Restore to this file, or into this directory, instead of the original path
+    pub to: Option<PathBuf>,
+    /// Replace an existing file at the destination
+    pub overwrite: bool,
+}
+
+/// Result of [`Trash::restore`]
+#[derive(Debug, Clone)]
+pub struct RestoreOutcome {
+    /// The restored item, with its original path decrypted if needed
+    pub item: TrashItem,
+    /// Where the item was restored to
+    pub path: PathBuf,
+}
+
+/// Criteria for [`Trash::list`]
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// Substring matched against file name, path and tags
+    pub pattern: Option<String>,
+    /// Only items deleted at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only items deleted before this time
+    pub before: Option<DateTime<Utc>>,
+    /// Only items carrying all of these tags
+    pub tags: Vec<String>,
+    /// Only items whose original path is this directory or beneath it
+    pub under: Option<PathBuf>,
+    /// Order of the returned items (None keeps store order)
+    pub sort: Option<SortKey>,
+    /// Reverse the sort order
+    pub reverse: bool,
+    /// Return at most this many items, counted after sorting
+    pub limit: Option<usize>,
+    /// Decrypt encrypted original paths (may require the encryption key)
+    pub reveal_paths: bool,
+}
+
+/// Sort order for [`Trash::list`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Newest deletion first
+    Deleted,
+    /// Largest first
+    Size,
+    /// Original path, A to Z
+    Path,
+    /// File name, A to Z
+    Name,
+    /// Alphabetically first tag, untagged items last
+    Tag,
+}
+
+impl SortKey {
+    /// Sort items by this key, breaking ties by newest deletion
+    pub fn sort(self, items: &mut [TrashItem], reverse: bool) {
+        items.sort_by(|a, b| {
+            let order = match self {
+                SortKey::Deleted => b.meta.deleted_at.cmp(&a.meta.deleted_at),
+                SortKey::Size => b.meta.size.cmp(&a.meta.size),
+                SortKey::Path => a.meta.original_path.cmp(&b.meta.original_path),
+                SortKey::Name => a.meta.filename().cmp(&b.meta.filename()),
+                SortKey::Tag => {
+                    let key = |item: &TrashItem| {
+                        let first = item.meta.tags.iter().min().cloned();
+                        (first.is_none(), first)
+                    };
+                    key(a).cmp(&key(b))
+                }
+            };
+            let order = order.then_with(|| b.meta.deleted_at.cmp(&a.meta.deleted_at));
+            if reverse {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+}
+
+impl ListFilter {
+    /// Check whether an item satisfies every criterion except the limit
+    pub fn matches(&self, item: &TrashItem) -> bool {
+        self.pattern
+            .as_ref()
+            .is_none_or(|pattern| item.meta.matches_pattern(pattern))
+            && self.since.is_none_or(|since| item.meta.deleted_at >= since)
+            && self.before.is_none_or(|before| item.meta.deleted_at < before)
+            && self.tags.iter().all(|tag| item.meta.tags.contains(tag))
+            && self.under.as_ref().is_none_or(|dir| is_beneath(&item.meta.original_path, dir))
+    }
+}
+
+/// Which trashed version of a path [`Trash::version`] picks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionSelector {
+    /// The most recently deleted version
+    #[default]
+    Latest,
+    /// The Nth version, counting from 1 for the oldest
+    Number(usize),
+    /// The newest version deleted at or before this time
+    At(DateTime<Utc>),
+}
+
+/// Which items [`Trash::purge`] removes
+#[derive(Debug, Clone)]
+pub enum PurgeSelector {
+    /// Every item in the trash
+    All,
+    /// Specific items
+    Ids(Vec<Uuid>),
+    /// Items deleted before this time
+    OlderThan(DateTime<Utc>),
+    /// Items deleted within a time range
+    DeletedWithin(TimeRange),
+    /// Oldest items until the stored size is at most this many bytes
+    ExceedingSize(u64),
+}
+
+/// Result of [`Trash::purge`]
+#[derive(Debug, Default)]
+pub struct PurgeOutcome {
+    /// Items that were permanently removed
+    pub purged: Vec<TrashItem>,
+    /// Bytes of storage released
+    pub freed: u64,
+    /// Items that could not be removed and why
+    pub failed: Vec<(TrashItem, RmzError)>,
+}
+
+type Listener = Box<dyn Fn(&TrashEvent)>;
+
+/// Handle to a trash directory and the settings that govern it
+pub struct Trash {
+    config: Config,
+    store: TrashStore,
+    listener: Option<Listener>,
+    hooks: Hooks,
+}
+
+impl Trash {
+    /// Open the trash described by an explicit configuration
+    pub fn open(config: Config) -> Result<Self> {
+        let store = TrashStore::new(config.trash_path.clone()).with_encryption(&config.encryption);
+        Ok(Self {
+            config,
+            store,
+            listener: None,
+            hooks: Hooks::disabled(),
+        })
+    }
+
+    /// Open a trash rooted at `root` with default settings
+    pub fn open_root(root: impl Into<PathBuf>) -> Result<Self> {
+        Self::open(Config {
+            trash_path: root.into(),
+            ..Config::default()
+        })
+    }
+
+    /// Open the trash configured for the current user
+    pub fn open_default() -> Result<Self> {
+        Self::open(ConfigManager::load()?)
+    }
+
+    /// Receive a [`TrashEvent`] for every step performed
+    pub fn with_listener(mut self, listener: impl Fn(&TrashEvent) + 'static) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    /// Run user-defined hooks around deletions, purges and restores
+    ///
+    /// Without this no hooks run, whatever the configuration says.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The underlying store, for operations not covered by this API
+    pub fn store(&self) -> &TrashStore {
+        &self.store
+    }
+
+    fn emit(&self, event: TrashEvent) {
+        if let Some(listener) = &self.listener {
+            listener(&event);
+        }
+    }
+
+    fn warn_missing_payload(&self, id: &Uuid) {
+        self.emit(TrashEvent::Warning(format!("Metadata exists but file missing for ID: {}", id)));
+    }
+
+    /// Every item with a payload, warning about metadata whose file is missing
+    fn list_items(&self) -> Result<Vec<TrashItem>> {
+        let (items, missing) = self.store.list_checked()?;
+        for id in &missing {
+            self.warn_missing_payload(id);
+        }
+        Ok(items)
+    }
+
+    fn list_revealed(&self) -> Result<Vec<TrashItem>> {
+        self.list_items()?.iter().map(|item| self.store.reveal(item)).collect()
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Result<Option<TrashItem>> {
+        let item = self.store.find_by_id(id)?;
+        if item.is_none() && self.store.meta_store().load_metadata(id)?.is_some() {
+            self.warn_missing_payload(id);
+        }
+        Ok(item)
+    }
+
+    /// Veto hook failures become an error, others are reported as warnings
+    fn hook_result(&self, point: HookPoint, failures: Vec<HookFailure>) -> Result<(), RmzError> {
+        for failure in failures {
+            if point.can_veto() {
+                return Err(RmzError::Vetoed {
+                    hook: point.name().to_string(),
+                    reason: failure.reason,
+                });
+            }
+            self.emit(TrashEvent::Warning(format!(
+                "{} hook '{}' failed: {}",
+                point.name(),
+                failure.hook,
+                failure.reason
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run the `pre_delete` hooks for a batch of paths, failing if one vetoes
+    ///
+    /// [`Trash::delete`] calls this itself; callers that delete path by path
+    /// with [`Trash::delete_path`] call it once before the batch.
+    pub fn check_delete<P: AsRef<Path>>(&self, paths: &[P], options: &DeleteOptions) -> Result<(), RmzError> {
+        let items: Vec<HookItem> = paths
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|path| path.exists())
+            .map(|path| HookItem {
+                id: None,
+                original_path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+                size: stored_size(path),
+                tags: options.tags.clone(),
+                restored_to: None,
+            })
+            .collect();
+        self.hook_result(HookPoint::PreDelete, self.hooks.run(HookPoint::PreDelete, &items))
+    }
+
+    /// Run the `post_delete` hooks for the items a batch moved to trash
+    pub fn finish_delete(&self, deleted: &[TrashItem]) {
+        let items: Vec<HookItem> = deleted.iter().map(HookItem::from_item).collect();
+        // Post hooks cannot veto, so this only emits warnings
+        let _ = self.hook_result(HookPoint::PostDelete, self.hooks.run(HookPoint::PostDelete, &items));
+    }
+
+    /// Move paths into the trash, then compress eligible items if enabled
+    ///
+    /// Fails without deleting anything when a `pre_delete` hook vetoes.
+    pub fn delete<P: AsRef<Path>>(&self, paths: &[P], options: &DeleteOptions) -> Result<DeleteOutcome> {
+        self.check_delete(paths, options)?;
+        let mut outcome = DeleteOutcome::default();
+
+        for path in paths {
+            let path = path.as_ref();
+            match self.delete_path(path, options) {
+                Ok(item) => outcome.deleted.push(item),
+                Err(e) => outcome.failed.push((path.to_path_buf(), e)),
+            }
+        }
+
+        self.finish_delete(&outcome.deleted);
+        self.compress_eligible();
+        Ok(outcome)
+    }
+
+    /// Move a single path into the trash, applying encryption or deduplication
+    ///
+    /// Unlike [`Trash::delete`] this does not run the compression sweep;
+    /// call [`Trash::compress_eligible`] after a batch of deletions.
+    pub fn delete_path(&self, path: &Path, options: &DeleteOptions) -> Result<TrashItem, RmzError> {
+        if !path.exists() {
+            return Err(RmzError::PathNotFound { path: path.to_path_buf() });
+        }
+
+        let is_dir = path.is_dir();
+        if is_dir && !options.recursive && path.read_dir()?.next().is_some() {
+            return Err(RmzError::DirectoryNotEmpty { path: path.to_path_buf() });
+        }
+
+        if self.config.is_protected(&path.to_path_buf()) {
+            return Err(RmzError::Protected { path: path.to_path_buf() });
+        }
+
+        let repo = self.git_repo(path);
+        let git = repo.as_ref().and_then(|repo| self.git_info(repo, path));
+        if let Some(reason) = git_risk(path, repo.as_ref().zip(git.as_ref())) {
+            match self.config.git.safeguard {
+                GitSafeguard::Refuse => {
+                    return Err(RmzError::GitSafeguard {
+                        path: path.to_path_buf(),
+                        reason,
+                    })
+                }
+                GitSafeguard::Warn => self.emit(TrashEvent::Warning(format!("Deleting {}: {}", path.display(), reason))),
+                GitSafeguard::Off => {}
+            }
+        }
+
+        let mut meta = FileMeta::from_path(path)?;
+        // Record where the item lived independently of the directory rmz ran in
+        meta.original_path = std::path::absolute(path)?;
+        if is_dir {
+            meta.size = directory_size(path)?;
+        }
+        for tag in &options.tags {
+            meta.add_tag(tag.clone());
+        }
+        meta.note = options.note.clone();
+        if self.config.git.record {
+            meta.git = git;
+        }
+
+        // Encryption takes precedence over deduplication. It fails closed: nothing
+        // meant to be encrypted is ever left in the trash as plaintext
+        let item = if self.config.encryption.enabled {
+            // Unlocking first refuses the deletion before anything has moved
+            self.store.key()?;
+            self.store.save(&meta, path)?;
+            match self.store.encrypt(&meta.id) {
+                Ok(item) => item,
+                Err(e) => {
+                    return Err(match self.store.restore_to(&meta.id, path) {
+                        Ok(_) => anyhow::anyhow!("Failed to encrypt {}: {:#}", path.display(), e),
+                        Err(restore_error) => anyhow::anyhow!(
+                            "Failed to encrypt {}: {:#}; moving it back also failed, so it is still in the trash unencrypted: {:#}",
+                            path.display(),
+                            e,
+                            restore_error
+                        ),
+                    }
+                    .into());
+                }
+            }
+        } else {
+            let item = self.store.save(&meta, path)?;
+            if !self.config.dedup.enabled {
+                item
+            } else {
+                // A failed deduplication leaves the item stored raw
+                self.store.deduplicate(&meta.id).unwrap_or_else(|e| {
+                    self.emit(TrashEvent::Warning(format!("Failed to deduplicate {}: {}", path.display(), e)));
+                    item
+                })
+            }
+        };
+
+        self.emit(TrashEvent::Deleted(item.clone()));
+        Ok(item)
+    }
+
+    /// Repository holding a path about to be deleted, when recording or safeguards need it
+    fn git_repo(&self, path: &Path) -> Option<GitRepo> {
+        if !self.config.git.record && self.config.git.safeguard == GitSafeguard::Off {
+            return None;
+        }
+        GitRepo::discover(path)
+    }
+
+    /// Git state of `path`, or None with a warning when the repository cannot be read
+    fn git_info(&self, repo: &GitRepo, path: &Path) -> Option<GitInfo> {
+        match repo.info(path) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                self.emit(TrashEvent::Warning(format!(
+                    "Could not read git repository {}: {}",
+                    repo.root().display(),
+                    e
+                )));
+                None
+            }
+        }
+    }
+
+    /// Compress items that are old enough, when compression is enabled
+    pub fn compress_eligible(&self) -> Vec<TrashItem> {
+        if !self.config.compression.enabled {
+            return Vec::new();
+        }
+        match self.store.compress_eligible(&self.config.compression) {
+            Ok(outcome) => {
+                for (item, e) in outcome.failed {
+                    self.emit(TrashEvent::Warning(format!("Failed to compress {}: {}", item.meta.id, e)));
+                }
+                outcome.processed
+            }
+            Err(e) => {
+                self.emit(TrashEvent::Warning(format!("Failed to compress trash items: {}", e)));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Find items by full ID or by an ID prefix, with original paths revealed
+    pub fn find(&self, id_or_prefix: &str) -> Result<Vec<TrashItem>> {
+        let items = match Uuid::parse_str(id_or_prefix) {
+            Ok(id) => self.find_by_id(&id)?.into_iter().collect(),
+            Err(_) => {
+                let prefix = id_or_prefix.to_lowercase();
+                let mut items = self.list_items()?;
+                items.retain(|item| item.meta.id.to_string().starts_with(&prefix));
+                items
+            }
+        };
+        items.iter().map(|item| self.store.reveal(item)).collect()
+    }
+
+    /// Resolve a full ID or an ID prefix to exactly one item
+    pub fn find_one(&self, id_or_prefix: &str) -> Result<TrashItem> {
+        let mut matches = self.find(id_or_prefix)?;
+        match matches.len() {
+            0 => Err(anyhow::anyhow!("No file found with ID starting with: {}", id_or_prefix)),
+            1 => Ok(matches.remove(0)),
+            _ => Err(anyhow::anyhow!(
+                "Multiple files match ID prefix '{}'; please provide a more specific ID",
+                id_or_prefix
+            )),
+        }
+    }
+
+    /// Every trashed version of `path` or of anything beneath it, oldest first
+    ///
+    /// Relative paths, given or recorded, are resolved against the current directory.
+    pub fn history(&self, path: &Path) -> Result<Vec<TrashItem>> {
+        let target = std::path::absolute(path)?;
+        let mut items = self.list_revealed()?;
+        items.retain(|item| is_beneath(&item.meta.original_path, &target));
+        items.sort_by_key(|item| (item.meta.deleted_at, item.meta.id));
+        Ok(items)
+    }
+
+    /// Versions needed to rebuild the tree under `dir` as it was at `as_of`
+    ///
+    /// Only items deleted after `as_of` existed at that moment. Of several such
+    /// versions of one path, the first one deleted afterwards is the one that was
+    /// live. Parents come before their children so restoring in order works.
+    pub fn versions_as_of(&self, dir: &Path, as_of: DateTime<Utc>) -> Result<Vec<TrashItem>> {
+        let mut live: Vec<TrashItem> = Vec::new();
+        for item in self.history(dir)? {
+            if item.meta.deleted_at <= as_of
+                || live.iter().any(|kept| kept.meta.original_path == item.meta.original_path)
+            {
+                continue;
+            }
+            live.push(item);
+        }
+        live.sort_by(|a, b| {
+            let depth = |item: &TrashItem| item.meta.original_path.components().count();
+            depth(a).cmp(&depth(b)).then_with(|| a.meta.original_path.cmp(&b.meta.original_path))
+        });
+        Ok(live)
+    }
+
+    /// Pick one trashed version of exactly `path`
+    pub fn version(&self, path: &Path, selector: VersionSelector) -> Result<TrashItem> {
+        let target = std::path::absolute(path)?;
+        let mut versions = self.history(path)?;
+        versions.retain(|item| std::path::absolute(&item.meta.original_path).is_ok_and(|original| original == target));
+        if versions.is_empty() {
+            anyhow::bail!("No trashed versions of {}", path.display());
+        }
+
+        let count = versions.len();
+        let picked = match selector {
+            VersionSelector::Latest => versions.pop(),
+            VersionSelector::Number(n) => (n >= 1).then(|| versions.get(n - 1).cloned()).flatten(),
+            VersionSelector::At(time) => versions.into_iter().rev().find(|item| item.meta.deleted_at <= time),
+        };
+        picked.ok_or_else(|| match selector {
+            VersionSelector::At(time) => anyhow::anyhow!(
+                "No version of {} was deleted at or before {}",
+                path.display(),
+                time.format("%Y-%m-%d %H:%M:%S")
+            ),
+            _ => anyhow::anyhow!("{} has {} trashed version(s); pick one from 1 to {}", path.display(), count, count),
+        })
+    }
+
+    /// Where an item would be restored to with the given target
+    pub fn restore_destination(&self, item: &TrashItem, to: Option<&Path>) -> Result<PathBuf> {
+        let item = self.store.reveal(item)?;
+        match to {
+            Some(to_path) if to_path.is_dir() => item
+                .meta
+                .filename()
+                .map(|filename| to_path.join(filename))
+                .ok_or_else(|| anyhow::anyhow!("Cannot determine filename for restoration")),
+            Some(to_path) => Ok(to_path.to_path_buf()),
+            None => Ok(item.meta.original_path),
+        }
+    }
+
+    /// Restore an item to its original path or to `options.to`
+    pub fn restore(&self, id: &Uuid, options: &RestoreOptions) -> Result<RestoreOutcome, RmzError> {
+        let item = self
+            .find_by_id(id)?
+            .ok_or_else(|| RmzError::ItemNotFound { id: id.to_string() })?;
+        let item = self.store.reveal(&item)?;
+
+        let destination = self.restore_destination(&item, options.to.as_deref())?;
+        if destination.exists() && !options.overwrite {
+            return Err(RmzError::Conflict { path: destination });
+        }
+
+        let path = self.store.restore_to(id, &destination)?;
+        self.emit(TrashEvent::Restored {
+            item: item.clone(),
+            path: path.clone(),
+        });
+        let restored = HookItem {
+            restored_to: Some(path.clone()),
+            ..HookItem::from_item(&item)
+        };
+        let _ = self.hook_result(HookPoint::PostRestore, self.hooks.run(HookPoint::PostRestore, &[restored]));
+        Ok(RestoreOutcome { item, path })
+    }
+
+    /// Replace the tags of an item
+    pub fn retag(&self, id: &Uuid, tags: Vec<String>) -> Result<TrashItem, RmzError> {
+        if self.find_by_id(id)?.is_none() {
+            return Err(RmzError::ItemNotFound { id: id.to_string() });
+        }
+        let item = self.store.reveal(&self.store.set_tags(id, tags)?)?;
+        self.emit(TrashEvent::Retagged(item.clone()));
+        Ok(item)
+    }
+
+    /// Replace or, with `None`, remove the note of an item
+    pub fn annotate(&self, id: &Uuid, note: Option<String>) -> Result<TrashItem, RmzError> {
+        if self.find_by_id(id)?.is_none() {
+            return Err(RmzError::ItemNotFound { id: id.to_string() });
+        }
+        let item = self.store.reveal(&self.store.set_note(id, note)?)?;
+        self.emit(TrashEvent::Annotated(item.clone()));
+        Ok(item)
+    }
+
+    /// List items matching a filter, sorted if the filter asks for it
+    pub fn list(&self, filter: &ListFilter) -> Result<Vec<TrashItem>> {
+        let mut items = if filter.reveal_paths {
+            self.list_revealed()?
+        } else {
+            self.list_items()?
+        };
+
+        items.retain(|item| filter.matches(item));
+        if let Some(key) = filter.sort {
+            key.sort(&mut items, filter.reverse);
+        }
+        if let Some(limit) = filter.limit {
+            items.truncate(limit);
+        }
+        Ok(items)
+    }
+
+    /// Resolve which items a selector refers to without removing anything
+    pub fn select(&self, selector: &PurgeSelector) -> Result<Vec<TrashItem>> {
+        let mut items = self.list_items()?;
+
+        match selector {
+            PurgeSelector::All => {}
+            PurgeSelector::Ids(ids) => {
+                for id in ids {
+                    if !items.iter().any(|item| &item.meta.id == id) {
+                        return Err(RmzError::ItemNotFound { id: id.to_string() }.into());
+                    }
+                }
+                items.retain(|item| ids.contains(&item.meta.id));
+            }
+            PurgeSelector::OlderThan(cutoff) => {
+                items.retain(|item| item.meta.deleted_at < *cutoff);
+            }
+            PurgeSelector::DeletedWithin(range) => {
+                items.retain(|item| range.contains(item.meta.deleted_at));
+            }
+            PurgeSelector::ExceedingSize(limit) => {
+                // Oldest items go first until the remaining items fit
+                items.sort_by_key(|item| item.meta.deleted_at);
+                let mut total: u64 = items.iter().map(|item| stored_size(&item.trash_path)).sum();
+                let mut selected = Vec::new();
+                for item in items {
+                    if total <= *limit {
+                        break;
+                    }
+                    total = total.saturating_sub(stored_size(&item.trash_path));
+                    selected.push(item);
+                }
+                items = selected;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Permanently remove the items a selector refers to
+    pub fn purge(&self, selector: &PurgeSelector) -> Result<PurgeOutcome> {
+        let items = self.select(selector)?;
+        Ok(self.purge_items(&items))
+    }
+
+    /// Permanently remove specific items, e.g. after confirming a [`Trash::select`]
+    ///
+    /// When a `pre_purge` hook vetoes, every item is reported as failed.
+    pub fn purge_items(&self, items: &[TrashItem]) -> PurgeOutcome {
+        let mut outcome = PurgeOutcome::default();
+
+        let hook_items: Vec<HookItem> = items.iter().map(HookItem::from_item).collect();
+        if let Some(failure) = self.hooks.run(HookPoint::PrePurge, &hook_items).into_iter().next() {
+            outcome.failed = items
+                .iter()
+                .map(|item| {
+                    let error = RmzError::Vetoed {
+                        hook: HookPoint::PrePurge.name().to_string(),
+                        reason: failure.reason.clone(),
+                    };
+                    (item.clone(), error)
+                })
+                .collect();
+            return outcome;
+        }
+
+        for item in items {
+            let size = stored_size(&item.trash_path);
+            match self.store.purge(&item.meta.id) {
+                Ok(()) => {
+                    outcome.freed += size;
+                    outcome.purged.push(item.clone());
+                    self.emit(TrashEvent::Purged(item.clone()));
+                }
+                Err(e) => outcome.failed.push((item.clone(), e.into())),
+            }
+        }
+
+        outcome
+    }
+}
+
+/// Why deleting `path` could lose work that git cannot bring back
+///
+/// Untracked directories only count when they hold files that are not ignored,
+/// so build output can still be deleted.
+fn git_risk(path: &Path, git: Option<(&GitRepo, &GitInfo)>) -> Option<String> {
+    if path.file_name().is_some_and(|name| name == ".git") {
+        return Some("it is a git directory holding the repository's history".to_string());
+    }
+    if path.is_dir() && path.join(".git").exists() {
+        return Some("it is the root of a git repository".to_string());
+    }
+    let (repo, info) = git?;
+    if !path.is_dir() {
+        return None;
+    }
+    match info.status {
+        GitFileStatus::Modified => Some("it contains uncommitted changes".to_string()),
+        GitFileStatus::Untracked if repo.has_untracked_files(path).unwrap_or(false) => {
+            Some("it contains files that were never committed".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Whether `path` is `dir` or lies beneath it, resolving relative paths against the current directory
+fn is_beneath(path: &Path, dir: &Path) -> bool {
+    match (std::path::absolute(path), std::path::absolute(dir)) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// Bytes occupied by a stored payload (file or directory)
+pub fn stored_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        directory_size(path).unwrap_or(0)
+    } else {
+        std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+    }
+}
+
+/// Calculate the total size of a directory recursively
+fn directory_size(path: &Path) -> Result<u64> {
+    let mut total_size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            total_size += metadata.len();
+        } else if metadata.is_dir() {
+            total_size += directory_size(&entry.path())?;
+        }
+    }
+    Ok(total_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    fn open(temp_dir: &TempDir) -> Trash {
+        Trash::open(Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            ..Config::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_delete_list_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        let trash = open(&temp_dir).with_listener(move |event| recorded.borrow_mut().push(event.clone()));
+
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "notes").unwrap();
+        let options = DeleteOptions {
+            tags: vec!["docs".to_string()],
+            ..DeleteOptions::default()
+        };
+        let outcome = trash.delete(&[&file, &temp_dir.path().join("missing")], &options).unwrap();
+        assert_eq!(outcome.deleted.len(), 1);
+        assert!(matches!(outcome.failed[0].1, RmzError::PathNotFound { .. }));
+        assert!(!file.exists());
+
+        let filter = ListFilter {
+            tags: vec!["docs".to_string()],
+            ..ListFilter::default()
+        };
+        assert_eq!(trash.list(&filter).unwrap().len(), 1);
+
+        let id = outcome.deleted[0].meta.id;
+        let restored = trash.restore(&id, &RestoreOptions::default()).unwrap();
+        assert_eq!(restored.path, file);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "notes");
+
+        let events = events.borrow();
+        assert!(matches!(events[0], TrashEvent::Deleted(_)));
+        assert!(matches!(events[1], TrashEvent::Restored { .. }));
+    }
+
+    #[test]
+    fn test_restore_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+
+        let file = temp_dir.path().join("a.txt");
+        fs::write(&file, "old").unwrap();
+        let item = trash.delete_path(&file, &DeleteOptions::default()).unwrap();
+        fs::write(&file, "new").unwrap();
+
+        let result = trash.restore(&item.meta.id, &RestoreOptions::default());
+        assert!(matches!(result, Err(RmzError::Conflict { .. })));
+
+        let options = RestoreOptions {
+            overwrite: true,
+            ..RestoreOptions::default()
+        };
+        trash.restore(&item.meta.id, &options).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_missing_payload_is_reported_as_warning() {
+        let temp_dir = TempDir::new().unwrap();
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let recorded = warnings.clone();
+        let trash = open(&temp_dir).with_listener(move |event| {
+            if let TrashEvent::Warning(message) = event {
+                recorded.borrow_mut().push(message.clone());
+            }
+        });
+
+        let file = temp_dir.path().join("lost.txt");
+        fs::write(&file, "lost").unwrap();
+        let item = trash.delete_path(&file, &DeleteOptions::default()).unwrap();
+        fs::remove_file(&item.trash_path).unwrap();
+
+        assert!(trash.list(&ListFilter::default()).unwrap().is_empty());
+        assert!(trash.find(&item.meta.id.to_string()).unwrap().is_empty());
+        let warnings = warnings.borrow();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|message| message.contains(&item.meta.id.to_string())));
+
+        let (items, missing) = trash.store().list_checked().unwrap();
+        assert!(items.is_empty());
+        assert_eq!(missing, vec![item.meta.id]);
+    }
+
+    #[test]
+    fn test_retag() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+
+        let file = temp_dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let item = trash.delete_path(&file, &DeleteOptions::default()).unwrap();
+
+        let retagged = trash.retag(&item.meta.id, vec!["keep".to_string()]).unwrap();
+        assert_eq!(retagged.meta.tags, vec!["keep"]);
+        assert_eq!(trash.find(&item.meta.id.to_string()).unwrap()[0].meta.tags, vec!["keep"]);
+
+        let missing = trash.retag(&Uuid::new_v4(), Vec::new());
+        assert!(matches!(missing, Err(RmzError::ItemNotFound { .. })));
+    }
+
+    #[test]
+    fn test_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+
+        let file = temp_dir.path().join("schema.sql");
+        fs::write(&file, "create table").unwrap();
+        let options = DeleteOptions {
+            note: Some("superseded by v2 schema".to_string()),
+            ..DeleteOptions::default()
+        };
+        let item = trash.delete_path(&file, &options).unwrap();
+        assert_eq!(item.meta.note.as_deref(), Some("superseded by v2 schema"));
+
+        let filter = ListFilter {
+            pattern: Some("v2 schema".to_string()),
+            ..ListFilter::default()
+        };
+        assert_eq!(trash.list(&filter).unwrap().len(), 1);
+
+        let annotated = trash.annotate(&item.meta.id, None).unwrap();
+        assert_eq!(annotated.meta.note, None);
+        assert!(trash.list(&filter).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_and_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+        let dir = temp_dir.path().join("app");
+        let config = dir.join("config.toml");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut versions = Vec::new();
+        for content in ["one", "two", "three"] {
+            fs::write(&config, content).unwrap();
+            versions.push(trash.delete_path(&config, &DeleteOptions::default()).unwrap());
+        }
+        fs::write(dir.join("other.toml"), "other").unwrap();
+        trash.delete_path(&dir.join("other.toml"), &DeleteOptions::default()).unwrap();
+
+        let history: Vec<Uuid> = trash.history(&config).unwrap().iter().map(|item| item.meta.id).collect();
+        let ids: Vec<Uuid> = versions.iter().map(|item| item.meta.id).collect();
+        assert_eq!(history, ids);
+        assert_eq!(trash.history(&dir).unwrap().len(), 4);
+
+        let pick = |selector| trash.version(&config, selector).map(|item| item.meta.id);
+        assert_eq!(pick(VersionSelector::Latest).unwrap(), ids[2]);
+        assert_eq!(pick(VersionSelector::Number(1)).unwrap(), ids[0]);
+        assert_eq!(pick(VersionSelector::At(versions[1].meta.deleted_at)).unwrap(), ids[1]);
+        assert!(pick(VersionSelector::Number(4)).is_err());
+        assert!(pick(VersionSelector::Number(0)).is_err());
+        assert!(pick(VersionSelector::At(versions[0].meta.deleted_at - chrono::Duration::seconds(1))).is_err());
+        assert!(trash.version(&dir, VersionSelector::Latest).is_err());
+    }
+
+    #[test]
+    fn test_versions_as_of() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("build")).unwrap();
+
+        let delete = |name: &str, content: &str| {
+            let path = project.join(name);
+            fs::write(&path, content).unwrap();
+            trash.delete_path(&path, &DeleteOptions::default()).unwrap()
+        };
+        delete("old.txt", "gone before");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let as_of = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let live = delete("notes.txt", "live at as_of");
+        delete("notes.txt", "recreated later");
+        let nested = delete("build/out.log", "log");
+        fs::write(project.join("build/cache"), "cache").unwrap();
+        let build = trash
+            .delete_path(&project.join("build"), &DeleteOptions { recursive: true, ..DeleteOptions::default() })
+            .unwrap();
+
+        // Parents first, so the directory exists before its earlier-deleted children return
+        let ids: Vec<Uuid> = trash
+            .versions_as_of(&project, as_of)
+            .unwrap()
+            .iter()
+            .map(|item| item.meta.id)
+            .collect();
+        assert_eq!(ids, vec![build.meta.id, live.meta.id, nested.meta.id]);
+    }
+
+    #[test]
+    fn test_list_sorts_before_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+        let sub = temp_dir.path().join("sub");
+        fs::create_dir_all(&sub).unwrap();
+
+        for (path, size) in [(sub.join("b"), 30), (temp_dir.path().join("a"), 10), (sub.join("c"), 20)] {
+            fs::write(&path, "x".repeat(size)).unwrap();
+            trash.delete_path(&path, &DeleteOptions::default()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let names = |filter: ListFilter| -> Vec<String> {
+            trash
+                .list(&filter)
+                .unwrap()
+                .iter()
+                .map(|item| item.meta.filename().unwrap().to_string())
+                .collect()
+        };
+        let sorted = |sort, reverse| ListFilter {
+            sort: Some(sort),
+            reverse,
+            ..ListFilter::default()
+        };
+
+        assert_eq!(names(sorted(SortKey::Deleted, false)), ["c", "a", "b"]);
+        assert_eq!(names(sorted(SortKey::Size, false)), ["b", "c", "a"]);
+        assert_eq!(names(sorted(SortKey::Name, true)), ["c", "b", "a"]);
+        assert_eq!(
+            names(ListFilter {
+                limit: Some(1),
+                ..sorted(SortKey::Size, true)
+            }),
+            ["a"]
+        );
+        assert_eq!(
+            names(ListFilter {
+                under: Some(sub.clone()),
+                ..sorted(SortKey::Name, false)
+            }),
+            ["b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_delete_refuses_when_encryption_key_is_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::open(Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            encryption: crate::domain::EncryptionConfig {
+                enabled: true,
+                key_file: Some(temp_dir.path().join("missing.key")),
+                encrypt_paths: false,
+            },
+            ..Config::default()
+        })
+        .unwrap();
+
+        let file = temp_dir.path().join(".env");
+        fs::write(&file, "API_TOKEN=secret").unwrap();
+        let error = trash.delete_path(&file, &DeleteOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("missing.key"), "{}", error);
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "API_TOKEN=secret");
+        assert!(trash.store().list().unwrap().is_empty());
+        let payloads = walkdir(&temp_dir.path().join("trash"));
+        assert!(payloads.iter().all(|path| !path.to_string_lossy().contains(".rmz")), "{:?}", payloads);
+    }
+
+    fn walkdir(dir: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            if entry.path().is_dir() {
+                paths.extend(walkdir(&entry.path()));
+            }
+            paths.push(entry.path());
+        }
+        paths
+    }
+
+    #[test]
+    fn test_delete_rejects_non_empty_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+
+        let dir = temp_dir.path().join("dir");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("file"), "x").unwrap();
+
+        let result = trash.delete_path(&dir, &DeleteOptions::default());
+        assert!(matches!(result, Err(RmzError::DirectoryNotEmpty { .. })));
+
+        let options = DeleteOptions {
+            recursive: true,
+            ..DeleteOptions::default()
+        };
+        assert_eq!(trash.delete_path(&dir, &options).unwrap().meta.size, 1);
+    }
+
+    #[test]
+    fn test_directory_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_dir = temp_dir.path().join("test_dir");
+        fs::create_dir_all(test_dir.join("subdir")).unwrap();
+        fs::write(test_dir.join("file1.txt"), "12345").unwrap();
+        fs::write(test_dir.join("file2.txt"), "1234567890").unwrap();
+        fs::write(test_dir.join("subdir/file3.txt"), "123").unwrap();
+
+        assert_eq!(directory_size(&test_dir).unwrap(), 18);
+        assert_eq!(stored_size(&test_dir), 18);
+    }
+
+    #[test]
+    fn test_purge_selectors() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = open(&temp_dir);
+
+        let mut ids = Vec::new();
+        for (name, size) in [("a", 10), ("b", 20), ("c", 30)] {
+            let file = temp_dir.path().join(name);
+            fs::write(&file, "x".repeat(size)).unwrap();
+            ids.push(trash.delete_path(&file, &DeleteOptions::default()).unwrap().meta.id);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        // Dropping the oldest item brings 60 bytes under a 55 byte limit
+        let selected = trash.select(&PurgeSelector::ExceedingSize(55)).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].meta.id, ids[0]);
+
+        let items = trash.list(&ListFilter::default()).unwrap();
+        let deleted_at = |id: &Uuid| items.iter().find(|item| &item.meta.id == id).unwrap().meta.deleted_at;
+        let range = TimeRange {
+            since: Some(deleted_at(&ids[1])),
+            before: Some(deleted_at(&ids[2])),
+        };
+        let selected = trash.select(&PurgeSelector::DeletedWithin(range)).unwrap();
+        assert_eq!(selected.iter().map(|item| item.meta.id).collect::<Vec<_>>(), vec![ids[1]]);
+
+        let outcome = trash.purge(&PurgeSelector::Ids(vec![ids[1]])).unwrap();
+        assert_eq!(outcome.freed, 20);
+        assert!(trash.select(&PurgeSelector::Ids(vec![ids[1]])).is_err());
+
+        let outcome = trash.purge(&PurgeSelector::All).unwrap();
+        assert_eq!(outcome.purged.len(), 2);
+        assert!(trash.list(&ListFilter::default()).unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hooks_veto_and_observe() {
+        use crate::domain::HooksConfig;
+
+        let temp_dir = TempDir::new().unwrap();
+        let restored_log = temp_dir.path().join("restored.json");
+        let hooks = Hooks::new(HooksConfig {
+            pre_delete: Some("! grep -q keep.txt".to_string()),
+            pre_purge: Some("echo 'purging is frozen' >&2; exit 1".to_string()),
+            post_restore: Some(format!("cat > {}", restored_log.display())),
+            dir: None,
+            ..HooksConfig::default()
+        });
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let recorded = warnings.clone();
+        let trash = open(&temp_dir).with_hooks(hooks).with_listener(move |event| {
+            if let TrashEvent::Warning(message) = event {
+                recorded.borrow_mut().push(message.clone());
+            }
+        });
+
+        let keep = temp_dir.path().join("keep.txt");
+        let scratch = temp_dir.path().join("scratch.txt");
+        fs::write(&keep, "keep").unwrap();
+        fs::write(&scratch, "scratch").unwrap();
+
+        // The veto covers the whole batch
+        let error = trash.delete(&[&scratch, &keep], &DeleteOptions::default()).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(RmzError::Vetoed { hook, .. }) if hook == "pre_delete"));
+        assert!(scratch.exists() && keep.exists());
+
+        let outcome = trash.delete(&[&scratch], &DeleteOptions::default()).unwrap();
+        let id = outcome.deleted[0].meta.id;
+
+        let outcome = trash.purge(&PurgeSelector::All).unwrap();
+        assert!(outcome.purged.is_empty());
+        assert!(matches!(&outcome.failed[0].1, RmzError::Vetoed { reason, .. } if reason == "purging is frozen"));
+
+        trash.restore(&id, &RestoreOptions::default()).unwrap();
+        let input: serde_json::Value = serde_json::from_str(&fs::read_to_string(&restored_log).unwrap()).unwrap();
+        assert_eq!(input["items"][0]["id"], id.to_string());
+        assert_eq!(input["items"][0]["restored_to"], scratch.display().to_string());
+        assert!(warnings.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_git_metadata_and_safeguards() {
+        use crate::domain::GitConfig;
+        use std::process::Command;
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join("docs")).unwrap();
+        fs::write(repo.join("docs/guide.md"), "guide").unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(["-c", "user.name=rmz", "-c", "user.email=rmz@example.com", "-c", "init.defaultBranch=main"])
+                .args(args)
+                .current_dir(&repo)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .status()
+                .expect("git must be installed to run this test")
+                .success()
+        };
+        assert!(git(&["init", "-q"]) && git(&["add", "."]) && git(&["commit", "-q", "-m", "Docs"]));
+        fs::write(repo.join("docs/draft.md"), "draft").unwrap();
+        fs::create_dir_all(repo.join("notes")).unwrap();
+        fs::write(repo.join("notes/todo.md"), "todo").unwrap();
+        fs::write(repo.join(".gitignore"), "build/\n").unwrap();
+        fs::create_dir_all(repo.join("build")).unwrap();
+        fs::write(repo.join("build/out.o"), "out").unwrap();
+
+        let trash = Trash::open(Config {
+            trash_path: temp_dir.path().join("trash"),
+            protected_paths: Vec::new(),
+            git: GitConfig {
+                safeguard: GitSafeguard::Refuse,
+                ..GitConfig::default()
+            },
+            ..Config::default()
+        })
+        .unwrap();
+
+        for path in [repo.clone(), repo.join(".git"), repo.join("docs"), repo.join("notes")] {
+            let result = trash.delete_path(&path, &DeleteOptions { recursive: true, ..Default::default() });
+            assert!(matches!(result, Err(RmzError::GitSafeguard { .. })), "{}", path.display());
+        }
+
+        // Ignored build output is not at risk
+        let build = trash.delete_path(&repo.join("build"), &DeleteOptions { recursive: true, ..Default::default() });
+        assert_eq!(build.unwrap().meta.git.unwrap().status, GitFileStatus::Untracked);
+
+        let item = trash.delete_path(&repo.join("docs/draft.md"), &DeleteOptions::default()).unwrap();
+        let info = item.meta.git.unwrap();
+        assert_eq!(info.repo_root, repo);
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.head.unwrap().len(), 40);
+        assert_eq!(info.status, GitFileStatus::Untracked);
+
+        // The remaining tree is clean again
+        let item = trash.delete_path(&repo.join("docs"), &DeleteOptions { recursive: true, ..Default::default() }).unwrap();
+        assert_eq!(item.meta.git.unwrap().status, GitFileStatus::Tracked);
+
+        let outside = temp_dir.path().join("outside.txt");
+        fs::write(&outside, "outside").unwrap();
+        assert!(trash.delete_path(&outside, &DeleteOptions::default()).unwrap().meta.git.is_none());
+    }
+}
@@ -0,0 +1,578 @@
+use crate::core::RmzError;
+use crate::domain::TrashItem;
+use crate::infra::preview;
+use crate::trash::{ListFilter, PurgeSelector, RestoreOptions, Trash};
+use anyhow::Result;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::TableState;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Lines kept for the preview pane
+const PREVIEW_LINES: usize = 200;
+
+/// Rows moved by PageUp / PageDown
+const PAGE_SIZE: usize = 10;
+
+/// Column the item table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Path,
+    Size,
+    DeletedAt,
+    Tag,
+}
+
+impl SortColumn {
+    pub fn title(self) -> &'static str {
+        match self {
+            SortColumn::Path => "Path",
+            SortColumn::Size => "Size",
+            SortColumn::DeletedAt => "Deleted",
+            SortColumn::Tag => "Tags",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Path => SortColumn::Size,
+            SortColumn::Size => SortColumn::DeletedAt,
+            SortColumn::DeletedAt => SortColumn::Tag,
+            SortColumn::Tag => SortColumn::Path,
+        }
+    }
+}
+
+/// What a line of text input will be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    RestoreTo,
+    ExtractTo,
+    Retag,
+}
+
+impl InputKind {
+    pub fn prompt(self) -> &'static str {
+        match self {
+            InputKind::RestoreTo => "Restore to",
+            InputKind::ExtractTo => "Extract to",
+            InputKind::Retag => "Tags (comma separated)",
+        }
+    }
+}
+
+/// A destructive action waiting for confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingAction {
+    /// Permanently delete these items
+    Purge(Vec<Uuid>),
+    /// Restore these items over existing files
+    Overwrite { ids: Vec<Uuid>, to: Option<PathBuf> },
+}
+
+/// Current interaction mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Filter,
+    Input { kind: InputKind, buffer: String },
+    Confirm(PendingAction),
+    Help,
+}
+
+/// State of the trash browser, independent of how it is drawn
+pub struct App {
+    trash: Trash,
+    items: Vec<TrashItem>,
+    visible: Vec<usize>,
+    selected: HashSet<Uuid>,
+    preview: Option<(Uuid, Vec<String>)>,
+    pub table_state: TableState,
+    pub sort: SortColumn,
+    pub descending: bool,
+    pub filter: String,
+    pub mode: Mode,
+    pub status: Option<String>,
+    pub should_quit: bool,
+}
+
+impl App {
+    /// Load the trash contents, newest first
+    pub fn new(trash: Trash) -> Result<Self> {
+        let mut app = Self {
+            trash,
+            items: Vec::new(),
+            visible: Vec::new(),
+            selected: HashSet::new(),
+            preview: None,
+            table_state: TableState::default(),
+            sort: SortColumn::DeletedAt,
+            descending: true,
+            filter: String::new(),
+            mode: Mode::Normal,
+            status: None,
+            should_quit: false,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Re-read the trash, keeping the selection for items that still exist
+    pub fn reload(&mut self) -> Result<()> {
+        let current = self.current().map(|item| item.meta.id);
+        self.items = self.trash.list(&ListFilter {
+            reveal_paths: true,
+            ..ListFilter::default()
+        })?;
+        let ids: HashSet<Uuid> = self.items.iter().map(|item| item.meta.id).collect();
+        self.selected.retain(|id| ids.contains(id));
+        self.preview = None;
+        self.visible.clear();
+        self.refresh_view_at(current);
+        Ok(())
+    }
+
+    /// Recompute the filtered, sorted rows and keep the cursor on the same item
+    fn refresh_view(&mut self) {
+        let current = self.current().map(|item| item.meta.id);
+        self.refresh_view_at(current);
+    }
+
+    fn refresh_view_at(&mut self, current: Option<Uuid>) {
+        let filter = ListFilter {
+            pattern: (!self.filter.is_empty()).then(|| self.filter.clone()),
+            ..ListFilter::default()
+        };
+
+        let items = &self.items;
+        let mut visible: Vec<usize> = (0..items.len()).filter(|&i| filter.matches(&items[i])).collect();
+        visible.sort_by(|&a, &b| {
+            let (a, b) = (&items[a].meta, &items[b].meta);
+            match self.sort {
+                SortColumn::Path => a.original_path.cmp(&b.original_path),
+                SortColumn::Size => a.size.cmp(&b.size),
+                SortColumn::DeletedAt => a.deleted_at.cmp(&b.deleted_at),
+                SortColumn::Tag => a.tags.cmp(&b.tags),
+            }
+        });
+        if self.descending {
+            visible.reverse();
+        }
+        self.visible = visible;
+
+        let position = current
+            .and_then(|id| self.visible_items().position(|item| item.meta.id == id))
+            .unwrap_or(0);
+        self.table_state
+            .select((!self.visible.is_empty()).then_some(position));
+    }
+
+    /// Rows in display order
+    pub fn visible_items(&self) -> impl Iterator<Item = &TrashItem> {
+        self.visible.iter().map(|&i| &self.items[i])
+    }
+
+    /// Number of items in the trash, before filtering
+    pub fn total(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Item under the cursor
+    pub fn current(&self) -> Option<&TrashItem> {
+        self.table_state
+            .selected()
+            .and_then(|row| self.visible.get(row))
+            .map(|&i| &self.items[i])
+    }
+
+    pub fn is_selected(&self, id: &Uuid) -> bool {
+        self.selected.contains(id)
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// Items an action applies to: the multi-selection, or the item under the cursor
+    pub fn targets(&self) -> Vec<TrashItem> {
+        if self.selected.is_empty() {
+            self.current().cloned().into_iter().collect()
+        } else {
+            self.items
+                .iter()
+                .filter(|item| self.selected.contains(&item.meta.id))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Preview of the item under the cursor, rendered once per item
+    pub fn preview(&mut self) -> &[String] {
+        let Some(item) = self.current().cloned() else {
+            return &[];
+        };
+        if self.preview.as_ref().is_none_or(|(id, _)| *id != item.meta.id) {
+            let lines = preview::preview_lines(self.trash.store(), &item, PREVIEW_LINES);
+            self.preview = Some((item.meta.id, lines));
+        }
+        self.preview.as_ref().map(|(_, lines)| lines.as_slice()).unwrap_or(&[])
+    }
+
+    /// Apply a key press to the current mode
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal(key),
+            Mode::Filter => self.handle_filter(key),
+            Mode::Input { kind, buffer } => self.handle_input(key, kind, buffer),
+            Mode::Confirm(action) => self.handle_confirm(key, action),
+            Mode::Help => {}
+        }
+    }
+
+    fn handle_normal(&mut self, key: KeyEvent) {
+        self.status = None;
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.refresh_view();
+            }
+            KeyCode::Esc => self.should_quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::PageDown => self.move_cursor(PAGE_SIZE as isize),
+            KeyCode::PageUp => self.move_cursor(-(PAGE_SIZE as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.move_cursor(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_cursor(isize::MAX / 2),
+            KeyCode::Char(' ') => {
+                if let Some(id) = self.current().map(|item| item.meta.id) {
+                    if !self.selected.remove(&id) {
+                        self.selected.insert(id);
+                    }
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Char('a') => {
+                let ids: Vec<Uuid> = self.visible_items().map(|item| item.meta.id).collect();
+                if ids.iter().all(|id| self.selected.contains(id)) {
+                    self.selected.clear();
+                } else {
+                    self.selected.extend(ids);
+                }
+            }
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                self.refresh_view();
+            }
+            KeyCode::Char('S') => {
+                self.descending = !self.descending;
+                self.refresh_view();
+            }
+            KeyCode::Enter | KeyCode::Char('r') => {
+                let ids = self.target_ids();
+                self.restore(ids, None, false);
+            }
+            KeyCode::Char('R') => self.start_input(InputKind::RestoreTo),
+            KeyCode::Char('e') => self.start_input(InputKind::ExtractTo),
+            KeyCode::Char('t') => self.start_input(InputKind::Retag),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                let ids = self.target_ids();
+                if !ids.is_empty() {
+                    self.mode = Mode::Confirm(PendingAction::Purge(ids));
+                }
+            }
+            KeyCode::Char('?') => self.mode = Mode::Help,
+            _ => {}
+        }
+    }
+
+    fn handle_filter(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => return,
+            KeyCode::Esc => self.filter.clear(),
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => {}
+        }
+        if key.code != KeyCode::Esc {
+            self.mode = Mode::Filter;
+        }
+        self.refresh_view();
+    }
+
+    fn handle_input(&mut self, key: KeyEvent, kind: InputKind, mut buffer: String) {
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Enter => return self.submit_input(kind, buffer),
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            KeyCode::Char(c) => buffer.push(c),
+            _ => {}
+        }
+        self.mode = Mode::Input { kind, buffer };
+    }
+
+    fn handle_confirm(&mut self, key: KeyEvent, action: PendingAction) {
+        if !matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+            self.status = Some("Cancelled".to_string());
+            return;
+        }
+        match action {
+            PendingAction::Purge(ids) => self.purge(ids),
+            PendingAction::Overwrite { ids, to } => self.restore(ids, to, true),
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let row = (current.saturating_add(delta)).clamp(0, self.visible.len() as isize - 1);
+        self.table_state.select(Some(row as usize));
+    }
+
+    fn target_ids(&self) -> Vec<Uuid> {
+        self.targets().iter().map(|item| item.meta.id).collect()
+    }
+
+    fn start_input(&mut self, kind: InputKind) {
+        let targets = self.targets();
+        if targets.is_empty() {
+            return;
+        }
+        let buffer = match kind {
+            InputKind::Retag if targets.len() == 1 => targets[0].meta.tags.join(", "),
+            InputKind::Retag => String::new(),
+            InputKind::RestoreTo | InputKind::ExtractTo => std::env::current_dir()
+                .map(|dir| format!("{}/", dir.display()))
+                .unwrap_or_default(),
+        };
+        self.mode = Mode::Input { kind, buffer };
+    }
+
+    fn submit_input(&mut self, kind: InputKind, buffer: String) {
+        let ids = self.target_ids();
+        match kind {
+            InputKind::RestoreTo if !buffer.trim().is_empty() => {
+                self.restore(ids, Some(PathBuf::from(buffer.trim())), false)
+            }
+            InputKind::ExtractTo if !buffer.trim().is_empty() => self.extract(PathBuf::from(buffer.trim())),
+            InputKind::Retag => {
+                let tags: Vec<String> = buffer
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+                self.retag(ids, tags);
+            }
+            _ => self.status = Some("No destination given".to_string()),
+        }
+    }
+
+    fn restore(&mut self, ids: Vec<Uuid>, to: Option<PathBuf>, overwrite: bool) {
+        let options = RestoreOptions { to: to.clone(), overwrite };
+        let mut restored = 0;
+        let mut conflicts = Vec::new();
+        let mut errors = Vec::new();
+
+        for id in ids {
+            match self.trash.restore(&id, &options) {
+                Ok(_) => restored += 1,
+                Err(RmzError::Conflict { .. }) => conflicts.push(id),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        self.finish(format!("Restored {} item(s)", restored), errors);
+        if !conflicts.is_empty() {
+            self.mode = Mode::Confirm(PendingAction::Overwrite { ids: conflicts, to });
+        }
+    }
+
+    fn extract(&mut self, destination: PathBuf) {
+        let targets = self.targets();
+        let into_dir = destination.is_dir() || targets.len() > 1;
+        let mut extracted = 0;
+        let mut errors = Vec::new();
+
+        for item in &targets {
+            let target = match item.meta.filename() {
+                Some(filename) if into_dir => destination.join(filename),
+                _ => destination.clone(),
+            };
+            let result = if target.exists() {
+                Err(RmzError::Conflict { path: target }.into())
+            } else {
+                self.trash.store().extract_to(item, &target)
+            };
+            match result {
+                Ok(()) => extracted += 1,
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        self.finish(format!("Extracted {} item(s)", extracted), errors);
+    }
+
+    fn retag(&mut self, ids: Vec<Uuid>, tags: Vec<String>) {
+        let mut retagged = 0;
+        let mut errors = Vec::new();
+        for id in ids {
+            match self.trash.retag(&id, tags.clone()) {
+                Ok(_) => retagged += 1,
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.finish(format!("Retagged {} item(s)", retagged), errors);
+    }
+
+    fn purge(&mut self, ids: Vec<Uuid>) {
+        let outcome = match self.trash.select(&PurgeSelector::Ids(ids)) {
+            Ok(items) => self.trash.purge_items(&items),
+            Err(e) => return self.finish(String::new(), vec![e.to_string()]),
+        };
+        let errors = outcome.failed.iter().map(|(_, e)| e.to_string()).collect();
+        self.finish(format!("Purged {} item(s)", outcome.purged.len()), errors);
+    }
+
+    /// Reload after an action and report its result in the status line
+    fn finish(&mut self, summary: String, errors: Vec<String>) {
+        if let Err(e) = self.reload() {
+            self.status = Some(format!("Failed to reload trash: {}", e));
+            return;
+        }
+        self.selected.clear();
+        self.status = Some(match errors.first() {
+            Some(error) if summary.is_empty() => error.clone(),
+            Some(error) => format!("{}; {} failed: {}", summary, errors.len(), error),
+            None => summary,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trash::DeleteOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn app_with(temp_dir: &TempDir, files: &[(&str, &str)]) -> App {
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        for (name, content) in files {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            trash.delete_path(&path, &DeleteOptions::default()).unwrap();
+        }
+        App::new(trash).unwrap()
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    fn names(app: &App) -> Vec<String> {
+        app.visible_items()
+            .map(|item| item.meta.filename().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = app_with(&temp_dir, &[("b.txt", "bb"), ("a.log", "a"), ("c.txt", "ccc")]);
+        assert_eq!(app.total(), 3);
+
+        // Path ascending, then size descending
+        press(&mut app, "ssS");
+        assert_eq!(app.sort, SortColumn::Path);
+        assert_eq!(names(&app), vec!["a.log", "b.txt", "c.txt"]);
+        press(&mut app, "sS");
+        assert_eq!(names(&app), vec!["c.txt", "b.txt", "a.log"]);
+
+        press(&mut app, "/txt");
+        assert_eq!(app.mode, Mode::Filter);
+        assert_eq!(names(&app), vec!["c.txt", "b.txt"]);
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.mode, Mode::Normal);
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(names(&app).len(), 3);
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn test_multi_select_purge_requires_confirmation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = app_with(&temp_dir, &[("a.txt", "a"), ("b.txt", "b"), ("c.txt", "c")]);
+
+        press(&mut app, "  d");
+        assert_eq!(app.selected_count(), 2);
+        assert!(matches!(&app.mode, Mode::Confirm(PendingAction::Purge(ids)) if ids.len() == 2));
+
+        press(&mut app, "n");
+        assert_eq!(app.total(), 3);
+        assert_eq!(app.status.as_deref(), Some("Cancelled"));
+
+        press(&mut app, "dy");
+        assert_eq!(app.total(), 1);
+        assert_eq!(app.selected_count(), 0);
+        assert_eq!(app.status.as_deref(), Some("Purged 2 item(s)"));
+    }
+
+    #[test]
+    fn test_restore_with_conflict_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = app_with(&temp_dir, &[("a.txt", "old")]);
+        let original = temp_dir.path().join("a.txt");
+        fs::write(&original, "new").unwrap();
+
+        press(&mut app, "r");
+        assert!(matches!(app.mode, Mode::Confirm(PendingAction::Overwrite { .. })));
+        press(&mut app, "y");
+        assert_eq!(fs::read_to_string(&original).unwrap(), "old");
+        assert_eq!(app.total(), 0);
+    }
+
+    #[test]
+    fn test_restore_to_extract_and_retag() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = app_with(&temp_dir, &[("a.txt", "a")]);
+        let dest = temp_dir.path().join("out");
+        fs::create_dir(&dest).unwrap();
+
+        type_input(&mut app, 't', "keep, later");
+        assert_eq!(app.current().unwrap().meta.tags, vec!["keep", "later"]);
+
+        type_input(&mut app, 'e', dest.to_str().unwrap());
+        assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "a");
+        assert_eq!(app.total(), 1);
+
+        type_input(&mut app, 'R', dest.join("b.txt").to_str().unwrap());
+        assert_eq!(fs::read_to_string(dest.join("b.txt")).unwrap(), "a");
+        assert_eq!(app.total(), 0);
+    }
+
+    fn type_input(app: &mut App, key: char, text: &str) {
+        press(app, &key.to_string());
+        if let Mode::Input { buffer, .. } = &mut app.mode {
+            buffer.clear();
+        }
+        press(app, text);
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.mode, Mode::Normal, "status: {:?}", app.status);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::domain::TrashItem;
+use crate::infra::{preview, FzfInterface, TrashStore};
+use crate::utils::fuzzy_match::{rank, FuzzyMatch};
+use anyhow::Result;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::collections::BTreeSet;
+
+/// Lines kept for the preview pane
+const PREVIEW_LINES: usize = 200;
+
+/// How a finder session ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinderOutcome {
+    /// Indices into the item slice, in item order
+    Selected(Vec<usize>),
+    Cancelled,
+}
+
+/// In-process fuzzy finder over trash items with a preview pane
+pub struct Finder<'a> {
+    store: &'a TrashStore,
+    items: &'a [TrashItem],
+    labels: Vec<String>,
+    matches: Vec<(usize, FuzzyMatch)>,
+    marked: BTreeSet<usize>,
+    multi: bool,
+    preview: Option<(usize, Vec<String>)>,
+    pub query: String,
+    pub list_state: ListState,
+}
+
+impl<'a> Finder<'a> {
+    pub fn new(store: &'a TrashStore, items: &'a [TrashItem], multi: bool) -> Self {
+        let mut finder = Self {
+            store,
+            items,
+            labels: items
+                .iter()
+                .map(|item| item.meta.original_path.display().to_string())
+                .collect(),
+            matches: Vec::new(),
+            marked: BTreeSet::new(),
+            multi,
+            preview: None,
+            query: String::new(),
+            list_state: ListState::default(),
+        };
+        finder.rerank();
+        finder
+    }
+
+    fn rerank(&mut self) {
+        self.matches = rank(&self.query, self.labels.iter().map(String::as_str));
+        self.list_state
+            .select((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Index of the item under the cursor
+    pub fn current(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|row| self.matches.get(row))
+            .map(|(index, _)| *index)
+    }
+
+    /// Item indices in rank order
+    pub fn ranked(&self) -> impl Iterator<Item = usize> + '_ {
+        self.matches.iter().map(|(index, _)| *index)
+    }
+
+    /// Apply a key press, returning the outcome once the session ends
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<FinderOutcome> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(FinderOutcome::Cancelled),
+            KeyCode::Char('c') | KeyCode::Char('g') if ctrl => return Some(FinderOutcome::Cancelled),
+            KeyCode::Enter => {
+                if self.multi && !self.marked.is_empty() {
+                    return Some(FinderOutcome::Selected(self.marked.iter().copied().collect()));
+                }
+                return self.current().map(|index| FinderOutcome::Selected(vec![index]));
+            }
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('p') | KeyCode::Char('k') if ctrl => self.move_cursor(-1),
+            KeyCode::Char('n') | KeyCode::Char('j') if ctrl => self.move_cursor(1),
+            KeyCode::Tab | KeyCode::BackTab if self.multi => {
+                if let Some(index) = self.current() {
+                    if !self.marked.remove(&index) {
+                        self.marked.insert(index);
+                    }
+                }
+                self.move_cursor(if key.code == KeyCode::Tab { 1 } else { -1 });
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.rerank();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.rerank();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.rerank();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let row = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state
+            .select(Some(row.clamp(0, self.matches.len() as isize - 1) as usize));
+    }
+
+    fn preview(&mut self) -> &[String] {
+        let Some(index) = self.current() else {
+            return &[];
+        };
+        if self.preview.as_ref().is_none_or(|(cached, _)| *cached != index) {
+            let lines = preview::preview_lines(self.store, &self.items[index], PREVIEW_LINES);
+            self.preview = Some((index, lines));
+        }
+        self.preview.as_ref().map(|(_, lines)| lines.as_slice()).unwrap_or(&[])
+    }
+
+    /// Draw the prompt, ranked list and preview
+    pub fn draw(&mut self, frame: &mut Frame, prompt: &str) {
+        let [list_area, preview_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .areas(frame.area());
+        let [prompt_area, info_area, matches_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(1)])
+            .areas(list_area);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![format!("{} > ", prompt).cyan().bold(), Span::raw(&self.query), "█".dim()])),
+            prompt_area,
+        );
+
+        let mut info = format!("  {}/{}", self.matches.len(), self.items.len());
+        if self.multi {
+            info.push_str(&format!(" ({} selected, tab to select)", self.marked.len()));
+        }
+        frame.render_widget(Paragraph::new(info).dim(), info_area);
+
+        let rows: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|(index, m)| {
+                let item = &self.items[*index];
+                let mut spans = vec![Span::raw(if self.marked.contains(index) { "● " } else { "  " })];
+                spans.extend(highlighted(&self.labels[*index], &m.positions));
+                spans.push(format!("  {}", item.meta.human_readable_size()).yellow());
+                spans.push(format!("  {}", &item.meta.id.to_string()[..8]).dark_gray());
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(rows)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▌");
+        frame.render_stateful_widget(list, matches_area, &mut self.list_state);
+
+        let lines: Vec<Line> = self.preview().iter().map(|line| Line::raw(line.as_str())).collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::LEFT).title(" Preview ")),
+            preview_area,
+        );
+    }
+}
+
+/// Split a label into spans with the matched characters emphasised
+fn highlighted<'l>(label: &'l str, positions: &[usize]) -> Vec<Span<'l>> {
+    label
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let span = Span::raw(c.to_string());
+            if positions.binary_search(&i).is_ok() {
+                span.green().bold()
+            } else {
+                span
+            }
+        })
+        .collect()
+}
+
+/// Run a finder session on the given terminal
+pub fn run_finder<B: Backend>(terminal: &mut Terminal<B>, finder: &mut Finder, prompt: &str) -> Result<FinderOutcome> {
+    loop {
+        terminal.draw(|frame| finder.draw(frame, prompt))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                if let Some(outcome) = finder.handle_key(key) {
+                    return Ok(outcome);
+                }
+            }
+        }
+    }
+}
+
+/// [`FzfInterface`] backed by the built-in finder
+pub struct FuzzySelector<'a> {
+    store: &'a TrashStore,
+}
+
+impl<'a> FuzzySelector<'a> {
+    pub fn new(store: &'a TrashStore) -> Self {
+        Self { store }
+    }
+
+    fn select(&self, items: &[TrashItem], multi: bool) -> Result<Vec<TrashItem>> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Unlock before entering the alternate screen so a passphrase prompt stays usable
+        if items.iter().any(|item| item.meta.is_encrypted()) {
+            self.store.key()?;
+        }
+
+        let mut finder = Finder::new(self.store, items, multi);
+        let mut terminal = ratatui::init();
+        let outcome = run_finder(&mut terminal, &mut finder, "rmz");
+        ratatui::restore();
+
+        match outcome? {
+            FinderOutcome::Selected(indices) => Ok(indices.into_iter().map(|i| items[i].clone()).collect()),
+            FinderOutcome::Cancelled => Ok(Vec::new()),
+        }
+    }
+}
+
+impl FzfInterface for FuzzySelector<'_> {
+    fn select_trash_item(&self, items: &[TrashItem]) -> Result<Option<TrashItem>> {
+        Ok(self.select(items, false)?.into_iter().next())
+    }
+
+    fn select_multiple_trash_items(&self, items: &[TrashItem]) -> Result<Vec<TrashItem>> {
+        self.select(items, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileMeta;
+    use crate::infra::trash_store::TrashStoreInterface;
+    use ratatui::backend::TestBackend;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn trashed(store: &TrashStore, dir: &TempDir, names: &[&str]) -> Vec<TrashItem> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                fs::write(&path, format!("contents of {}", name)).unwrap();
+                store.save(&FileMeta::from_path(&path).unwrap(), &path).unwrap()
+            })
+            .collect()
+    }
+
+    fn type_text(finder: &mut Finder, text: &str) {
+        for c in text.chars() {
+            assert_eq!(finder.handle_key(KeyEvent::from(KeyCode::Char(c))), None);
+        }
+    }
+
+    #[test]
+    fn test_ranking_and_single_select() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+        let items = trashed(&store, &temp_dir, &["notes.txt", "main.rs", "Cargo.toml"]);
+
+        let mut finder = Finder::new(&store, &items, false);
+        assert_eq!(finder.ranked().count(), 3);
+
+        type_text(&mut finder, "mai");
+        assert_eq!(finder.ranked().collect::<Vec<_>>(), vec![1]);
+
+        finder.handle_key(KeyEvent::from(KeyCode::Backspace));
+        finder.handle_key(KeyEvent::from(KeyCode::Backspace));
+        finder.handle_key(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(finder.ranked().count(), 3);
+
+        type_text(&mut finder, "toml");
+        assert_eq!(
+            finder.handle_key(KeyEvent::from(KeyCode::Enter)),
+            Some(FinderOutcome::Selected(vec![2]))
+        );
+        assert_eq!(finder.handle_key(KeyEvent::from(KeyCode::Esc)), Some(FinderOutcome::Cancelled));
+    }
+
+    #[test]
+    fn test_multi_select_and_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TrashStore::new(temp_dir.path().join("trash"));
+        let items = trashed(&store, &temp_dir, &["a.txt", "b.txt", "c.log"]);
+
+        let mut finder = Finder::new(&store, &items, true);
+        type_text(&mut finder, "txt");
+        finder.handle_key(KeyEvent::from(KeyCode::Tab));
+        finder.handle_key(KeyEvent::from(KeyCode::Tab));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal.draw(|frame| finder.draw(frame, "restore")).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("restore > txt"));
+        assert!(screen.contains("2/3 (2 selected"));
+        assert!(screen.contains("contents of b.txt"));
+
+        assert_eq!(
+            finder.handle_key(KeyEvent::from(KeyCode::Enter)),
+            Some(FinderOutcome::Selected(vec![0, 1]))
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Full-screen trash browser
+//!
+//! [`App`] holds the browser state and reacts to key presses; [`ui::draw`]
+//! renders it. Both work with any ratatui backend, so the browser can be
+//! driven headlessly with `TestBackend`.
+
+pub mod app;
+pub mod finder;
+pub mod ui;
+
+pub use app::App;
+
+use crate::infra::trash_store::TrashStoreInterface;
+use crate::trash::Trash;
+use anyhow::Result;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::Terminal;
+
+/// Run the browser on the real terminal until the user quits
+pub fn run(trash: Trash) -> Result<()> {
+    // Unlock before entering the alternate screen so a passphrase prompt stays usable
+    if trash.store().list()?.iter().any(|item| item.meta.is_encrypted()) {
+        trash.store().key()?;
+    }
+
+    let mut app = App::new(trash)?;
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+/// Draw and handle key presses until the app asks to quit
+pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    while !app.should_quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use super::app::{App, Mode, PendingAction, SortColumn};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+
+const KEY_HINTS: &str =
+    "↑↓ move  space select  / filter  s sort  r restore  R restore to  e extract  t tag  d purge  ? help  q quit";
+
+const HELP: &[(&str, &str)] = &[
+    ("↑ ↓ j k", "Move the cursor"),
+    ("PgUp PgDn g G", "Jump by page, to the top or bottom"),
+    ("space", "Select or deselect the item and move down"),
+    ("a", "Select or deselect every visible item"),
+    ("/", "Filter by name, path, tag or note (Esc clears)"),
+    ("s / S", "Change sort column / reverse sort order"),
+    ("r, Enter", "Restore to the original location"),
+    ("R", "Restore to another path"),
+    ("e", "Extract a copy without removing it from the trash"),
+    ("t", "Replace tags"),
+    ("d, Delete", "Purge permanently (asks for confirmation)"),
+    ("q, Esc", "Quit"),
+];
+
+/// Draw the whole browser for the current state
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3), Constraint::Length(1)])
+        .areas(frame.area());
+    let [table_area, preview_area] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .areas(body);
+
+    draw_header(frame, app, header);
+    draw_table(frame, app, table_area);
+    draw_preview(frame, app, preview_area);
+    draw_footer(frame, app, footer);
+
+    match &app.mode {
+        Mode::Confirm(action) => draw_confirm(frame, action),
+        Mode::Help => draw_help(frame),
+        _ => {}
+    }
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut title = format!(
+        "🗑️  rmz trash — {} of {} items, {} selected — sorted by {} {}",
+        app.visible_items().count(),
+        app.total(),
+        app.selected_count(),
+        app.sort.title(),
+        if app.descending { "↓" } else { "↑" }
+    );
+    if !app.filter.is_empty() {
+        title.push_str(&format!(" — filter: {}", app.filter));
+    }
+    frame.render_widget(Paragraph::new(title).bold(), area);
+}
+
+fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let columns = [SortColumn::Path, SortColumn::Size, SortColumn::DeletedAt, SortColumn::Tag];
+    let header = Row::new(std::iter::once(Cell::from("")).chain(columns.iter().map(|&column| {
+        let title = if column == app.sort {
+            format!("{} {}", column.title(), if app.descending { "↓" } else { "↑" })
+        } else {
+            column.title().to_string()
+        };
+        Cell::from(title)
+    })))
+    .style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+
+    let rows: Vec<Row> = app
+        .visible_items()
+        .map(|item| {
+            let marker = if app.is_selected(&item.meta.id) { "●" } else { " " };
+            let row = Row::new(vec![
+                Cell::from(marker),
+                Cell::from(item.meta.original_path.display().to_string()),
+                Cell::from(item.meta.human_readable_size()),
+                Cell::from(format_relative_time(item.meta.deleted_at)),
+                Cell::from(item.meta.tags.join(", ")),
+            ]);
+            if app.is_selected(&item.meta.id) {
+                row.yellow()
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Min(20),
+            Constraint::Length(9),
+            Constraint::Length(14),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(" Trash "))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.table_state);
+}
+
+fn draw_preview(frame: &mut Frame, app: &mut App, area: Rect) {
+    let title = app
+        .current()
+        .and_then(|item| item.meta.filename().map(|name| format!(" Preview: {} ", name)))
+        .unwrap_or_else(|| " Preview ".to_string());
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(note) = app.current().and_then(|item| item.meta.note.clone()) {
+        lines.extend(note.lines().map(|line| Line::raw(format!("📝 {}", line)).cyan().italic()));
+        lines.push(Line::raw(""));
+    }
+    lines.extend(app.preview().iter().map(|line| Line::raw(line.as_str())));
+
+    let preview = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(preview, area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match &app.mode {
+        Mode::Filter => format!("/{}█", app.filter),
+        Mode::Input { kind, buffer } => format!("{}: {}█", kind.prompt(), buffer),
+        _ => app.status.clone().unwrap_or_else(|| KEY_HINTS.to_string()),
+    };
+    frame.render_widget(Paragraph::new(text).dim(), area);
+}
+
+fn draw_confirm(frame: &mut Frame, action: &PendingAction) {
+    let question = match action {
+        PendingAction::Purge(ids) => format!("Permanently delete {} item(s)? This cannot be undone.", ids.len()),
+        PendingAction::Overwrite { ids, .. } => {
+            format!("{} item(s) already exist at the destination. Overwrite?", ids.len())
+        }
+    };
+
+    let area = centered(frame.area(), 60, 5);
+    let dialog = Paragraph::new(vec![Line::raw(question), Line::raw(""), Line::raw("[y] Yes   [any other key] No").bold()])
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title(" Confirm ").red());
+    frame.render_widget(Clear, area);
+    frame.render_widget(dialog, area);
+}
+
+fn draw_help(frame: &mut Frame) {
+    let lines: Vec<Line> = HELP
+        .iter()
+        .map(|(keys, action)| Line::raw(format!("{:<14} {}", keys, action)))
+        .collect();
+
+    let area = centered(frame.area(), 70, HELP.len() as u16 + 2);
+    let help = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Keys "));
+    frame.render_widget(Clear, area);
+    frame.render_widget(help, area);
+}
+
+/// A box of at most `width` x `height` in the middle of `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Format relative time (e.g., "2 hours ago")
+fn format_relative_time(datetime: chrono::DateTime<chrono::Utc>) -> String {
+    let now = chrono::Utc::now();
+    let duration = now.signed_duration_since(datetime);
+
+    if duration.num_days() > 0 {
+        format!("{} days ago", duration.num_days())
+    } else if duration.num_hours() > 0 {
+        format!("{} hours ago", duration.num_hours())
+    } else if duration.num_minutes() > 0 {
+        format!("{} minutes ago", duration.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trash::{DeleteOptions, Trash};
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::Terminal;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn screen(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_draw_table_preview_and_confirm() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = Trash::open_root(temp_dir.path().join("trash")).unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "hello from the preview").unwrap();
+        trash
+            .delete_path(
+                &file,
+                &DeleteOptions {
+                    tags: vec!["docs".to_string()],
+                    note: Some("moved to the wiki".to_string()),
+                    ..DeleteOptions::default()
+                },
+            )
+            .unwrap();
+
+        let mut app = App::new(trash).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(140, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+
+        let text = screen(&terminal);
+        assert!(text.contains("1 of 1 items"));
+        assert!(text.contains("Deleted ↓"));
+        assert!(text.contains("notes.txt"));
+        assert!(text.contains("docs"));
+        assert!(text.contains("hello from the preview"));
+        assert!(text.contains("moved to the wiki"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('d')));
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        assert!(screen(&terminal).contains("Permanently delete 1 item(s)?"));
+    }
+}
@@ -0,0 +1,156 @@
+/// Score for every matched character
+const SCORE_MATCH: i64 = 16;
+/// Bonus when a match directly follows the previous one
+const BONUS_CONSECUTIVE: i64 = 12;
+/// Bonus when a match starts a word (after a separator or at a camelCase hump)
+const BONUS_BOUNDARY: i64 = 10;
+/// Bonus for matches within the last path component
+const BONUS_FILENAME: i64 = 4;
+/// Penalty per skipped character between two matches
+const PENALTY_GAP: i64 = 1;
+
+/// Result of matching a query against a candidate string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better
+    pub score: i64,
+    /// Char indices in the candidate that matched the query, ascending
+    pub positions: Vec<usize>,
+}
+
+/// Match `query` against `candidate` the way fzf does
+///
+/// Whitespace separates terms that must all match. Matching ignores case
+/// unless the query contains an uppercase letter. An empty query matches
+/// everything with a score of zero.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let chars: Vec<char> = candidate.chars().collect();
+    let folded: Vec<char> = if case_sensitive {
+        chars.clone()
+    } else {
+        chars.iter().map(|c| c.to_ascii_lowercase()).collect()
+    };
+    let filename_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+
+    let mut result = FuzzyMatch {
+        score: 0,
+        positions: Vec::new(),
+    };
+    for term in query.split_whitespace() {
+        let term: Vec<char> = term.chars().collect();
+        let term_match = match_term(&term, &chars, &folded, filename_start)?;
+        result.score += term_match.score;
+        result.positions.extend(term_match.positions);
+    }
+    result.positions.sort_unstable();
+    result.positions.dedup();
+    Some(result)
+}
+
+/// Rank candidates by match quality, dropping those that do not match
+///
+/// Ties go to the shorter candidate, then to the earlier one.
+pub fn rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<(usize, FuzzyMatch)> {
+    let mut ranked: Vec<(usize, usize, FuzzyMatch)> = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            fuzzy_match(query, candidate).map(|m| (index, candidate.chars().count(), m))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.2.score.cmp(&a.2.score).then(a.1.cmp(&b.1)).then(a.0.cmp(&b.0)));
+    ranked.into_iter().map(|(index, _, m)| (index, m)).collect()
+}
+
+/// Best greedy match of one term, trying every possible start position
+fn match_term(term: &[char], chars: &[char], folded: &[char], filename_start: usize) -> Option<FuzzyMatch> {
+    let first = *term.first()?;
+    let mut best: Option<FuzzyMatch> = None;
+
+    'starts: for start in (0..folded.len()).filter(|&i| folded[i] == first) {
+        let mut positions = vec![start];
+        let mut next = start + 1;
+        for &c in &term[1..] {
+            // A later start cannot match either once the rest of the term runs out
+            let Some(offset) = folded[next..].iter().position(|&f| f == c) else {
+                break 'starts;
+            };
+            positions.push(next + offset);
+            next += offset + 1;
+        }
+
+        let score = score_positions(&positions, chars, filename_start);
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(FuzzyMatch { score, positions });
+        }
+    }
+
+    best
+}
+
+fn score_positions(positions: &[usize], chars: &[char], filename_start: usize) -> i64 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+
+    for &i in positions {
+        score += SCORE_MATCH;
+        if is_boundary(chars, i) {
+            score += BONUS_BOUNDARY;
+        }
+        if i >= filename_start {
+            score += BONUS_FILENAME;
+        }
+        match previous {
+            Some(p) if i == p + 1 => score += BONUS_CONSECUTIVE,
+            Some(p) => score -= PENALTY_GAP * (i - p - 1) as i64,
+            None => {}
+        }
+        previous = Some(i);
+    }
+
+    score
+}
+
+fn is_boundary(chars: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let (prev, current) = (chars[i - 1], chars[i]);
+    matches!(prev, '/' | '_' | '-' | '.' | ' ') || (prev.is_lowercase() && current.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        let m = fuzzy_match("rdm", "/home/user/README.md").unwrap();
+        assert_eq!(m.positions, vec![11, 14, 15]);
+
+        assert!(fuzzy_match("xyz", "/home/user/README.md").is_none());
+        assert_eq!(fuzzy_match("ab", "ab-a").unwrap().positions, vec![0, 1]);
+        assert!(fuzzy_match("", "anything").unwrap().positions.is_empty());
+
+        // Smart case: uppercase in the query makes matching case sensitive
+        assert!(fuzzy_match("README", "/home/readme.md").is_none());
+        assert!(fuzzy_match("readme", "/home/README.md").is_some());
+
+        // Every term has to match
+        assert!(fuzzy_match("home md", "/home/README.md").is_some());
+        assert!(fuzzy_match("home rs", "/home/README.md").is_none());
+    }
+
+    #[test]
+    fn test_rank_prefers_contiguous_filename_matches() {
+        let candidates = [
+            "/src/commands/config.rs",
+            "/tmp/c_o_n_f_i_g.txt",
+            "/etc/config",
+            "/var/log/app.log",
+        ];
+        let ranked: Vec<usize> = rank("config", candidates).into_iter().map(|(i, _)| i).collect();
+        assert_eq!(ranked, vec![2, 0, 1]);
+    }
+}
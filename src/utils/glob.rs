@@ -0,0 +1,119 @@
+/// Whether `pattern` uses any glob syntax
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Match a path against a shell-style glob
+///
+/// `*` and `?` stay within one path component, `**` spans any number of
+/// components and `[abc]`, `[a-z]` or `[!abc]` match one character from a
+/// set. Patterns without a `/` are matched against the file name only.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let subject = if pattern.contains('/') {
+        path
+    } else {
+        path.rsplit('/').next().unwrap_or(path)
+    };
+    let pattern: Vec<char> = pattern.chars().collect();
+    let subject: Vec<char> = subject.chars().collect();
+    matches(&pattern, &subject)
+}
+
+fn matches(pattern: &[char], subject: &[char]) -> bool {
+    match pattern.first() {
+        None => subject.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // `**/` may also match nothing, so `a/**/b` matches `a/b`
+            let rest = &pattern[2..];
+            if rest.first() == Some(&'/') && matches(&rest[1..], subject) {
+                return true;
+            }
+            (0..=subject.len()).any(|skip| matches(rest, &subject[skip..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for skip in 0..=subject.len() {
+                if matches(rest, &subject[skip..]) {
+                    return true;
+                }
+                if subject.get(skip) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => subject.first().is_some_and(|&c| c != '/') && matches(&pattern[1..], &subject[1..]),
+        Some('[') => match (subject.first(), class_end(pattern)) {
+            (Some(&c), Some(end)) => {
+                c != '/' && class_contains(&pattern[1..end], c) && matches(&pattern[end + 1..], &subject[1..])
+            }
+            // An unterminated `[` is literal
+            (Some(&c), None) => c == '[' && matches(&pattern[1..], &subject[1..]),
+            (None, _) => false,
+        },
+        Some(&literal) => subject.first() == Some(&literal) && matches(&pattern[1..], &subject[1..]),
+    }
+}
+
+/// Index of the `]` closing the class that starts at `pattern[0]`
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let first = if pattern.get(1) == Some(&'!') { 2 } else { 1 };
+    // A `]` right after the opening bracket is part of the set
+    (first + 1..pattern.len()).find(|&i| pattern[i] == ']')
+}
+
+fn class_contains(class: &[char], c: char) -> bool {
+    let (negated, set) = match class.first() {
+        Some('!') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if set.get(i + 1) == Some(&'-') && i + 2 < set.len() {
+            found |= set[i] <= c && c <= set[i + 2];
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcards_stay_within_components() {
+        assert!(glob_match("*.log", "/var/tmp/build.log"));
+        assert!(!glob_match("*.log", "/var/tmp/build.log.1"));
+        assert!(glob_match("/var/*/build.log", "/var/tmp/build.log"));
+        assert!(!glob_match("/var/*.log", "/var/tmp/build.log"));
+        assert!(glob_match("/home/?ob/notes.txt", "/home/bob/notes.txt"));
+    }
+
+    #[test]
+    fn test_double_star_spans_directories() {
+        assert!(glob_match("/home/**/*.rs", "/home/me/src/main.rs"));
+        assert!(glob_match("/home/**/main.rs", "/home/main.rs"));
+        assert!(glob_match("**/target/**", "/work/app/target/debug/app"));
+        assert!(!glob_match("/srv/**", "/home/me"));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        assert!(glob_match("report[0-9].pdf", "/docs/report7.pdf"));
+        assert!(!glob_match("report[!0-9].pdf", "/docs/report7.pdf"));
+        assert!(glob_match("report[!0-9].pdf", "/docs/reportA.pdf"));
+        assert!(glob_match("[]x].txt", "/docs/].txt"));
+        assert!(glob_match("a[b", "/a[b"));
+    }
+
+    #[test]
+    fn test_is_glob() {
+        assert!(is_glob("*.log"));
+        assert!(!is_glob("/home/me/project"));
+    }
+}
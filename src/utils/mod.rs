@@ -1,2 +1,5 @@
-pub mod duration_parser;
 pub mod size_parser;
+pub mod fuzzy_match;
+pub mod time_expr;
+pub mod glob;
+pub mod shell;
@@ -0,0 +1,27 @@
+/// Quote an argument for `sh`, leaving it bare when it holds no special characters
+pub fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '/' | '.' | '_' | '-' | '+' | '=' | ':' | ',' | '@')
+        });
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/usr/bin/rmz"), "/usr/bin/rmz");
+        assert_eq!(shell_quote("/it's/rmz"), "'/it'\\''s/rmz'");
+        assert_eq!(shell_quote("/srv/my trash"), "'/srv/my trash'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
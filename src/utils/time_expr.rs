@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Shown with every parse error so users do not have to guess the syntax
+pub const ACCEPTED_FORMS: &str = "\
+  relative:   2h, 30m, 90s, 1h30m, 3 days ago, 2 weeks (units: s m h d w mo y)
+  named:      now, today, yesterday, week, month (today/yesterday start at local midnight)
+  date:       2024-01-01 (local midnight)
+  local time: 2024-01-01 13:00, 2024-01-01 13:00:05, 2024-01-01T13:00
+  ISO 8601:   2024-01-01T13:00:00Z, 2024-01-01T13:00:00+02:00, 2024-01-01 13:00 +0200";
+
+/// A window of time, open on either side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    /// Inclusive lower bound
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// Build a range from `--since`, `--before` and `--between` arguments
+    ///
+    /// `between` takes `START..END` and cannot be combined with the others.
+    pub fn parse(since: Option<&str>, before: Option<&str>, between: Option<&str>) -> Result<Self> {
+        if let Some(between) = between {
+            if since.is_some() || before.is_some() {
+                return Err(anyhow!("--between cannot be combined with --since or --before"));
+            }
+            let (start, end) = between
+                .split_once("..")
+                .ok_or_else(|| anyhow!("Invalid range '{}': expected START..END, e.g. 'yesterday..today'", between))?;
+            return Self::bounded(Some(parse_time(start)?), Some(parse_time(end)?));
+        }
+
+        Self::bounded(since.map(parse_time).transpose()?, before.map(parse_time).transpose()?)
+    }
+
+    fn bounded(since: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Result<Self> {
+        if let (Some(start), Some(end)) = (since, before) {
+            if start >= end {
+                return Err(anyhow!(
+                    "Empty time range: {} is not earlier than {}",
+                    start.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                    end.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+                ));
+            }
+        }
+        Ok(Self { since, before })
+    }
+
+    /// Check whether `time` falls inside the range
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| time >= since) && self.before.is_none_or(|before| time < before)
+    }
+
+    /// Whether neither bound is set
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.before.is_none()
+    }
+}
+
+/// Parse a time expression relative to the current time
+pub fn parse_time(input: &str) -> Result<DateTime<Utc>> {
+    parse_time_at(input, Utc::now())
+}
+
+/// Parse a time expression with relative forms measured back from `now`
+pub fn parse_time_at(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let trimmed = input.trim();
+    let lower = trimmed.to_lowercase();
+
+    if let Some(time) = parse_anchor(&lower, now) {
+        return Ok(time);
+    }
+    if let Some(ago) = parse_relative(&lower) {
+        return now
+            .checked_sub_signed(ago)
+            .ok_or_else(|| anyhow!("Time expression '{}' is too far in the past", input));
+    }
+    if let Some(time) = parse_absolute(trimmed) {
+        return time;
+    }
+
+    Err(anyhow!("Invalid time expression '{}'. Accepted forms:\n{}", input, ACCEPTED_FORMS))
+}
+
+/// Parse a span such as "2h", "1h30m" or "3 days"
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    parse_relative(&input.trim().to_lowercase())
+        .ok_or_else(|| anyhow!("Invalid duration '{}'. Use e.g. 2h, 30m, 1h30m, 3 days, 2w, 6mo, 1y", input))
+}
+
+fn parse_anchor(lower: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let today = now.with_timezone(&Local).date_naive();
+    match lower {
+        "now" => Some(now),
+        "today" => local_midnight(today),
+        "yesterday" => local_midnight(today.pred_opt()?),
+        "week" | "this-week" => now.checked_sub_signed(Duration::weeks(1)),
+        "month" | "this-month" => now.checked_sub_signed(Duration::days(30)),
+        _ => None,
+    }
+}
+
+/// One or more `<number><unit>` terms, optionally followed by "ago"
+fn parse_relative(lower: &str) -> Option<Duration> {
+    let expression = lower.strip_suffix("ago").unwrap_or(lower).trim_end();
+    let mut chars = expression.chars().peekable();
+    let mut total = Duration::zero();
+    let mut terms = 0;
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut number = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            number.push(digit);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut unit = String::new();
+        while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+            unit.push(letter);
+        }
+
+        let value: i64 = number.parse().ok()?;
+        let term = match unit.as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(value)?,
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(value)?,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(value)?,
+            "d" | "day" | "days" => Duration::try_days(value)?,
+            "w" | "wk" | "wks" | "week" | "weeks" => Duration::try_weeks(value)?,
+            "mo" | "month" | "months" => Duration::try_days(value.checked_mul(30)?)?,
+            "y" | "yr" | "yrs" | "year" | "years" => Duration::try_days(value.checked_mul(365)?)?,
+            _ => return None,
+        };
+        total = total.checked_add(&term)?;
+        terms += 1;
+    }
+
+    (terms > 0).then_some(total)
+}
+
+/// ISO 8601 with an offset, or a date or date-time in the local time zone
+fn parse_absolute(input: &str) -> Option<Result<DateTime<Utc>>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Some(Ok(time.with_timezone(&Utc)));
+    }
+
+    const WITH_OFFSET: &[&str] = &[
+        "%Y-%m-%d %H:%M:%S%#z",
+        "%Y-%m-%d %H:%M:%S %#z",
+        "%Y-%m-%d %H:%M%#z",
+        "%Y-%m-%d %H:%M %#z",
+        "%Y-%m-%dT%H:%M%#z",
+    ];
+    if let Some(time) = WITH_OFFSET
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(input, format).ok())
+    {
+        return Some(Ok(time.with_timezone(&Utc)));
+    }
+
+    const LOCAL: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+    if let Some(time) = LOCAL
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return Some(
+            local(time).ok_or_else(|| anyhow!("{} does not exist in the local time zone", input)),
+        );
+    }
+
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .map(|date| local_midnight(date).ok_or_else(|| anyhow!("{} has no midnight in the local time zone", input)))
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Utc>> {
+    local(date.and_time(NaiveTime::MIN))
+}
+
+/// Interpret a wall-clock time in the local zone, taking the earlier instant when a DST change repeats it
+fn local(time: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-14T13:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_relative_expressions() {
+        let now = now();
+        assert_eq!(parse_time_at("2h", now).unwrap(), now - Duration::hours(2));
+        assert_eq!(parse_time_at("3 days ago", now).unwrap(), now - Duration::days(3));
+        assert_eq!(parse_time_at("1h30m", now).unwrap(), now - Duration::minutes(90));
+        assert_eq!(parse_time_at("1 week, 2 days", now).unwrap(), now - Duration::days(9));
+        assert_eq!(parse_time_at("6mo", now).unwrap(), now - Duration::days(180));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::seconds(90));
+        assert!(parse_duration("5 fortnights").is_err());
+        assert!(parse_duration("ago").is_err());
+    }
+
+    #[test]
+    fn test_named_anchors_and_local_dates() {
+        let now = now();
+        let today = now.with_timezone(&Local).date_naive();
+        assert_eq!(parse_time_at("now", now).unwrap(), now);
+        assert_eq!(parse_time_at("Today", now).unwrap(), local_midnight(today).unwrap());
+        assert_eq!(
+            parse_time_at("yesterday", now).unwrap(),
+            local_midnight(today.pred_opt().unwrap()).unwrap()
+        );
+        assert_eq!(parse_time_at("week", now).unwrap(), now - Duration::weeks(1));
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(parse_time_at("2024-01-01", now).unwrap(), local_midnight(date).unwrap());
+        assert_eq!(
+            parse_time_at("2024-01-01 13:00", now).unwrap(),
+            local(date.and_hms_opt(13, 0, 0).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_iso_8601_with_offsets() {
+        let expected = DateTime::parse_from_rfc3339("2024-01-01T11:00:00Z").unwrap();
+        for input in [
+            "2024-01-01T11:00:00Z",
+            "2024-01-01T13:00:00+02:00",
+            "2024-01-01 13:00:00+02:00",
+            "2024-01-01 13:00 +0200",
+        ] {
+            assert_eq!(parse_time(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_errors_list_accepted_forms() {
+        let message = parse_time("next tuesday").unwrap_err().to_string();
+        assert!(message.contains("Invalid time expression 'next tuesday'"));
+        assert!(message.contains("3 days ago"));
+        assert!(message.contains("ISO 8601"));
+    }
+
+    #[test]
+    fn test_time_range() {
+        let range = TimeRange::parse(None, None, Some("2024-01-01T00:00:00Z..2024-01-02T00:00:00Z")).unwrap();
+        assert!(range.contains(DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().into()));
+        assert!(!range.contains(DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z").unwrap().into()));
+
+        assert!(TimeRange::parse(Some("1h"), Some("2h"), None).is_err());
+        assert!(TimeRange::parse(Some("1h"), None, Some("2h..1h")).is_err());
+        assert!(TimeRange::parse(None, None, Some("yesterday")).is_err());
+        assert!(TimeRange::parse(None, None, None).unwrap().is_unbounded());
+    }
+}
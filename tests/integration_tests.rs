@@ -68,3 +68,455 @@ fn test_restore_invalid_id() {
         .failure()
         .stderr(predicate::str::contains("No files matching partial ID"));
 }
+
+#[test]
+fn test_show_unknown_item() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("show")
+        .arg("zzzzzzzz")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No file found matching"));
+}
+
+#[test]
+fn test_cat_unknown_id() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("cat")
+        .arg("zzzzzzzz")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No file found with ID starting with"));
+}
+
+#[test]
+fn test_invalid_time_expression_lists_accepted_forms() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("list")
+        .arg("--since")
+        .arg("next tuesday")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid time expression 'next tuesday'"))
+        .stderr(predicate::str::contains("3 days ago"));
+}
+
+#[test]
+fn test_history_unknown_path() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("history")
+        .arg("/nonexistent/never-trashed.toml")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No trashed versions of"));
+}
+
+#[test]
+fn test_diff_unknown_id() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("diff")
+        .arg("zzzzzzzz")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No file found with ID starting with"));
+}
+
+#[test]
+fn test_tag_unknown_id() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.args(["tag", "add", "zzzzzzzz", "--tag", "keep"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No file found with ID starting with"));
+}
+
+#[test]
+fn test_config_get_unknown_key() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.args(["config", "get", "no_such_key"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown configuration key: no_such_key"));
+}
+
+#[test]
+fn test_gc_install_timer_writes_units() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+        .arg("gc")
+        .arg("--install-timer")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rmz-gc.timer"));
+
+    let unit_dir = temp_dir.path().join("systemd/user");
+    let service = fs::read_to_string(unit_dir.join("rmz-gc.service")).unwrap();
+    let timer = fs::read_to_string(unit_dir.join("rmz-gc.timer")).unwrap();
+    assert!(service.contains("Type=oneshot"));
+    assert!(service.contains(" gc"));
+    assert!(timer.contains("OnCalendar=daily"));
+    assert!(timer.contains("WantedBy=timers.target"));
+}
+
+#[test]
+fn test_gc_install_timer_quotes_trash_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let trash_dir = temp_dir.path().join("my trash 100%");
+
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+        .arg("--trash-dir")
+        .arg(&trash_dir)
+        .arg("gc")
+        .arg("--install-timer")
+        .assert()
+        .success();
+
+    let service = fs::read_to_string(temp_dir.path().join("systemd/user/rmz-gc.service")).unwrap();
+    let quoted = format!("--trash-dir \"{}\" gc", trash_dir.display().to_string().replace('%', "%%"));
+    assert!(service.contains(&quoted), "{}", service);
+
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("--trash-dir")
+        .arg(&trash_dir)
+        .arg("gc")
+        .arg("--cron")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "--trash-dir '{}' gc",
+            trash_dir.display().to_string().replace('%', "\\%")
+        )));
+}
+
+#[test]
+fn test_gc_cron_prints_line() {
+    let mut cmd = Command::cargo_bin("rmz").unwrap();
+    cmd.arg("gc")
+        .arg("--cron")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("* * * ").and(predicate::str::contains(" gc")));
+}
+
+#[test]
+fn test_rmz_home_isolates_trash() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let test_file = work.path().join("isolated.txt");
+    fs::write(&test_file, "test content").unwrap();
+
+    Command::cargo_bin("rmz")
+        .unwrap()
+        .env("RMZ_HOME", home.path())
+        .args(["delete", "--force"])
+        .arg(&test_file)
+        .assert()
+        .success();
+    assert!(!test_file.exists());
+    assert!(home.path().join("trash").is_dir());
+
+    Command::cargo_bin("rmz")
+        .unwrap()
+        .env("RMZ_HOME", home.path())
+        .args(["list", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("isolated.txt"));
+
+    // A different trash directory does not see the item
+    Command::cargo_bin("rmz")
+        .unwrap()
+        .env("RMZ_HOME", home.path())
+        .arg("--trash-dir")
+        .arg(work.path().join("other"))
+        .args(["list", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("isolated.txt").not());
+}
+
+#[test]
+fn test_unknown_profile() {
+    let home = TempDir::new().unwrap();
+    let config = home.path().join("rmz.toml");
+    fs::write(&config, "[profiles.work]\nauto_clean_days = 7\n").unwrap();
+
+    Command::cargo_bin("rmz")
+        .unwrap()
+        .env("RMZ_HOME", home.path())
+        .arg("--config")
+        .arg(&config)
+        .args(["--profile", "scratch", "status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Defined profiles: work"));
+}
+
+#[test]
+fn test_config_set_validates_and_unset_restores_default() {
+    let home = TempDir::new().unwrap();
+    let rmz = || {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path());
+        cmd
+    };
+
+    rmz()
+        .args(["config", "set", "compression.level", "40"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("integer 1-22"));
+
+    rmz().args(["config", "set", "compression.level", "9"]).assert().success();
+    rmz()
+        .args(["config", "get", "compression.level"])
+        .assert()
+        .success()
+        .stdout("9\n");
+
+    rmz().args(["config", "unset", "compression.level"]).assert().success();
+    rmz()
+        .args(["config", "get", "compression.level"])
+        .assert()
+        .success()
+        .stdout("3\n");
+}
+
+#[test]
+fn test_doctor_adopts_payload_without_metadata() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let test_file = work.path().join("lost.txt");
+    fs::write(&test_file, "test content").unwrap();
+
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+    rmz(&["delete", "--force"]).arg(&test_file).assert().success();
+
+    let metadata_dir = home.path().join("trash").join("metadata");
+    for entry in fs::read_dir(&metadata_dir).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+
+    rmz(&["doctor", "--check", "trash-zone", "--fix", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Payload without metadata"))
+        .stdout(predicate::str::contains("Adopt with reconstructed metadata"));
+    assert_eq!(fs::read_dir(&metadata_dir).unwrap().count(), 0);
+
+    rmz(&["doctor", "--check", "trash-zone", "--fix", "--force"]).assert().success();
+    rmz(&["list", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recovered"));
+}
+
+#[test]
+fn test_doctor_reports_repairs_it_could_not_log() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let test_file = work.path().join("lost.txt");
+    fs::write(&test_file, "test content").unwrap();
+
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+    rmz(&["delete", "--force"]).arg(&test_file).assert().success();
+
+    let metadata_dir = home.path().join("trash").join("metadata");
+    for entry in fs::read_dir(&metadata_dir).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+    // A directory in place of the log makes every append fail
+    let log_path = home.path().join("logs").join("operations.jsonl");
+    fs::remove_file(&log_path).unwrap();
+    fs::create_dir(&log_path).unwrap();
+
+    rmz(&["doctor", "--check", "trash-zone", "--fix", "--force"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Failed to log repair"));
+    assert_eq!(fs::read_dir(&metadata_dir).unwrap().count(), 1);
+}
+
+#[test]
+fn test_log_verify_detects_removed_entry() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        let file = work.path().join(name);
+        fs::write(&file, name).unwrap();
+        rmz(&["delete", "--force"]).arg(&file).assert().success();
+    }
+
+    rmz(&["log", "verify"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 entries in 1 file(s)"));
+
+    let log_path = home.path().join("logs").join("operations.jsonl");
+    let content = fs::read_to_string(&log_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    fs::write(&log_path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+    rmz(&["log", "verify"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("operations.jsonl:2"));
+}
+
+#[test]
+fn test_log_filters_and_follow() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+
+    let report = work.path().join("report.txt");
+    let build = work.path().join("build.log");
+    fs::write(&report, "report").unwrap();
+    fs::write(&build, "build").unwrap();
+    rmz(&["delete", "--force", "--tag", "docs"]).arg(&report).assert().success();
+    rmz(&["delete", "--force"]).arg(&build).assert().success();
+    let listed = rmz(&["list", "--json"]).output().unwrap();
+    let items: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    for item in items.as_array().unwrap() {
+        rmz(&["restore", "--id", item["id"].as_str().unwrap()]).assert().success();
+    }
+
+    rmz(&["log", "--tag", "docs", "--operation", "delete"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("report.txt"))
+        .stdout(predicate::str::contains("build.log").not());
+    rmz(&["log", "--path", "*.log", "--reverse", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Delete"))
+        .stdout(predicate::str::contains("Restore").not());
+    rmz(&["log", "--result", "failed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No operations found"));
+
+    // --follow keeps running; the backlog is printed as NDJSON before it waits
+    let output = rmz(&["log", "--follow", "--limit", "2", "--operation", "restore"])
+        .timeout(std::time::Duration::from_millis(1500))
+        .output()
+        .unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line["operation"] == "Restore"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hooks_veto_deletion_unless_disabled() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+
+    rmz(&["config", "set", "hooks.pre_delete", "grep -q '\"size\":[0-9]\\{4\\}' && echo 'too large' >&2 && exit 1; exit 0"])
+        .assert()
+        .success();
+    let notified = work.path().join("notified.json");
+    let post_delete = home.path().join("hooks.d").join("post_delete");
+    fs::create_dir_all(&post_delete).unwrap();
+    let script = post_delete.join("10-notify");
+    fs::write(&script, format!("#!/bin/sh\ncat > {}\n", notified.display())).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let small = work.path().join("small.txt");
+    let large = work.path().join("large.bin");
+    fs::write(&small, "small").unwrap();
+    fs::write(&large, vec![0u8; 4096]).unwrap();
+
+    rmz(&["delete", "--force"])
+        .arg(&small)
+        .arg(&large)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cancelled by pre_delete hook: too large"));
+    assert!(small.exists() && large.exists());
+    assert!(!notified.exists());
+
+    rmz(&["delete", "--force"]).arg(&small).assert().success();
+    assert!(!small.exists());
+    assert!(fs::read_to_string(&notified).unwrap().contains("\"hook\":\"post_delete\""));
+
+    rmz(&["delete", "--force", "--no-hooks"]).arg(&large).assert().success();
+    assert!(!large.exists());
+}
+
+#[test]
+fn test_git_metadata_and_checkout_hint() {
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    let rmz = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rmz").unwrap();
+        cmd.env("RMZ_HOME", home.path()).args(args);
+        cmd
+    };
+    let repo = work.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    fs::write(repo.join("notes.txt"), "notes").unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=rmz", "-c", "user.email=rmz@example.com", "-c", "init.defaultBranch=main"])
+            .args(args)
+            .current_dir(&repo)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .status()
+            .expect("git must be installed to run this test")
+            .success()
+    };
+    assert!(git(&["init", "-q"]) && git(&["add", "."]) && git(&["commit", "-q", "-m", "Notes"]));
+
+    rmz(&["delete", "--force"]).arg(repo.join("notes.txt")).assert().success();
+    let output = rmz(&["list", "--json"]).output().unwrap();
+    let items: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(items[0]["git"]["branch"], "main");
+    assert_eq!(items[0]["git"]["status"], "tracked");
+    let id = items[0]["id"].as_str().unwrap().to_string();
+
+    rmz(&["show", &id]).assert().success().stdout(predicate::str::contains("tracked in "));
+    let output = rmz(&["restore", "--id", &id]).assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let hint = output.find("checkout -- notes.txt` would also work").expect(&output);
+    // The hint comes before the restore, while it can still change the user's mind
+    assert!(hint < output.find("Restored:").unwrap(), "{}", output);
+
+    rmz(&["delete", "--force", "--recursive"])
+        .arg(&repo)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("it is the root of a git repository"));
+    assert!(!repo.exists());
+}